@@ -0,0 +1,72 @@
+//! The parts of the `cc`-style driver that deal with files and other programs: what each
+//! input is, where intermediate files go, and running the system compiler to assemble and
+//! link.
+
+use std::{
+    env,
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// What an input file is, judging by its extension like `cc` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// `.c`, compiled by fbcc
+    Source,
+    /// `.s` and `.S`, assembled by the system compiler
+    Assembly,
+    /// Anything else, such as `.o` and `.a`, which only the linker looks at
+    Object,
+}
+
+impl InputKind {
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("c") => InputKind::Source,
+            Some("s" | "S") => InputKind::Assembly,
+            _ => InputKind::Object,
+        }
+    }
+}
+
+/// A directory for the intermediate files of one run, removed again when dropped.
+pub struct TempDir {
+    dir: tempfile::TempDir,
+    count: usize,
+}
+
+impl TempDir {
+    /// Creates the directory under a random name, failing rather than reusing anything already
+    /// there, so that no one else can have files or links waiting in it.
+    pub fn new() -> io::Result<Self> {
+        let dir = tempfile::Builder::new().prefix("fbcc-").tempdir()?;
+        Ok(Self { dir, count: 0 })
+    }
+
+    /// A fresh path for an intermediate file made from `input`. The files are numbered, so
+    /// inputs with the same name in different directories don't overwrite each other.
+    pub fn file(&mut self, input: &Path, extension: &str) -> PathBuf {
+        self.count += 1;
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        self.dir.path().join(format!("{stem}-{}.{extension}", self.count))
+    }
+}
+
+/// Runs the system C compiler, `$CC` or else `cc`, to assemble or link, and reports why
+/// it failed if it did.
+pub fn run_cc(args: &[OsString]) -> bool {
+    let cc = env::var_os("CC").unwrap_or_else(|| "cc".into());
+    match Command::new(&cc).args(args).status() {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            eprintln!("error: {} failed ({status})", cc.to_string_lossy());
+            false
+        }
+        Err(error) => {
+            eprintln!("error: cannot run {}: {error}", cc.to_string_lossy());
+            false
+        }
+    }
+}
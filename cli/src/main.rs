@@ -1,21 +1,83 @@
+mod driver;
+mod render;
+
 use colored::{Color, Colorize};
-use fbcc::analysis::node::Span;
-use fbcc::compiler::Compiler;
-use fbcc::core::errors::{CompilerError, CompilerErrorKind, Diagnostic, DiagnosticKind};
+use driver::{run_cc, InputKind, TempDir};
+use fbcc::analysis::ast::format_translationunit;
+use fbcc::compiler::{CompilerOptions, Optimizations, Session};
+use fbcc::core::errors::{CompilerError, Diagnostic, ErrorCode, Severity};
+use fbcc::core::target::{
+    host_triple, target_from_triple, Aarch64AppleDarwin, ObjectFormat, Target, SUPPORTED_TRIPLES,
+};
+use fbcc::synthesis::dwarf::DebugInfo;
+use fbcc::synthesis::interpreter::Interpreter;
+use fbcc::synthesis::ir::IrFunction;
 use std::io;
 use std::{
+    ffi::OsString,
     fs,
     path::{Path, PathBuf},
+    thread,
     time::Instant,
 };
 
+/// How far the driver takes its inputs, like the `-S` and `-c` flags of `cc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// `--dump-*` or `--stats` on its own: print, but write nothing
+    Dump,
+    /// `-S`, `--emit-llvm` or `--emit-c`: a text file for every source file
+    Assembly,
+    /// `-c`: an object file for every source and assembly file
+    Object,
+    /// The default: compile and assemble everything, then link it into an executable
+    Link,
+}
+
 struct CliOptions {
     paths: Vec<PathBuf>,
     dump_ast: bool,
     dump_ir: bool,
     dump_asm: bool,
+    /// `-S` or `--emit-asm`
     emit_asm: bool,
+    emit_llvm: bool,
+    emit_c: bool,
+    /// `-c`: assemble into an object file with the integrated assembler
+    object: bool,
+    run: bool,
+    opt_level: u32,
+    ssa: bool,
+    no_inline: bool,
+    /// `-f[no-]optimize-sibling-calls`, overriding the optimisation level
+    sibling_calls: Option<bool>,
+    /// `-f[no-]rotate-loops`
+    rotate_loops: Option<bool>,
+    /// `-f[no-]move-loop-invariants`
+    move_loop_invariants: Option<bool>,
+    /// `-f[no-]strength-reduce`
+    strength_reduce: Option<bool>,
+    /// `-f[no-]unroll-loops`
+    unroll_loops: Option<bool>,
+    /// `-f[no-]peephole`
+    peephole: Option<bool>,
+    stats: bool,
+    /// `-g`: emit DWARF debug info
+    debug: bool,
+    /// `-ftime-report`: print how long compilation took
+    time_report: bool,
+    /// `-ferror-limit=N`: stop reporting errors after N of them, 0 meaning never
+    error_limit: usize,
+    /// `-Rpass`: report the optimisations made, like the calls inlined
+    remarks: bool,
+    /// `-fdiagnostics-parseable-fixits`: also print fix-its in a format editors can apply
+    parseable_fixits: bool,
+    /// `--explain CODE`: describe an error code instead of compiling
+    explain: Option<ErrorCode>,
     output: Option<PathBuf>,
+    /// `-l` and `-L` arguments, passed on to the linker in order
+    linker_args: Vec<String>,
+    target: Box<dyn Target>,
 }
 
 impl CliOptions {
@@ -26,26 +88,156 @@ impl CliOptions {
             dump_ir: false,
             dump_asm: false,
             emit_asm: false,
+            emit_llvm: false,
+            emit_c: false,
+            object: false,
+            run: false,
+            opt_level: 0,
+            ssa: false,
+            no_inline: false,
+            sibling_calls: None,
+            rotate_loops: None,
+            move_loop_invariants: None,
+            strength_reduce: None,
+            unroll_loops: None,
+            peephole: None,
+            stats: false,
+            debug: false,
+            time_report: false,
+            error_limit: 20,
+            remarks: false,
+            parseable_fixits: false,
+            explain: None,
             output: None,
+            linker_args: Vec::new(),
+            // the host's own target, or else the one fbcc was first written for
+            target: host_triple()
+                .and_then(target_from_triple)
+                .unwrap_or_else(|| Box::new(Aarch64AppleDarwin)),
+        }
+    }
+
+    fn stage(&self) -> Stage {
+        if self.emit_asm || self.emit_llvm || self.emit_c {
+            Stage::Assembly
+        } else if self.object {
+            Stage::Object
+        } else if (self.dump_ast || self.dump_ir || self.dump_asm || self.stats) && self.output.is_none() {
+            Stage::Dump
+        } else {
+            Stage::Link
+        }
+    }
+
+    /// The options to compile with, apart from debug info.
+    fn compiler_options(&self) -> CompilerOptions {
+        CompilerOptions::new(self.optimizations()).with_error_limit((self.error_limit > 0).then_some(self.error_limit))
+    }
+
+    /// The passes `-O<level>` enables, plus the ones asked for individually.
+    fn optimizations(&self) -> Optimizations {
+        let level = Optimizations::level(self.opt_level);
+        Optimizations {
+            ssa: self.ssa,
+            inline: level.inline && !self.no_inline,
+            sibling_calls: self.sibling_calls.unwrap_or(level.sibling_calls),
+            rotate: self.rotate_loops.unwrap_or(level.rotate),
+            licm: self.move_loop_invariants.unwrap_or(level.licm),
+            strength_reduction: self.strength_reduce.unwrap_or(level.strength_reduction),
+            unroll: self.unroll_loops.unwrap_or(level.unroll),
+            peephole: self.peephole.unwrap_or(level.peephole),
+            ..level
         }
     }
 }
 
 fn parse_cli(args: Vec<String>) -> Result<CliOptions, io::Error> {
     let mut cli_options = CliOptions::new();
-    let mut args = args.into_iter();
+    let mut args = args.into_iter().peekable();
+
+    // `cli run file.c` interprets the program instead of compiling it
+    if args.peek().is_some_and(|arg| arg == "run") {
+        args.next();
+        cli_options.run = true;
+    }
+
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--dump-ast" => cli_options.dump_ast = true,
             "--dump-ir" => cli_options.dump_ir = true,
             "--dump-asm" => cli_options.dump_asm = true,
-            "--emit-asm" => cli_options.emit_asm = true,
+            "-S" | "--emit-asm" => cli_options.emit_asm = true,
+            "--emit-llvm" => cli_options.emit_llvm = true,
+            "--emit-c" => cli_options.emit_c = true,
+            "-c" => cli_options.object = true,
+            "--ssa" => cli_options.ssa = true,
+            "-fno-inline" => cli_options.no_inline = true,
+            "-foptimize-sibling-calls" => cli_options.sibling_calls = Some(true),
+            "-fno-optimize-sibling-calls" => cli_options.sibling_calls = Some(false),
+            "-frotate-loops" => cli_options.rotate_loops = Some(true),
+            "-fno-rotate-loops" => cli_options.rotate_loops = Some(false),
+            "-fmove-loop-invariants" => cli_options.move_loop_invariants = Some(true),
+            "-fno-move-loop-invariants" => cli_options.move_loop_invariants = Some(false),
+            "-fstrength-reduce" => cli_options.strength_reduce = Some(true),
+            "-fno-strength-reduce" => cli_options.strength_reduce = Some(false),
+            "-funroll-loops" => cli_options.unroll_loops = Some(true),
+            "-fno-unroll-loops" => cli_options.unroll_loops = Some(false),
+            "-fpeephole" => cli_options.peephole = Some(true),
+            "-fno-peephole" => cli_options.peephole = Some(false),
+            "--stats" => cli_options.stats = true,
+            "-g" => cli_options.debug = true,
+            "-ftime-report" => cli_options.time_report = true,
+            "-Rpass" => cli_options.remarks = true,
+            "-fdiagnostics-parseable-fixits" => cli_options.parseable_fixits = true,
+            "--explain" => {
+                let code = args
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "--explain requires an error code"))?;
+                let code = code
+                    .parse()
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+                cli_options.explain = Some(code);
+            }
+            limit if limit.starts_with("-ferror-limit=") => {
+                cli_options.error_limit = limit["-ferror-limit=".len()..].parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("invalid error limit `{limit}`"))
+                })?;
+            }
+            level if level.starts_with("-O") => {
+                cli_options.opt_level = level[2..].parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("invalid optimisation level `{level}`"),
+                    )
+                })?;
+            }
             "-o" => {
                 let path = args
                     .next()
                     .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "-o requires a path argument"))?;
                 cli_options.output = Some(PathBuf::from(path));
             }
+            "-l" | "-L" => {
+                let value = args.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("{arg} requires an argument"))
+                })?;
+                cli_options.linker_args.push(format!("{arg}{value}"));
+            }
+            linker if linker.starts_with("-l") || linker.starts_with("-L") => cli_options.linker_args.push(arg),
+            "--target" => {
+                let target = args
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "--target requires an argument"))?;
+                cli_options.target = target_from_triple(&target).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "unknown target `{target}`, expected one of: {}",
+                            SUPPORTED_TRIPLES.join(", ")
+                        ),
+                    )
+                })?;
+            }
 
             _ => {
                 let path = PathBuf::from(arg);
@@ -53,7 +245,7 @@ fn parse_cli(args: Vec<String>) -> Result<CliOptions, io::Error> {
                 if !path.exists() {
                     return Err(io::Error::new(
                         io::ErrorKind::NotFound,
-                        format!("Path does not exist: {}", path.to_str().unwrap()),
+                        format!("Path does not exist: {}", path.display()),
                     ));
                 }
 
@@ -64,150 +256,310 @@ fn parse_cli(args: Vec<String>) -> Result<CliOptions, io::Error> {
     Ok(cli_options)
 }
 
-fn format_diagnostic(
-    severity: &str,
-    accent: Color,
-    kind_str: &str,
-    message: &str,
-    span: Span,
-    path: &Path,
-    line_str: &str,
-) -> String {
-    let file = path.display();
-    let (line, col) = (span.start.line, span.start.column);
-    let mut out = String::new();
-
-    out += &format!(
-        "{}: {}\n",
-        severity.bold().color(accent),
-        format!("[{}] {}", kind_str, message).yellow()
-    );
-
-    out += &format!(
-        "  {} {}\n",
-        "-->".bright_blue(),
-        format!("{file}:{line}:{col}").bold().white()
-    );
+/// Parses, analyses and lowers `source`, printing the dumps and statistics asked for along
+/// the way.
+fn front_end(
+    session: &mut Session,
+    source: &str,
+    cli_options: &CliOptions,
+) -> Result<Vec<IrFunction>, Vec<CompilerError>> {
+    let mut translation_unit = session.parse(source)?;
+    let symboltable = session.analyze(&mut translation_unit)?;
+    if cli_options.dump_ast {
+        println!("{}", format_translationunit(&translation_unit));
+        println!("\n\n{}", symboltable);
+    }
 
-    out += &format!("   {} {}\n", format!("{:>4} |", line).bright_black(), line_str);
+    let ir = session.lower(&translation_unit).map_err(|error| vec![error])?;
+    let statistics = session.statistics();
+    if cli_options.stats && !statistics.functions.is_empty() {
+        println!("\n------- Statistics -------\n");
+        for function in &statistics.functions {
+            println!(
+                "`{}`: {} statements eliminated, frame {} -> {} bytes",
+                function.name, function.eliminated, function.frame_before, function.frame_after
+            );
+        }
+        let total: usize = statistics.functions.iter().map(|function| function.eliminated).sum();
+        println!(
+            "total: {total} statements eliminated, {} calls inlined",
+            statistics.inlined
+        );
+    }
+    if cli_options.dump_ir {
+        println!("\n------- Intermediate Representation (IR) -------\n");
+        for function in &ir {
+            println!("{function}");
+        }
+    }
+    Ok(ir)
+}
 
-    let line_len = line_str.chars().count();
-    let start_idx = col.saturating_sub(1);
+/// Prints the warnings of a compilation, and its errors, if any. Remarks are only printed
+/// with `-Rpass`.
+fn report(path: &Path, source: &str, diagnostics: &[Diagnostic], errors: &[CompilerError], cli_options: &CliOptions) {
+    let errors = errors.iter().map(CompilerError::to_diagnostic);
+    for diagnostic in diagnostics.iter().cloned().chain(errors) {
+        if diagnostic.severity == Severity::Remark && !cli_options.remarks {
+            continue;
+        }
+        eprintln!("{}", render::render(&diagnostic, path, source));
+        if cli_options.parseable_fixits {
+            eprint!("{}", render::parseable_fixits(&diagnostic, path));
+        }
+    }
+}
 
-    let width = if span.end.line == span.start.line {
-        span.end.column.saturating_sub(span.start.column)
-    } else {
-        line_len.saturating_sub(start_idx)
+/// Compiles a source file into assembly (or LLVM IR or C, when asked for), or into an object
+/// file with the integrated assembler if `object` is set. Diagnostics are printed on the way.
+fn compile_file(path: &Path, cli_options: &CliOptions, object: bool) -> Option<Vec<u8>> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: cannot read {}: {error}", path.display());
+            return None;
+        }
     };
+    let assembly = !object && !cli_options.emit_llvm && !cli_options.emit_c;
+    let debug = (assembly && cli_options.debug).then(|| DebugInfo {
+        file: path.display().to_string(),
+        directory: std::env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default(),
+    });
+    let options = cli_options.compiler_options().with_debug_info(debug);
+    let mut session = Session::new(options, cli_options.target.as_ref());
 
-    let width = width.min(line_len.saturating_sub(start_idx));
-
-    let mut underline = String::new();
-    for ch in line_str.chars().take(start_idx) {
-        underline.push(if ch == '\t' { '\t' } else { ' ' });
-    }
-    // A column past the end of the line still needs its remaining padding.
-    underline.push_str(&" ".repeat(start_idx.saturating_sub(line_len)));
-
-    underline.push('^');
-    underline.push_str(&"^".repeat(width.saturating_sub(1)));
+    let result = front_end(&mut session, &source, cli_options).and_then(|ir| {
+        let output = if object {
+            session.codegen_object(&ir)
+        } else if cli_options.emit_llvm {
+            session.codegen_llvm_ir(&ir).map(String::into_bytes)
+        } else if cli_options.emit_c {
+            session.codegen_c(&ir).map(String::into_bytes)
+        } else {
+            session.codegen(&ir).map(|asm| {
+                if cli_options.dump_asm {
+                    println!("------- Assembly -------\n\n{}", asm);
+                }
+                asm.into_bytes()
+            })
+        };
+        output.map_err(|error| vec![error])
+    });
 
-    out += &format!("   {} {}\n", "     |".bright_black(), underline.color(accent));
+    report(
+        path,
+        &source,
+        session.diagnostics(),
+        result.as_ref().err().map_or(&[], Vec::as_slice),
+        cli_options,
+    );
+    result.ok()
+}
 
-    out
+fn write_output(path: &Path, contents: &[u8]) -> bool {
+    match fs::write(path, contents) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("failed to write {}: {e}", path.display());
+            false
+        }
+    }
 }
 
-fn format_error(error: &CompilerError, path: &Path, line_str: &str) -> String {
-    let span = error.span.unwrap_or_default();
-    let kind_str = match error.kind {
-        CompilerErrorKind::InternalError => "internal error",
-        CompilerErrorKind::TokenizerError => "tokenizer error",
-        CompilerErrorKind::SyntaxError => "syntax error",
-        CompilerErrorKind::SemanticError => "semantic error",
+/// Turns a source or assembly file into the object file `out`. Source files go through the
+/// integrated assembler where the target has one; with `-g`, or without one, the assembly
+/// goes through the system assembler instead.
+fn object_file(path: &Path, out: &Path, cli_options: &CliOptions, temps: &mut TempDir) -> bool {
+    let target = cli_options.target.as_ref();
+    if InputKind::of(path) == InputKind::Assembly {
+        return run_cc(&["-c".into(), path.into(), "-o".into(), out.into()]);
+    }
+    if target.object_format() == ObjectFormat::Wasm {
+        eprintln!("error: {} has no object files; use -S", target.triple());
+        return false;
+    }
+    if target.has_integrated_assembler() && !cli_options.debug {
+        return compile_file(path, cli_options, true).is_some_and(|object| write_output(out, &object));
+    }
+    let Some(assembly) = compile_file(path, cli_options, false) else {
+        return false;
     };
-
-    format_diagnostic("error", Color::Red, kind_str, &error.message, span, path, line_str)
+    let asm = temps.file(path, "s");
+    write_output(&asm, &assembly) && run_cc(&["-c".into(), asm.into(), "-o".into(), out.into()])
 }
 
-fn format_warning(warning: &Diagnostic, path: &Path, line_str: &str) -> String {
-    let span = warning.span.unwrap_or_default();
-    let kind_str = match warning.kind {
-        DiagnosticKind::Warning => "warning",
+/// Interprets the program's IR and returns its exit status; a trap is reported like an
+/// abort, with status 134.
+fn run_file(path: &PathBuf, cli_options: &CliOptions) -> i32 {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: cannot read {}: {error}", path.display());
+            return 1;
+        }
     };
+    let mut session = Session::new(cli_options.compiler_options(), cli_options.target.as_ref());
+    let result = front_end(&mut session, &source, cli_options);
 
-    format_diagnostic(
-        "warning",
-        Color::Yellow,
-        kind_str,
-        &warning.message,
-        span,
+    report(
         path,
-        line_str,
-    )
-}
-
-fn compile_file(path: &PathBuf, cli_options: &CliOptions) -> bool {
-    let source = fs::read_to_string(path).unwrap();
-    let (diagnostics, result) = Compiler::compile(
-        source.as_str(),
-        cli_options.dump_ast,
-        cli_options.dump_ir,
-        cli_options.dump_asm,
+        &source,
+        session.diagnostics(),
+        result.as_ref().err().map_or(&[], Vec::as_slice),
+        cli_options,
     );
+    let Ok(ir) = result else {
+        return 1;
+    };
 
-    // Print any warnings collected during compilation (non-fatal).
-    for warning in &diagnostics {
-        let line = warning
-            .span
-            .and_then(|span| source.lines().nth(span.start.line.saturating_sub(1)))
-            .unwrap_or("");
-        eprintln!("{}", format_warning(warning, path, line));
-    }
-
-    match result {
-        Ok(assembly) => {
-            // Write the `.s` when asked: `-o <path>`, else default to `<input>.s`.
-            if cli_options.emit_asm || cli_options.output.is_some() {
-                let out_path = cli_options.output.clone().unwrap_or_else(|| path.with_extension("s"));
-                if let Err(e) = fs::write(&out_path, assembly) {
-                    eprintln!("failed to write {}: {e}", out_path.display());
-                    return false;
-                }
-            }
-            true
-        }
-        Err(error) => {
-            if let Some(span) = error.span {
-                // Saturate line 0 and tolerate out-of-range lines so a bogus
-                // location degrades the diagnostic instead of panicking.
-                let line = source.lines().nth(span.start.line.saturating_sub(1)).unwrap_or("");
-                eprintln!("{}", format_error(&error, path, line));
-            } else {
-                eprintln!("error: {}", error.message);
-            }
-            false
+    let stdout = io::stdout();
+    let status = Interpreter::new(&ir, stdout.lock()).run_main();
+    match status {
+        Ok(status) => status,
+        Err(trap) => {
+            eprintln!("{}: {trap}", "trap".bold().color(Color::Red));
+            134
         }
     }
 }
 
+/// Takes every input as far as the stage asks for, and links them unless told not to.
 fn run(cli_options: &CliOptions) -> bool {
+    let stage = cli_options.stage();
+    let kinds: Vec<InputKind> = cli_options.paths.iter().map(|path| InputKind::of(path)).collect();
+
+    // `-o` names the one output file; with `-S` or `-c` there is one per input
+    let outputs = match stage {
+        Stage::Dump | Stage::Link => 0,
+        Stage::Assembly => kinds.iter().filter(|kind| **kind == InputKind::Source).count(),
+        Stage::Object => kinds.iter().filter(|kind| **kind != InputKind::Object).count(),
+    };
+    if cli_options.output.is_some() && outputs > 1 {
+        eprintln!("error: cannot specify -o when generating multiple output files");
+        return false;
+    }
+    if stage == Stage::Link && cli_options.paths.is_empty() {
+        eprintln!("error: no input files");
+        return false;
+    }
+
+    let mut temps = match TempDir::new() {
+        Ok(temps) => temps,
+        Err(error) => {
+            eprintln!("error: cannot create a temporary directory: {error}");
+            return false;
+        }
+    };
     let mut success = true;
-    for path in &cli_options.paths {
-        success &= compile_file(&path, cli_options);
+    let mut link_inputs: Vec<OsString> = Vec::new();
+    for (path, kind) in cli_options.paths.iter().zip(kinds) {
+        match (stage, kind) {
+            (Stage::Dump, InputKind::Source) => success &= compile_file(path, cli_options, false).is_some(),
+            // Write the `.s` when asked: `-o <path>`, else default to `<input>.s` (`.wat` for
+            // Wasm, `.ll` for LLVM IR, `.out.c` for C so the input isn't overwritten).
+            (Stage::Assembly, InputKind::Source) => {
+                let extension = if cli_options.emit_llvm {
+                    "ll"
+                } else if cli_options.emit_c {
+                    "out.c"
+                } else {
+                    cli_options.target.asm_extension()
+                };
+                let out = cli_options
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| path.with_extension(extension));
+                success &= compile_file(path, cli_options, false).is_some_and(|output| write_output(&out, &output));
+            }
+            (Stage::Object, InputKind::Source | InputKind::Assembly) => {
+                let out = cli_options
+                    .output
+                    .clone()
+                    .or_else(|| Some(PathBuf::from(path.file_name()?).with_extension("o")));
+                let Some(out) = out else {
+                    eprintln!("error: {}: no file name for the object file; use -o", path.display());
+                    success = false;
+                    continue;
+                };
+                success &= object_file(path, &out, cli_options, &mut temps);
+            }
+            (Stage::Link, InputKind::Source) => {
+                let out = temps.file(path, "o");
+                success &= object_file(path, &out, cli_options, &mut temps);
+                link_inputs.push(out.into());
+            }
+            (Stage::Link, InputKind::Assembly | InputKind::Object) => link_inputs.push(path.into()),
+            (Stage::Dump | Stage::Assembly | Stage::Object, _) => {
+                eprintln!(
+                    "warning: {}: linker input unused because linking is not done",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    if stage != Stage::Link || !success {
+        return success;
     }
-    success
+    let output = cli_options.output.clone().unwrap_or_else(|| PathBuf::from("a.out"));
+    link_inputs.extend(cli_options.linker_args.iter().map(OsString::from));
+    link_inputs.extend(["-o".into(), output.into()]);
+    run_cc(&link_inputs)
 }
 
+/// The passes walk the syntax tree recursively, and in a debug build a program nested as deep
+/// as the parser allows needs more stack than the main thread has
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
 fn main() {
-    let cli_options = parse_cli(std::env::args().skip(1).collect()).unwrap();
+    let compiler = thread::Builder::new().stack_size(STACK_SIZE).spawn(cli_main);
+    let status = compiler.and_then(|compiler| compiler.join().map_err(|_| io::Error::other("the compiler crashed")));
+
+    match status {
+        Ok(status) => std::process::exit(status),
+        Err(error) => {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Does what the command line asks and returns the exit status
+fn cli_main() -> i32 {
+    let cli_options = match parse_cli(std::env::args().skip(1).collect()) {
+        Ok(cli_options) => cli_options,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return 1;
+        }
+    };
+
+    if let Some(code) = cli_options.explain {
+        print!("{}", code.explanation());
+        return 0;
+    }
+
+    if cli_options.run {
+        let [path] = cli_options.paths.as_slice() else {
+            eprintln!("run expects exactly one source file");
+            return 1;
+        };
+        return run_file(path, &cli_options);
+    }
+
     let start = Instant::now();
 
     let success = run(&cli_options);
 
-    let end = Instant::now() - start;
-    println!("Compilation took {:?}", end);
+    if cli_options.time_report {
+        eprintln!("Compilation took {:?}", start.elapsed());
+    }
 
-    if !success {
-        std::process::exit(1);
+    if success {
+        0
+    } else {
+        1
     }
 }
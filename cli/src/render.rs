@@ -0,0 +1,237 @@
+//! Prints diagnostics as snippets of the source: the lines the diagnostic is about, with its
+//! own span underlined with `^` and every label's span with `-`, followed by its notes and a
+//! preview of its fix-its.
+
+use colored::{Color, ColoredString, Colorize};
+use fbcc::analysis::node::Span;
+use fbcc::core::errors::{Diagnostic, FixIt, Severity};
+use std::path::Path;
+
+/// Lines of a span spread over more than this many lines are elided in the middle
+const MAX_SPAN_LINES: usize = 4;
+
+/// A span to underline, with `^` for the diagnostic's own span and `-` for a label's.
+struct Annotation<'a> {
+    span: Span,
+    primary: bool,
+    message: Option<&'a str>,
+}
+
+pub fn render(diagnostic: &Diagnostic, path: &Path, source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let accent = accent(diagnostic.severity);
+
+    let mut annotations: Vec<Annotation> = diagnostic
+        .span
+        .map(|span| Annotation {
+            span,
+            primary: true,
+            message: None,
+        })
+        .into_iter()
+        .chain(diagnostic.labels.iter().map(|label| Annotation {
+            span: label.span,
+            primary: false,
+            message: Some(&label.message),
+        }))
+        .collect();
+    // Drop annotations whose start line is out of range (including line 0) so a bogus
+    // location degrades the diagnostic instead of panicking.
+    annotations.retain(|annotation| (1..=lines.len()).contains(&annotation.span.start.line));
+
+    let last_line = annotations
+        .iter()
+        .map(|annotation| annotation.span.end.line.min(lines.len()))
+        .chain(diagnostic.fixits.iter().map(|fixit| fixit.span.end.line))
+        .max()
+        .unwrap_or(0);
+    let gutter = " ".repeat(last_line.to_string().len());
+    let bar = format!("{gutter} |").bright_blue().bold();
+
+    let code = diagnostic.code.map(|code| format!("[{code}]")).unwrap_or_default();
+    let mut out = format!(
+        "{}: {}\n",
+        format!("{}{code}", diagnostic.severity).bold().color(accent),
+        diagnostic.message.bold()
+    );
+    let location = match diagnostic.span {
+        Some(span) => format!("{}:{}:{}", path.display(), span.start.line, span.start.column),
+        None => path.display().to_string(),
+    };
+    out += &format!("{gutter}{} {location}\n", "-->".bright_blue().bold());
+
+    if !annotations.is_empty() {
+        out += &format!("{bar}\n");
+        out += &snippet(&annotations, &lines, &gutter, accent);
+    }
+    if !diagnostic.notes.is_empty() {
+        if annotations.is_empty() {
+            out += &format!("{bar}\n");
+        }
+        for note in &diagnostic.notes {
+            out += &format!("{gutter} {} {}: {note}\n", "=".bright_blue().bold(), "note".bold());
+        }
+    }
+    for fixit in &diagnostic.fixits {
+        out += &suggestion(fixit, &lines, &gutter);
+    }
+    out
+}
+
+/// The fix-its of a diagnostic in the format of clang's `-fdiagnostics-parseable-fixits`,
+/// for editors to apply.
+pub fn parseable_fixits(diagnostic: &Diagnostic, path: &Path) -> String {
+    diagnostic
+        .fixits
+        .iter()
+        .map(|fixit| {
+            let (start, end) = (fixit.span.start, fixit.span.end);
+            format!(
+                "fix-it:{:?}:{{{}:{}-{}:{}}}:{:?}\n",
+                path.display().to_string(),
+                start.line,
+                start.column,
+                end.line,
+                end.column,
+                fixit.replacement
+            )
+        })
+        .collect()
+}
+
+fn accent(severity: Severity) -> Color {
+    match severity {
+        Severity::Error => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Note => Color::Green,
+        Severity::Remark => Color::Cyan,
+    }
+}
+
+/// The source lines the annotations cover, each followed by the underlines on it.
+fn snippet(annotations: &[Annotation], lines: &[&str], gutter: &str, accent: Color) -> String {
+    let mut shown: Vec<usize> = Vec::new();
+    for annotation in annotations {
+        let (start, end) = (annotation.span.start.line, annotation.span.end.line.min(lines.len()));
+        if end.saturating_sub(start) >= MAX_SPAN_LINES {
+            shown.extend([start, start + 1, end]);
+        } else {
+            shown.extend(start..=end.max(start));
+        }
+    }
+    shown.sort_unstable();
+    shown.dedup();
+
+    let mut out = String::new();
+    let mut previous = None;
+    for &line in &shown {
+        match previous {
+            // A single hidden line takes as much room as the `...` replacing it
+            Some(previous) if line == previous + 2 => out += &source_line(previous + 1, lines[previous], gutter),
+            Some(previous) if line > previous + 2 => out += &format!("{}\n", "...".bright_blue().bold()),
+            _ => {}
+        }
+        let text = lines[line - 1];
+        out += &source_line(line, text, gutter);
+
+        let mut underlines: Vec<(usize, &Annotation)> = annotations
+            .iter()
+            .filter(|annotation| {
+                (annotation.span.start.line..=annotation.span.end.line.max(annotation.span.start.line)).contains(&line)
+            })
+            .map(|annotation| (columns(annotation.span, line, text).0, annotation))
+            .collect();
+        underlines.sort_by_key(|&(column, annotation)| (!annotation.primary, column));
+        for (_, annotation) in underlines {
+            let (start, end) = columns(annotation.span, line, text);
+            let (mark, color) = if annotation.primary {
+                ('^', accent)
+            } else {
+                ('-', Color::BrightBlue)
+            };
+            let mut underline = mark.to_string().repeat(end - start);
+            // Only the last line of a span carries its message
+            if let Some(message) = annotation.message.filter(|_| line >= annotation.span.end.line) {
+                underline = format!("{underline} {message}");
+            }
+            out += &format!(
+                "{} {}{}\n",
+                format!("{gutter} |").bright_blue().bold(),
+                padding(text, start),
+                underline.color(color).bold()
+            );
+        }
+        previous = Some(line);
+    }
+    out
+}
+
+fn source_line(line: usize, text: &str, gutter: &str) -> String {
+    let number: ColoredString = format!("{line:>width$} |", width = gutter.len()).bright_blue().bold();
+    format!("{number} {text}\n")
+}
+
+/// The character columns, from 0 and end exclusive, that `span` covers on `line`, whose
+/// text is `text`. The lines in the middle of a span are underlined from their first
+/// non-blank character.
+fn columns(span: Span, line: usize, text: &str) -> (usize, usize) {
+    let length = text.chars().count();
+    let indent = text.chars().take_while(|ch| ch.is_whitespace()).count();
+    let start = if line == span.start.line {
+        character(text, span.start.column)
+    } else {
+        indent
+    };
+    let end = if line == span.end.line || span.end.line < span.start.line {
+        character(text, span.end.column)
+    } else {
+        length
+    };
+    // Even an empty span, like the point where something is missing, gets one mark
+    (start, end.max(start + 1))
+}
+
+/// The character index of the 1-based byte column `column` of `text`.
+fn character(text: &str, column: usize) -> usize {
+    let offset = column.saturating_sub(1);
+    text.get(..offset).map_or(offset, |prefix| prefix.chars().count())
+}
+
+/// Blanks as wide as the first `width` characters of `text`, keeping its tabs so that the
+/// underline lines up with the text.
+fn padding(text: &str, width: usize) -> String {
+    let mut padding: String = text
+        .chars()
+        .take(width)
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+    padding.extend(std::iter::repeat_n(' ', width.saturating_sub(text.chars().count())));
+    padding
+}
+
+/// `help: <message>` and the line the fix-it changes, as it reads with the fix-it applied,
+/// with `+` under inserted text and `~` under replaced text.
+fn suggestion(fixit: &FixIt, lines: &[&str], gutter: &str) -> String {
+    let mut out = format!("{}: {}\n", "help".bold().bright_cyan(), fixit.message);
+    let (start, end) = (fixit.span.start, fixit.span.end);
+    if start.line != end.line || !(1..=lines.len()).contains(&start.line) {
+        return out;
+    }
+
+    let text = lines[start.line - 1];
+    let (from, to) = (character(text, start.column), character(text, end.column));
+    let before: String = text.chars().take(from).collect();
+    let after: String = text.chars().skip(to).collect();
+    let mark = if from == to { '+' } else { '~' };
+    let width = fixit.replacement.chars().count().max(1);
+
+    out += &format!("{}\n", format!("{gutter} |").bright_blue().bold());
+    out += &source_line(start.line, &format!("{before}{}{after}", fixit.replacement), gutter);
+    out += &format!(
+        "{} {}{}\n",
+        format!("{gutter} |").bright_blue().bold(),
+        padding(&before, from),
+        mark.to_string().repeat(width).green().bold()
+    );
+    out
+}
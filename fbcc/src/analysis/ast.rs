@@ -259,12 +259,22 @@ pub enum FunctionSpecifier {
     NoReturn,
 }
 
+/// GNU attributes the compiler understands; the rest are parsed and ignored.
+#[derive(Debug, Clone)]
+pub enum Attribute {
+    AlwaysInline,
+    NoInline,
+    Unknown(String),
+}
+
 #[derive(Debug, Clone)]
 pub enum DeclarationSpecifier {
     StorageClassSpecifier(StorageClassSpecifier),
     TypeSpecifier(TypeSpecifier),
     TypeQualifier(TypeQualifier),
     FunctionSpecifier(FunctionSpecifier),
+    /// GNU `__attribute__((...))`, one specifier per attribute in the list
+    Attribute(Attribute),
     // TODO: Add alignment specifier, etc. according to C17 standard
 }
 
@@ -410,14 +420,15 @@ pub enum Initializer {
 }
 
 /// Grammar for Translation Unit according to C17 ISO standard:
-///      translation-unit:
-///           external-declaration
-///           translation-unit external-declaration
-///
-///      external-declaration:
-///           function-definition
-///           declaration
+/// ```text
+/// translation-unit:
+///      external-declaration
+///      translation-unit external-declaration
 ///
+/// external-declaration:
+///      function-definition
+///      declaration
+/// ```
 /// This is the topmost Node in the hierarchy of AST as it represents the entire file
 #[derive(Debug)]
 pub struct TranslationUnit {
@@ -455,6 +466,9 @@ impl fmt::Display for DeclarationSpecifier {
             DeclarationSpecifier::StorageClassSpecifier(storagespec) => {
                 write!(f, "StorageClassSpecifier -> {:?}", storagespec)
             }
+            DeclarationSpecifier::Attribute(attribute) => {
+                write!(f, "Attribute -> {:?}", attribute)
+            }
         }
     }
 }
@@ -873,7 +887,9 @@ pub fn display_funcdeclarator(declarator: &FunctionDeclarator, span: Span) {
                     Declarator::DirectDeclarator(paramidentifier) => {
                         add_leaf!("DirectDeclarator -> \"{}\" {}", paramidentifier, paramdecl.span)
                     }
-                    _ => panic!("Parameter Declarator should not be Function Declarator!"),
+                    Declarator::FunctionDeclarator(paramfuncdecl) => {
+                        display_funcdeclarator(paramfuncdecl, paramdecl.span)
+                    }
                 },
                 None => add_leaf!("DirectDeclarator -> None"),
             }
@@ -911,31 +927,29 @@ pub fn display_declaration(declaration: &Declaration, span: &Span) {
     }
 }
 
-pub fn display_translationunit(tunit: &TranslationUnit) {
-    defer_print!();
-    add_branch!("TranslationUnit");
-    for extdecl in &tunit.external_declarations {
-        match &extdecl.node {
-            ExternalDeclaration::Declaration(decl) => display_declaration(decl, &extdecl.span),
-            ExternalDeclaration::FunctionDefinition(funcdef) => {
-                add_branch!("FunctionDefinition {}", extdecl.span);
-                {
-                    add_branch!("FunctionDeclaration");
-                    display_declspec(&funcdef.specifiers);
-                    display_funcdeclarator(&funcdef.declarator.node, funcdef.declarator.span);
-                }
+/// Renders the syntax tree of a translation unit, as `--dump-ast` shows it.
+pub fn format_translationunit(tunit: &TranslationUnit) -> String {
+    {
+        add_branch!("TranslationUnit");
+        for extdecl in &tunit.external_declarations {
+            match &extdecl.node {
+                ExternalDeclaration::Declaration(decl) => display_declaration(decl, &extdecl.span),
+                ExternalDeclaration::FunctionDefinition(funcdef) => {
+                    add_branch!("FunctionDefinition {}", extdecl.span);
+                    {
+                        add_branch!("FunctionDeclaration");
+                        display_declspec(&funcdef.specifiers);
+                        display_funcdeclarator(&funcdef.declarator.node, funcdef.declarator.span);
+                    }
 
-                add_branch!("FunctionBody");
-                if let Statement::CompoundStatement(block) = &funcdef.body.node {
-                    if block.is_empty() {
-                        add_leaf!("Empty");
-                    } else {
-                        display_statement(&funcdef.body.node, &funcdef.body.span);
+                    add_branch!("FunctionBody");
+                    match &funcdef.body.node {
+                        Statement::CompoundStatement(block) if block.is_empty() => add_leaf!("Empty"),
+                        body => display_statement(body, &funcdef.body.span),
                     }
-                } else {
-                    panic!("FunctionBody must be a compound statement");
                 }
             }
         }
     }
+    default_tree().string()
 }
@@ -2,7 +2,7 @@
 
 use crate::analysis::node::{Location, Node, Span};
 use crate::analysis::tokenizer::{Keyword, TokenType, Tokenizer};
-use crate::core::errors::{CompilerError, CompilerErrorKind};
+use crate::core::errors::{CompilerError, CompilerErrorKind, ErrorCode, FixIt};
 
 use crate::analysis::ast::*;
 use crate::core::typedefs::*;
@@ -88,6 +88,32 @@ fn token2unaryop(token: &TokenType) -> Option<UnaryOperator> {
     Some(unaryop)
 }
 
+/// Token -> Binary Operator, with its precedence; higher binds tighter
+fn token2binaryop(token: &TokenType) -> Option<(BinaryOperator, u8)> {
+    let binaryop = match token {
+        TokenType::LogicalOrOperator => (BinaryOperator::LogicalOr, 0),
+        TokenType::LogicalAndOperator => (BinaryOperator::LogicalAnd, 1),
+        TokenType::BitwiseOrOperator => (BinaryOperator::BitwiseOr, 2),
+        TokenType::ExclusiveOrOperator => (BinaryOperator::BitwiseXor, 3),
+        TokenType::BitwiseAndOperator => (BinaryOperator::BitwiseAnd, 4),
+        TokenType::EqualityOperator => (BinaryOperator::Equals, 5),
+        TokenType::NotEqualsOperator => (BinaryOperator::NotEquals, 5),
+        TokenType::LessThanOperator => (BinaryOperator::Less, 6),
+        TokenType::LessThanEqualsOperator => (BinaryOperator::LessOrEqual, 6),
+        TokenType::GreaterThanOperator => (BinaryOperator::Greater, 6),
+        TokenType::GreaterThanEqualsOperator => (BinaryOperator::GreaterOrEqual, 6),
+        TokenType::LeftShiftOperator => (BinaryOperator::ShiftLeft, 7),
+        TokenType::RightShiftOperator => (BinaryOperator::ShiftRight, 7),
+        TokenType::Plus => (BinaryOperator::Plus, 8),
+        TokenType::Minus => (BinaryOperator::Minus, 8),
+        TokenType::Asterisk => (BinaryOperator::Multiply, 9),
+        TokenType::Slash => (BinaryOperator::Divide, 9),
+        TokenType::Percent => (BinaryOperator::Modulo, 9),
+        _ => return None,
+    };
+    Some(binaryop)
+}
+
 /// Token -> Assignment Binary Operator
 fn token_to_assign_operator(token: &TokenType) -> Option<AssignOperator> {
     let binaryop = match token {
@@ -108,6 +134,25 @@ fn token_to_assign_operator(token: &TokenType) -> Option<AssignOperator> {
     Some(binaryop)
 }
 
+/// Wraps `expression` in a cast to each of `typenames`, the innermost last, whose `(` start
+/// at `starts`. Every cast spans up to `end`.
+fn wrap_in_casts(
+    mut expression: Node<Expression>,
+    mut typenames: Vec<Node<TypeName>>,
+    mut starts: Vec<Location>,
+    end: Location,
+) -> Node<Expression> {
+    // Pop all the typenames and create cast expressions
+    while let (Some(typename), Some(start)) = (typenames.pop(), starts.pop()) {
+        // Span = Start of the corresponding OpenParenthesis -> End of the cast expression
+        expression = Node::new(
+            Expression::Cast(Box::new(CastExpression { typename, expression })),
+            Span::new(start, end),
+        )
+    }
+    expression
+}
+
 fn is_expr_unary(expression: &Expression) -> bool {
     match expression {
         Expression::Identifier(_)
@@ -131,117 +176,236 @@ fn is_expr_unary(expression: &Expression) -> bool {
     }
 }
 
+/// How deeply statements and expressions may nest. The parser and the passes after it walk the
+/// tree recursively, so a program nested deeper than this is an error rather than a stack
+/// overflow. Chains of left-associative operators and of `else if` are walked in loops and
+/// don't count. This is well past the 63 parentheses and 127 blocks C11 (5.2.4.1) requires,
+/// and needs more stack than a thread gets by default in a debug build
+pub const MAX_NESTING_DEPTH: usize = 256;
+
 #[derive(Default)]
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
+    errors: Vec<CompilerError>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(src: &'a str) -> Self {
         Parser {
             tokenizer: Tokenizer::new(src),
+            errors: Vec::new(),
+            depth: 0,
         }
     }
 
     /// The main parse function that uses the tokenizer to generate an Abstract Syntax Tree
-    pub fn parse(&mut self) -> Result<TranslationUnit, CompilerError> {
+    ///
+    /// A syntax error doesn't stop the parser: it is recorded, the tokens up to the next `;`,
+    /// `}` or declaration are skipped, and parsing carries on from there, so that every error
+    /// in the file is reported at once.
+    pub fn parse(&mut self) -> Result<TranslationUnit, Vec<CompilerError>> {
         let mut tranlation_unit = TranslationUnit {
             external_declarations: Vec::new(),
         };
 
-        while self.tokenizer.peek_token()?.is_some() {
-            // Parse a Declaration
-            // Irrespective of the next part of the program being a declaration or a function definition...
-            // We need to parse some declaration-like code
-            let declaration = self.parse_declaration()?;
+        loop {
+            match self.tokenizer.peek_token() {
+                Ok(None) => break,
+                // A `}` without a matching `{` at file scope, skip it instead of complaining
+                // about it after every error
+                Ok(Some((TokenType::CloseBrace, ..))) if !self.errors.is_empty() => {
+                    let _ = self.tokenizer.next_token();
+                    continue;
+                }
+                _ => {}
+            }
 
-            match self.tokenizer.next_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::OpenBrace => {
-                        // In case of a function definition...
-                        // It is compulsory to have exactly 1 declarator which is the function declarator
-                        if declaration.init_declarators.len() != 1 {
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SyntaxError,
-                                message: "Unexpected `{`, expected a semicolon".to_string(),
-                                span: Some(Span::new(start, end)),
-                            });
-                        }
+            let start = self.tokenizer.get_cidx();
+            match self.parse_external_declaration() {
+                Ok(external_declaration) => tranlation_unit.external_declarations.push(external_declaration),
+                Err(error) => {
+                    self.depth = 0;
+                    self.recover(error, start)
+                }
+            }
+        }
 
-                        // As it is confirmed by the previous if statement that there is only 1 declarator
-                        // We can grab it from the init declarator list
-                        let fdeclarator = &declaration.init_declarators.first().unwrap().node.declarator;
-
-                        // Parse a function definition
-                        if let Declarator::FunctionDeclarator(fdecl) = &fdeclarator.node {
-                            // A function body must be a compound statement
-                            let funcbody = self.parse_compound_stmt()?;
-                            // Consume the CloseBrace
-                            let (_, brace_end) = self.accept_token(TokenType::CloseBrace)?;
-
-                            // Calculate the span of the function definition
-                            // The span of the function definition is from the start of the declaration
-                            // to the end of the definition, i.e., the CloseBrace end index
-                            let declspan_start = declaration.specifiers[0].span.start;
-                            let funcdef_span = Span::new(declspan_start, brace_end);
-
-                            // Create and push the function definition that we just parsed
-                            tranlation_unit.external_declarations.push(Node::new(
-                                ExternalDeclaration::FunctionDefinition(FunctionDefinition {
-                                    specifiers: declaration.specifiers,
-                                    declarator: Node::new(
-                                        // Cloning here to avoid ownership issues
-                                        // Will this be a performance overhead?
-                                        fdecl.clone(),
-                                        fdeclarator.span,
-                                    ),
-                                    body: funcbody,
-                                }),
-                                funcdef_span,
-                            ));
-                        } else {
-                            // If `{` is encountered, then it must be a function definition
-                            // Reaching this line of code indicates the C code looks like this:
-                            // const int variable_declaration{};
-                            //                               ^^ Unexpected `{`
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SyntaxError,
-                                message: "Unexpected token: `{`, statement is not a valid function declaration"
-                                    .to_string(),
-                                span: Some(Span::new(start, end)),
-                            });
-                        }
-                    }
-                    TokenType::Semicolon => {
-                        // Calculate the span of the declaration
-                        // span = start of the first DeclarationSpecifier in the declaration -> end of the semicolon
-                        let declspan_start = declaration.specifiers.first().unwrap().span.start; // Here we assume that a declaration will always have atleast one specifier
-                        let declspan = Span::new(declspan_start, end);
-
-                        // Create an ExternalDeclaration and push it
-                        tranlation_unit
-                            .external_declarations
-                            .push(Node::new(ExternalDeclaration::Declaration(declaration), declspan));
-                    }
-                    _ => {
+        if self.errors.is_empty() {
+            // Return the entire translation unit AKA the root node of the parse tree
+            Ok(tranlation_unit)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Parses a declaration or a function definition at file scope
+    fn parse_external_declaration(&mut self) -> Result<Node<ExternalDeclaration>, CompilerError> {
+        // Parse a Declaration
+        // Irrespective of the next part of the program being a declaration or a function definition...
+        // We need to parse some declaration-like code
+        let declaration = self.parse_declaration()?;
+
+        match self.tokenizer.peek_token()? {
+            Some((token, start, end)) => match token {
+                TokenType::OpenBrace => {
+                    // In case of a function definition...
+                    // It is compulsory to have exactly 1 declarator which is the function declarator
+                    if declaration.init_declarators.len() != 1 {
                         return Err(CompilerError {
                             kind: CompilerErrorKind::SyntaxError,
-                            message: format!("Expected a `;` or `{{` instead got: {:?}", token),
+                            message: "Unexpected `{`, expected a semicolon".to_string(),
+                            span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::ExpectedToken),
+                            ..Default::default()
+                        });
+                    }
+
+                    // As it is confirmed by the previous if statement that there is only 1 declarator
+                    // We can grab it from the init declarator list
+                    let fdeclarator = &declaration.init_declarators.first().unwrap().node.declarator;
+
+                    // Parse a function definition
+                    if let Declarator::FunctionDeclarator(fdecl) = &fdeclarator.node {
+                        // Consume the OpenBrace
+                        self.tokenizer.next_token()?;
+                        // A function body must be a compound statement
+                        let funcbody = self.parse_compound_stmt()?;
+                        // Consume the CloseBrace
+                        let (_, brace_end) = self.accept_token(TokenType::CloseBrace)?;
+
+                        // Calculate the span of the function definition
+                        // The span of the function definition is from the start of the declaration
+                        // to the end of the definition, i.e., the CloseBrace end index
+                        let declspan_start = declaration.specifiers[0].span.start;
+                        let funcdef_span = Span::new(declspan_start, brace_end);
+
+                        // Create and return the function definition that we just parsed
+                        Ok(Node::new(
+                            ExternalDeclaration::FunctionDefinition(FunctionDefinition {
+                                specifiers: declaration.specifiers,
+                                declarator: Node::new(
+                                    // Cloning here to avoid ownership issues
+                                    // Will this be a performance overhead?
+                                    fdecl.clone(),
+                                    fdeclarator.span,
+                                ),
+                                body: funcbody,
+                            }),
+                            funcdef_span,
+                        ))
+                    } else {
+                        // If `{` is encountered, then it must be a function definition
+                        // Reaching this line of code indicates the C code looks like this:
+                        // const int variable_declaration{};
+                        //                               ^^ Unexpected `{`
+                        Err(CompilerError {
+                            kind: CompilerErrorKind::SyntaxError,
+                            message: "Unexpected token: `{`, statement is not a valid function declaration".to_string(),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         })
                     }
-                },
-                _ => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected a `;` or `{` instead got end of file".to_string(),
-                        span: None,
-                    })
                 }
+                TokenType::Semicolon => {
+                    self.tokenizer.next_token()?;
+
+                    // Calculate the span of the declaration
+                    // span = start of the first DeclarationSpecifier in the declaration -> end of the semicolon
+                    let declspan_start = declaration.specifiers.first().unwrap().span.start; // Here we assume that a declaration will always have atleast one specifier
+                    let declspan = Span::new(declspan_start, end);
+
+                    // Create and return an ExternalDeclaration
+                    Ok(Node::new(ExternalDeclaration::Declaration(declaration), declspan))
+                }
+                _ => Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: format!("Expected a `;` or `{{` instead got: {:?}", token),
+                    span: Some(Span::new(start, end)),
+                    code: Some(ErrorCode::ExpectedToken),
+                    ..Default::default()
+                }),
+            },
+            _ => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected a `;` or `{` instead got end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::ExpectedToken),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Goes one level deeper into the syntax tree, returning the depth to go back to once the
+    /// level is parsed. Errors past [`MAX_NESTING_DEPTH`]
+    fn descend(&mut self) -> Result<usize, CompilerError> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: format!("Nesting is too deep, the limit is {}", MAX_NESTING_DEPTH),
+                span: self
+                    .tokenizer
+                    .peek_token()?
+                    .map(|(_, start, end)| Span::new(start, end)),
+                code: Some(ErrorCode::NestingTooDeep),
+                ..Default::default()
+            });
+        }
+
+        self.depth += 1;
+        Ok(self.depth - 1)
+    }
+
+    /// Records a syntax error and skips ahead to where parsing can sensibly carry on, which is
+    /// after the next `;`, or before the next `}` or declaration. Whole `{ ... }` blocks are
+    /// skipped at once. `start` is where the tokenizer stood before the failed parse began; if
+    /// the parse didn't get past it, the offending token is skipped so that parsing moves on.
+    fn recover(&mut self, error: CompilerError, start: usize) {
+        self.errors.push(error);
+
+        if self.tokenizer.get_cidx() == start {
+            self.skip_token();
+        }
+
+        let mut depth = 0;
+        loop {
+            let token = match self.tokenizer.peek_token() {
+                Ok(Some((token, ..))) => token,
+                Ok(None) => return,
+                // Characters that can't be tokenized are skipped like any other token
+                Err(_) => {
+                    self.tokenizer.skip_char();
+                    continue;
+                }
+            };
+
+            match token {
+                TokenType::Semicolon if depth == 0 => {
+                    self.skip_token();
+                    return;
+                }
+                TokenType::CloseBrace if depth == 0 => return,
+                TokenType::CloseBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.skip_token();
+                        return;
+                    }
+                }
+                TokenType::OpenBrace => depth += 1,
+                TokenType::Keyword(keyword) if depth == 0 && keyword2declspec(&keyword).is_some() => return,
+                _ => {}
             }
+            self.skip_token();
+        }
+    }
+
+    /// Consumes the next token, or the next character if it isn't a valid token
+    fn skip_token(&mut self) {
+        if self.tokenizer.next_token().is_err() {
+            self.tokenizer.skip_char();
         }
-        // Return the entire translation unit AKA the root node of the parse tree
-        Ok(tranlation_unit)
     }
 
     /// Parses a C declaration
@@ -261,10 +425,16 @@ impl<'a> Parser<'a> {
                             kind: CompilerErrorKind::SyntaxError,
                             message: format!("Unexpected keyword: {:?}", keyword),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         })
                     }
                 },
 
+                TokenType::Identifier(identifier) if identifier == "__attribute__" => {
+                    specifiers.extend(self.parse_attributes()?);
+                }
+
                 TokenType::Identifier(identifier) => {
                     // Once we hit an identifier (we should always hit one, if the program is syntactically right)
                     // Parse the declarator
@@ -285,6 +455,8 @@ impl<'a> Parser<'a> {
                                 identifier
                             ),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         });
                     }
                 }
@@ -297,6 +469,8 @@ impl<'a> Parser<'a> {
                         kind: CompilerErrorKind::SyntaxError,
                         message: format!("Unexpected Token: {:?}", token),
                         span: Some(Span::new(start, end)),
+                        code: Some(ErrorCode::UnexpectedToken),
+                        ..Default::default()
                     })
                 }
             }
@@ -306,9 +480,90 @@ impl<'a> Parser<'a> {
             kind: CompilerErrorKind::SyntaxError,
             message: "Unexpected end of file".to_string(),
             span: None,
+            code: Some(ErrorCode::UnexpectedToken),
+            ..Default::default()
         })
     }
 
+    /// Parses a GNU attribute specifier, `__attribute__((name, name(arguments), ...))`, into
+    /// one declaration specifier per attribute. Arguments are skipped.
+    fn parse_attributes(&mut self) -> Result<Vec<Node<DeclarationSpecifier>>, CompilerError> {
+        self.accept_token(TokenType::Identifier("__attribute__".to_string()))?;
+        self.accept_token(TokenType::OpenParenthesis)?;
+        self.accept_token(TokenType::OpenParenthesis)?;
+
+        let mut attributes = Vec::new();
+        loop {
+            let Some((token, start, mut end)) = self.tokenizer.next_token()? else {
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: "Unexpected end of file in attribute list".to_string(),
+                    span: None,
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                });
+            };
+            let name = match token {
+                TokenType::CloseParenthesis => break,
+                // Empty entries are allowed: `__attribute__((, noinline))`
+                TokenType::Comma => continue,
+                TokenType::Identifier(name) => name,
+                // Some attributes are spelled like keywords, e.g. `const`
+                TokenType::Keyword(keyword) => format!("{:?}", keyword).to_lowercase(),
+                _ => {
+                    return Err(CompilerError {
+                        kind: CompilerErrorKind::SyntaxError,
+                        message: format!("Expected an attribute name, instead found: {:?}", token),
+                        span: Some(Span::new(start, end)),
+                        code: Some(ErrorCode::UnexpectedToken),
+                        ..Default::default()
+                    })
+                }
+            };
+
+            // Skip the arguments, keeping track of nested parentheses
+            if let Some((TokenType::OpenParenthesis, ..)) = self.tokenizer.peek_token()? {
+                let mut depth = 0;
+                loop {
+                    match self.tokenizer.next_token()? {
+                        Some((TokenType::OpenParenthesis, ..)) => depth += 1,
+                        Some((TokenType::CloseParenthesis, _, close)) => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = close;
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                        None => {
+                            return Err(CompilerError {
+                                kind: CompilerErrorKind::SyntaxError,
+                                message: format!("Unexpected end of file in the arguments of `{}`", name),
+                                span: Some(Span::new(start, end)),
+                                code: Some(ErrorCode::UnexpectedToken),
+                                ..Default::default()
+                            })
+                        }
+                    }
+                }
+            }
+
+            // GNU accepts every attribute name with or without surrounding double underscores
+            let attribute = match name.trim_start_matches("__").trim_end_matches("__") {
+                "always_inline" => Attribute::AlwaysInline,
+                "noinline" => Attribute::NoInline,
+                _ => Attribute::Unknown(name),
+            };
+            attributes.push(Node::new(
+                DeclarationSpecifier::Attribute(attribute),
+                Span::new(start, end),
+            ));
+        }
+
+        self.accept_token(TokenType::CloseParenthesis)?;
+        Ok(attributes)
+    }
+
     fn parse_init_declarator_list(&mut self) -> Result<Vec<Node<InitDeclarator>>, CompilerError> {
         // init-declarator-list:
         //      init-declarator
@@ -372,6 +627,8 @@ impl<'a> Parser<'a> {
                         kind: CompilerErrorKind::SyntaxError,
                         message: "Expected a semicolon, or an init-declarator, instead found end of file".to_string(),
                         span: None,
+                        code: Some(ErrorCode::ExpectedToken),
+                        ..Default::default()
                     })
                 }
             }
@@ -386,10 +643,13 @@ impl<'a> Parser<'a> {
         //      { initializer-list , }
 
         match self.tokenizer.peek_token()? {
-            Some((TokenType::OpenBrace, _, _)) => {
-                // Parse Initializer-List
-                todo!()
-            }
+            Some((TokenType::OpenBrace, start, end)) => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Initializer lists are not supported yet".to_string(),
+                span: Some(Span::new(start, end)),
+                code: Some(ErrorCode::Unsupported),
+                ..Default::default()
+            }),
             Some(_) => {
                 // Parse an expression
                 // NOTE: self.parse_assignment_expr() to be used instead of self.parse_expr() to
@@ -410,6 +670,8 @@ impl<'a> Parser<'a> {
                 kind: CompilerErrorKind::SyntaxError,
                 message: "Expected assignment expression or an initializer list, instead got end of file".to_string(),
                 span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
             }),
         }
     }
@@ -418,9 +680,11 @@ impl<'a> Parser<'a> {
         // declarator:
         //      pointeropt direct-declarator
 
-        if let Some((token, start, end)) = self.tokenizer.next_token()? {
+        if let Some((token, start, end)) = self.tokenizer.peek_token()? {
             match token {
                 TokenType::Identifier(identifier) => {
+                    self.tokenizer.next_token()?; // Consume the Identifier
+
                     // Decide whether it's a function declarator or a direct declarator
                     match self.tokenizer.peek_token()? {
                         Some((TokenType::OpenParenthesis, _, _)) => {
@@ -438,43 +702,49 @@ impl<'a> Parser<'a> {
                             let fdeclarator_end = if let Some(param) = fdeclarator.parameters.last() { param.span.end } else { end };
 
                             // Return the final function declarator node
-                            return Ok(Node::new(
+                            Ok(Node::new(
                                 Declarator::FunctionDeclarator(fdeclarator),
                                 Span::new(start, fdeclarator_end),
-                            ));
+                            ))
                         },
-                        Some((TokenType::Semicolon | TokenType::Comma | TokenType::Equals, _, _)) => return Ok(Node::new(
+                        Some((TokenType::Semicolon | TokenType::Comma | TokenType::Equals, _, _)) => Ok(Node::new(
                             Declarator::DirectDeclarator(identifier),
                             Span::new(start, end),
                         )),
-                        Some((next, start, _)) => return Err(CompilerError{
+                        Some((next, next_start, next_end)) => Err(CompilerError{
                             kind: CompilerErrorKind::SyntaxError,
                             message: format!("Unexpected token: {:?}, expected a `(` (Function Declarator), or `;` (Direct Declarator)", next),
-                            span: Some(Span::new(start, end))
+                            span: Some(Span::new(next_start, next_end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         }),
-                        None => return Err(CompilerError{
+                        None => Err(CompilerError{
                             kind: CompilerErrorKind::SyntaxError,
                             message: "Unexpected token, expected a `(` (Function Declarator), or `;` (Direct Declarator), instead encountered an End of File".to_string(),
-                            span: None
+                            span: None,
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         })
                     }
                 }
-                _ => panic!("Internal Error: Expected Identifier, but found no token!"),
+                // Pointer and parenthesised declarators aren't supported yet
+                _ => Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: format!("Expected an identifier in the declarator, instead found: {:?}", token),
+                    span: Some(Span::new(start, end)),
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                }),
             }
+        } else {
+            Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected an identifier in the declarator, instead found end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            })
         }
-        panic!("Internal Error: Expected Identifier, but found no token!");
-    }
-
-    fn parse_direct_declarator(&mut self) {
-        // direct-declarator:
-        //      identifier
-        //      ( declarator )
-        //      direct-declarator [ type-qualifier-listopt assignment-expressionopt ]
-        //      direct-declarator [ static type-qualifier-listopt assignment-expression ]
-        //      direct-declarator [ type-qualifier-list static assignment-expression ]
-        //      direct-declarator [ type-qualifier-listopt * ]
-        //      direct-declarator ( parameter-type-list )
-        //      direct-declarator ( identifier-listopt )
     }
 
     fn parse_parameters(&mut self) -> Result<Vec<Node<FunctionParameter>>, CompilerError> {
@@ -519,6 +789,8 @@ impl<'a> Parser<'a> {
                             message: "Expected type specifier for parameter declaration after `,` instead got `)`"
                                 .to_string(),
                             span: None,
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         });
                     }
                     // Return the parameters
@@ -532,6 +804,8 @@ impl<'a> Parser<'a> {
                 kind: CompilerErrorKind::SyntaxError,
                 message: "Missing `)` in the function declaration/definition".to_string(),
                 span: None,
+                code: Some(ErrorCode::ExpectedToken),
+                ..Default::default()
             }),
         }
     }
@@ -553,6 +827,8 @@ impl<'a> Parser<'a> {
                             kind: CompilerErrorKind::SyntaxError,
                             message: format!("Unexpected keyword: {:?}", keyword),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         })
                     }
                 },
@@ -591,6 +867,8 @@ impl<'a> Parser<'a> {
                                 identifier
                             ),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         });
                     }
                 }
@@ -619,6 +897,8 @@ impl<'a> Parser<'a> {
                             message: "Expected a type specifier for parameter declaration, instead found: `,` or `)`"
                                 .to_string(),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         });
                     }
                 }
@@ -630,12 +910,21 @@ impl<'a> Parser<'a> {
         Err(CompilerError {
             kind: CompilerErrorKind::SyntaxError,
             message: "Expected a type specifier, or `,` or `)`".to_string(),
-            span: None,
+            span: self
+                .tokenizer
+                .peek_token()?
+                .map(|(_, start, end)| Span::new(start, end)),
+            code: Some(ErrorCode::UnexpectedToken),
+            ..Default::default()
         })
     }
 
     fn parse_statement(&mut self) -> Result<Node<Statement>, CompilerError> {
-        match self.tokenizer.peek_token()? {
+        let depth = self.descend()?;
+
+        // Statements nest through this function, so each kind of statement is parsed by a
+        // function of its own that only takes up stack while that statement is parsed
+        let statement = match self.tokenizer.peek_token()? {
             Some((token, start, end)) => {
                 match token {
                     TokenType::Keyword(keyword) => {
@@ -647,291 +936,15 @@ impl<'a> Parser<'a> {
 
                         // Parse the statements starting with keywords
                         match keyword {
-                            Keyword::Case => {
-                                // labeled-statement:
-                                //      case constant-expression : statement
-
-                                let constexpr = self.parse_constant_expr()?;
-                                // Consume a colon
-                                self.accept_token(TokenType::Colon)?;
-                                // Parse a statement
-                                let casestmt = self.parse_statement()?;
-
-                                // Calculate the span of the case statement
-                                // Span = Start of the `case` keyword -> End of the case statement
-                                let span = Span::new(start, casestmt.span.end);
-
-                                // Create and return the Case Statement
-                                Ok(Node::new(
-                                    Statement::CaseStatement(Box::new(CaseStatement {
-                                        constexpr,
-                                        statement: casestmt,
-                                    })),
-                                    span,
-                                ))
-                            }
-                            Keyword::Default => {
-                                // labeled-statement:
-                                //      default : statement
-
-                                // Consume a colon
-                                self.accept_token(TokenType::Colon)?;
-
-                                // Parse a statement
-                                let defaultstmt = self.parse_statement()?;
-
-                                // Calculate the span of the default statement
-                                // Span = Start of the `default` keyword -> End of the default statement
-                                let span = Span::new(start, defaultstmt.span.end);
-
-                                // Create and return the Default Statement
-                                Ok(Node::new(Statement::DefaultStatement(Box::new(defaultstmt)), span))
-                            }
-                            Keyword::If => {
-                                // if (<expression>) <statement>
-                                //    ^ Accept this OpenParenthesis
-                                self.accept_token(TokenType::OpenParenthesis)?;
-
-                                // if (<expression>) <statement>
-                                //      ^^^ Parse this expression
-                                let if_expr = self.parse_expr()?;
-
-                                // if (<expression>) <statement>
-                                //                 ^ Accept this CloseParenthesis
-                                self.accept_token(TokenType::CloseParenthesis)?;
-
-                                // if (<expression>) <statement>
-                                //                    ^^^ Parse this statement
-                                let if_block = self.parse_statement()?;
-
-                                let else_block;
-                                let stmt_span;
-
-                                // Check for an else statement and parse it
-                                // Also calculate the span for the entire if statement
-                                if let Some((TokenType::Keyword(Keyword::Else), _, _)) = self.tokenizer.peek_token()? {
-                                    // Consume the Else Token once it is confirmed that it is really an Else Token
-                                    self.tokenizer.next_token()?;
-                                    // Parse the else statement
-                                    let else_stmt = self.parse_statement()?;
-                                    // The span of the entire if statement =
-                                    // Start of if keyword -> End of else statement
-                                    stmt_span = Span::new(start, else_stmt.span.end);
-                                    else_block = Some(else_stmt);
-                                } else {
-                                    // There is no else statement
-                                    else_block = None;
-                                    // The span of the entire if statement =
-                                    // Start of if keyword -> End of if statement
-                                    stmt_span = Span::new(start, if_block.span.end);
-                                }
-
-                                // Create and return the If statement
-                                Ok(Node::new(
-                                    Statement::IfStatement(Box::new(IfStatement {
-                                        condition: if_expr,
-                                        if_block,
-                                        else_block,
-                                    })),
-                                    stmt_span,
-                                ))
-                            }
-                            Keyword::Switch => {
-                                // selection-statement:
-                                //      switch ( expression ) statement
-
-                                // Consume the OpenParenthesis
-                                self.accept_token(TokenType::OpenParenthesis)?;
-                                // Parse the Switch Expression
-                                let switchexpr = self.parse_expr()?;
-                                // Consume the CloseParenthesis
-                                self.accept_token(TokenType::CloseParenthesis)?;
-                                // Parse the switch block/statement
-                                let switchstmt = self.parse_statement()?;
-
-                                // Calculate span of the entire switch statement
-                                // Span of switch statement = (start of the switch keyword, end of the switch statement)
-                                let span = Span::new(start, switchstmt.span.end);
-                                // Create and return the switch statement
-                                Ok(Node::new(
-                                    Statement::SwitchStatement(Box::new(SwitchStatement {
-                                        expression: switchexpr,
-                                        statement: switchstmt,
-                                    })),
-                                    span,
-                                ))
-                            }
-                            Keyword::While => {
-                                // Accept a OpenParenthesis
-                                self.accept_token(TokenType::OpenParenthesis)?;
-                                // Parse the condition inside the while (<expression>) statement
-                                //                                       ^^^
-                                let expression = self.parse_expr()?;
-                                // Accept a CloseParenthesis
-                                self.accept_token(TokenType::CloseParenthesis)?;
-                                // Parse the while statement
-                                let block = self.parse_statement()?;
-
-                                // Calculate the span of the while statement
-                                // Span = Start of the `while` keyword -> End of the statement
-                                let span = Span::new(start, block.span.end);
-
-                                Ok(Node::new(
-                                    Statement::WhileStatement(Box::new(WhileStatement {
-                                        condition: expression,
-                                        statement: block,
-                                    })),
-                                    span,
-                                ))
-                            }
-                            Keyword::Do => {
-                                // iteration-statement:
-                                //      do statement while ( expression ) ;
-
-                                // Parse the do statement
-                                let dostmt = self.parse_statement()?;
-
-                                // Accept `while (`
-                                self.accept_token(TokenType::Keyword(Keyword::While))?;
-                                self.accept_token(TokenType::OpenParenthesis)?;
-                                // Parse the while expression
-                                let doexpr = self.parse_expr()?;
-                                // Accept `)`
-                                self.accept_token(TokenType::CloseParenthesis)?;
-                                // Accept `;`
-                                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                // Calculate span of the entire while statement
-                                // Span of the while statement = Start of do keyword -> End of semicolon after `while` keyword
-                                let span = Span::new(start, semicolon_end);
-
-                                // Create and return the DoWhileStatement
-                                Ok(Node::new(
-                                    Statement::DoWhileStatement(Box::new(WhileStatement {
-                                        statement: dostmt,
-                                        condition: doexpr,
-                                    })),
-                                    span,
-                                ))
-                            }
-                            Keyword::For => {
-                                // Accept `(`
-                                self.accept_token(TokenType::OpenParenthesis)?;
-
-                                let forinitializer: Node<ForInitializer>;
-                                match self.tokenizer.peek_token()? {
-                                    Some((token, peek_start, peek_end)) => {
-                                        // Check if the next token is a DeclarationSpecifier
-                                        if let TokenType::Keyword(keyword) = token {
-                                            if keyword2declspec(&keyword).is_some() {
-                                                // If yes, Expect a ForInitializer::Declaration
-                                                let declaration = self.parse_declaration()?;
-
-                                                // Accept a `;`
-                                                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                                let forinit = ForInitializer::Declaration(declaration);
-                                                let forinit_span = Span::new(peek_start, semicolon_end);
-
-                                                forinitializer = Node::new(forinit, forinit_span);
-                                            } else {
-                                                // This must happen when code may have something like:
-                                                // for (return; i = 0; i++)
-                                                //      ^^ Unexpected keyword which is not a DeclarationSpecifier
-                                                return Err(CompilerError {
-                                                    kind: CompilerErrorKind::SyntaxError,
-                                                    message: format!("Expected a declaration specifier or an expression, instead got unexpected Keyword: {:?}", keyword),
-                                                    span: Some(Span::new(peek_start, peek_end))
-                                                });
-                                            }
-                                        } else if token == TokenType::Semicolon {
-                                            // Consume the first semicolon which follows the ForInitializer
-                                            self.tokenizer.next_token()?;
-
-                                            // Else if the next token is a Semicolon, then return an Empty Initializer
-                                            forinitializer =
-                                                Node::new(ForInitializer::Empty, Span::new(peek_start, peek_start));
-                                        } else {
-                                            // Else expect an expression (like an assignment expression)
-                                            let expression = self.parse_expr()?;
-
-                                            // Accept a `;`
-                                            let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                            // Calculate the span of the ForInitializer
-                                            // Span = Start of expression -> End of the semicolon
-                                            let span = Span::new(expression.span.start, semicolon_end);
-
-                                            // Create and store the ForInitializer
-                                            forinitializer =
-                                                Node::new(ForInitializer::Expression(expression.node), span);
-                                        }
-                                    }
-                                    None => {
-                                        // This error should occur when we encounter an end of file instead of an initializer
-                                        return Err(CompilerError {
-                                            kind: CompilerErrorKind::SyntaxError,
-                                            message: "Expected a for initializer, instead got end of file".to_string(),
-                                            span: None,
-                                        });
-                                    }
-                                }
-
-                                // Parse the For Condition
-                                let condition =
-                                    if let Some((TokenType::Semicolon, _, _)) = self.tokenizer.peek_token()? {
-                                        // If the next token is semicolon then the condition is None
-                                        None
-                                    } else {
-                                        // Else parse the condition expression
-                                        Some(self.parse_expr()?)
-                                    };
-
-                                // Accept a semicolon irrespective of the presence of a for condition
-                                self.accept_token(TokenType::Semicolon)?;
-
-                                // Parse the For Step Expression
-                                let step =
-                                    if let Some((TokenType::CloseParenthesis, _, _)) = self.tokenizer.peek_token()? {
-                                        // If the next token is `)` then the step expression is None
-                                        None
-                                    } else {
-                                        // Else parse the step expression
-                                        Some(self.parse_expr()?)
-                                    };
-
-                                // Accept a `)`
-                                self.accept_token(TokenType::CloseParenthesis)?;
-
-                                // Parse the actual for statement
-                                let statement = self.parse_statement()?;
-
-                                // Calculate the span of the entire ForStatement
-                                // Span = Start of the `for` keyword -> End of the statement
-                                let span = Span::new(start, statement.span.end);
-
-                                // Create and return the actual For Statement
-                                Ok(Node::new(
-                                    Statement::ForStatement(Box::new(ForStatement {
-                                        initializer: forinitializer,
-                                        condition,
-                                        step,
-                                        statement,
-                                    })),
-                                    span,
-                                ))
-                            }
-                            Keyword::Return => {
-                                // Return -> Jump Statement
-                                let expression = self.parse_expr()?;
-                                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                // Calculate span of the entire return statement
-                                // Span of return statement = (start of the return keyword, end of the semicolon token)
-                                let span = Span::new(start, semicolon_end);
-                                // Create and return the actual return statement
-                                Ok(Node::new(Statement::ReturnStatement(expression), span))
-                            }
+                            Keyword::Case => self.parse_case_stmt(start),
+                            Keyword::Default => self.parse_default_stmt(start),
+                            Keyword::If => self.parse_if_stmt(start),
+                            Keyword::Switch => self.parse_switch_stmt(start),
+                            Keyword::While => self.parse_while_stmt(start),
+                            Keyword::Do => self.parse_do_while_stmt(start),
+                            Keyword::For => self.parse_for_stmt(start),
+                            Keyword::Return => self.parse_return_stmt(start),
+                            Keyword::Goto => self.parse_goto_stmt(start, end),
                             Keyword::Break => {
                                 // Accept a semicolon
                                 let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
@@ -944,103 +957,17 @@ impl<'a> Parser<'a> {
                                 // Create and return a Continue Statement
                                 Ok(Node::new(Statement::ContinueStatement, Span::new(start, semicolon_end)))
                             }
-                            Keyword::Goto => {
-                                // jump-statement:
-                                //      goto identifier ;
-
-                                // Force the next token to be an identifier
-                                match self.tokenizer.next_token()? {
-                                    Some((TokenType::Identifier(identifier), id_start, id_end)) => {
-                                        // Accept a `;`
-                                        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                        // Create and return a goto statement
-                                        Ok(Node::new(
-                                            Statement::GotoStatement(Node::new(
-                                                identifier,
-                                                Span::new(id_start, id_end),
-                                            )),
-                                            // Span of the entire goto statement = Start of goto keyword -> End of semicolon
-                                            Span::new(start, semicolon_end),
-                                        ))
-                                    }
-                                    // This case will occur when code is something like:
-                                    // goto  ;
-                                    //      ^^ Missing Identifier
-                                    Some((_, start, _)) => Err(CompilerError {
-                                        kind: CompilerErrorKind::SyntaxError,
-                                        message: "Expected an identifier".to_string(),
-                                        span: Some(Span::new(start, end)),
-                                    }),
-                                    None => Err(CompilerError {
-                                        kind: CompilerErrorKind::SyntaxError,
-                                        message: "Expected an identifier, instead got end of file".to_string(),
-                                        span: None,
-                                    }),
-                                }
-                            }
-                            Keyword::Sizeof | Keyword::_Alignof => {
-                                let expression = self.parse_expr()?;
-
-                                // Accept `;`
-                                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                                // Calculate the span of the entire expression statement
-                                // Span = Start of the expression -> End of the semicolon
-                                let span = Span::new(start, semicolon_end);
-
-                                Ok(Node::new(Statement::ExpressionStatement(Some(expression)), span))
-                            }
+                            Keyword::Sizeof | Keyword::_Alignof => self.parse_expression_stmt(start),
                             _ => Err(CompilerError {
                                 kind: CompilerErrorKind::SyntaxError,
                                 message: format!("Unexpected start of a statement with keyword: {:?}", keyword),
                                 span: Some(Span::new(start, end)),
+                                code: Some(ErrorCode::UnexpectedToken),
+                                ..Default::default()
                             }),
                         }
                     }
-                    TokenType::Identifier(identifier) => {
-                        // Either it can be a labeled statement
-                        // Or an expression statement
-                        // In both cases we can parse it as an expression
-                        let expression = self.parse_expr()?;
-
-                        // Then decide the type of the statement
-                        // Based on whether the expression is just an identifier or not
-                        if let Expression::Identifier(_) = &expression.node {
-                            if let Some((TokenType::Colon, _, _)) = self.tokenizer.peek_token()? {
-                                // labeled-statement:
-                                //      identifier : statement
-                                self.tokenizer.next_token()?;
-
-                                // Parse the labeled statement
-                                let statement = self.parse_statement()?;
-
-                                // Calculate the span of the entire labeled statement
-                                // Span = Start of the identifier -> End of the statement
-                                let span = Span::new(start, statement.span.end);
-
-                                return Ok(Node::new(
-                                    Statement::LabeledStatement(Box::new(LabeledStatement {
-                                        identifier: Node::new(identifier, Span::new(start, end)),
-                                        statement,
-                                    })),
-                                    span,
-                                ));
-                            }
-                        }
-                        // Else the statement is an expression-statement with the following grammar:
-                        //      expression-statement:
-                        //           expressionopt ;
-
-                        // Accept `;`
-                        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-
-                        // Calculate the span of the entire expression statement
-                        // Span = Start of the expression -> End of the semicolon
-                        let span = Span::new(start, semicolon_end);
-
-                        Ok(Node::new(Statement::ExpressionStatement(Some(expression)), span))
-                    }
+                    TokenType::Identifier(identifier) => self.parse_labeled_or_expression_stmt(identifier, start, end),
                     TokenType::OpenBrace => {
                         // Consume the OpenBrace
                         self.tokenizer.next_token()?;
@@ -1059,123 +986,555 @@ impl<'a> Parser<'a> {
                         // The span of this statement will be the (start, end) of the semicolon token
                         Ok(Node::new(Statement::ExpressionStatement(None), Span::new(start, end)))
                     }
-                    _ => {
-                        // expression-statement:
-                        //      expressionopt ;
+                    // If no specific token is encountered then we expect an expression statement
+                    _ => self.parse_expression_stmt(start),
+                }
+            }
+            None => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected a statement, instead got end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            }),
+        };
 
-                        // If no specific token is encountered then we expect an expression statement
-                        let expression = self.parse_expr()?;
+        self.depth = depth;
+        statement
+    }
 
-                        // Accept `;`
-                        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+    /// Parses an expression statement starting at `start`
+    fn parse_expression_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // expression-statement:
+        //      expressionopt ;
+        let expression = self.parse_expr()?;
 
-                        // Calculate the span of the entire expression statement
-                        // Span = Start of the expression -> End of the semicolon
-                        let span = Span::new(start, semicolon_end);
+        // Accept `;`
+        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
 
-                        Ok(Node::new(Statement::ExpressionStatement(Some(expression)), span))
-                    }
-                }
-            }
-            _ => todo!(),
-        }
+        // Calculate the span of the entire expression statement
+        // Span = Start of the expression -> End of the semicolon
+        let span = Span::new(start, semicolon_end);
+
+        Ok(Node::new(Statement::ExpressionStatement(Some(expression)), span))
     }
 
-    /// Note: This function doesn't consume either of OpenBrace and CloseBrace tokens associated with it.
-    /// It is the caller's responsibility to check for OpenBrace and consume a CloseBrace after calling this function.
-    fn parse_compound_stmt(&mut self) -> Result<Node<Statement>, CompilerError> {
-        // compound-statement:
-        //      { block-item-listopt }
-        // block-item-list:
-        //      block-item
-        //      block-item-list block-item
-        // block-item:
-        //      declaration
-        //      statement
-        let mut blockitems: Vec<Node<BlockItem>> = Vec::new();
+    /// Parses a `case` label and the statement it labels, whose keyword at `start` is already consumed
+    fn parse_case_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // labeled-statement:
+        //      case constant-expression : statement
+
+        let constexpr = self.parse_constant_expr()?;
+        // Consume a colon
+        self.accept_token(TokenType::Colon)?;
+        // Parse a statement
+        let casestmt = self.parse_statement()?;
+
+        // Calculate the span of the case statement
+        // Span = Start of the `case` keyword -> End of the case statement
+        let span = Span::new(start, casestmt.span.end);
+
+        // Create and return the Case Statement
+        Ok(Node::new(
+            Statement::CaseStatement(Box::new(CaseStatement {
+                constexpr,
+                statement: casestmt,
+            })),
+            span,
+        ))
+    }
 
-        let span_start = self.tokenizer.get_lineinfo();
+    /// Parses a `default` label and the statement it labels, whose keyword at `start` is already consumed
+    fn parse_default_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // labeled-statement:
+        //      default : statement
 
-        while !matches!(self.tokenizer.peek_token()?, Some((TokenType::CloseBrace, _, _))) {
+        // Consume a colon
+        self.accept_token(TokenType::Colon)?;
+
+        // Parse a statement
+        let defaultstmt = self.parse_statement()?;
+
+        // Calculate the span of the default statement
+        // Span = Start of the `default` keyword -> End of the default statement
+        let span = Span::new(start, defaultstmt.span.end);
+
+        // Create and return the Default Statement
+        Ok(Node::new(Statement::DefaultStatement(Box::new(defaultstmt)), span))
+    }
+
+    /// Parses an `if` statement, whose keyword at `start` is already consumed
+    fn parse_if_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        let (if_expr, if_block) = self.parse_if_arm()?;
+
+        // An `else if` chain is parsed in a loop rather than recursively, and doesn't count
+        // towards the nesting limit, as it nests as deep as it is long. Each `else if` is kept
+        // as its start, condition and statement until the `else` of the last one is known
+        let mut else_ifs = Vec::new();
+        let mut else_block = None;
+        while let Some((TokenType::Keyword(Keyword::Else), _, _)) = self.tokenizer.peek_token()? {
+            // Consume the Else Token once it is confirmed that it is really an Else Token
+            self.tokenizer.next_token()?;
             match self.tokenizer.peek_token()? {
-                Some((token, start, _)) => {
-                    let mut is_declaration = false;
-
-                    // The logic here is that a BlockItem can be either a declaration or a statement
-                    // If it is a declaration then it should start with a DeclarationSpecifier
-                    if let TokenType::Keyword(keyword) = token {
-                        if keyword2declspec(&keyword).is_some() {
-                            is_declaration = true;
-                            // Parse a declaration
-                            let declaration = self.parse_declaration()?;
-                            // Accept a semicolon after the declaration
-                            let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
-                            // Create a block item using the parsed declaration
-                            // The span of the block item =
-                            // start of the first token -> end of the semicolon after the declaration
-                            blockitems.push(Node::new(
-                                BlockItem::Declaration(declaration),
-                                Span::new(start, semicolon_end),
-                            ));
-                        }
-                    }
-                    // If not then it must be a statement, hence we parse it here
-                    if !is_declaration {
-                        // Parse a statement
-                        let statement = self.parse_statement()?;
-                        // Create a block item using the node and span of the statement
-                        // The span of the block item will be the same as that of the statement
-                        blockitems.push(Node::new(BlockItem::Statement(statement.node), statement.span));
-                    }
+                Some((TokenType::Keyword(Keyword::If), else_if_start, _)) => {
+                    self.tokenizer.next_token()?;
+                    let (else_if_expr, else_if_block) = self.parse_if_arm()?;
+                    else_ifs.push((else_if_start, else_if_expr, else_if_block));
                 }
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected a declaration or a statement, instead got end of file".to_string(),
-                        span: None,
-                    })
+                _ => {
+                    // Parse the else statement
+                    else_block = Some(self.parse_statement()?);
+                    break;
                 }
-            };
+            }
         }
 
-        // Calculate the span of the compound statement
-        let span = if !blockitems.is_empty() {
-            // If the compound statement is not empty then the span is the start index of the first statement
-            // and the end of the last statement
-            Span::new(span_start, blockitems.last().unwrap().span.end)
-        } else {
-            // peek_token() has to return some token as the while loop before this will only exit
-            // when the next token is CloseBrace
-            // If it were to return None token then it would've been handled in the while loop itself
-            let start = self.tokenizer.peek_token()?.unwrap().1;
-            // If the compound statement is empty then the span is the start index of the CloseBrace
-            Span::new(span_start, start)
-        };
+        // Each `else if` is the else statement of the arm before it
+        for (else_if_start, else_if_expr, else_if_block) in else_ifs.into_iter().rev() {
+            else_block = Some(Self::if_stmt(else_if_start, else_if_expr, else_if_block, else_block));
+        }
+        Ok(Self::if_stmt(start, if_expr, if_block, else_block))
+    }
 
-        // Create and return the compound statement
-        Ok(Node::new(Statement::CompoundStatement(blockitems), span))
+    /// Parses the `(<expression>) <statement>` after an `if` keyword
+    fn parse_if_arm(&mut self) -> Result<(Node<Expression>, Node<Statement>), CompilerError> {
+        // if (<expression>) <statement>
+        //    ^ Accept this OpenParenthesis
+        self.accept_token(TokenType::OpenParenthesis)?;
+
+        // if (<expression>) <statement>
+        //      ^^^ Parse this expression
+        let if_expr = self.parse_expr()?;
+
+        // if (<expression>) <statement>
+        //                 ^ Accept this CloseParenthesis
+        self.accept_token(TokenType::CloseParenthesis)?;
+
+        // if (<expression>) <statement>
+        //                    ^^^ Parse this statement
+        let if_block = self.parse_statement()?;
+        Ok((if_expr, if_block))
     }
 
-    /// Note: This function doesn't consume a semicolon at the end.
-    /// That must be handled by the calling function
-    fn parse_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // expression:
-        //      assignment-expression
-        //      expression , assignment-expression
-        let mut expressions: Vec<Node<Expression>> = Vec::new();
-        while self.tokenizer.peek_token()?.is_some() {
-            let expression = self.parse_assignment_expr()?;
-            expressions.push(expression);
+    /// Builds the if statement whose keyword is at `start`
+    fn if_stmt(
+        start: Location,
+        condition: Node<Expression>,
+        if_block: Node<Statement>,
+        else_block: Option<Node<Statement>>,
+    ) -> Node<Statement> {
+        // The span of the entire if statement =
+        // Start of if keyword -> End of else statement, or of if statement if there is no else
+        let end = else_block.as_ref().unwrap_or(&if_block).span.end;
+        Node::new(
+            Statement::IfStatement(Box::new(IfStatement {
+                condition,
+                if_block,
+                else_block,
+            })),
+            Span::new(start, end),
+        )
+    }
 
-            if let Some((TokenType::Comma, _, _)) = self.tokenizer.peek_token()? {
-                // Consume the `,`
-                self.tokenizer.next_token()?;
-            } else {
-                break;
-            }
-        }
+    /// Parses a `switch` statement, whose keyword at `start` is already consumed
+    fn parse_switch_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // selection-statement:
+        //      switch ( expression ) statement
+
+        // Consume the OpenParenthesis
+        self.accept_token(TokenType::OpenParenthesis)?;
+        // Parse the Switch Expression
+        let switchexpr = self.parse_expr()?;
+        // Consume the CloseParenthesis
+        self.accept_token(TokenType::CloseParenthesis)?;
+        // Parse the switch block/statement
+        let switchstmt = self.parse_statement()?;
+
+        // Calculate span of the entire switch statement
+        // Span of switch statement = (start of the switch keyword, end of the switch statement)
+        let span = Span::new(start, switchstmt.span.end);
+        // Create and return the switch statement
+        Ok(Node::new(
+            Statement::SwitchStatement(Box::new(SwitchStatement {
+                expression: switchexpr,
+                statement: switchstmt,
+            })),
+            span,
+        ))
+    }
 
-        if expressions.len() == 1 {
+    /// Parses a `while` statement, whose keyword at `start` is already consumed
+    fn parse_while_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // Accept a OpenParenthesis
+        self.accept_token(TokenType::OpenParenthesis)?;
+        // Parse the condition inside the while (<expression>) statement
+        //                                       ^^^
+        let expression = self.parse_expr()?;
+        // Accept a CloseParenthesis
+        self.accept_token(TokenType::CloseParenthesis)?;
+        // Parse the while statement
+        let block = self.parse_statement()?;
+
+        // Calculate the span of the while statement
+        // Span = Start of the `while` keyword -> End of the statement
+        let span = Span::new(start, block.span.end);
+
+        Ok(Node::new(
+            Statement::WhileStatement(Box::new(WhileStatement {
+                condition: expression,
+                statement: block,
+            })),
+            span,
+        ))
+    }
+
+    /// Parses a `do`-`while` statement, whose keyword at `start` is already consumed
+    fn parse_do_while_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // iteration-statement:
+        //      do statement while ( expression ) ;
+
+        // Parse the do statement
+        let dostmt = self.parse_statement()?;
+
+        // Accept `while (`
+        self.accept_token(TokenType::Keyword(Keyword::While))?;
+        self.accept_token(TokenType::OpenParenthesis)?;
+        // Parse the while expression
+        let doexpr = self.parse_expr()?;
+        // Accept `)`
+        self.accept_token(TokenType::CloseParenthesis)?;
+        // Accept `;`
+        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+        // Calculate span of the entire while statement
+        // Span of the while statement = Start of do keyword -> End of semicolon after `while` keyword
+        let span = Span::new(start, semicolon_end);
+
+        // Create and return the DoWhileStatement
+        Ok(Node::new(
+            Statement::DoWhileStatement(Box::new(WhileStatement {
+                statement: dostmt,
+                condition: doexpr,
+            })),
+            span,
+        ))
+    }
+
+    /// Parses a `for` statement, whose keyword at `start` is already consumed
+    fn parse_for_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // Accept `(`
+        self.accept_token(TokenType::OpenParenthesis)?;
+
+        let forinitializer: Node<ForInitializer>;
+        match self.tokenizer.peek_token()? {
+            Some((token, peek_start, peek_end)) => {
+                // Check if the next token is a DeclarationSpecifier
+                if let TokenType::Keyword(keyword) = token {
+                    if keyword2declspec(&keyword).is_some() {
+                        // If yes, Expect a ForInitializer::Declaration
+                        let declaration = self.parse_declaration()?;
+
+                        // Accept a `;`
+                        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+                        let forinit = ForInitializer::Declaration(declaration);
+                        let forinit_span = Span::new(peek_start, semicolon_end);
+
+                        forinitializer = Node::new(forinit, forinit_span);
+                    } else {
+                        // This must happen when code may have something like:
+                        // for (return; i = 0; i++)
+                        //      ^^ Unexpected keyword which is not a DeclarationSpecifier
+                        return Err(CompilerError {
+                            kind: CompilerErrorKind::SyntaxError,
+                            message: format!("Expected a declaration specifier or an expression, instead got unexpected Keyword: {:?}", keyword),
+                            span: Some(Span::new(peek_start, peek_end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
+                        });
+                    }
+                } else if token == TokenType::Semicolon {
+                    // Consume the first semicolon which follows the ForInitializer
+                    self.tokenizer.next_token()?;
+
+                    // Else if the next token is a Semicolon, then return an Empty Initializer
+                    forinitializer = Node::new(ForInitializer::Empty, Span::new(peek_start, peek_start));
+                } else {
+                    // Else expect an expression (like an assignment expression)
+                    let expression = self.parse_expr()?;
+
+                    // Accept a `;`
+                    let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+                    // Calculate the span of the ForInitializer
+                    // Span = Start of expression -> End of the semicolon
+                    let span = Span::new(expression.span.start, semicolon_end);
+
+                    // Create and store the ForInitializer
+                    forinitializer = Node::new(ForInitializer::Expression(expression.node), span);
+                }
+            }
+            None => {
+                // This error should occur when we encounter an end of file instead of an initializer
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: "Expected a for initializer, instead got end of file".to_string(),
+                    span: None,
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                });
+            }
+        }
+
+        // Parse the For Condition
+        let condition = if let Some((TokenType::Semicolon, _, _)) = self.tokenizer.peek_token()? {
+            // If the next token is semicolon then the condition is None
+            None
+        } else {
+            // Else parse the condition expression
+            Some(self.parse_expr()?)
+        };
+
+        // Accept a semicolon irrespective of the presence of a for condition
+        self.accept_token(TokenType::Semicolon)?;
+
+        // Parse the For Step Expression
+        let step = if let Some((TokenType::CloseParenthesis, _, _)) = self.tokenizer.peek_token()? {
+            // If the next token is `)` then the step expression is None
+            None
+        } else {
+            // Else parse the step expression
+            Some(self.parse_expr()?)
+        };
+
+        // Accept a `)`
+        self.accept_token(TokenType::CloseParenthesis)?;
+
+        // Parse the actual for statement
+        let statement = self.parse_statement()?;
+
+        // Calculate the span of the entire ForStatement
+        // Span = Start of the `for` keyword -> End of the statement
+        let span = Span::new(start, statement.span.end);
+
+        // Create and return the actual For Statement
+        Ok(Node::new(
+            Statement::ForStatement(Box::new(ForStatement {
+                initializer: forinitializer,
+                condition,
+                step,
+                statement,
+            })),
+            span,
+        ))
+    }
+
+    /// Parses a `return` statement, whose keyword at `start` is already consumed
+    fn parse_return_stmt(&mut self, start: Location) -> Result<Node<Statement>, CompilerError> {
+        // Return -> Jump Statement
+        let expression = self.parse_expr()?;
+        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+        // Calculate span of the entire return statement
+        // Span of return statement = (start of the return keyword, end of the semicolon token)
+        let span = Span::new(start, semicolon_end);
+        // Create and return the actual return statement
+        Ok(Node::new(Statement::ReturnStatement(expression), span))
+    }
+
+    /// Parses a `goto` statement, whose keyword at `start` is already consumed
+    fn parse_goto_stmt(&mut self, start: Location, end: Location) -> Result<Node<Statement>, CompilerError> {
+        // jump-statement:
+        //      goto identifier ;
+
+        // Force the next token to be an identifier
+        match self.tokenizer.next_token()? {
+            Some((TokenType::Identifier(identifier), id_start, id_end)) => {
+                // Accept a `;`
+                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+                // Create and return a goto statement
+                Ok(Node::new(
+                    Statement::GotoStatement(Node::new(identifier, Span::new(id_start, id_end))),
+                    // Span of the entire goto statement = Start of goto keyword -> End of semicolon
+                    Span::new(start, semicolon_end),
+                ))
+            }
+            // This case will occur when code is something like:
+            // goto  ;
+            //      ^^ Missing Identifier
+            Some((_, start, _)) => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected an identifier".to_string(),
+                span: Some(Span::new(start, end)),
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            }),
+            None => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected an identifier, instead got end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Parses a statement that starts with the identifier `identifier` at `start`..`end`: a
+    /// labeled statement or an expression statement
+    fn parse_labeled_or_expression_stmt(
+        &mut self,
+        identifier: String,
+        start: Location,
+        end: Location,
+    ) -> Result<Node<Statement>, CompilerError> {
+        // Either it can be a labeled statement
+        // Or an expression statement
+        // In both cases we can parse it as an expression
+        let expression = self.parse_expr()?;
+
+        // Then decide the type of the statement
+        // Based on whether the expression is just an identifier or not
+        if let Expression::Identifier(_) = &expression.node {
+            if let Some((TokenType::Colon, _, _)) = self.tokenizer.peek_token()? {
+                // labeled-statement:
+                //      identifier : statement
+                self.tokenizer.next_token()?;
+
+                // Parse the labeled statement
+                let statement = self.parse_statement()?;
+
+                // Calculate the span of the entire labeled statement
+                // Span = Start of the identifier -> End of the statement
+                let span = Span::new(start, statement.span.end);
+
+                return Ok(Node::new(
+                    Statement::LabeledStatement(Box::new(LabeledStatement {
+                        identifier: Node::new(identifier, Span::new(start, end)),
+                        statement,
+                    })),
+                    span,
+                ));
+            }
+        }
+        // Else the statement is an expression-statement with the following grammar:
+        //      expression-statement:
+        //           expressionopt ;
+
+        // Accept `;`
+        let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+
+        // Calculate the span of the entire expression statement
+        // Span = Start of the expression -> End of the semicolon
+        let span = Span::new(start, semicolon_end);
+
+        Ok(Node::new(Statement::ExpressionStatement(Some(expression)), span))
+    }
+
+    /// Parses a declaration or a statement inside a compound statement
+    fn parse_block_item(&mut self) -> Result<Node<BlockItem>, CompilerError> {
+        // The logic here is that a BlockItem can be either a declaration or a statement
+        // If it is a declaration then it should start with a DeclarationSpecifier
+        if let Some((TokenType::Keyword(keyword), start, _)) = self.tokenizer.peek_token()? {
+            if keyword2declspec(&keyword).is_some() {
+                // Parse a declaration
+                let declaration = self.parse_declaration()?;
+                // Accept a semicolon after the declaration
+                let (_, semicolon_end) = self.accept_token(TokenType::Semicolon)?;
+                // Create a block item using the parsed declaration
+                // The span of the block item =
+                // start of the first token -> end of the semicolon after the declaration
+                return Ok(Node::new(
+                    BlockItem::Declaration(declaration),
+                    Span::new(start, semicolon_end),
+                ));
+            }
+        }
+
+        // If not then it must be a statement, hence we parse it here
+        let statement = self.parse_statement()?;
+        // Create a block item using the node and span of the statement
+        // The span of the block item will be the same as that of the statement
+        Ok(Node::new(BlockItem::Statement(statement.node), statement.span))
+    }
+
+    /// Note: This function doesn't consume either of OpenBrace and CloseBrace tokens associated with it.
+    /// It is the caller's responsibility to check for OpenBrace and consume a CloseBrace after calling this function.
+    fn parse_compound_stmt(&mut self) -> Result<Node<Statement>, CompilerError> {
+        // compound-statement:
+        //      { block-item-listopt }
+        // block-item-list:
+        //      block-item
+        //      block-item-list block-item
+        // block-item:
+        //      declaration
+        //      statement
+        let mut blockitems: Vec<Node<BlockItem>> = Vec::new();
+
+        let span_start = self.tokenizer.get_lineinfo();
+
+        while !matches!(self.tokenizer.peek_token()?, Some((TokenType::CloseBrace, _, _))) {
+            if self.tokenizer.peek_token()?.is_none() {
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: "Expected a declaration or a statement, instead got end of file".to_string(),
+                    span: None,
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                });
+            }
+
+            // An error in one block item is recorded and the parser carries on with the next, as
+            // deep as the block item started
+            let start = self.tokenizer.get_cidx();
+            let depth = self.depth;
+            match self.parse_block_item() {
+                Ok(blockitem) => blockitems.push(blockitem),
+                Err(error) => {
+                    self.depth = depth;
+                    self.recover(error, start)
+                }
+            }
+        }
+
+        // Calculate the span of the compound statement
+        let span = if !blockitems.is_empty() {
+            // If the compound statement is not empty then the span is the start index of the first statement
+            // and the end of the last statement
+            Span::new(span_start, blockitems.last().unwrap().span.end)
+        } else {
+            // peek_token() has to return some token as the while loop before this will only exit
+            // when the next token is CloseBrace
+            // If it were to return None token then it would've been handled in the while loop itself
+            let start = self.tokenizer.peek_token()?.unwrap().1;
+            // If the compound statement is empty then the span is the start index of the CloseBrace
+            Span::new(span_start, start)
+        };
+
+        // Create and return the compound statement
+        Ok(Node::new(Statement::CompoundStatement(blockitems), span))
+    }
+
+    /// Note: This function doesn't consume a semicolon at the end.
+    /// That must be handled by the calling function
+    fn parse_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
+        // expression:
+        //      assignment-expression
+        //      expression , assignment-expression
+        let mut expressions: Vec<Node<Expression>> = Vec::new();
+        while self.tokenizer.peek_token()?.is_some() {
+            let expression = self.parse_assignment_expr()?;
+            expressions.push(expression);
+
+            if let Some((TokenType::Comma, _, _)) = self.tokenizer.peek_token()? {
+                // Consume the `,`
+                self.tokenizer.next_token()?;
+            } else {
+                break;
+            }
+        }
+
+        if expressions.len() == 1 {
             Ok(expressions.pop().unwrap())
         } else {
             let span = Span::new(
@@ -1203,7 +1562,9 @@ impl<'a> Parser<'a> {
                     // If yes then consume that token
                     self.tokenizer.next_token()?;
                     // And then parse another assignment expression
+                    let depth = self.descend()?;
                     let rhs = self.parse_assignment_expr()?;
+                    self.depth = depth;
                     // Calculate the span for the expression
                     // Span = Start of lhs -> End of rhs
                     let span = Span::new(expression.span.start, rhs.span.end);
@@ -1237,19 +1598,21 @@ impl<'a> Parser<'a> {
         // conditional-expression:
         //      logical-OR-expression
         //      logical-OR-expression ? expression : conditional-expression
-        let expression = self.parse_logical_OR_expr()?;
+        let expression = self.parse_binary_expr(0)?;
 
         match self.tokenizer.peek_token()? {
             Some((token, _, _)) => match token {
                 TokenType::QuestionMark => {
                     // Consume the Question Mark
                     self.tokenizer.next_token()?;
+                    let depth = self.descend()?;
                     // Parse the if expression
                     let if_expr = self.parse_expr()?;
                     // Accept a Colon as it is compulsory to have both the if and else expressions
                     self.accept_token(TokenType::Colon)?;
                     // Parse the else expression
                     let else_expr = self.parse_conditional_expr()?;
+                    self.depth = depth;
 
                     // Create and return a Ternary Operator Expression
                     // Span of Ternary Operator Expression =
@@ -1260,432 +1623,60 @@ impl<'a> Parser<'a> {
                             condition: expression,
                             if_expr,
                             else_expr,
-                        })),
-                        span,
-                    ))
-                }
-                _ => Ok(expression),
-            },
-            None => Err(CompilerError {
-                kind: CompilerErrorKind::SyntaxError,
-                message: "Expected expression, instead got end of file".to_string(),
-                span: None,
-            }),
-        }
-    }
-
-    #[allow(non_snake_case)]
-    fn parse_logical_OR_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // logical-OR-expression:
-        //      logical-AND-expression
-        //      logical-OR-expression || logical-AND-expression
-        let mut expression = self.parse_logical_AND_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::LogicalOrOperator => {
-                        // Consume the ExclusiveOrOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_logical_AND_expr()?;
-
-                        let operator = BinaryOperator::LogicalOr;
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    #[allow(non_snake_case)]
-    fn parse_logical_AND_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // logical-AND-expression:
-        //      inclusive-OR-expression
-        //      logical-AND-expression && inclusive-OR-expression
-        let mut expression = self.parse_inclusive_or_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::LogicalAndOperator => {
-                        // Consume the ExclusiveOrOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_inclusive_or_expr()?;
-
-                        let operator = BinaryOperator::LogicalAnd;
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_inclusive_or_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // inclusive-OR-expression:
-        //      exclusive-OR-expression
-        //      inclusive-OR-expression | exclusive-OR-expression
-        let mut expression = self.parse_exor_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::BitwiseOrOperator => {
-                        // Consume the ExclusiveOrOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_exor_expr()?;
-
-                        let operator = BinaryOperator::BitwiseOr;
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_exor_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // exclusive-OR-expression:
-        //      AND-expression
-        //      exclusive-OR-expression ^ AND-expression
-        let mut expression = self.parse_AND_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::ExclusiveOrOperator => {
-                        // Consume the ExclusiveOrOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_AND_expr()?;
-
-                        let operator = BinaryOperator::BitwiseXor;
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    #[allow(non_snake_case)]
-    fn parse_AND_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // AND-expression:
-        //      equality-expression
-        //      AND-expression & equality-expression
-        let mut expression = self.parse_equality_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::BitwiseAndOperator => {
-                        // Consume the BitwiseAndOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_equality_expr()?;
-
-                        let operator = BinaryOperator::BitwiseAnd;
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_equality_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
-        // equality-expression:
-        //      relational-expression
-        //      equality-expression == relational-expression
-        //      equality-expression != relational-expression
-        let mut expression = self.parse_relational_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::EqualityOperator | TokenType::NotEqualsOperator => {
-                        // Consume the EqualityOperator/NotEqualsOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_relational_expr()?;
-
-                        let operator = if token == TokenType::EqualityOperator {
-                            BinaryOperator::Equals
-                        } else {
-                            BinaryOperator::NotEquals
-                        };
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
+                        })),
+                        span,
+                    ))
                 }
-            }
+                _ => Ok(expression),
+            },
+            None => Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected expression, instead got end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            }),
         }
-        Ok(expression)
     }
 
-    fn parse_relational_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
+    /// Parses the binary operators from `||` down to `*`, `/` and `%`, of which the operators
+    /// binding at least as tightly as `min_precedence` are taken. By precedence climbing, one
+    /// function handles all ten levels of the grammar instead of one function each, so that a
+    /// parenthesized expression nests through a single frame for them.
+    fn parse_binary_expr(&mut self, min_precedence: u8) -> Result<Node<Expression>, CompilerError> {
+        // logical-OR-expression:
+        //      logical-AND-expression
+        //      logical-OR-expression || logical-AND-expression
+        // logical-AND-expression:
+        //      inclusive-OR-expression
+        //      logical-AND-expression && inclusive-OR-expression
+        // inclusive-OR-expression:
+        //      exclusive-OR-expression
+        //      inclusive-OR-expression | exclusive-OR-expression
+        // exclusive-OR-expression:
+        //      AND-expression
+        //      exclusive-OR-expression ^ AND-expression
+        // AND-expression:
+        //      equality-expression
+        //      AND-expression & equality-expression
+        // equality-expression:
+        //      relational-expression
+        //      equality-expression == relational-expression
+        //      equality-expression != relational-expression
         // relational-expression:
         //      shift-expression
         //      relational-expression < shift-expression
         //      relational-expression > shift-expression
         //      relational-expression <= shift-expression
         //      relational-expression >= shift-expression
-        let mut expression = self.parse_shift_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::LessThanOperator
-                    | TokenType::LessThanEqualsOperator
-                    | TokenType::GreaterThanOperator
-                    | TokenType::GreaterThanEqualsOperator => {
-                        // Consume the LessThanOperator/LessThanEqualsOperator/GreaterThanOperator/GreaterThanEqualsOperator token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_shift_expr()?;
-
-                        let operator = if token == TokenType::LessThanOperator {
-                            BinaryOperator::Less
-                        } else if token == TokenType::LessThanEqualsOperator {
-                            BinaryOperator::LessOrEqual
-                        } else if token == TokenType::GreaterThanOperator {
-                            BinaryOperator::Greater
-                        } else {
-                            BinaryOperator::GreaterOrEqual
-                        };
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_shift_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
         // shift-expression:
         //      additive-expression
-        //      shift-expression « additive-expression
-        //      shift-expression » additive-expression
-        let mut expression = self.parse_additive_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::LeftShiftOperator | TokenType::RightShiftOperator => {
-                        // Consume the LeftShift/RightShift token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        let rhs = self.parse_additive_expr()?;
-
-                        let operator = if token == TokenType::LeftShiftOperator {
-                            BinaryOperator::ShiftLeft
-                        } else {
-                            BinaryOperator::ShiftRight
-                        };
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_additive_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
+        //      shift-expression << additive-expression
+        //      shift-expression >> additive-expression
         // additive-expression:
         //      multiplicative-expression
         //      additive-expression + multiplicative-expression
         //      additive-expression - multiplicative-expression
-        let mut expression = self.parse_multiplicative_expr()?;
-
-        // Doing the parsing iteratively instead of recursively
-        loop {
-            match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::Plus | TokenType::Minus => {
-                        // Consume the Plus/Minus token
-                        self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        // For now it is assumed to be a primary expression
-                        let rhs = self.parse_multiplicative_expr()?;
-
-                        let operator = if token == TokenType::Plus {
-                            BinaryOperator::Plus
-                        } else {
-                            BinaryOperator::Minus
-                        };
-
-                        let span = Span::new(expression.span.start, rhs.span.end);
-                        expression = Node::new(
-                            Expression::BinaryOperator(Box::new(BinaryOperatorExpression {
-                                operator: Node::new(operator, Span::new(start, end)),
-                                lhs: expression,
-                                rhs,
-                            })),
-                            span,
-                        );
-                    }
-                    _ => break,
-                },
-                None => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: "Expected expression, instead got end of file".to_string(),
-                        span: None,
-                    })
-                }
-            }
-        }
-        Ok(expression)
-    }
-
-    fn parse_multiplicative_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
         // multiplicative-expression:
         //      cast-expression
         //      multiplicative-expression * cast-expression
@@ -1693,24 +1684,17 @@ impl<'a> Parser<'a> {
         //      multiplicative-expression % cast-expression
         let mut expression = self.parse_cast_expr()?;
 
-        // Doing the parsing iteratively instead of recursively
+        // Doing the parsing iteratively instead of recursively, operators of the same
+        // precedence chain to the left without nesting any deeper here, however many there are
         loop {
             match self.tokenizer.peek_token()? {
-                Some((token, start, end)) => match token {
-                    TokenType::Asterisk | TokenType::Slash | TokenType::Percent => {
-                        // Consume the Plus/Minus token
+                Some((token, start, end)) => match token2binaryop(&token) {
+                    Some((operator, precedence)) if precedence >= min_precedence => {
+                        // Consume the operator token
                         self.tokenizer.next_token()?;
-                        // Parse the RHS expression
-                        // For now it is assumed to be a primary expression
-                        let rhs = self.parse_cast_expr()?;
-
-                        let operator = if token == TokenType::Asterisk {
-                            BinaryOperator::Multiply
-                        } else if token == TokenType::Slash {
-                            BinaryOperator::Divide
-                        } else {
-                            BinaryOperator::Modulo
-                        };
+                        // Parse the RHS expression, which takes the operators binding tighter
+                        // than this one, so that operators of the same precedence group to the left
+                        let rhs = self.parse_binary_expr(precedence + 1)?;
 
                         let span = Span::new(expression.span.start, rhs.span.end);
                         expression = Node::new(
@@ -1729,6 +1713,8 @@ impl<'a> Parser<'a> {
                         kind: CompilerErrorKind::SyntaxError,
                         message: "Expected expression, instead got end of file".to_string(),
                         span: None,
+                        code: Some(ErrorCode::UnexpectedToken),
+                        ..Default::default()
                     })
                 }
             }
@@ -1743,7 +1729,12 @@ impl<'a> Parser<'a> {
         let mut typenames: Vec<Node<TypeName>> = Vec::new();
         let mut start_arr: Vec<Location> = Vec::new();
 
+        let depth = self.depth;
+
         while let Some((TokenType::OpenParenthesis, paren_start, _)) = self.tokenizer.peek_token()? {
+            // Every cast, and every parenthesized expression, is one level deeper
+            self.descend()?;
+
             // Consume the `(`
             self.tokenizer.next_token()?;
 
@@ -1767,42 +1758,17 @@ impl<'a> Parser<'a> {
                 let expr_start = expression.span.start;
                 expression = self.parse_postfix_operators_with_init_expr(expression, expr_start)?;
 
-                // Pop all the typenames and create cast expressions
-                for _ in 0..typenames.len() {
-                    // Calculate the span of the cast expression
-                    // Span = Start of the corresponding OpenParenthesis -> End of the expression's CloseParenthesis
-                    let span = Span::new(start_arr.pop().unwrap(), paren_end);
-                    // Create and store the cast expression
-                    expression = Node::new(
-                        Expression::Cast(Box::new(CastExpression {
-                            typename: typenames.pop().unwrap(),
-                            expression,
-                        })),
-                        span,
-                    )
-                }
-                return Ok(expression);
+                self.depth = depth;
+                return Ok(wrap_in_casts(expression, typenames, start_arr, paren_end));
             }
         }
 
         // Parse a Unary Expression
-        let mut expression = self.parse_unary_expr()?;
-
-        // Pop all the typenames and create cast expressions
-        for _ in 0..typenames.len() {
-            // Calculate the span of the cast expression
-            // Span = Start of the corresponding OpenParenthesis -> End of the expression's CloseParenthesis
-            let span = Span::new(start_arr.pop().unwrap(), expression.span.end);
-            // Create and store the cast expression
-            expression = Node::new(
-                Expression::Cast(Box::new(CastExpression {
-                    typename: typenames.pop().unwrap(),
-                    expression,
-                })),
-                span,
-            )
-        }
-        Ok(expression)
+        let expression = self.parse_unary_expr()?;
+        self.depth = depth;
+
+        let end = expression.span.end;
+        Ok(wrap_in_casts(expression, typenames, start_arr, end))
     }
 
     /// This function currently parses only typenames without an abstract-declarator
@@ -1831,6 +1797,8 @@ impl<'a> Parser<'a> {
                             kind: CompilerErrorKind::SyntaxError,
                             message: "Unexpected keyword: {}, expected a Specifier-Qualifier".to_string(),
                             span: Some(Span::new(start, end)),
+                            code: Some(ErrorCode::UnexpectedToken),
+                            ..Default::default()
                         })
                     }
                 },
@@ -1843,6 +1811,7 @@ impl<'a> Parser<'a> {
                             kind: CompilerErrorKind::SyntaxError,
                             message: "No specifier-qualifiers are present in type-name".to_string(),
                             span: Some(Span::new(start, end)),
+                            ..Default::default()
                         });
                     }
 
@@ -1871,6 +1840,8 @@ impl<'a> Parser<'a> {
             kind: CompilerErrorKind::SyntaxError,
             message: "Expected a type-name, instead got end of file".to_string(),
             span: None,
+            code: Some(ErrorCode::UnexpectedToken),
+            ..Default::default()
         })
     }
 
@@ -1884,7 +1855,9 @@ impl<'a> Parser<'a> {
         //      sizeof unary-expression
         //      sizeof ( type-name )
         //      _Alignof ( type-name )
-        match self.tokenizer.peek_token()? {
+        let depth = self.descend()?;
+
+        let expression = match self.tokenizer.peek_token()? {
             Some((token, start, end)) => match token {
                 TokenType::IncrementOperator => {
                     // Consume the `++`
@@ -1918,76 +1891,8 @@ impl<'a> Parser<'a> {
                         span,
                     ))
                 }
-                TokenType::Keyword(Keyword::Sizeof) => {
-                    // Consume the Sizeof Keyword
-                    self.tokenizer.next_token()?;
-
-                    // Check if the next token is `(`
-                    if let Some((TokenType::OpenParenthesis, paren_start, _)) = self.tokenizer.peek_token()? {
-                        self.tokenizer.next_token()?;
-                        // If Yes then check if the next_token is a Keyword
-                        if let Some((TokenType::Keyword(keyword), _, _)) = self.tokenizer.peek_token()? {
-                            // If Yes then check if the Keyword is a Specifier-Qualifier
-                            if keyword2specifierqualifier(&keyword).is_some() {
-                                // If Yes then it must be a type-name, so parse a type-name
-                                let type_name = self.parse_type_name()?;
-                                // Accept a `)`
-                                let (_, paren_end) = self.accept_token(TokenType::CloseParenthesis)?;
-                                // Calculate the span of the entire sizeof expression
-                                // Span = Start of the sizeof Keyword -> End of the CloseParenthesis
-                                let span = Span::new(start, paren_end);
-                                // Create and return a SizeofType Expression
-                                return Ok(Node::new(Expression::SizeofType(Box::new(type_name)), span));
-                            }
-                        }
-                        // Else If the token is not a specifier-qualifier keyword
-                        // Then parse an expression
-
-                        // In case of an expression, there need not be Parenthesis enclosing the expression
-                        // But in the current line of code we already have ensured a presence of OpenParenthesis
-                        // So that OpenParenthesis becomes a part of the expression and not a part of the sizeof operator
-                        // In the unary-expression inside sizeof operator we have to ensure somehow that the postfix operators get noticed
-                        // This is because we consume the first OpenParenthesis before calling `parse_expr()`
-                        // As a result after parsing an expression we also check for postfix operators
-                        // Also we won't check for any other operators like +, -, *, /, %, &&, ==, etc. because they don't come under unary expressions
-
-                        let mut expression = self.parse_expr()?;
-                        // But remember that we consumed an open parenthesis at the start
-                        self.accept_token(TokenType::CloseParenthesis)?;
-
-                        // Check for any number of postfix operators keeping expression as the already parsed part
-                        expression = self.parse_postfix_operators_with_init_expr(expression, paren_start)?;
-
-                        // Calculate the span of the entire SizeofVal Expression
-                        // Span = Start of Sizeof Keyword -> End of Expression inside Sizeof
-                        let span = Span::new(start, expression.span.end);
-                        // Create and return the SizeofVal Expression
-                        Ok(Node::new(Expression::SizeofVal(Box::new(expression)), span))
-                    } else {
-                        // Parse a unary expression
-                        let unary_expr = self.parse_unary_expr()?;
-                        // Calculate the span of the entire unary expression
-                        // Span = Start of first token in the expression -> End of Expression
-                        let span = Span::new(start, unary_expr.span.end);
-                        // Create and return a unary expression
-                        Ok(Node::new(Expression::SizeofVal(Box::new(unary_expr)), span))
-                    }
-                }
-                TokenType::Keyword(Keyword::_Alignof) => {
-                    // Consume the Alignof Keyword
-                    self.tokenizer.next_token()?;
-                    // Accept a `(`
-                    self.accept_token(TokenType::OpenParenthesis)?;
-                    // Parse a typename
-                    let type_name = self.parse_type_name()?;
-                    // Accept a `)`
-                    let (_, paren_end) = self.accept_token(TokenType::CloseParenthesis)?;
-                    // Calculate the span of the entire Alignof Expression
-                    // Span = Start of the Alignof Keyword -> End of parenthesis
-                    let span = Span::new(start, paren_end);
-                    // Create and return an Alignof Expression
-                    Ok(Node::new(Expression::Alignof(Box::new(type_name)), span))
-                }
+                TokenType::Keyword(Keyword::Sizeof) => self.parse_sizeof_expr(start),
+                TokenType::Keyword(Keyword::_Alignof) => self.parse_alignof_expr(start),
                 tokentype => {
                     if let Some(unary_op) = token2unaryop(&tokentype) {
                         // Consume the unary operator
@@ -2013,10 +1918,89 @@ impl<'a> Parser<'a> {
                 kind: CompilerErrorKind::SyntaxError,
                 message: "Expected an expression, instead got end of file".to_string(),
                 span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
             }),
+        };
+
+        self.depth = depth;
+        expression
+    }
+
+    /// Parses a `sizeof` expression, whose keyword starts at `start`
+    fn parse_sizeof_expr(&mut self, start: Location) -> Result<Node<Expression>, CompilerError> {
+        // Consume the Sizeof Keyword
+        self.tokenizer.next_token()?;
+
+        // Check if the next token is `(`
+        if let Some((TokenType::OpenParenthesis, paren_start, _)) = self.tokenizer.peek_token()? {
+            self.tokenizer.next_token()?;
+            // If Yes then check if the next_token is a Keyword
+            if let Some((TokenType::Keyword(keyword), _, _)) = self.tokenizer.peek_token()? {
+                // If Yes then check if the Keyword is a Specifier-Qualifier
+                if keyword2specifierqualifier(&keyword).is_some() {
+                    // If Yes then it must be a type-name, so parse a type-name
+                    let type_name = self.parse_type_name()?;
+                    // Accept a `)`
+                    let (_, paren_end) = self.accept_token(TokenType::CloseParenthesis)?;
+                    // Calculate the span of the entire sizeof expression
+                    // Span = Start of the sizeof Keyword -> End of the CloseParenthesis
+                    let span = Span::new(start, paren_end);
+                    // Create and return a SizeofType Expression
+                    return Ok(Node::new(Expression::SizeofType(Box::new(type_name)), span));
+                }
+            }
+            // Else If the token is not a specifier-qualifier keyword
+            // Then parse an expression
+
+            // In case of an expression, there need not be Parenthesis enclosing the expression
+            // But in the current line of code we already have ensured a presence of OpenParenthesis
+            // So that OpenParenthesis becomes a part of the expression and not a part of the sizeof operator
+            // In the unary-expression inside sizeof operator we have to ensure somehow that the postfix operators get noticed
+            // This is because we consume the first OpenParenthesis before calling `parse_expr()`
+            // As a result after parsing an expression we also check for postfix operators
+            // Also we won't check for any other operators like +, -, *, /, %, &&, ==, etc. because they don't come under unary expressions
+
+            let mut expression = self.parse_expr()?;
+            // But remember that we consumed an open parenthesis at the start
+            self.accept_token(TokenType::CloseParenthesis)?;
+
+            // Check for any number of postfix operators keeping expression as the already parsed part
+            expression = self.parse_postfix_operators_with_init_expr(expression, paren_start)?;
+
+            // Calculate the span of the entire SizeofVal Expression
+            // Span = Start of Sizeof Keyword -> End of Expression inside Sizeof
+            let span = Span::new(start, expression.span.end);
+            // Create and return the SizeofVal Expression
+            Ok(Node::new(Expression::SizeofVal(Box::new(expression)), span))
+        } else {
+            // Parse a unary expression
+            let unary_expr = self.parse_unary_expr()?;
+            // Calculate the span of the entire unary expression
+            // Span = Start of first token in the expression -> End of Expression
+            let span = Span::new(start, unary_expr.span.end);
+            // Create and return a unary expression
+            Ok(Node::new(Expression::SizeofVal(Box::new(unary_expr)), span))
         }
     }
 
+    /// Parses an `_Alignof` expression, whose keyword starts at `start`
+    fn parse_alignof_expr(&mut self, start: Location) -> Result<Node<Expression>, CompilerError> {
+        // Consume the Alignof Keyword
+        self.tokenizer.next_token()?;
+        // Accept a `(`
+        self.accept_token(TokenType::OpenParenthesis)?;
+        // Parse a typename
+        let type_name = self.parse_type_name()?;
+        // Accept a `)`
+        let (_, paren_end) = self.accept_token(TokenType::CloseParenthesis)?;
+        // Calculate the span of the entire Alignof Expression
+        // Span = Start of the Alignof Keyword -> End of parenthesis
+        let span = Span::new(start, paren_end);
+        // Create and return an Alignof Expression
+        Ok(Node::new(Expression::Alignof(Box::new(type_name)), span))
+    }
+
     fn parse_postfix_operators_with_init_expr(
         &mut self,
         mut expression: Node<Expression>,
@@ -2030,7 +2014,12 @@ impl<'a> Parser<'a> {
         //      postfix-expression -> identifier
         //      postfix-expression ++
         //      postfix-expression --
+        let depth = self.depth;
+
         while let Some((token, start, end)) = self.tokenizer.peek_token()? {
+            // Every postfix operator wraps the expression so far
+            self.descend()?;
+
             match token {
                 TokenType::IncrementOperator => {
                     // Consume the `++`
@@ -2064,51 +2053,7 @@ impl<'a> Parser<'a> {
                 }
                 TokenType::DotOperator | TokenType::ArrowOperator => {
                     self.tokenizer.next_token()?;
-                    // Expect an identifier
-                    match self.tokenizer.next_token()? {
-                        Some((TokenType::Identifier(identifier), id_start, id_end)) => {
-                            // Calculate span of the entire postfix expression
-                            // Span = Start of the previously parsed expression -> End of postfix operator
-                            let span = Span::new(expr_start, id_end);
-                            // Store the right member access operator
-                            let operator = if token == TokenType::DotOperator {
-                                MemberOperator::Direct
-                            } else {
-                                MemberOperator::Indirect
-                            };
-                            // Create and store a postfix expression using the already passed expression plus the postfix operator
-                            expression = Node::new(
-                                Expression::Member(Box::new(MemberExpression {
-                                    operator: Node::new(operator, Span::new(start, end)),
-                                    expression,
-                                    identifier: Node::new(identifier, Span::new(id_start, id_end)),
-                                })),
-                                span,
-                            );
-                        }
-                        Some((unexpected, unexpected_start, unexpected_end)) => {
-                            // This error will occur when there is no identifier specified after dot/arrow operator
-                            // struct_instance-> ;
-                            // struct_instance.  ;
-                            //                  ^ Missing identifiers
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SyntaxError,
-                                message: format!("Expected an identifier, instead got {:?}", unexpected),
-                                span: Some(Span::new(unexpected_start, unexpected_end)),
-                            });
-                        }
-                        None => {
-                            // This error will occur when there is an end of file and no identifier specified after dot/arrow operator
-                            // struct_instance->
-                            // struct_instance.
-                            //                  ^^ End of file, instead of identifiers
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SyntaxError,
-                                message: "Expected an identifier, instead got end of file".to_string(),
-                                span: None,
-                            });
-                        }
-                    }
+                    expression = self.parse_member_access(expression, expr_start, token, start, end)?;
                 }
                 TokenType::OpenSquareBracket => {
                     // Consume the `[`
@@ -2154,6 +2099,7 @@ impl<'a> Parser<'a> {
                     // No need to modify the already parsed expression
                     // As there is no postfix operator to be taken care of
                     // Hence return the expression
+                    self.depth = depth;
                     return Ok(expression);
                 }
             }
@@ -2162,9 +2108,72 @@ impl<'a> Parser<'a> {
             kind: CompilerErrorKind::SyntaxError,
             message: "Expected a postfix-expression, instead found end of file".to_string(),
             span: None,
+            code: Some(ErrorCode::UnexpectedToken),
+            ..Default::default()
         })
     }
 
+    /// Parses the identifier after the member access operator `operator` at `start`..`end`,
+    /// which is already consumed, and wraps `expression` in the member access
+    fn parse_member_access(
+        &mut self,
+        expression: Node<Expression>,
+        expr_start: Location,
+        operator: TokenType,
+        start: Location,
+        end: Location,
+    ) -> Result<Node<Expression>, CompilerError> {
+        // Expect an identifier
+        match self.tokenizer.next_token()? {
+            Some((TokenType::Identifier(identifier), id_start, id_end)) => {
+                // Calculate span of the entire postfix expression
+                // Span = Start of the previously parsed expression -> End of postfix operator
+                let span = Span::new(expr_start, id_end);
+                // Store the right member access operator
+                let operator = if operator == TokenType::DotOperator {
+                    MemberOperator::Direct
+                } else {
+                    MemberOperator::Indirect
+                };
+                // Create and return a postfix expression using the already passed expression plus the postfix operator
+                Ok(Node::new(
+                    Expression::Member(Box::new(MemberExpression {
+                        operator: Node::new(operator, Span::new(start, end)),
+                        expression,
+                        identifier: Node::new(identifier, Span::new(id_start, id_end)),
+                    })),
+                    span,
+                ))
+            }
+            Some((unexpected, unexpected_start, unexpected_end)) => {
+                // This error will occur when there is no identifier specified after dot/arrow operator
+                // struct_instance-> ;
+                // struct_instance.  ;
+                //                  ^ Missing identifiers
+                Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: format!("Expected an identifier, instead got {:?}", unexpected),
+                    span: Some(Span::new(unexpected_start, unexpected_end)),
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                })
+            }
+            None => {
+                // This error will occur when there is an end of file and no identifier specified after dot/arrow operator
+                // struct_instance->
+                // struct_instance.
+                //                  ^^ End of file, instead of identifiers
+                Err(CompilerError {
+                    kind: CompilerErrorKind::SyntaxError,
+                    message: "Expected an identifier, instead got end of file".to_string(),
+                    span: None,
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
+                })
+            }
+        }
+    }
+
     fn parse_postfix_expr(&mut self) -> Result<Node<Expression>, CompilerError> {
         // postfix-expression:
         //      primary-expression
@@ -2216,6 +2225,8 @@ impl<'a> Parser<'a> {
                 message: "Expected assignment expression for argument in the function call after `,` instead got `)`"
                     .to_string(),
                 span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
             });
         }
         // Return the argument expression list
@@ -2229,93 +2240,77 @@ impl<'a> Parser<'a> {
         //      string-literal
         //      ( expression )
         //      generic-selection
-        let expression: Node<Expression> = match self.tokenizer.next_token()? {
-            Some((token, start, end)) => match token {
-                TokenType::Identifier(identifier) => {
-                    Node::new(Expression::Identifier(identifier), Span::new(start, end))
-                }
-                TokenType::Integer(integer) => {
-                    Node::new(Expression::Constant(Constant::Integer(integer)), Span::new(start, end))
-                }
-                TokenType::FloatingPoint(floatingpoint) => Node::new(
-                    Expression::Constant(Constant::Float(floatingpoint)),
-                    Span::new(start, end),
-                ),
-                TokenType::Character(ch) => {
-                    Node::new(Expression::Constant(Constant::Character(ch)), Span::new(start, end))
-                }
-                TokenType::StringLiteral(strliteral) => {
-                    Node::new(Expression::StringLiteral(strliteral), Span::new(start, end))
-                }
-                TokenType::OpenParenthesis => {
-                    // TODO: This is probably never gonna be reached now as cast expressions have ( type-name ) in there grammar
-                    // And we check if inside the Parenthesis is a type-name, if not then we parse an expression
-                    // So all such expressions inside parenthesis type of expressions will be parsed in the `parse_cast_expression` function
-
-                    // Parse the entire expression inside the Parenthesis
-                    // A key thinking behind this is that the `parse_expr()` function
-                    // will keep parsing till it encounters something other than a recognized operator, constant or an identifier or another OpenParenthesis
-                    // So when encountered a CloseParenthesis, the function should return the existing expression, hence no need to check for that
-                    let expr = self.parse_expr()?;
-                    self.accept_token(TokenType::CloseParenthesis)?;
-                    expr
-                }
-                _ => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: format!("Expected expression, instead found: {:?}", token),
-                        span: Some(Span::new(start, end)),
-                    })
-                }
-            },
+        let Some((token, start, end)) = self.tokenizer.peek_token()? else {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SyntaxError,
+                message: "Expected expression, instead found end of file".to_string(),
+                span: None,
+                code: Some(ErrorCode::UnexpectedToken),
+                ..Default::default()
+            });
+        };
+
+        let expression: Node<Expression> = match token {
+            TokenType::Identifier(identifier) => Node::new(Expression::Identifier(identifier), Span::new(start, end)),
+            TokenType::Integer(integer) => {
+                Node::new(Expression::Constant(Constant::Integer(integer)), Span::new(start, end))
+            }
+            TokenType::FloatingPoint(floatingpoint) => Node::new(
+                Expression::Constant(Constant::Float(floatingpoint)),
+                Span::new(start, end),
+            ),
+            TokenType::Character(ch) => Node::new(Expression::Constant(Constant::Character(ch)), Span::new(start, end)),
+            TokenType::StringLiteral(strliteral) => {
+                Node::new(Expression::StringLiteral(strliteral), Span::new(start, end))
+            }
+            TokenType::OpenParenthesis => {
+                // TODO: This is probably never gonna be reached now as cast expressions have ( type-name ) in there grammar
+                // And we check if inside the Parenthesis is a type-name, if not then we parse an expression
+                // So all such expressions inside parenthesis type of expressions will be parsed in the `parse_cast_expression` function
+                self.tokenizer.next_token()?;
+
+                // Parse the entire expression inside the Parenthesis
+                // A key thinking behind this is that the `parse_expr()` function
+                // will keep parsing till it encounters something other than a recognized operator, constant or an identifier or another OpenParenthesis
+                // So when encountered a CloseParenthesis, the function should return the existing expression, hence no need to check for that
+                let expr = self.parse_expr()?;
+                self.accept_token(TokenType::CloseParenthesis)?;
+                return Ok(expr);
+            }
+            // The token is left unconsumed for error recovery to look at
             _ => {
                 return Err(CompilerError {
                     kind: CompilerErrorKind::SyntaxError,
-                    message: "Expected expression, instead found end of file".to_string(),
-                    span: None,
+                    message: format!("Expected expression, instead found: {:?}", token),
+                    span: Some(Span::new(start, end)),
+                    code: Some(ErrorCode::UnexpectedToken),
+                    ..Default::default()
                 })
             }
         };
-        Ok(expression)
-    }
 
-    fn accept_token_if<F>(&mut self, mut predicate: F) -> Result<(), CompilerError>
-    where
-        F: FnMut(TokenType) -> bool,
-    {
-        match self.tokenizer.next_token()? {
-            Some((token, start, end)) => {
-                // Token is cloned to avoid borrowing
-                if predicate(token.clone()) {
-                    Ok(())
-                } else {
-                    Err(CompilerError {
-                        kind: CompilerErrorKind::SyntaxError,
-                        message: format!("Failed to accept token: {:?}, predicate failed", token),
-                        span: Some(Span::new(start, end)),
-                    })
-                }
-            }
-            None => Err(CompilerError {
-                kind: CompilerErrorKind::SyntaxError,
-                message: "Expected a token".to_string(),
-                span: None,
-            }),
-        }
+        // Consume the token of the expression
+        self.tokenizer.next_token()?;
+        Ok(expression)
     }
 
     /// Forces the next token to be the given `tokentype`
     /// Returns (start, end) both being character indices in the file
+    /// A token of any other type is left for error recovery to look at
     fn accept_token(&mut self, tokentype: TokenType) -> Result<(Location, Location), CompilerError> {
-        match self.tokenizer.next_token()? {
+        match self.tokenizer.peek_token()? {
             Some((token, start, end)) => {
                 if token == tokentype {
+                    self.tokenizer.next_token()?;
                     Ok((start, end))
                 } else {
                     Err(CompilerError {
                         kind: CompilerErrorKind::SyntaxError,
                         message: format!("Expected token: {:?}, instead found: {:?}", tokentype, token),
                         span: Some(Span::new(start, end)),
+                        code: Some(ErrorCode::ExpectedToken),
+                        fixits: self.insert_missing(&tokentype),
+                        ..Default::default()
                     })
                 }
             }
@@ -2323,7 +2318,24 @@ impl<'a> Parser<'a> {
                 kind: CompilerErrorKind::SyntaxError,
                 message: format!("Expected token: {:?}", tokentype),
                 span: None,
+                code: Some(ErrorCode::ExpectedToken),
+                fixits: self.insert_missing(&tokentype),
+                ..Default::default()
             }),
         }
     }
+
+    /// A fix-it inserting `tokentype` right after the last token accepted, if it is
+    /// punctuation that is usually just forgotten, like the `;` ending a statement
+    fn insert_missing(&self, tokentype: &TokenType) -> Box<[FixIt]> {
+        let text = match tokentype {
+            TokenType::Semicolon => ";",
+            TokenType::CloseParenthesis => ")",
+            TokenType::CloseSquareBracket => "]",
+            TokenType::Colon => ":",
+            _ => return Box::default(),
+        };
+        let after = self.tokenizer.get_lineinfo();
+        Box::new([FixIt::insert(Span::new(after, after), text)])
+    }
 }
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
 use std::iter::zip;
+use std::mem;
 
 use crate::analysis::ast::ExpressionValueType::{LValue, ModifiableLValue, RValue};
-use crate::analysis::ast::UnaryOperator::PostDecrement;
 use crate::analysis::{ast::*, node::Span};
-use crate::core::errors::{CompilerError, CompilerErrorKind, Diagnostic, VecExtensionDiagnosticHelpers};
+use crate::core::errors::{
+    CompilerError, CompilerErrorKind, Diagnostic, ErrorCode, Label, VecExtensionDiagnosticHelpers,
+};
 use crate::core::symboltable::{SymbolDefinition, SymbolTable};
+use crate::core::target::DataLayout;
 use crate::core::typedefs::{AssignmentConversionResult, DataType, IntegerRank, Type, TypeQualifiers};
 
 use super::node::Node;
@@ -15,16 +19,22 @@ pub struct SemanticAnalyzer<'a> {
     counter: u32,
     num_loops_or_switches: u32,
     diagnostics: &'a mut Vec<Diagnostic>,
+    layout: DataLayout,
+    errors: Vec<CompilerError>,
+    defined_functions: HashMap<String, Span>,
 }
 
 impl<'a> SemanticAnalyzer<'a> {
-    pub fn new(symboltableref: &'a mut SymbolTable, diagnostics: &'a mut Vec<Diagnostic>) -> Self {
+    pub fn new(symboltableref: &'a mut SymbolTable, diagnostics: &'a mut Vec<Diagnostic>, layout: DataLayout) -> Self {
         Self {
             symboltableref,
             scopeidstack: vec![0], // 0 represents global scope
             counter: 1,
             num_loops_or_switches: 0,
             diagnostics,
+            layout,
+            errors: Vec::new(),
+            defined_functions: HashMap::new(),
         }
     }
 
@@ -52,31 +62,58 @@ impl<'a> SemanticAnalyzer<'a> {
         self.scopeidstack.pop();
     }
 
-    pub fn analyze(&mut self, translation_unit: &mut TranslationUnit) -> Result<(), CompilerError> {
+    /// Checks the whole translation unit. An error doesn't stop the analysis: it is recorded,
+    /// and whatever failed to check is given [`DataType::Error`] so that the expressions and
+    /// declarations using it don't report it again.
+    pub fn analyze(&mut self, translation_unit: &mut TranslationUnit) -> Result<(), Vec<CompilerError>> {
         for extdecl in &mut translation_unit.external_declarations {
             match &mut extdecl.node {
-                ExternalDeclaration::FunctionDefinition(funcdef) => self.validate_function_def(funcdef)?,
-                ExternalDeclaration::Declaration(declaration) => self.validate_declaration(declaration)?,
+                ExternalDeclaration::FunctionDefinition(funcdef) => self.validate_function_def(funcdef),
+                ExternalDeclaration::Declaration(declaration) => self.validate_declaration(declaration),
             }
         }
-        Ok(())
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Records the error of a check that failed, so that the analysis can carry on
+    fn record<T>(&mut self, result: Result<T, CompilerError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.push(error);
+                None
+            }
+        }
+    }
+
+    /// The type and storage class that the declaration specifiers describe, or the error type
+    /// if they don't describe one
+    fn declared_type(&mut self, specifiers: &[Node<DeclarationSpecifier>]) -> (Type, StorageClassFlags) {
+        self.record(Type::from_declaration_specifiers(specifiers))
+            .unwrap_or((Type::new(DataType::Error), 0))
     }
 
-    fn validate_function_def(&mut self, function_def: &mut FunctionDefinition) -> Result<(), CompilerError> {
+    fn validate_function_def(&mut self, function_def: &mut FunctionDefinition) {
         let Statement::CompoundStatement(compound_stmt) = &mut function_def.body.node else {
-            return Err(CompilerError {
+            self.errors.push(CompilerError {
                 kind: CompilerErrorKind::SemanticError,
                 message: "Function body must be a compound statement".to_string(),
                 span: Some(function_def.body.span),
+                ..Default::default()
             });
+            return;
         };
 
         // Get the return type from declaration specifiers using the function definition
-        let (expected_return_type, _) = Type::from_declaration_specifiers(&function_def.specifiers)?;
+        let (expected_return_type, _) = self.declared_type(&function_def.specifiers);
 
         // Note the scope outside the function
         let scopeid: u32 = *self.scopeidstack.last().unwrap();
-        let mut param_types: Vec<Type> = Vec::new();
 
         assert!(
             scopeid == 0,
@@ -84,73 +121,138 @@ impl<'a> SemanticAnalyzer<'a> {
             scopeid
         );
 
+        // Declare the function itself before its body, so that it may call itself
+        let declarator = &function_def.declarator;
+        let function_type = self.function_type(expected_return_type.clone(), &declarator.node);
+        self.declare_function(&declarator.node.identifier, function_type, declarator.span);
+
+        if let Some(previous) = self
+            .defined_functions
+            .insert(declarator.node.identifier.clone(), declarator.span)
+        {
+            self.errors.push(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!("Redefinition of function `{}`", declarator.node.identifier),
+                span: Some(declarator.span),
+                code: Some(ErrorCode::Redefinition),
+                labels: Box::new([Label {
+                    span: previous,
+                    message: "previous definition is here".to_string(),
+                }]),
+                ..Default::default()
+            });
+        }
+
         self.push_scope();
 
-        // Insert params as symbols in the symbol table belonging to the function scope
+        // Insert params as symbols in the symbol table belonging to the function scope, to help
+        // evaluation of the function body
         for param in &function_def.declarator.node.parameters {
-            let (param_type, param_storage_class) = Type::from_declaration_specifiers(&param.node.specifiers)?;
-
-            // 1. Push the param_type into the param_types vector, which will be needed for the
-            //    function symbol definition info
-            param_types.push(param_type.clone());
-
-            // 2. Insert the individual param as a separate symbol, to help evaluation of the
-            //    function body
-            if let Some(declarator) = &param.node.declarator {
-                match &declarator.node {
-                    Declarator::DirectDeclarator(idname) => {
-                        self.symboltableref.insert(
-                            idname.as_str(),
-                            *self.scopeidstack.last().unwrap(),
-                            param_type,
-                            param_storage_class,
-                            None,
-                        )?;
-                    }
-                    _ => todo!(),
-                }
+            let (param_type, param_storage_class) = self.declared_type(&param.node.specifiers);
+
+            if let Some(Node {
+                node: Declarator::DirectDeclarator(idname),
+                span,
+            }) = &param.node.declarator
+            {
+                let inserted = self.symboltableref.insert(
+                    idname.as_str(),
+                    *self.scopeidstack.last().unwrap(),
+                    param_type,
+                    param_storage_class,
+                    None,
+                    Some(*span),
+                );
+                self.record(inserted.map_err(|error| located(error, *span)));
             }
         }
 
-        // Construct the function signature
-        let function_type = Type::new(DataType::Function {
-            return_type: Box::new(expected_return_type.clone()),
-            parameters: param_types,
-        });
-
-        // Insert the function itself as a symbol into the symbol table
-        self.symboltableref.insert(
-            function_def.declarator.node.identifier.as_str(),
-            scopeid,
-            function_type,
-            0,
-            None,
-        )?;
-
         for blockitem in compound_stmt {
+            let span = blockitem.span;
             match &mut blockitem.node {
-                BlockItem::Declaration(declaration) => self.validate_declaration(declaration)?,
-                BlockItem::Statement(stmt) => self.validate_statement(stmt, &expected_return_type)?,
+                BlockItem::Declaration(declaration) => self.validate_declaration(declaration),
+                BlockItem::Statement(stmt) => self.validate_statement(stmt, &span, &expected_return_type),
             }
         }
 
         self.pop_scope();
-        Ok(())
     }
 
-    fn validate_statement(
-        &mut self,
-        statement: &mut Statement,
-        expected_return_type: &Type,
-    ) -> Result<(), CompilerError> {
+    /// The type of a function returning `return_type` with the parameters of `declarator`
+    fn function_type(&mut self, return_type: Type, declarator: &FunctionDeclarator) -> Type {
+        let mut parameters = Vec::new();
+
+        for param in &declarator.parameters {
+            let (param_type, _) = self.declared_type(&param.node.specifiers);
+
+            if let Some(
+                param_declarator @ Node {
+                    node: Declarator::FunctionDeclarator(_),
+                    ..
+                },
+            ) = &param.node.declarator
+            {
+                self.errors.push(CompilerError {
+                    kind: CompilerErrorKind::SemanticError,
+                    message: "Function parameters of function type are not supported yet".to_string(),
+                    span: Some(param_declarator.span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                });
+            }
+
+            parameters.push(param_type);
+        }
+
+        Type::new(DataType::Function {
+            return_type: Box::new(return_type),
+            parameters,
+        })
+    }
+
+    /// Declares a function at file scope. A function may be declared any number of times, as
+    /// long as every declaration gives it the same type
+    fn declare_function(&mut self, name: &str, function_type: Type, span: Span) {
+        match self.symboltableref.lookup(name, 0) {
+            Some(symboldef) if symboldef.typeinfo != function_type => {
+                let message = format!(
+                    "Conflicting types for `{}`: {} and {}",
+                    name, symboldef.typeinfo, function_type
+                );
+                self.errors.push(CompilerError {
+                    kind: CompilerErrorKind::SemanticError,
+                    message,
+                    span: Some(span),
+                    code: Some(ErrorCode::ConflictingTypes),
+                    labels: symboldef
+                        .span
+                        .map(|span| Label {
+                            span,
+                            message: "previous declaration is here".to_string(),
+                        })
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
+                });
+            }
+            Some(_) => {}
+            None => {
+                let inserted = self.symboltableref.insert(name, 0, function_type, 0, None, Some(span));
+                self.record(inserted.map_err(|error| located(error, span)));
+            }
+        }
+    }
+
+    fn validate_statement(&mut self, statement: &mut Statement, span: &Span, expected_return_type: &Type) {
         match statement {
             Statement::CompoundStatement(compound_stmt) => {
                 self.push_scope();
 
                 for blockitem in compound_stmt {
+                    let span = blockitem.span;
                     match &mut blockitem.node {
-                        BlockItem::Declaration(declaration) => self.validate_declaration(declaration)?,
-                        BlockItem::Statement(stmt) => self.validate_statement(stmt, expected_return_type)?,
+                        BlockItem::Declaration(declaration) => self.validate_declaration(declaration),
+                        BlockItem::Statement(stmt) => self.validate_statement(stmt, &span, expected_return_type),
                     }
                 }
 
@@ -159,11 +261,13 @@ impl<'a> SemanticAnalyzer<'a> {
 
             Statement::ReturnStatement(return_stmt) => {
                 // Check if return type is same as the expected_return_type, if not check if it's castable
-                let (return_type, _) = self.validate_expr(&mut return_stmt.node, &return_stmt.span)?;
+                let (return_type, _) = self.check_expr(&mut return_stmt.node, &return_stmt.span);
 
-                match Type::check_assignment_conversion(expected_return_type, &return_type)? {
-                    AssignmentConversionResult::Identical => {}
-                    result => {
+                let conversion = Type::check_assignment_conversion(expected_return_type, &return_type, &self.layout)
+                    .map_err(|error| located(error, return_stmt.span));
+                match self.record(conversion) {
+                    None | Some(AssignmentConversionResult::Identical) => {}
+                    Some(result) => {
                         Self::implicit_cast(return_stmt, &expected_return_type.datatype);
 
                         if let AssignmentConversionResult::CastWithWarning(warning) = result {
@@ -175,7 +279,7 @@ impl<'a> SemanticAnalyzer<'a> {
 
             Statement::ExpressionStatement(expr_node) => {
                 if let Some(expression) = expr_node {
-                    self.validate_expr(&mut expression.node, &expression.span)?;
+                    self.check_expr(&mut expression.node, &expression.span);
                 }
             }
 
@@ -199,33 +303,39 @@ impl<'a> SemanticAnalyzer<'a> {
                 match &mut for_stmt.initializer.node {
                     ForInitializer::Empty => {}
                     ForInitializer::Expression(expression) => {
-                        self.validate_expr(expression, &for_stmt.initializer.span)?;
+                        self.check_expr(expression, &for_stmt.initializer.span);
                     }
-                    ForInitializer::Declaration(declaration) => self.validate_declaration(declaration)?,
+                    ForInitializer::Declaration(declaration) => self.validate_declaration(declaration),
                 }
 
                 // 2. Evaluate condition and check if the type can evaluate into a boolean
                 if let Some(condition) = &mut for_stmt.condition {
-                    let (condition_type, _) = self.validate_expr(&mut condition.node, &condition.span)?;
+                    let (condition_type, _) = self.check_expr(&mut condition.node, &condition.span);
 
                     if Type::is_boolean_compatible(&condition_type) {
                         Self::implicit_cast_to_bool(condition, &condition_type);
                     } else {
-                        return Err(CompilerError {
+                        self.errors.push(CompilerError {
                             kind: CompilerErrorKind::SemanticError,
                             message: format!("Expected boolean expression, instead got {}", condition_type),
                             span: Some(condition.span),
+                            code: Some(ErrorCode::NonScalarCondition),
+                            ..Default::default()
                         });
                     }
                 }
 
                 // 3. Evaluate step statement of for-loop
                 if let Some(step_expr) = &mut for_stmt.step {
-                    self.validate_expr(&mut step_expr.node, &step_expr.span)?;
+                    self.check_expr(&mut step_expr.node, &step_expr.span);
                 }
 
                 // 4. Evaluate the for-loop body
-                self.validate_statement(&mut for_stmt.statement.node, expected_return_type)?;
+                self.validate_statement(
+                    &mut for_stmt.statement.node,
+                    &for_stmt.statement.span,
+                    expected_return_type,
+                );
 
                 // Pop the scope id as we have exited the for-loop scope
                 self.pop_scope();
@@ -236,91 +346,141 @@ impl<'a> SemanticAnalyzer<'a> {
 
             Statement::WhileStatement(while_stmt) | Statement::DoWhileStatement(while_stmt) => {
                 // 1. Evaluate condition and check if the type can evaluate into a boolean
-                let (condition_type, _) =
-                    self.validate_expr(&mut while_stmt.condition.node, &while_stmt.condition.span)?;
+                let (condition_type, _) = self.check_expr(&mut while_stmt.condition.node, &while_stmt.condition.span);
 
                 if Type::is_boolean_compatible(&condition_type) {
                     Self::implicit_cast_to_bool(&mut while_stmt.condition, &condition_type);
                 } else {
-                    return Err(CompilerError {
+                    self.errors.push(CompilerError {
                         kind: CompilerErrorKind::SemanticError,
                         message: format!("Expected boolean expression, instead got {}", condition_type),
                         span: Some(while_stmt.condition.span),
+                        code: Some(ErrorCode::NonScalarCondition),
+                        ..Default::default()
                     });
                 }
 
                 self.num_loops_or_switches += 1;
                 // 2. Evaluate the while-loop body
-                self.validate_statement(&mut while_stmt.statement.node, expected_return_type)?;
+                self.validate_statement(
+                    &mut while_stmt.statement.node,
+                    &while_stmt.statement.span,
+                    expected_return_type,
+                );
                 self.num_loops_or_switches -= 1;
             }
 
             Statement::IfStatement(if_stmt) => {
-                // 1. Evaluate condition and check if the type can evaluate into a boolean
-                let (condition_type, _) = self.validate_expr(&mut if_stmt.condition.node, &if_stmt.condition.span)?;
+                // An `else if` chain is checked arm by arm in a loop, as it nests as deep as it
+                // is long
+                let mut if_stmt = &mut **if_stmt;
+                loop {
+                    // 1. Evaluate condition and check if the type can evaluate into a boolean
+                    let (condition_type, _) = self.check_expr(&mut if_stmt.condition.node, &if_stmt.condition.span);
 
-                if Type::is_boolean_compatible(&condition_type) {
-                    Self::implicit_cast_to_bool(&mut if_stmt.condition, &condition_type);
-                } else {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SemanticError,
-                        message: format!("Expected boolean expression, instead got {}", condition_type),
-                        span: Some(if_stmt.condition.span),
-                    });
-                }
-
-                // 2. Evaluate the if-statement body
-                self.validate_statement(&mut if_stmt.if_block.node, expected_return_type)?;
+                    if Type::is_boolean_compatible(&condition_type) {
+                        Self::implicit_cast_to_bool(&mut if_stmt.condition, &condition_type);
+                    } else {
+                        self.errors.push(CompilerError {
+                            kind: CompilerErrorKind::SemanticError,
+                            message: format!("Expected boolean expression, instead got {}", condition_type),
+                            span: Some(if_stmt.condition.span),
+                            code: Some(ErrorCode::NonScalarCondition),
+                            ..Default::default()
+                        });
+                    }
 
-                // 3. Evaluate the else-statement body if it exists
-                if let Some(else_block) = &mut if_stmt.else_block {
-                    self.validate_statement(&mut else_block.node, expected_return_type)?;
+                    // 2. Evaluate the if-statement body
+                    self.validate_statement(&mut if_stmt.if_block.node, &if_stmt.if_block.span, expected_return_type);
+
+                    // 3. Evaluate the else-statement body if it exists
+                    match &mut if_stmt.else_block {
+                        Some(Node {
+                            node: Statement::IfStatement(else_if),
+                            ..
+                        }) => if_stmt = else_if,
+                        Some(else_block) => {
+                            self.validate_statement(&mut else_block.node, &else_block.span, expected_return_type);
+                            break;
+                        }
+                        None => break,
+                    }
                 }
             }
 
             Statement::SwitchStatement(switch_stmt) => {
                 self.num_loops_or_switches += 1;
-                self.validate_statement(&mut switch_stmt.statement.node, expected_return_type)?;
+                self.validate_statement(
+                    &mut switch_stmt.statement.node,
+                    &switch_stmt.statement.span,
+                    expected_return_type,
+                );
                 self.num_loops_or_switches -= 1;
             }
 
             Statement::BreakStatement | Statement::ContinueStatement => {
                 if self.num_loops_or_switches == 0 {
-                    let keyword = match statement {
-                        Statement::BreakStatement => "break",
-                        Statement::ContinueStatement => "continue",
-                        _ => unreachable!(), // Only these two arms are possible
+                    let keyword = if matches!(statement, Statement::BreakStatement) {
+                        "break"
+                    } else {
+                        "continue"
                     };
 
-                    return Err(CompilerError {
+                    self.errors.push(CompilerError {
                         kind: CompilerErrorKind::SemanticError,
                         message: format!("{} statement not allowed outside of a loop", keyword),
-                        span: None,
+                        span: Some(*span),
+                        code: Some(ErrorCode::JumpOutsideLoop),
+                        ..Default::default()
                     });
                 }
             }
 
-            _ => todo!(),
+            Statement::CaseStatement(case_stmt) => {
+                self.check_expr(&mut case_stmt.constexpr.node, &case_stmt.constexpr.span);
+                self.validate_statement(
+                    &mut case_stmt.statement.node,
+                    &case_stmt.statement.span,
+                    expected_return_type,
+                );
+            }
+
+            Statement::DefaultStatement(statement) => {
+                self.validate_statement(&mut statement.node, &statement.span, expected_return_type);
+            }
+
+            Statement::LabeledStatement(labeled_stmt) => {
+                self.validate_statement(
+                    &mut labeled_stmt.statement.node,
+                    &labeled_stmt.statement.span,
+                    expected_return_type,
+                );
+            }
+
+            // Labels aren't recorded, so there is nothing to check the target against
+            Statement::GotoStatement(_) => {}
         }
-        Ok(())
     }
 
-    fn validate_declaration(&mut self, declaration: &mut Declaration) -> Result<(), CompilerError> {
+    fn validate_declaration(&mut self, declaration: &mut Declaration) {
         for init_decl in &mut declaration.init_declarators {
             // 1. Convert set of declaration specifiers to an actual type
-            let (declaration_type, storage_class) = Type::from_declaration_specifiers(&declaration.specifiers)?;
+            let (declaration_type, storage_class) = self.declared_type(&declaration.specifiers);
 
             if let Some(init_node) = &mut init_decl.node.initializer {
                 match &mut init_node.node {
                     Initializer::AssignmentExpression(asgn_expr) => {
                         // Evaluate the Type of the assignment expression
-                        let (rhs_typeinfo, _) = self.validate_expr(asgn_expr, &init_node.span)?;
+                        let (rhs_typeinfo, _) = self.check_expr(asgn_expr, &init_node.span);
 
                         // 2. Check if the expression type is compatible with the declaration type
-                        match Type::check_assignment_conversion(&declaration_type, &rhs_typeinfo)? {
-                            AssignmentConversionResult::Identical => {}
+                        let conversion =
+                            Type::check_assignment_conversion(&declaration_type, &rhs_typeinfo, &self.layout)
+                                .map_err(|error| located(error, init_node.span));
+                        match self.record(conversion) {
+                            None | Some(AssignmentConversionResult::Identical) => {}
 
-                            result => {
+                            Some(result) => {
                                 // 1. Extracting expression from the existing initializer enum
                                 let Initializer::AssignmentExpression(temp_expr) = std::mem::replace(
                                     &mut init_node.node,
@@ -345,22 +505,58 @@ impl<'a> SemanticAnalyzer<'a> {
             }
 
             // 3. Insert into the symbol table this declaration with it's details and scope ID
-            match &init_decl.node.declarator.node {
+            let declarator = &init_decl.node.declarator;
+            match &declarator.node {
                 Declarator::DirectDeclarator(idname) => {
-                    self.symboltableref.insert(
+                    let inserted = self.symboltableref.insert(
                         idname,
                         *self.scopeidstack.last().unwrap(),
                         declaration_type,
                         storage_class,
                         None,
-                    )?;
+                        Some(declarator.span),
+                    );
+                    self.record(inserted.map_err(|error| located(error, declarator.span)));
                 }
-                Declarator::FunctionDeclarator(_) => {
-                    todo!()
+                Declarator::FunctionDeclarator(function_declarator) => {
+                    if *self.scopeidstack.last().unwrap() != 0 {
+                        self.errors.push(CompilerError {
+                            kind: CompilerErrorKind::SemanticError,
+                            message: "Function declarations are only supported at file scope".to_string(),
+                            span: Some(declarator.span),
+                            code: Some(ErrorCode::Unsupported),
+                            ..Default::default()
+                        });
+                        continue;
+                    }
+
+                    if let Some(initializer) = &init_decl.node.initializer {
+                        self.errors.push(CompilerError {
+                            kind: CompilerErrorKind::SemanticError,
+                            message: format!("Function `{}` cannot be initialized", function_declarator.identifier),
+                            span: Some(initializer.span),
+                            ..Default::default()
+                        });
+                    }
+
+                    let function_type = self.function_type(declaration_type, function_declarator);
+                    self.declare_function(&function_declarator.identifier, function_type, declarator.span);
                 }
             }
         }
-        Ok(())
+    }
+
+    /// Same as [`SemanticAnalyzer::validate_expr`], but records the error if the expression
+    /// doesn't check, and gives it the error type instead. Being a modifiable lvalue of the
+    /// error type, it is accepted everywhere an expression is.
+    fn check_expr(&mut self, expression: &mut Expression, span: &Span) -> (Type, ExpressionValueType) {
+        match self.validate_expr(expression, span) {
+            Ok(checked) => checked,
+            Err(error) => {
+                self.errors.push(located(error, *span));
+                (Type::new(DataType::Error), ModifiableLValue)
+            }
+        }
     }
 
     fn validate_expr(
@@ -375,6 +571,9 @@ impl<'a> SemanticAnalyzer<'a> {
         // 4. If conversion is needed and possible then:
         //      a. Either insert an implicit conversion in AST (like gcc does)
         //      b. Or if it is a constant then convert it immediately
+        //
+        // Every subexpression is checked through this function, so the expressions that have
+        // subexpressions are checked by functions of their own to keep its frame small.
 
         match expression {
             Expression::Empty => Ok((Type::new(DataType::Void), RValue)),
@@ -397,6 +596,8 @@ impl<'a> SemanticAnalyzer<'a> {
                             idname
                         ),
                         span: Some(*span),
+                        code: Some(ErrorCode::UndeclaredIdentifier),
+                        ..Default::default()
                     }),
                 }
             }
@@ -411,301 +612,440 @@ impl<'a> SemanticAnalyzer<'a> {
                 LValue,
             )),
 
-            Expression::UnaryOperator(unary_expr) => {
-                let (operand_type, operand_val_type) =
-                    self.validate_expr(&mut unary_expr.operand.node, &unary_expr.operand.span)?;
+            Expression::UnaryOperator(unary_expr) => self.validate_unary_expr(unary_expr),
 
-                // check if type is compatible with the unary operator
-                if !operand_type
-                    .datatype
-                    .is_compatible_with_unary_operator(&unary_expr.operator.node)
-                {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::SemanticError,
-                        message: format!(
-                            "operand type {} not compatible with {}",
-                            operand_type, unary_expr.operator.node
-                        ),
-                        span: Some(unary_expr.operand.span),
-                    });
-                }
+            Expression::BinaryOperator(binary_expr) => self.validate_binary_expr(binary_expr),
 
-                match &unary_expr.operator.node {
-                    UnaryOperator::Minus | UnaryOperator::Plus | UnaryOperator::Complement | UnaryOperator::Negate => {
-                        if !operand_type.datatype.is_integer() {
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SemanticError,
-                                message: format!(
-                                    "unary operator {} is only supported on integer types for now",
-                                    unary_expr.operator.node
-                                ),
-                                span: Some(unary_expr.operator.span),
-                            });
-                        }
+            Expression::AssignOperator(assign_expr) => self.validate_assign_expr(assign_expr),
 
-                        Ok((
-                            if matches!(&unary_expr.operator.node, UnaryOperator::Negate) {
-                                Type {
-                                    datatype: DataType::new_integer(IntegerRank::Int, true),
-                                    qualifiers: operand_type.qualifiers,
-                                }
-                            } else {
-                                operand_type
-                            },
-                            RValue,
-                        ))
-                    }
+            Expression::TernaryOperator(ternary_expr) => self.validate_ternary_expr(ternary_expr, span),
 
-                    inc_dec_op @ (UnaryOperator::PreIncrement
-                    | UnaryOperator::PostIncrement
-                    | UnaryOperator::PreDecrement
-                    | UnaryOperator::PostDecrement) => {
-                        // ensure that operand is a modifiable lvalue
-                        if !matches!(operand_val_type, ModifiableLValue) {
-                            Err(CompilerError {
-                                kind: CompilerErrorKind::SemanticError,
-                                message: format!("operand to {} must be a modifiable lvalue", inc_dec_op),
-                                span: Some(unary_expr.operator.span),
-                            })
-                        } else {
-                            Ok((operand_type, RValue))
-                        }
-                    }
+            Expression::Call(call_expr) => self.validate_call_expr(call_expr, span),
 
-                    op => {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::SemanticError,
-                            message: format!("unary operator {} is not supported yet", op),
-                            span: Some(unary_expr.operator.span),
-                        })
-                    }
-                }
-            }
+            Expression::Comma(comma_exprs) => self.validate_comma_expr(comma_exprs, span),
 
-            Expression::BinaryOperator(binary_expr) => {
-                // 1. evaluate lhs and rhs expression types
-                let (lhs_typeinfo, _) = self.validate_expr(&mut binary_expr.lhs.node, &binary_expr.lhs.span)?;
-                let (rhs_typeinfo, _) = self.validate_expr(&mut binary_expr.rhs.node, &binary_expr.rhs.span)?;
+            Expression::ArraySubscript(_)
+            | Expression::SizeofType(_)
+            | Expression::SizeofVal(_)
+            | Expression::Alignof(_)
+            | Expression::Member(_)
+            | Expression::Cast(_)
+            | Expression::ImplicitCast(_) => {
+                let kind = match expression {
+                    Expression::ArraySubscript(_) => "Array subscript",
+                    Expression::SizeofType(_) | Expression::SizeofVal(_) => "`sizeof`",
+                    Expression::Alignof(_) => "`_Alignof`",
+                    Expression::Member(_) => "Member access",
+                    _ => "Cast",
+                };
 
-                // 2. usual arithmetic conversions
-                let uac_datatype = Type::common_datatype_for_uac(&lhs_typeinfo, &rhs_typeinfo)?;
+                Err(CompilerError {
+                    kind: CompilerErrorKind::SemanticError,
+                    message: format!("{} expressions are not supported yet", kind),
+                    span: Some(*span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                })
+            }
+        }
+    }
 
-                if uac_datatype != lhs_typeinfo.datatype {
-                    Self::implicit_cast(&mut binary_expr.lhs, &uac_datatype);
-                }
+    fn validate_unary_expr(
+        &mut self,
+        unary_expr: &mut UnaryOperatorExpression,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        let (operand_type, operand_val_type) = self.check_expr(&mut unary_expr.operand.node, &unary_expr.operand.span);
 
-                if uac_datatype != rhs_typeinfo.datatype {
-                    Self::implicit_cast(&mut binary_expr.rhs, &uac_datatype);
-                }
+        if operand_type.datatype.is_error() {
+            return Ok((operand_type, RValue));
+        }
+
+        // check if type is compatible with the unary operator
+        if !operand_type
+            .datatype
+            .is_compatible_with_unary_operator(&unary_expr.operator.node)
+        {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!(
+                    "operand type {} not compatible with {}",
+                    operand_type, unary_expr.operator.node
+                ),
+                span: Some(unary_expr.operand.span),
+                code: Some(ErrorCode::InvalidOperands),
+                ..Default::default()
+            });
+        }
 
-                // 3. check if the uac type is compatible with the type of operator used
-                if !uac_datatype.is_compatible_with_binary_operator(&binary_expr.operator.node) {
+        match &unary_expr.operator.node {
+            UnaryOperator::Minus | UnaryOperator::Plus | UnaryOperator::Complement | UnaryOperator::Negate => {
+                if !operand_type.datatype.is_integer() {
                     return Err(CompilerError {
                         kind: CompilerErrorKind::SemanticError,
                         message: format!(
-                            "Incompatible operand ({}) usage with operator ({:?})",
-                            uac_datatype, binary_expr.operator.node
+                            "unary operator {} is only supported on integer types for now",
+                            unary_expr.operator.node
                         ),
-                        span: Some(binary_expr.operator.span),
+                        span: Some(unary_expr.operator.span),
+                        code: Some(ErrorCode::Unsupported),
+                        ..Default::default()
                     });
                 }
 
-                // 4. return evaluated type and drop qualifiers as binary expression is always an rvalue
-                Ok((Type::new(uac_datatype), RValue))
+                Ok((
+                    if matches!(&unary_expr.operator.node, UnaryOperator::Negate) {
+                        Type {
+                            datatype: DataType::new_integer(IntegerRank::Int, true),
+                            qualifiers: operand_type.qualifiers,
+                        }
+                    } else {
+                        operand_type
+                    },
+                    RValue,
+                ))
             }
 
-            Expression::AssignOperator(assign_expr) => {
-                // 1. evaluate lhs and rhs expression types
-                let (lhs_type, lhs_val_type) = self.validate_expr(&mut assign_expr.lhs.node, &assign_expr.lhs.span)?;
-                let (rhs_type, _) = self.validate_expr(&mut assign_expr.rhs.node, &assign_expr.rhs.span)?;
-
-                if !matches!(lhs_val_type, ModifiableLValue) {
-                    return Err(CompilerError {
+            inc_dec_op @ (UnaryOperator::PreIncrement
+            | UnaryOperator::PostIncrement
+            | UnaryOperator::PreDecrement
+            | UnaryOperator::PostDecrement) => {
+                // ensure that operand is a modifiable lvalue
+                if !matches!(operand_val_type, ModifiableLValue) {
+                    Err(CompilerError {
                         kind: CompilerErrorKind::SemanticError,
-                        message: "lhs of an assignment expression must be a modifiable lvalue".to_string(),
-                        span: Some(assign_expr.lhs.span),
-                    });
+                        message: format!("operand to {} must be a modifiable lvalue", inc_dec_op),
+                        span: Some(unary_expr.operator.span),
+                        code: Some(ErrorCode::NotAssignable),
+                        ..Default::default()
+                    })
+                } else {
+                    Ok((operand_type, RValue))
                 }
+            }
 
-                let final_rhs_type: Type;
+            op => Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!("unary operator {} is not supported yet", op),
+                span: Some(unary_expr.operator.span),
+                code: Some(ErrorCode::Unsupported),
+                ..Default::default()
+            }),
+        }
+    }
 
-                if let Some(underlying_binary_op) = assign_expr.operator.node.underlying_binary_op() {
-                    // 2. usual arithmetic conversions
-                    let uac_datatype = Type::common_datatype_for_uac(&lhs_type, &rhs_type)?;
+    fn validate_binary_expr(
+        &mut self,
+        binary_expr: &mut BinaryOperatorExpression,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        // `a + b + c + ...` nests to the left as deep as it is long, so the operators down the
+        // left side are taken out of the tree and checked from the innermost one out in a loop,
+        // then put back
+        let mut chain = Vec::new();
+        let mut lhs = mem::replace(&mut binary_expr.lhs.node, Expression::Empty);
+        while let Expression::BinaryOperator(mut inner) = lhs {
+            lhs = mem::replace(&mut inner.lhs.node, Expression::Empty);
+            chain.push(inner);
+        }
 
-                    if uac_datatype != rhs_type.datatype {
-                        Self::implicit_cast(&mut assign_expr.rhs, &uac_datatype);
-                    }
+        let innermost = chain.last_mut().map_or(&mut *binary_expr, |inner| &mut **inner);
+        innermost.lhs.node = lhs;
+        let (mut lhs_typeinfo, _) = self.check_expr(&mut innermost.lhs.node, &innermost.lhs.span);
+
+        while let Some(mut inner) = chain.pop() {
+            let outer = chain.last_mut().map_or(&mut *binary_expr, |outer| &mut **outer);
+            lhs_typeinfo = match self.validate_binary_operator(&mut inner, lhs_typeinfo) {
+                Ok((typeinfo, _)) => typeinfo,
+                Err(error) => {
+                    self.errors.push(located(error, outer.lhs.span));
+                    Type::new(DataType::Error)
+                }
+            };
+            outer.lhs.node = Expression::BinaryOperator(inner);
+        }
 
-                    // 3. check if the composite type is compatible with the type of operator used
-                    if !uac_datatype.is_compatible_with_binary_operator(&underlying_binary_op) {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::SemanticError,
-                            message: format!(
-                                "Incompatible operand ({}) usage with operator ({:?})",
-                                uac_datatype, assign_expr.operator.node
-                            ),
-                            span: Some(assign_expr.operator.span),
-                        });
-                    }
+        self.validate_binary_operator(binary_expr, lhs_typeinfo)
+    }
 
-                    final_rhs_type = Type::new(uac_datatype);
-                    assign_expr.uac_type = Some(final_rhs_type.clone());
-                } else {
-                    final_rhs_type = rhs_type.clone();
-                }
+    /// Checks a binary operator whose left operand is already checked and has type
+    /// `lhs_typeinfo`
+    fn validate_binary_operator(
+        &mut self,
+        binary_expr: &mut BinaryOperatorExpression,
+        lhs_typeinfo: Type,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        // 1. evaluate rhs expression type
+        let (rhs_typeinfo, _) = self.check_expr(&mut binary_expr.rhs.node, &binary_expr.rhs.span);
 
-                // 4. now check assignment conversion
-                match Type::check_assignment_conversion(&lhs_type, &final_rhs_type)? {
-                    AssignmentConversionResult::Identical => {}
-                    result => {
-                        assign_expr.should_cast = true;
+        if lhs_typeinfo.datatype.is_error() || rhs_typeinfo.datatype.is_error() {
+            return Ok((Type::new(DataType::Error), RValue));
+        }
 
-                        if let AssignmentConversionResult::CastWithWarning(warning) = result {
-                            self.diagnostics.warning(warning, Some(assign_expr.operator.span));
-                        }
-                    }
-                }
+        // 2. usual arithmetic conversions
+        let uac_datatype = Type::common_datatype_for_uac(&lhs_typeinfo, &rhs_typeinfo, &self.layout)?;
 
-                // 5. return evaluated type and drop qualifiers as binary expression is always an rvalue
-                Ok((Type::new(lhs_type.datatype), RValue))
-            }
+        if uac_datatype != lhs_typeinfo.datatype {
+            Self::implicit_cast(&mut binary_expr.lhs, &uac_datatype);
+        }
 
-            Expression::TernaryOperator(ternary_expr) => {
-                // 1. evaluate type of the condition expression
-                let (condition_type, _) =
-                    self.validate_expr(&mut ternary_expr.condition.node, &ternary_expr.condition.span)?;
+        if uac_datatype != rhs_typeinfo.datatype {
+            Self::implicit_cast(&mut binary_expr.rhs, &uac_datatype);
+        }
 
-                // 2. check if condition expression type is boolean compatible
-                if Type::is_boolean_compatible(&condition_type) {
-                    // 3. cast to bool if not already
-                    Self::implicit_cast_to_bool(&mut ternary_expr.condition, &condition_type);
+        // 3. check if the uac type is compatible with the type of operator used
+        if !uac_datatype.is_compatible_with_binary_operator(&binary_expr.operator.node) {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!(
+                    "Incompatible operand ({}) usage with operator ({:?})",
+                    uac_datatype, binary_expr.operator.node
+                ),
+                span: Some(binary_expr.operator.span),
+                code: Some(ErrorCode::InvalidOperands),
+                ..Default::default()
+            });
+        }
+
+        // 4. return evaluated type and drop qualifiers as binary expression is always an rvalue
+        Ok((Type::new(uac_datatype), RValue))
+    }
 
-                    // 4. evaluate types of if expression and else expression
-                    let (if_type, _) =
-                        self.validate_expr(&mut ternary_expr.if_expr.node, &ternary_expr.if_expr.span)?;
-                    let (else_type, _) =
-                        self.validate_expr(&mut ternary_expr.else_expr.node, &ternary_expr.else_expr.span)?;
+    fn validate_assign_expr(
+        &mut self,
+        assign_expr: &mut AssignOperatorExpression,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        // 1. evaluate lhs and rhs expression types
+        let (lhs_type, lhs_val_type) = self.check_expr(&mut assign_expr.lhs.node, &assign_expr.lhs.span);
+        let (rhs_type, _) = self.check_expr(&mut assign_expr.rhs.node, &assign_expr.rhs.span);
 
-                    // 5. if if_type and else_type are arithmetic then perform uac
-                    if if_type.datatype.is_arithmetic() && else_type.datatype.is_arithmetic() {
-                        let uac_datatype = Type::common_datatype_for_uac(&if_type, &else_type)?;
+        if lhs_type.datatype.is_error() || rhs_type.datatype.is_error() {
+            return Ok((Type::new(DataType::Error), RValue));
+        }
 
-                        if uac_datatype != if_type.datatype {
-                            Self::implicit_cast(&mut ternary_expr.if_expr, &uac_datatype);
-                        }
+        if !matches!(lhs_val_type, ModifiableLValue) {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: "lhs of an assignment expression must be a modifiable lvalue".to_string(),
+                span: Some(assign_expr.lhs.span),
+                code: Some(ErrorCode::NotAssignable),
+                ..Default::default()
+            });
+        }
 
-                        if uac_datatype != else_type.datatype {
-                            Self::implicit_cast(&mut ternary_expr.else_expr, &uac_datatype);
-                        }
+        let final_rhs_type: Type;
 
-                        Ok((Type::new(uac_datatype), RValue))
-                    } else {
-                        Err(CompilerError {
-                            kind: CompilerErrorKind::InternalError,
-                            message: format!(
-                                "cannot check compatibility for types ({}, {}) in ternary operator expression yet",
-                                if_type, else_type
-                            ),
-                            span: Some(*span),
-                        })
-                    }
-                } else {
-                    Err(CompilerError {
-                        kind: CompilerErrorKind::SemanticError,
-                        message: "condition expression in a ternary expression must be boolean compatible".to_string(),
-                        span: Some(ternary_expr.condition.span),
-                    })
+        if let Some(underlying_binary_op) = assign_expr.operator.node.underlying_binary_op() {
+            // 2. usual arithmetic conversions
+            let uac_datatype = Type::common_datatype_for_uac(&lhs_type, &rhs_type, &self.layout)?;
+
+            if uac_datatype != rhs_type.datatype {
+                Self::implicit_cast(&mut assign_expr.rhs, &uac_datatype);
+            }
+
+            // 3. check if the composite type is compatible with the type of operator used
+            if !uac_datatype.is_compatible_with_binary_operator(&underlying_binary_op) {
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::SemanticError,
+                    message: format!(
+                        "Incompatible operand ({}) usage with operator ({:?})",
+                        uac_datatype, assign_expr.operator.node
+                    ),
+                    span: Some(assign_expr.operator.span),
+                    code: Some(ErrorCode::InvalidOperands),
+                    ..Default::default()
+                });
+            }
+
+            final_rhs_type = Type::new(uac_datatype);
+            assign_expr.uac_type = Some(final_rhs_type.clone());
+        } else {
+            final_rhs_type = rhs_type.clone();
+        }
+
+        // 4. now check assignment conversion
+        match Type::check_assignment_conversion(&lhs_type, &final_rhs_type, &self.layout)? {
+            AssignmentConversionResult::Identical => {}
+            result => {
+                assign_expr.should_cast = true;
+
+                if let AssignmentConversionResult::CastWithWarning(warning) = result {
+                    self.diagnostics.warning(warning, Some(assign_expr.operator.span));
                 }
             }
+        }
 
-            Expression::Call(call_expr) => {
-                // Get the function signature
-                let (callee_type, _) = self.validate_expr(&mut call_expr.callee.node, span)?;
-
-                if let Type {
-                    datatype:
-                        DataType::Function {
-                            return_type,
-                            parameters,
-                        },
-                    qualifiers: _,
-                } = &callee_type
-                {
-                    // ensure number of args = number of parameters
-                    if call_expr.argument_expr_list.len() != parameters.len() {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::SemanticError,
-                            message: format!(
-                                "expected {} args to function instead got {}",
-                                parameters.len(),
-                                call_expr.argument_expr_list.len()
-                            ),
-                            span: Some(*span),
-                        });
-                    }
+        // 5. return evaluated type and drop qualifiers as binary expression is always an rvalue
+        Ok((Type::new(lhs_type.datatype), RValue))
+    }
 
-                    for (param, arg) in zip(parameters, call_expr.argument_expr_list.iter_mut()) {
-                        // 1. evaluate argument expression type
-                        let (arg_type, _) = self.validate_expr(&mut arg.node, &arg.span)?;
+    fn validate_ternary_expr(
+        &mut self,
+        ternary_expr: &mut TernaryOperatorExpression,
+        span: &Span,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        // 1. evaluate type of the condition expression
+        let (condition_type, _) = self.check_expr(&mut ternary_expr.condition.node, &ternary_expr.condition.span);
 
-                        // 2. check whether arg type is assignable to param type and add an implicit cast if necessary
-                        match Type::check_assignment_conversion(param, &arg_type)? {
-                            AssignmentConversionResult::Identical => {}
-                            result => {
-                                Self::implicit_cast(arg, &param.datatype);
+        // 2. check if condition expression type is boolean compatible
+        if Type::is_boolean_compatible(&condition_type) {
+            // 3. cast to bool if not already
+            Self::implicit_cast_to_bool(&mut ternary_expr.condition, &condition_type);
 
-                                if let AssignmentConversionResult::CastWithWarning(warning) = result {
-                                    self.diagnostics.warning(warning, Some(arg.span));
-                                }
-                            }
-                        }
-                    }
+            // 4. evaluate types of if expression and else expression
+            let (if_type, _) = self.check_expr(&mut ternary_expr.if_expr.node, &ternary_expr.if_expr.span);
+            let (else_type, _) = self.check_expr(&mut ternary_expr.else_expr.node, &ternary_expr.else_expr.span);
 
-                    // 3. return type of the function is the expression type
-                    Ok((return_type.as_ref().clone(), RValue))
-                } else {
-                    Err(CompilerError {
-                        kind: CompilerErrorKind::SemanticError,
-                        message: format!("Called object type {} is not a function.", callee_type),
-                        span: Some(call_expr.callee.span),
-                    })
-                }
+            if if_type.datatype.is_error() || else_type.datatype.is_error() {
+                return Ok((Type::new(DataType::Error), RValue));
             }
 
-            Expression::Comma(comma_exprs) => {
-                if comma_exprs.is_empty() {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::InternalError,
-                        message: "Comma Expression vector can't be empty".to_string(),
-                        span: Some(*span),
-                    });
-                }
+            // 5. if if_type and else_type are arithmetic then perform uac
+            if if_type.datatype.is_arithmetic() && else_type.datatype.is_arithmetic() {
+                let uac_datatype = Type::common_datatype_for_uac(&if_type, &else_type, &self.layout)?;
 
-                // stores evaluated type of last expression
-                let mut ty = Type {
-                    datatype: DataType::Void,
-                    qualifiers: TypeQualifiers::default(),
-                };
+                if uac_datatype != if_type.datatype {
+                    Self::implicit_cast(&mut ternary_expr.if_expr, &uac_datatype);
+                }
 
-                // In case of initializer expressions, we should check whether all comma
-                // expressions are of the same type or not, but in case of generic expressions like
-                // 4 + 5, false;
-                // Above is a valid statement
-                for comma_expr in comma_exprs.iter_mut() {
-                    (ty, _) = self.validate_expr(&mut comma_expr.node, &comma_expr.span)?;
+                if uac_datatype != else_type.datatype {
+                    Self::implicit_cast(&mut ternary_expr.else_expr, &uac_datatype);
                 }
 
-                Ok((ty, RValue))
+                Ok((Type::new(uac_datatype), RValue))
+            } else {
+                Err(CompilerError {
+                    kind: CompilerErrorKind::InternalError,
+                    message: format!(
+                        "cannot check compatibility for types ({}, {}) in ternary operator expression yet",
+                        if_type, else_type
+                    ),
+                    span: Some(*span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                })
+            }
+        } else {
+            Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: "condition expression in a ternary expression must be boolean compatible".to_string(),
+                span: Some(ternary_expr.condition.span),
+                code: Some(ErrorCode::NonScalarCondition),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn validate_call_expr(
+        &mut self,
+        call_expr: &mut CallExpression,
+        span: &Span,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        // Get the function signature
+        let (callee_type, _) = self.check_expr(&mut call_expr.callee.node, span);
+
+        // Nothing is known about the parameters, so the arguments can only be checked
+        // on their own
+        if callee_type.datatype.is_error() {
+            for arg in call_expr.argument_expr_list.iter_mut() {
+                self.check_expr(&mut arg.node, &arg.span);
+            }
+            return Ok((callee_type, RValue));
+        }
+
+        if let Type {
+            datatype: DataType::Function {
+                return_type,
+                parameters,
+            },
+            qualifiers: _,
+        } = &callee_type
+        {
+            // ensure number of args = number of parameters
+            if call_expr.argument_expr_list.len() != parameters.len() {
+                let declaration = match &call_expr.callee.node {
+                    Expression::Identifier(name) => self
+                        .lookup_innermost_scope_symbol(name)
+                        .and_then(|symboldef| symboldef.span)
+                        .map(|span| Label {
+                            span,
+                            message: format!("`{}` is declared here", name),
+                        }),
+                    _ => None,
+                };
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::SemanticError,
+                    message: format!(
+                        "expected {} args to function instead got {}",
+                        parameters.len(),
+                        call_expr.argument_expr_list.len()
+                    ),
+                    span: Some(*span),
+                    code: Some(ErrorCode::ArgumentCount),
+                    labels: declaration.into_iter().collect(),
+                    ..Default::default()
+                });
             }
 
-            _ => {
-                println!("SemanticAnalyzer::validate_expr not implemented for {:?}", expression);
-                todo!()
+            for (param, arg) in zip(parameters, call_expr.argument_expr_list.iter_mut()) {
+                // 1. evaluate argument expression type
+                let (arg_type, _) = self.check_expr(&mut arg.node, &arg.span);
+
+                // 2. check whether arg type is assignable to param type and add an implicit cast if necessary
+                match Type::check_assignment_conversion(param, &arg_type, &self.layout)? {
+                    AssignmentConversionResult::Identical => {}
+                    result => {
+                        Self::implicit_cast(arg, &param.datatype);
+
+                        if let AssignmentConversionResult::CastWithWarning(warning) = result {
+                            self.diagnostics.warning(warning, Some(arg.span));
+                        }
+                    }
+                }
             }
+
+            // 3. return type of the function is the expression type
+            Ok((return_type.as_ref().clone(), RValue))
+        } else {
+            Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!("Called object type {} is not a function.", callee_type),
+                span: Some(call_expr.callee.span),
+                code: Some(ErrorCode::NotAFunction),
+                ..Default::default()
+            })
         }
     }
 
+    fn validate_comma_expr(
+        &mut self,
+        comma_exprs: &mut [Node<Expression>],
+        span: &Span,
+    ) -> Result<(Type, ExpressionValueType), CompilerError> {
+        if comma_exprs.is_empty() {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: "Comma Expression vector can't be empty".to_string(),
+                span: Some(*span),
+                ..Default::default()
+            });
+        }
+
+        // stores evaluated type of last expression
+        let mut ty = Type {
+            datatype: DataType::Void,
+            qualifiers: TypeQualifiers::default(),
+        };
+
+        // In case of initializer expressions, we should check whether all comma
+        // expressions are of the same type or not, but in case of generic expressions like
+        // 4 + 5, false;
+        // Above is a valid statement
+        for comma_expr in comma_exprs.iter_mut() {
+            (ty, _) = self.check_expr(&mut comma_expr.node, &comma_expr.span);
+        }
+
+        Ok((ty, RValue))
+    }
+
     fn implicit_cast(expr_node: &mut Node<Expression>, datatype: &DataType) {
         let temp_expr = std::mem::replace(&mut expr_node.node, Expression::Empty);
 
@@ -733,3 +1073,9 @@ impl<'a> SemanticAnalyzer<'a> {
         }
     }
 }
+
+/// Points an error that has no span of its own at `span`
+fn located(mut error: CompilerError, span: Span) -> CompilerError {
+    error.span.get_or_insert(span);
+    error
+}
@@ -1,7 +1,7 @@
 //! Module for performing lexical analysis on source code.
 use crate::{
-    analysis::node::Location,
-    core::errors::{CompilerError, CompilerErrorKind},
+    analysis::node::{Location, Span},
+    core::errors::{CompilerError, CompilerErrorKind, ErrorCode},
     core::typedefs::*,
 };
 
@@ -135,6 +135,26 @@ where
     (&src[..cidx], cidx)
 }
 
+/// Parses the digits of a numeric constant into the type its suffix gives it, which they
+/// may not fit in
+fn parse_constant<T: std::str::FromStr>(number: &str) -> Result<T, CompilerError> {
+    number.parse().map_err(|_| CompilerError {
+        kind: CompilerErrorKind::TokenizerError,
+        message: format!("Numeric constant `{}` is too large for its type", number),
+        span: None,
+        code: Some(ErrorCode::ConstantTooLarge),
+        ..Default::default()
+    })
+}
+
+/// Points an error of the tokenizer at the character `start` where the token it failed on
+/// begins
+fn located(mut error: CompilerError, start: Location) -> CompilerError {
+    let end = Location::new(start.line, start.column + 1);
+    error.span.get_or_insert(Span::new(start, end));
+    error
+}
+
 /// The tokenizer class
 pub struct Tokenizer<'a> {
     cidx: usize,                   // Current index
@@ -236,7 +256,8 @@ impl<'a> Tokenizer<'a> {
         if temp_srcbuffer.is_empty() {
             Ok(None)
         } else {
-            let (token, bytes) = self.tokenize(temp_srcbuffer)?;
+            let start = Location::new(self.peeked_linerow, self.peeked_linecol);
+            let (token, bytes) = self.tokenize(temp_srcbuffer).map_err(|error| located(error, start))?;
 
             // Store the peeked token info
             self.peeked_linecol += bytes;
@@ -284,7 +305,8 @@ impl<'a> Tokenizer<'a> {
         if self.srcbuffer.is_empty() {
             Ok(None)
         } else {
-            let (token, bytes) = self.tokenize(self.srcbuffer)?;
+            let start = Location::new(self.linerow, self.linecol);
+            let (token, bytes) = self.tokenize(self.srcbuffer).map_err(|error| located(error, start))?;
             self.srcbuffer = &self.srcbuffer[bytes..];
             self.cidx += bytes;
 
@@ -301,6 +323,22 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Skips the next character that isn't whitespace or part of a comment, to get past one
+    /// that can't be tokenized
+    pub fn skip_char(&mut self) {
+        // Forget the peeked token, if any, and step over the whitespace before the character
+        self.peekedtoken = (TokenType::None, Location::none(), Location::none());
+        self.peekedbytes = 0;
+        self.skip_whitespace();
+        self.skip_comments();
+
+        if let Some(ch) = self.srcbuffer.chars().next() {
+            self.srcbuffer = &self.srcbuffer[ch.len_utf8()..];
+            self.cidx += ch.len_utf8();
+            self.linecol += 1;
+        }
+    }
+
     pub fn get_cidx(&self) -> usize {
         self.cidx
     }
@@ -345,7 +383,12 @@ impl<'a> Tokenizer<'a> {
         let next = match src.chars().next() {
             Some(c) => c,
             None => {
-                panic!("Internal Error: Failed to get the next character from the src buffer, presumably it's empty!")
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::InternalError,
+                    message: "Failed to get the next character from the src buffer, presumably it's empty!".to_string(),
+                    span: None,
+                    ..Default::default()
+                })
             }
         };
 
@@ -450,6 +493,8 @@ impl<'a> Tokenizer<'a> {
                 kind: CompilerErrorKind::TokenizerError,
                 message: format!("Unexpected token: {}", next),
                 span: None,
+                code: Some(ErrorCode::InvalidToken),
+                ..Default::default()
             }),
         }
     }
@@ -463,8 +508,8 @@ impl<'a> Tokenizer<'a> {
                         if captures.name("dot").is_some() || captures.name("exp").is_some() {
                             let fptype = match captures.name("suffix") {
                                 Some(suffix) => match suffix.as_str() {
-                                    "f" | "F" => FloatingPointType::Float(number.as_str().parse::<f32>().unwrap()),
-                                    "l" | "L" => FloatingPointType::LongDouble(number.as_str().parse::<f64>().unwrap()),
+                                    "f" | "F" => FloatingPointType::Float(parse_constant::<f32>(number.as_str())?),
+                                    "l" | "L" => FloatingPointType::LongDouble(parse_constant::<f64>(number.as_str())?),
                                     _ => {
                                         return Err(CompilerError {
                                             kind: CompilerErrorKind::TokenizerError,
@@ -472,27 +517,29 @@ impl<'a> Tokenizer<'a> {
                                                 "Invalid suffix: `{}` to a floating point constant",
                                                 suffix.as_str()
                                             ),
-                                            span: None, // TODO: Give the right location
+                                            span: None, // The tokenizer points it at the constant
+                                            code: Some(ErrorCode::InvalidToken),
+                                            ..Default::default()
                                         });
                                     }
                                 },
-                                None => FloatingPointType::Double(number.as_str().parse::<f64>().unwrap()),
+                                None => FloatingPointType::Double(parse_constant::<f64>(number.as_str())?),
                             };
                             Ok((TokenType::FloatingPoint(fptype), captures[0].len()))
                         } else {
                             // Else it is a integer constant
                             let inttype = match captures.name("suffix") {
                                 Some(suffix) => match suffix.as_str() {
-                                    "u" | "U" => IntegerType::Unsigned(number.as_str().parse::<u32>().unwrap()),
-                                    "l" | "L" => IntegerType::SignedLong(number.as_str().parse::<i64>().unwrap()),
+                                    "u" | "U" => IntegerType::Unsigned(parse_constant::<u32>(number.as_str())?),
+                                    "l" | "L" => IntegerType::SignedLong(parse_constant::<i64>(number.as_str())?),
                                     "ul" | "uL" | "Ul" | "UL" => {
-                                        IntegerType::UnsignedLong(number.as_str().parse::<u64>().unwrap())
+                                        IntegerType::UnsignedLong(parse_constant::<u64>(number.as_str())?)
                                     }
                                     "ll" | "lL" | "Ll" | "LL" => {
-                                        IntegerType::SignedLongLong(number.as_str().parse::<i128>().unwrap())
+                                        IntegerType::SignedLongLong(parse_constant::<i128>(number.as_str())?)
                                     }
                                     "ull" | "ulL" | "uLl" | "uLL" | "Ull" | "UlL" | "ULl" | "ULL" => {
-                                        IntegerType::UnsignedLongLong(number.as_str().parse::<u128>().unwrap())
+                                        IntegerType::UnsignedLongLong(parse_constant::<u128>(number.as_str())?)
                                     }
                                     _ => {
                                         return Err(CompilerError {
@@ -501,34 +548,63 @@ impl<'a> Tokenizer<'a> {
                                                 "Expected an integer suffix, instead got `{}`",
                                                 suffix.as_str()
                                             ),
-                                            span: None, // TODO: Give the right location
+                                            span: None, // The tokenizer points it at the constant
+                                            code: Some(ErrorCode::InvalidToken),
+                                            ..Default::default()
                                         });
                                     }
                                 },
                                 None => {
                                     // TODO: Figure out the integer type
-                                    IntegerType::Generic(number.as_str().parse::<i64>().unwrap())
+                                    IntegerType::Generic(parse_constant::<i64>(number.as_str())?)
                                 }
                             };
                             Ok((TokenType::Integer(inttype), captures[0].len()))
                         }
                     }
-                    None => panic!("Internal Error: `number` part of the regex is not captured"),
+                    None => Err(CompilerError {
+                        kind: CompilerErrorKind::InternalError,
+                        message: "`number` part of the regex is not captured".to_string(),
+                        span: None,
+                        ..Default::default()
+                    }),
                 }
             }
-            None => panic!("Internal Error: Numeric Pattern not found!"),
+            // Digits followed by letters that aren't a suffix, like `1x`
+            None => {
+                let (constant, _) = iter_while(src, |ch| ch.is_alphanumeric() || ch == '_' || ch == '.');
+                Err(CompilerError {
+                    kind: CompilerErrorKind::TokenizerError,
+                    message: format!("Invalid numeric constant `{}`", constant),
+                    span: None,
+                    code: Some(ErrorCode::InvalidToken),
+                    ..Default::default()
+                })
+            }
         }
     }
 
     fn tokenize_char(&self, src: &str) -> Result<(TokenType, usize), CompilerError> {
         let (ch, bytes) = iter_while(&src[1..], |ch| ch != '\'');
 
+        if !src[1 + bytes..].starts_with('\'') {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::TokenizerError,
+                message: "Missing ' in a character constant".to_string(),
+                span: None,
+                code: Some(ErrorCode::InvalidToken),
+                ..Default::default()
+            });
+        }
+
         match ch.len() {
             1 => Ok((TokenType::Character(ch.chars().next().unwrap()), bytes + 2)),
             _ => Err(CompilerError {
                 kind: CompilerErrorKind::TokenizerError,
                 message: format!("A single quoted literal can only have 1 character and not: {}", ch),
                 span: None,
+                code: Some(ErrorCode::InvalidToken),
+                ..Default::default()
             }),
         }
     }
@@ -536,12 +612,14 @@ impl<'a> Tokenizer<'a> {
     fn tokenize_string(&self, src: &str) -> Result<(TokenType, usize), CompilerError> {
         let (stringliteral, bytes) = iter_while(&src[1..], |ch| ch != '"');
 
-        match src.chars().nth(bytes + 1) {
+        match src[1 + bytes..].chars().next() {
             Some('"') => Ok((TokenType::StringLiteral(stringliteral.to_string()), bytes + 2)),
             _ => Err(CompilerError {
                 kind: CompilerErrorKind::TokenizerError,
                 message: "Missing \" in a quoted string literal".to_string(),
                 span: None,
+                code: Some(ErrorCode::InvalidToken),
+                ..Default::default()
             }),
         }
     }
@@ -1,49 +1,411 @@
-use crate::analysis::ast::display_translationunit;
+use crate::analysis::ast::TranslationUnit;
 use crate::analysis::parser::Parser;
 use crate::analysis::semantic_analyzer::SemanticAnalyzer;
-use crate::core::errors::{CompilerError, Diagnostic};
+use crate::core::errors::{CompilerError, CompilerErrorKind, Diagnostic};
 use crate::core::symboltable::SymbolTable;
-use crate::synthesis::asm::Arm64AsmEmitter;
-use crate::synthesis::ir::IrEmitter;
+use crate::core::target::Target;
+use crate::synthesis::c::CEmitter;
+use crate::synthesis::cfg::{first_free_label, Cfg};
+use crate::synthesis::coalesce::{coalesce_copies, coalesce_slots, propagate_copies};
+use crate::synthesis::dce::eliminate_dead_code;
+use crate::synthesis::dwarf::DebugInfo;
+use crate::synthesis::inline::inline_functions;
+use crate::synthesis::ir::{IrEmitter, IrFunction, IrStatement};
+use crate::synthesis::llvm::LlvmIrEmitter;
+use crate::synthesis::loopopt::{hoist_invariants, reduce_strength, rotate_loops, unroll_loops};
+use crate::synthesis::sccp::propagate_constants;
+use crate::synthesis::ssa::SsaFunction;
+use crate::synthesis::tailcall::optimize_tail_calls;
 
+/// Passes run over the IR between lowering and code generation.
+#[derive(Debug, Default, Clone)]
+pub struct Optimizations {
+    /// Inline small, `inline` and `always_inline` functions into their callers
+    pub inline: bool,
+    /// Take the IR through SSA form and back
+    pub ssa: bool,
+    /// Sparse conditional constant propagation
+    pub sccp: bool,
+    /// Dead code and dead store elimination
+    pub dce: bool,
+    /// Copy propagation, copy coalescing and stack slot sharing
+    pub coalesce: bool,
+    /// Turn calls whose result is returned straight away into loops or jumps
+    pub sibling_calls: bool,
+    /// Test loop conditions at the bottom of the loop instead of the top
+    pub rotate: bool,
+    /// Move loop-invariant computations out of loops
+    pub licm: bool,
+    /// Replace multiplications of induction variables with additions
+    pub strength_reduction: bool,
+    /// Fully unroll small loops with a constant trip count
+    pub unroll: bool,
+    /// Clean up the generated ARM64 code with the peephole optimiser
+    pub peephole: bool,
+}
+
+impl Optimizations {
+    /// The passes enabled by `-O<level>`.
+    pub fn level(level: u32) -> Self {
+        Optimizations {
+            inline: level >= 1,
+            sccp: level >= 1,
+            dce: level >= 1,
+            coalesce: level >= 1,
+            sibling_calls: level >= 1,
+            rotate: level >= 1,
+            licm: level >= 1,
+            strength_reduction: level >= 1,
+            peephole: level >= 1,
+            ..Default::default()
+        }
+    }
+
+    fn loops(&self) -> bool {
+        self.rotate || self.licm || self.strength_reduction || self.unroll
+    }
+}
+
+/// How to compile a translation unit: the passes to run over the IR, the source file to
+/// describe in DWARF debug info, if any, and how many errors to report before giving up.
+#[derive(Debug, Default, Clone)]
+pub struct CompilerOptions {
+    pub optimizations: Optimizations,
+    pub debug: Option<DebugInfo>,
+    /// Stop reporting errors after this many; `None` reports them all
+    pub error_limit: Option<usize>,
+}
+
+impl CompilerOptions {
+    pub fn new(optimizations: Optimizations) -> Self {
+        Self {
+            optimizations,
+            debug: None,
+            error_limit: None,
+        }
+    }
+
+    pub fn with_debug_info(mut self, debug: Option<DebugInfo>) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn with_error_limit(mut self, error_limit: Option<usize>) -> Self {
+        self.error_limit = error_limit;
+        self
+    }
+}
+
+/// What the optimisation passes did to one function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionStatistics {
+    pub name: String,
+    /// Statements removed by dead code elimination and coalescing
+    pub eliminated: usize,
+    /// Stack frame size in bytes before and after the passes
+    pub frame_before: usize,
+    pub frame_after: usize,
+}
+
+/// What the optimisation passes did to a translation unit, as `--stats` reports it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pub functions: Vec<FunctionStatistics>,
+    /// Calls replaced with a copy of the callee
+    pub inlined: usize,
+}
+
+/// One compilation of a translation unit, a stage at a time: [`Session::parse`],
+/// [`Session::analyze`], [`Session::lower`] and [`Session::codegen`]. Every stage hands its
+/// result back, to be inspected or passed on to the next, while warnings collect in the
+/// session. Nothing is printed.
+///
+/// Parsing and analysis carry on past an error to report every error in the translation
+/// unit, up to the error limit of the options; the later stages stop at their first.
+pub struct Session<'a> {
+    options: CompilerOptions,
+    target: &'a dyn Target,
+    diagnostics: Vec<Diagnostic>,
+    statistics: Statistics,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(options: CompilerOptions, target: &'a dyn Target) -> Self {
+        Self {
+            options,
+            target,
+            diagnostics: Vec::new(),
+            statistics: Statistics::default(),
+        }
+    }
+
+    /// Parses the source of a translation unit into its syntax tree.
+    pub fn parse(&mut self, input: &str) -> Result<TranslationUnit, Vec<CompilerError>> {
+        Parser::new(input)
+            .parse()
+            .map_err(|errors| self.limit(errors, CompilerErrorKind::SyntaxError))
+    }
+
+    /// Checks a translation unit, annotating its expressions with their types and implicit
+    /// conversions, and returns the symbols it declares.
+    pub fn analyze(&mut self, translation_unit: &mut TranslationUnit) -> Result<SymbolTable, Vec<CompilerError>> {
+        let mut symboltable = SymbolTable::new();
+        SemanticAnalyzer::new(&mut symboltable, &mut self.diagnostics, self.target.data_layout())
+            .analyze(translation_unit)
+            .map_err(|errors| self.limit(errors, CompilerErrorKind::SemanticError))?;
+        Ok(symboltable)
+    }
+
+    /// Lowers an analysed translation unit to IR and runs the optimisation passes over it.
+    pub fn lower(&mut self, translation_unit: &TranslationUnit) -> Result<Vec<IrFunction>, CompilerError> {
+        let ir = IrEmitter::new(self.target.data_layout()).emit(translation_unit)?;
+        let (mut ir, statistics) = Compiler::optimize(ir, &mut self.diagnostics, &self.options.optimizations);
+        self.statistics = statistics;
+        // the passes point their warnings at the source positions, but after them only debug
+        // info needs them
+        if self.options.debug.is_none() {
+            for function in &mut ir {
+                function
+                    .body
+                    .retain(|statement| !matches!(statement, IrStatement::Loc(_)));
+            }
+        }
+        Ok(ir)
+    }
+
+    /// Generates assembly for the IR, with debug info if the options ask for it.
+    pub fn codegen(&mut self, ir: &[IrFunction]) -> Result<String, CompilerError> {
+        self.target
+            .emit_asm(ir, &self.options.optimizations, self.options.debug.as_ref())
+    }
+
+    /// Same as [`Session::codegen`], but assembles the code into a relocatable object file
+    /// with the target's integrated assembler.
+    pub fn codegen_object(&mut self, ir: &[IrFunction]) -> Result<Vec<u8>, CompilerError> {
+        self.target.emit_object(ir, &self.options.optimizations)
+    }
+
+    /// Same as [`Session::codegen`], but produces textual LLVM IR for the target.
+    pub fn codegen_llvm_ir(&mut self, ir: &[IrFunction]) -> Result<String, CompilerError> {
+        LlvmIrEmitter::new(self.target.triple()).emit(ir)
+    }
+
+    /// Same as [`Session::codegen`], but translates the IR back into portable C.
+    pub fn codegen_c(&mut self, ir: &[IrFunction]) -> Result<String, CompilerError> {
+        CEmitter.emit(ir)
+    }
+
+    /// The warnings of the stages run so far.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// What the optimisation passes did in [`Session::lower`].
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Parses, analyses and lowers `input`.
+    fn front_end(&mut self, input: &str) -> Result<Vec<IrFunction>, Vec<CompilerError>> {
+        let mut translation_unit = self.parse(input)?;
+        self.analyze(&mut translation_unit)?;
+        self.lower(&translation_unit).map_err(|error| vec![error])
+    }
+
+    /// Cuts `errors` down to the error limit, ending them with one that says the rest were
+    /// left out.
+    fn limit(&self, mut errors: Vec<CompilerError>, kind: CompilerErrorKind) -> Vec<CompilerError> {
+        if let Some(limit) = self.options.error_limit {
+            if errors.len() > limit {
+                errors.truncate(limit);
+                errors.push(CompilerError {
+                    kind,
+                    message: "too many errors emitted, stopping now [-ferror-limit=]".to_string(),
+                    span: None,
+                    ..Default::default()
+                });
+            }
+        }
+        errors
+    }
+}
+
+/// Shorthands that run a whole [`Session`] at once.
 #[derive(Debug)]
 pub struct Compiler {}
 
 impl Compiler {
+    /// Compiles `input` to assembly for `target`. If it doesn't compile, every error found
+    /// in it is returned, up to the error limit of the `options`.
     pub fn compile(
         input: &str,
-        dump_ast: bool,
-        dump_ir: bool,
-        dump_asm: bool,
-    ) -> (Vec<Diagnostic>, Result<String, CompilerError>) {
-        let mut symboltable = SymbolTable::new();
-        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        options: &CompilerOptions,
+        target: &dyn Target,
+    ) -> (Vec<Diagnostic>, Result<String, Vec<CompilerError>>) {
+        Self::run(input, options, target, |session, ir| session.codegen(&ir))
+    }
+
+    /// Same as [`Compiler::compile`], but assembles the code into a relocatable object file
+    /// with the target's integrated assembler.
+    pub fn compile_object(
+        input: &str,
+        options: &CompilerOptions,
+        target: &dyn Target,
+    ) -> (Vec<Diagnostic>, Result<Vec<u8>, Vec<CompilerError>>) {
+        Self::run(input, options, target, |session, ir| session.codegen_object(&ir))
+    }
+
+    /// Same as [`Compiler::compile`], but produces textual LLVM IR for `target` instead of
+    /// assembly.
+    pub fn compile_llvm_ir(
+        input: &str,
+        options: &CompilerOptions,
+        target: &dyn Target,
+    ) -> (Vec<Diagnostic>, Result<String, Vec<CompilerError>>) {
+        Self::run(input, options, target, |session, ir| session.codegen_llvm_ir(&ir))
+    }
 
-        let result = (|| -> Result<String, CompilerError> {
-            let mut translation_unit = Parser::new(input).parse()?;
-            SemanticAnalyzer::new(&mut symboltable, &mut diagnostics).analyze(&mut translation_unit)?;
+    /// Same as [`Compiler::compile`], but stops at the IR, e.g. to run it with the
+    /// [`Interpreter`](crate::synthesis::interpreter::Interpreter).
+    pub fn compile_ir(
+        input: &str,
+        options: &CompilerOptions,
+        target: &dyn Target,
+    ) -> (Vec<Diagnostic>, Result<Vec<IrFunction>, Vec<CompilerError>>) {
+        Self::run(input, options, target, |_, ir| Ok(ir))
+    }
+
+    /// Same as [`Compiler::compile`], but translates the IR back into portable C.
+    pub fn compile_c(
+        input: &str,
+        options: &CompilerOptions,
+        target: &dyn Target,
+    ) -> (Vec<Diagnostic>, Result<String, Vec<CompilerError>>) {
+        Self::run(input, options, target, |session, ir| session.codegen_c(&ir))
+    }
+
+    fn run<T>(
+        input: &str,
+        options: &CompilerOptions,
+        target: &dyn Target,
+        backend: impl FnOnce(&mut Session, Vec<IrFunction>) -> Result<T, CompilerError>,
+    ) -> (Vec<Diagnostic>, Result<T, Vec<CompilerError>>) {
+        let mut session = Session::new(options.clone(), target);
+        let result = session
+            .front_end(input)
+            .and_then(|ir| backend(&mut session, ir).map_err(|error| vec![error]));
+        (session.into_diagnostics(), result)
+    }
+
+    fn optimize(
+        mut ir: Vec<IrFunction>,
+        diagnostics: &mut Vec<Diagnostic>,
+        optimizations: &Optimizations,
+    ) -> (Vec<IrFunction>, Statistics) {
+        let inlined = if optimizations.inline {
+            inline_functions(&mut ir, diagnostics)
+        } else {
+            0
+        };
+
+        let mut statistics = Statistics {
+            inlined,
+            ..Default::default()
+        };
+        if optimizations.ssa
+            || optimizations.sccp
+            || optimizations.dce
+            || optimizations.coalesce
+            || optimizations.loops()
+        {
+            (ir, statistics.functions) = Self::optimize_functions(ir, diagnostics, optimizations);
+        }
 
-            if dump_ast {
-                display_translationunit(&translation_unit);
-                println!("\n\n{}", symboltable);
+        // last, so that the calls are still directly followed by their returns
+        if optimizations.sibling_calls {
+            let mut next_label = first_free_label(&ir);
+            for function in &mut ir {
+                optimize_tail_calls(function, &mut next_label);
             }
+        }
 
-            let ir = IrEmitter::new().emit(&translation_unit)?;
-            if dump_ir {
-                println!("\n------- Intermediate Representation (IR) -------\n");
-                for function in &ir {
-                    println!("{function}");
+        (ir, statistics)
+    }
+
+    /// Runs the passes that work on one function at a time, through its [`Cfg`].
+    fn optimize_functions(
+        ir: Vec<IrFunction>,
+        diagnostics: &mut Vec<Diagnostic>,
+        optimizations: &Optimizations,
+    ) -> (Vec<IrFunction>, Vec<FunctionStatistics>) {
+        let ssa = optimizations.ssa || optimizations.sccp;
+        let mut next_label = first_free_label(&ir);
+        let mut optimized = Vec::with_capacity(ir.len());
+        let mut stats = Vec::new();
+        for function in ir {
+            let frame = function.framesize;
+            let mut cfg = Cfg::from_function(function);
+            if ssa {
+                let mut ssa = SsaFunction::construct(cfg);
+                if optimizations.sccp {
+                    propagate_constants(&mut ssa, diagnostics);
                 }
+                cfg = ssa.destruct();
             }
-
-            let asm = Arm64AsmEmitter::new().emit(&ir)?;
-            if dump_asm {
-                println!("------- Assembly -------\n\n{}", asm);
+            if optimizations.coalesce {
+                propagate_copies(&mut cfg);
+            }
+            let mut eliminated = 0;
+            if optimizations.dce {
+                eliminated += eliminate_dead_code(&mut cfg);
+            }
+            if optimizations.coalesce {
+                // merging `t = i + 1; i = t` into `i = i + 1` shows the loop passes the
+                // induction variables
+                eliminated += coalesce_copies(&mut cfg);
             }
+            if optimizations.loops() && Self::optimize_loops(&mut cfg, optimizations) > 0 {
+                if optimizations.coalesce {
+                    propagate_copies(&mut cfg);
+                }
+                if optimizations.dce {
+                    eliminated += eliminate_dead_code(&mut cfg);
+                }
+            }
+            if optimizations.coalesce {
+                eliminated += coalesce_slots(&mut cfg);
+            }
+            stats.push(FunctionStatistics {
+                name: cfg.name.clone(),
+                eliminated,
+                frame_before: frame,
+                frame_after: cfg.framesize,
+            });
+            optimized.push(cfg.into_function(&mut next_label));
+        }
 
-            Ok(asm)
-        })();
+        (optimized, stats)
+    }
 
-        (diagnostics, result)
+    /// Runs the enabled loop passes and returns how many changes they made.
+    fn optimize_loops(cfg: &mut Cfg, optimizations: &Optimizations) -> usize {
+        let mut changes = 0;
+        if optimizations.rotate {
+            changes += rotate_loops(cfg);
+        }
+        if optimizations.licm {
+            changes += hoist_invariants(cfg);
+        }
+        if optimizations.strength_reduction {
+            changes += reduce_strength(cfg);
+        }
+        if optimizations.unroll {
+            changes += unroll_loops(cfg);
+        }
+        changes
     }
 }
@@ -1,42 +1,255 @@
 use crate::analysis::node::Span;
+use std::{fmt, str::FromStr};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum CompilerErrorKind {
+    #[default]
     InternalError,
     TokenizerError,
     SyntaxError,
     SemanticError,
 }
 
-#[derive(Debug)]
+/// An error that stops compilation. Apart from the message and where it happened, an error
+/// can carry a stable code that `--explain` describes, spans elsewhere in the source that
+/// explain it (like the previous declaration of a redefined symbol), notes, and fix-its.
+/// Those are boxed slices rather than `Vec`s, as every `Result` the compiler passes around
+/// is as big as its error.
+#[derive(Debug, Default)]
 pub struct CompilerError {
     pub kind: CompilerErrorKind,
     pub message: String,
     pub span: Option<Span>,
+    pub code: Option<ErrorCode>,
+    pub labels: Box<[Label]>,
+    pub notes: Box<[String]>,
+    pub fixits: Box<[FixIt]>,
 }
 
-#[derive(Debug)]
-pub enum DiagnosticKind {
+impl CompilerError {
+    /// The error as a diagnostic of severity `error`, for rendering it like any other.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: self.code,
+            message: self.message.clone(),
+            span: self.span,
+            labels: self.labels.to_vec(),
+            notes: self.notes.to_vec(),
+            fixits: self.fixits.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something the compiler did, like inlining a call; only shown when asked for
+    Remark,
+    Note,
     Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Remark => "remark",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Stable codes for the errors users run into most, printed as `E0001` and so on. Codes
+/// are never reused or renumbered, so they can be searched for and passed to `--explain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    InvalidToken = 1,
+    ConstantTooLarge = 2,
+    ExpectedToken = 3,
+    UnexpectedToken = 4,
+    NestingTooDeep = 5,
+    UndeclaredIdentifier = 6,
+    Redefinition = 7,
+    ConflictingTypes = 8,
+    IncompatibleTypes = 9,
+    InvalidOperands = 10,
+    NotAssignable = 11,
+    ArgumentCount = 12,
+    NotAFunction = 13,
+    JumpOutsideLoop = 14,
+    NonScalarCondition = 15,
+    InvalidTypeSpecifiers = 16,
+    Unsupported = 17,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 17] = [
+        ErrorCode::InvalidToken,
+        ErrorCode::ConstantTooLarge,
+        ErrorCode::ExpectedToken,
+        ErrorCode::UnexpectedToken,
+        ErrorCode::NestingTooDeep,
+        ErrorCode::UndeclaredIdentifier,
+        ErrorCode::Redefinition,
+        ErrorCode::ConflictingTypes,
+        ErrorCode::IncompatibleTypes,
+        ErrorCode::InvalidOperands,
+        ErrorCode::NotAssignable,
+        ErrorCode::ArgumentCount,
+        ErrorCode::NotAFunction,
+        ErrorCode::JumpOutsideLoop,
+        ErrorCode::NonScalarCondition,
+        ErrorCode::InvalidTypeSpecifiers,
+        ErrorCode::Unsupported,
+    ];
+
+    /// The long description `--explain` prints: what the error means, an example that
+    /// triggers it and how to fix it.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidToken => include_str!("explanations/E0001.md"),
+            ErrorCode::ConstantTooLarge => include_str!("explanations/E0002.md"),
+            ErrorCode::ExpectedToken => include_str!("explanations/E0003.md"),
+            ErrorCode::UnexpectedToken => include_str!("explanations/E0004.md"),
+            ErrorCode::NestingTooDeep => include_str!("explanations/E0005.md"),
+            ErrorCode::UndeclaredIdentifier => include_str!("explanations/E0006.md"),
+            ErrorCode::Redefinition => include_str!("explanations/E0007.md"),
+            ErrorCode::ConflictingTypes => include_str!("explanations/E0008.md"),
+            ErrorCode::IncompatibleTypes => include_str!("explanations/E0009.md"),
+            ErrorCode::InvalidOperands => include_str!("explanations/E0010.md"),
+            ErrorCode::NotAssignable => include_str!("explanations/E0011.md"),
+            ErrorCode::ArgumentCount => include_str!("explanations/E0012.md"),
+            ErrorCode::NotAFunction => include_str!("explanations/E0013.md"),
+            ErrorCode::JumpOutsideLoop => include_str!("explanations/E0014.md"),
+            ErrorCode::NonScalarCondition => include_str!("explanations/E0015.md"),
+            ErrorCode::InvalidTypeSpecifiers => include_str!("explanations/E0016.md"),
+            ErrorCode::Unsupported => include_str!("explanations/E0017.md"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", *self as u32)
+    }
+}
+
+/// Parses `E0007`, `e0007` or just `0007`.
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+        digits
+            .parse::<u32>()
+            .ok()
+            .filter(|_| digits.len() == 4)
+            .and_then(|number| ErrorCode::ALL.into_iter().find(|code| *code as u32 == number))
+            .ok_or_else(|| format!("`{s}` is not a valid error code"))
+    }
+}
+
+/// A span other than the one the diagnostic is about, with a message saying why it matters.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A machine-applicable change to the source that fixes an error: `span` is replaced by
+/// `replacement`, so a span that starts and ends at the same location is an insertion.
+#[derive(Debug, Clone)]
+pub struct FixIt {
+    pub span: Span,
+    pub replacement: String,
+    /// What the change does, like "insert `;`"
+    pub message: String,
+}
+
+impl FixIt {
+    pub fn insert(at: Span, text: &str) -> Self {
+        let location = at.end;
+        FixIt {
+            span: Span::new(location, location),
+            replacement: text.to_string(),
+            message: format!("insert `{text}`"),
+        }
+    }
+}
+
+/// Applies `fixits` to `source`. Fix-its that overlap one applied before them are skipped.
+pub fn apply_fixits(source: &str, fixits: &[FixIt]) -> String {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let offset = |line: usize, column: usize| {
+        let start = *line_starts.get(line.saturating_sub(1))?;
+        let end = line_starts.get(line).map_or(source.len(), |next| next - 1);
+        let offset = start + column.saturating_sub(1);
+        (offset <= end && source.is_char_boundary(offset)).then_some(offset)
+    };
+
+    let mut edits: Vec<(usize, usize, &str)> = fixits
+        .iter()
+        .filter_map(|fixit| {
+            let start = offset(fixit.span.start.line, fixit.span.start.column)?;
+            let end = offset(fixit.span.end.line, fixit.span.end.column)?;
+            (start <= end).then_some((start, end, fixit.replacement.as_str()))
+        })
+        .collect();
+    edits.sort_by_key(|&(start, end, _)| (start, end));
+
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (start, end, replacement) in edits {
+        if start < copied {
+            continue;
+        }
+        out += &source[copied..start];
+        out += replacement;
+        copied = end;
+    }
+    out + &source[copied..]
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Diagnostic {
-    pub kind: DiagnosticKind,
+    pub severity: Severity,
+    pub code: Option<ErrorCode>,
     pub message: String,
     pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub fixits: Vec<FixIt>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message,
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            fixits: Vec::new(),
+        }
+    }
 }
 
 pub trait VecExtensionDiagnosticHelpers {
     fn warning(&mut self, msg: String, span: Option<Span>);
+    fn remark(&mut self, msg: String, span: Option<Span>);
 }
 
 impl VecExtensionDiagnosticHelpers for Vec<Diagnostic> {
     fn warning(&mut self, msg: String, span: Option<Span>) {
-        self.push(Diagnostic {
-            kind: DiagnosticKind::Warning,
-            message: msg,
-            span,
-        });
+        self.push(Diagnostic::new(Severity::Warning, msg, span));
+    }
+
+    fn remark(&mut self, msg: String, span: Option<Span>) {
+        self.push(Diagnostic::new(Severity::Remark, msg, span));
     }
 }
@@ -1,3 +1,4 @@
 pub mod errors;
 pub mod symboltable;
+pub mod target;
 pub mod typedefs;
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 use std::fmt;
 
 use crate::analysis::ast::StorageClassFlags;
-use crate::core::errors::{CompilerError, CompilerErrorKind};
+use crate::analysis::node::Span;
+use crate::core::errors::{CompilerError, CompilerErrorKind, ErrorCode, Label};
 use crate::core::typedefs::{Constant, Type};
 
 #[derive(Debug, Clone)]
@@ -11,6 +12,8 @@ pub struct SymbolDefinition {
     pub storageclass: StorageClassFlags,
     pub value: Option<Constant>,
     pub scopeid: u32,
+    /// Where the symbol is declared, for pointing at it from errors about later declarations
+    pub span: Option<Span>,
 }
 
 #[derive(Debug)]
@@ -93,6 +96,7 @@ impl SymbolTable {
         typeinfo: Type,
         storageclass: StorageClassFlags,
         value: Option<Constant>,
+        span: Option<Span>,
     ) -> Result<(), CompilerError> {
         let index = self.symbolbuffer.len();
 
@@ -101,6 +105,7 @@ impl SymbolTable {
             storageclass,
             value,
             scopeid,
+            span,
         });
 
         if let Some(entry) = self.hashmap.get_mut(name) {
@@ -111,12 +116,19 @@ impl SymbolTable {
                     Ok(())
                 }
                 Some(idx) => Err(CompilerError {
-                    kind: CompilerErrorKind::InternalError,
-                    message: format!(
-                        "Failed to insert symbol: Duplicate Symbols are not allowed in the Symbol Table.\n{:?}",
-                        self.symbolbuffer[idx]
-                    ),
+                    kind: CompilerErrorKind::SemanticError,
+                    message: format!("Redefinition of `{}`", name),
                     span: None,
+                    code: Some(ErrorCode::Redefinition),
+                    labels: self.symbolbuffer[idx]
+                        .span
+                        .map(|span| Label {
+                            span,
+                            message: "previous definition is here".to_string(),
+                        })
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
                 }),
             }
         } else {
@@ -0,0 +1,399 @@
+//! Description of the machines fbcc can generate code for.
+//!
+//! A [`Target`] bundles everything that differs between platforms: the data layout used by
+//! the front end to size types, the calling convention and object format conventions used by
+//! the backend, and the assembly emitter itself.
+
+use crate::{
+    compiler::Optimizations,
+    core::errors::{CompilerError, CompilerErrorKind},
+    synthesis::{
+        arm64_assembler, asm::Arm64AsmEmitter, dwarf::DebugInfo, ir::IrFunction, riscv64::Riscv64AsmEmitter,
+        wasm::WatEmitter, x86_64::X86_64AsmEmitter, x86_64_assembler,
+    },
+};
+
+/// `(size, alignment)` in bytes of a scalar type.
+pub type SizeAlign = (usize, usize);
+
+/// Sizes and alignments of the scalar C types on a target.
+#[derive(Debug, Clone, Copy)]
+pub struct DataLayout {
+    pub bool: SizeAlign,
+    pub char: SizeAlign,
+    pub short: SizeAlign,
+    pub int: SizeAlign,
+    pub long: SizeAlign,
+    pub long_long: SizeAlign,
+    pub float: SizeAlign,
+    pub double: SizeAlign,
+    pub long_double: SizeAlign,
+    pub pointer: SizeAlign,
+}
+
+impl DataLayout {
+    /// The common LP64 layout: 64-bit `long` and pointers, 32-bit `int`.
+    const LP64: DataLayout = DataLayout {
+        bool: (1, 1),
+        char: (1, 1),
+        short: (2, 2),
+        int: (4, 4),
+        long: (8, 8),
+        long_long: (8, 8),
+        float: (4, 4),
+        double: (8, 8),
+        long_double: (16, 16),
+        pointer: (8, 8),
+    };
+}
+
+/// Object file format, which decides symbol naming and directive spelling in the assembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectFormat {
+    MachO,
+    Elf,
+    /// WebAssembly module, written in the text format
+    Wasm,
+}
+
+impl ObjectFormat {
+    /// Assembly-level name of a C symbol; Mach-O prepends an underscore.
+    pub fn symbol(&self, name: &str) -> String {
+        match self {
+            ObjectFormat::MachO => format!("_{name}"),
+            ObjectFormat::Elf | ObjectFormat::Wasm => name.to_string(),
+        }
+    }
+
+    /// Name of a function-local jump label, kept out of the object's symbol table.
+    pub fn local_label(&self, label: u32) -> String {
+        format!(".L{label}")
+    }
+}
+
+/// Procedure call standard used for calls between functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// Apple's variant of AAPCS64 used on Darwin
+    AppleArm64,
+    /// The standard Arm procedure call standard (AAPCS64)
+    Aapcs64,
+    /// System V AMD64 ABI
+    SysV,
+    /// RISC-V LP64D: 64-bit `long` and pointers, floating-point arguments in registers
+    Lp64d,
+    /// WebAssembly, where every argument is a parameter of the callee's function type
+    Wasm,
+}
+
+impl CallingConvention {
+    /// 32-bit views of the integer argument registers, in argument order. RISC-V has no
+    /// narrower register views, so its full registers are listed.
+    pub fn int_arg_regs(&self) -> &'static [&'static str] {
+        match self {
+            CallingConvention::AppleArm64 | CallingConvention::Aapcs64 => {
+                &["w0", "w1", "w2", "w3", "w4", "w5", "w6", "w7"]
+            }
+            CallingConvention::SysV => &["%edi", "%esi", "%edx", "%ecx", "%r8d", "%r9d"],
+            CallingConvention::Lp64d => &["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7"],
+            CallingConvention::Wasm => &[],
+        }
+    }
+
+    /// Alignment of the stack pointer required at every call.
+    pub fn stack_alignment(&self) -> usize {
+        16
+    }
+
+    /// Offsets (from sp at the call) of `count` `int` arguments passed on the stack, and the
+    /// size of that outgoing area rounded up to the stack alignment.
+    pub fn int_stack_args(&self, count: usize) -> (Vec<usize>, usize) {
+        // Apple packs stack arguments at their natural size and alignment, whereas AAPCS64,
+        // System V and LP64D give every argument its own 8-byte slot
+        let slotsize = match self {
+            CallingConvention::AppleArm64 => 4,
+            CallingConvention::Aapcs64 | CallingConvention::SysV | CallingConvention::Lp64d => 8,
+            // there is no stack to pass arguments on
+            CallingConvention::Wasm => 0,
+        };
+        let offsets = (0..count).map(|index| index * slotsize).collect();
+        (offsets, (count * slotsize).next_multiple_of(self.stack_alignment()))
+    }
+}
+
+pub trait Target {
+    /// The target triple this target is selected by, e.g. `x86_64-linux-gnu`.
+    fn triple(&self) -> &'static str;
+
+    fn data_layout(&self) -> DataLayout;
+
+    fn calling_convention(&self) -> CallingConvention;
+
+    fn object_format(&self) -> ObjectFormat;
+
+    /// Lowers the IR of a translation unit to assembly for this target. `optimizations`
+    /// picks the passes the backend runs over its own code, if it has any, and `debug` asks
+    /// for DWARF debug info on the backends that support it.
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        optimizations: &Optimizations,
+        debug: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError>;
+
+    /// Lowers the IR of a translation unit straight to a relocatable object file, on the
+    /// targets that have an integrated assembler.
+    fn emit_object(&self, irfuncs: &[IrFunction], optimizations: &Optimizations) -> Result<Vec<u8>, CompilerError> {
+        let _ = (irfuncs, optimizations);
+        Err(CompilerError {
+            kind: CompilerErrorKind::InternalError,
+            message: format!(
+                "there is no integrated assembler for {}; emit assembly instead",
+                self.triple()
+            ),
+            span: None,
+            ..Default::default()
+        })
+    }
+
+    /// Whether [`Target::emit_object`] can write object files, or the assembly has to go
+    /// through the system assembler.
+    fn has_integrated_assembler(&self) -> bool {
+        false
+    }
+
+    /// File extension for the output of [`Target::emit_asm`].
+    fn asm_extension(&self) -> &'static str {
+        "s"
+    }
+}
+
+/// ARM64 macOS (Apple silicon).
+pub struct Aarch64AppleDarwin;
+
+impl Target for Aarch64AppleDarwin {
+    fn triple(&self) -> &'static str {
+        "aarch64-apple-darwin"
+    }
+
+    fn data_layout(&self) -> DataLayout {
+        DataLayout {
+            // Apple arm64: long double == double
+            long_double: (8, 8),
+            ..DataLayout::LP64
+        }
+    }
+
+    fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::AppleArm64
+    }
+
+    fn object_format(&self) -> ObjectFormat {
+        ObjectFormat::MachO
+    }
+
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        optimizations: &Optimizations,
+        debug: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError> {
+        Arm64AsmEmitter::new(self.object_format(), self.calling_convention(), optimizations.peephole)
+            .with_debug_info(debug.cloned())
+            .emit(irfuncs)
+    }
+
+    fn emit_object(&self, irfuncs: &[IrFunction], optimizations: &Optimizations) -> Result<Vec<u8>, CompilerError> {
+        let code = Arm64AsmEmitter::new(self.object_format(), self.calling_convention(), optimizations.peephole)
+            .lower(irfuncs)?;
+        arm64_assembler::assemble(&code)?.write(self.object_format())
+    }
+
+    fn has_integrated_assembler(&self) -> bool {
+        true
+    }
+}
+
+/// ARM64 Linux with the GNU toolchain.
+pub struct Aarch64LinuxGnu;
+
+impl Target for Aarch64LinuxGnu {
+    fn triple(&self) -> &'static str {
+        "aarch64-linux-gnu"
+    }
+
+    fn data_layout(&self) -> DataLayout {
+        DataLayout::LP64
+    }
+
+    fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::Aapcs64
+    }
+
+    fn object_format(&self) -> ObjectFormat {
+        ObjectFormat::Elf
+    }
+
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        optimizations: &Optimizations,
+        debug: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError> {
+        Arm64AsmEmitter::new(self.object_format(), self.calling_convention(), optimizations.peephole)
+            .with_debug_info(debug.cloned())
+            .emit(irfuncs)
+    }
+
+    fn emit_object(&self, irfuncs: &[IrFunction], optimizations: &Optimizations) -> Result<Vec<u8>, CompilerError> {
+        let code = Arm64AsmEmitter::new(self.object_format(), self.calling_convention(), optimizations.peephole)
+            .lower(irfuncs)?;
+        arm64_assembler::assemble(&code)?.write(self.object_format())
+    }
+
+    fn has_integrated_assembler(&self) -> bool {
+        true
+    }
+}
+
+/// x86-64 Linux with the GNU toolchain.
+pub struct X86_64LinuxGnu;
+
+impl Target for X86_64LinuxGnu {
+    fn triple(&self) -> &'static str {
+        "x86_64-linux-gnu"
+    }
+
+    fn data_layout(&self) -> DataLayout {
+        DataLayout::LP64
+    }
+
+    fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::SysV
+    }
+
+    fn object_format(&self) -> ObjectFormat {
+        ObjectFormat::Elf
+    }
+
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        _: &Optimizations,
+        debug: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError> {
+        X86_64AsmEmitter::new(self.object_format(), self.calling_convention())
+            .with_debug_info(debug.cloned())
+            .emit(irfuncs)
+    }
+
+    fn emit_object(&self, irfuncs: &[IrFunction], _: &Optimizations) -> Result<Vec<u8>, CompilerError> {
+        let asm = X86_64AsmEmitter::new(self.object_format(), self.calling_convention()).emit(irfuncs)?;
+        x86_64_assembler::assemble(&asm)?.write(self.object_format())
+    }
+
+    fn has_integrated_assembler(&self) -> bool {
+        true
+    }
+}
+
+/// 64-bit RISC-V (RV64GC) Linux with the GNU toolchain.
+pub struct Riscv64LinuxGnu;
+
+impl Target for Riscv64LinuxGnu {
+    fn triple(&self) -> &'static str {
+        "riscv64-linux-gnu"
+    }
+
+    fn data_layout(&self) -> DataLayout {
+        DataLayout::LP64
+    }
+
+    fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::Lp64d
+    }
+
+    fn object_format(&self) -> ObjectFormat {
+        ObjectFormat::Elf
+    }
+
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        _: &Optimizations,
+        _: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError> {
+        Riscv64AsmEmitter::new(self.object_format(), self.calling_convention()).emit(irfuncs)
+    }
+}
+
+/// WebAssembly without any host OS, emitted as a module in the text format.
+pub struct Wasm32UnknownUnknown;
+
+impl Target for Wasm32UnknownUnknown {
+    fn triple(&self) -> &'static str {
+        "wasm32-unknown-unknown"
+    }
+
+    fn data_layout(&self) -> DataLayout {
+        DataLayout {
+            // ILP32: linear memory is addressed with 32 bits
+            long: (4, 4),
+            pointer: (4, 4),
+            ..DataLayout::LP64
+        }
+    }
+
+    fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::Wasm
+    }
+
+    fn object_format(&self) -> ObjectFormat {
+        ObjectFormat::Wasm
+    }
+
+    fn emit_asm(
+        &self,
+        irfuncs: &[IrFunction],
+        _: &Optimizations,
+        _: Option<&DebugInfo>,
+    ) -> Result<String, CompilerError> {
+        WatEmitter.emit(irfuncs)
+    }
+
+    fn asm_extension(&self) -> &'static str {
+        "wat"
+    }
+}
+
+/// Triples accepted by [`target_from_triple`].
+pub const SUPPORTED_TRIPLES: [&str; 5] = [
+    "aarch64-apple-darwin",
+    "aarch64-linux-gnu",
+    "x86_64-linux-gnu",
+    "riscv64-linux-gnu",
+    "wasm32-unknown-unknown",
+];
+
+/// The triple of the machine fbcc runs on, if it can generate code for it.
+pub fn host_triple() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("linux", "aarch64") => Some("aarch64-linux-gnu"),
+        ("linux", "x86_64") => Some("x86_64-linux-gnu"),
+        ("linux", "riscv64") => Some("riscv64-linux-gnu"),
+        _ => None,
+    }
+}
+
+/// Looks up the target for a triple such as `aarch64-apple-darwin`.
+pub fn target_from_triple(triple: &str) -> Option<Box<dyn Target>> {
+    match triple {
+        "aarch64-apple-darwin" => Some(Box::new(Aarch64AppleDarwin)),
+        "aarch64-linux-gnu" => Some(Box::new(Aarch64LinuxGnu)),
+        "x86_64-linux-gnu" => Some(Box::new(X86_64LinuxGnu)),
+        "riscv64-linux-gnu" => Some(Box::new(Riscv64LinuxGnu)),
+        "wasm32-unknown-unknown" => Some(Box::new(Wasm32UnknownUnknown)),
+        _ => None,
+    }
+}
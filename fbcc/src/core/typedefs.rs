@@ -1,10 +1,12 @@
 use std::fmt;
 
 use crate::analysis::ast::{
-    BinaryOperator, DeclarationSpecifier, StorageClassFlags, TypeName, TypeQualifier, TypeSpecifier, UnaryOperator,
+    BinaryOperator, DeclarationSpecifier, SpecifierQualifier, StorageClassFlags, TypeName, TypeQualifier,
+    TypeSpecifier, UnaryOperator,
 };
 use crate::analysis::node::Node;
-use crate::core::errors::{CompilerError, CompilerErrorKind};
+use crate::core::errors::{CompilerError, CompilerErrorKind, ErrorCode};
+use crate::core::target::{DataLayout, SizeAlign};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum IntegerType {
@@ -83,6 +85,11 @@ pub enum DataType {
         name: String,
         actual_type: Box<Type>,
     },
+
+    /// The type of an expression or declaration that already failed to check. It is accepted
+    /// wherever a type is checked, so that a mistake is reported once and not again by
+    /// everything that uses it.
+    Error,
 }
 
 #[derive(Debug)]
@@ -97,7 +104,7 @@ pub enum AssignmentConversionResult {
 
 impl DataType {
     pub fn new_integer(rank: IntegerRank, signed: bool) -> Self {
-        Self::Integer { rank, signed: signed }
+        Self::Integer { rank, signed }
     }
 
     pub fn is_integer(&self) -> bool {
@@ -116,7 +123,14 @@ impl DataType {
         matches!(self, DataType::Pointer { .. })
     }
 
+    pub fn is_error(&self) -> bool {
+        matches!(self, DataType::Error)
+    }
+
     pub fn is_compatible_with_binary_operator(&self, operator: &BinaryOperator) -> bool {
+        if self.is_error() {
+            return true;
+        }
         match operator {
             BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide => {
                 self.is_arithmetic()
@@ -141,6 +155,9 @@ impl DataType {
     }
 
     pub fn is_compatible_with_unary_operator(&self, operator: &UnaryOperator) -> bool {
+        if self.is_error() {
+            return true;
+        }
         match operator {
             UnaryOperator::PreIncrement
             | UnaryOperator::PostIncrement
@@ -177,70 +194,74 @@ impl Type {
         }
     }
 
-    pub fn from_typename(typename: TypeName) -> Self {
-        for specqual in &typename.specifier_qualifier_list {
-            println!("{:?}", specqual);
+    /// The type a type name, as in a cast or `sizeof`, spells out. Abstract declarators are
+    /// not supported yet, so the type is made of the specifiers and qualifiers alone
+    pub fn from_typename(typename: &Node<TypeName>) -> Result<Self, CompilerError> {
+        if let Some(declarator) = &typename.node.abstract_declarator {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: "Abstract declarators in type names are not supported yet".to_string(),
+                span: Some(declarator.span),
+                code: Some(ErrorCode::Unsupported),
+                ..Default::default()
+            });
         }
 
-        todo!()
+        let declaration_specifiers: Vec<_> = typename
+            .node
+            .specifier_qualifier_list
+            .iter()
+            .map(|specqual| {
+                let specifier = match &specqual.node {
+                    SpecifierQualifier::TypeSpecifier(specifier) => {
+                        DeclarationSpecifier::TypeSpecifier(specifier.clone())
+                    }
+                    SpecifierQualifier::TypeQualifier(qualifier) => {
+                        DeclarationSpecifier::TypeQualifier(qualifier.clone())
+                    }
+                };
+                Node::new(specifier, specqual.span)
+            })
+            .collect();
+        Ok(Self::from_declaration_specifiers(&declaration_specifiers)?.0)
     }
 
-    pub fn size(&self) -> Result<usize, CompilerError> {
-        let size = match &self.datatype {
-            DataType::Void => 0,
-            DataType::Integer {
-                rank: subtype,
-                signed: _,
-            } => match subtype {
-                IntegerRank::Bool => 1,
-                IntegerRank::Char { .. } => 1,
-                IntegerRank::Short { .. } => 2,
-                IntegerRank::Int { .. } => 4,
-                IntegerRank::Long { .. } => 8,
-                IntegerRank::LongLong { .. } => 8,
-            },
-            DataType::Float => 4,
-            DataType::Double => 8,
-            DataType::LongDouble => 8, // Apple arm64: long double == double
-            DataType::Pointer { .. } => 8,
-            other => {
-                return Err(CompilerError {
-                    kind: CompilerErrorKind::InternalError,
-                    message: format!("size() is not yet implemented for type: {other:?}"),
-                    span: None,
-                });
-            }
-        };
-        Ok(size)
+    pub fn size(&self, layout: &DataLayout) -> Result<usize, CompilerError> {
+        Ok(self.size_align(layout)?.0)
     }
 
-    pub fn align(&self) -> Result<usize, CompilerError> {
-        let size = match &self.datatype {
-            DataType::Void => 0,
+    pub fn align(&self, layout: &DataLayout) -> Result<usize, CompilerError> {
+        Ok(self.size_align(layout)?.1)
+    }
+
+    fn size_align(&self, layout: &DataLayout) -> Result<SizeAlign, CompilerError> {
+        let size_align = match &self.datatype {
+            DataType::Void => (0, 0),
             DataType::Integer {
                 rank: subtype,
                 signed: _,
             } => match subtype {
-                IntegerRank::Bool => 1,
-                IntegerRank::Char { .. } => 1,
-                IntegerRank::Short { .. } => 2,
-                IntegerRank::Int { .. } => 4,
-                IntegerRank::Long { .. } => 8,
-                IntegerRank::LongLong { .. } => 8,
+                IntegerRank::Bool => layout.bool,
+                IntegerRank::Char => layout.char,
+                IntegerRank::Short => layout.short,
+                IntegerRank::Int => layout.int,
+                IntegerRank::Long => layout.long,
+                IntegerRank::LongLong => layout.long_long,
             },
-            DataType::Float => 4,
-            DataType::Double => 8,
-            DataType::LongDouble => 8, // Apple arm64: long double == double
-            DataType::Pointer { .. } => 8,
+            DataType::Float => layout.float,
+            DataType::Double => layout.double,
+            DataType::LongDouble => layout.long_double,
+            DataType::Pointer { .. } => layout.pointer,
             other => {
                 return Err(CompilerError {
                     kind: CompilerErrorKind::InternalError,
-                    message: format!("alignment() is not yet implemented for type: {other:?}"),
+                    message: format!("size() is not yet implemented for type: {other:?}"),
                     span: None,
+                    ..Default::default()
                 });
             }
         };
-        Ok(size)
+        Ok(size_align)
     }
 
     pub fn from_declaration_specifiers(
@@ -275,6 +296,8 @@ impl Type {
                                 message: "Cannot combine `signed` keyword with previous declaration specifier"
                                     .to_string(),
                                 span: Some(decl_spec.span),
+                                code: Some(ErrorCode::InvalidTypeSpecifiers),
+                                ..Default::default()
                             });
                         }
                         signed_keyword = true;
@@ -287,6 +310,8 @@ impl Type {
                                 message: "Cannot combine `unsigned` keyword with previous declaration specifier"
                                     .to_string(),
                                 span: Some(decl_spec.span),
+                                code: Some(ErrorCode::InvalidTypeSpecifiers),
+                                ..Default::default()
                             });
                         }
                         unsigned_keyword = true;
@@ -314,8 +339,15 @@ impl Type {
                             // This is never possible so basically dead code
                             TypeSpecifier::Signed | TypeSpecifier::Unsigned => unreachable!(),
 
-                            // Yet to handle type specifiers like Complex
-                            TypeSpecifier::Complex => todo!(),
+                            TypeSpecifier::Complex => {
+                                return Err(CompilerError {
+                                    kind: CompilerErrorKind::SemanticError,
+                                    message: "`_Complex` types are not supported yet".to_string(),
+                                    span: Some(decl_spec.span),
+                                    code: Some(ErrorCode::Unsupported),
+                                    ..Default::default()
+                                })
+                            }
 
                             // Long Compatible types are handled specially as there are different
                             // variations to long it which are allowed by C language like having:
@@ -342,7 +374,8 @@ impl Type {
                         }
                     }
                 },
-                _ => todo!(),
+                // Function specifiers and attributes say nothing about the type
+                DeclarationSpecifier::FunctionSpecifier(_) | DeclarationSpecifier::Attribute(_) => {}
             }
         }
 
@@ -367,7 +400,9 @@ impl Type {
                         return Err(CompilerError {
                             kind: CompilerErrorKind::SemanticError,
                             message: "long long double is an invalid type.".to_string(),
-                            span: Some(declaration_specifiers.first().unwrap().span),
+                            span: declaration_specifiers.first().map(|specifier| specifier.span),
+                            code: Some(ErrorCode::InvalidTypeSpecifiers),
+                            ..Default::default()
                         }); // long long double x; <-- Not Allowed
                     } else {
                         // long long int
@@ -378,7 +413,9 @@ impl Type {
                     return Err(CompilerError {
                         kind: CompilerErrorKind::SemanticError,
                         message: "Invalid declaration containing more than 2 long specifiers.".to_string(),
-                        span: Some(declaration_specifiers.first().unwrap().span),
+                        span: declaration_specifiers.first().map(|specifier| specifier.span),
+                        code: Some(ErrorCode::InvalidTypeSpecifiers),
+                        ..Default::default()
                     }); // long long long x;
                 }
             }
@@ -390,7 +427,9 @@ impl Type {
             Err(CompilerError {
                 kind: CompilerErrorKind::SemanticError,
                 message: "Missing primitive type specifier".to_string(),
-                span: Some(declaration_specifiers.first().unwrap().span),
+                span: declaration_specifiers.first().map(|specifier| specifier.span),
+                code: Some(ErrorCode::InvalidTypeSpecifiers),
+                ..Default::default()
             })
         }
     }
@@ -401,22 +440,10 @@ impl Type {
                 match int_type {
                     IntegerType::Generic(val) => {
                         // Infer the smallest type that can hold the value
-                        if *val >= 0 {
-                            if *val <= i32::MAX as i64 {
-                                Type::new(DataType::new_integer(IntegerRank::Int, true))
-                            } else if *val <= i64::MAX {
-                                Type::new(DataType::new_integer(IntegerRank::Long, true))
-                            } else {
-                                Type::new(DataType::new_integer(IntegerRank::LongLong, true))
-                            }
+                        if i32::try_from(*val).is_ok() {
+                            Type::new(DataType::new_integer(IntegerRank::Int, true))
                         } else {
-                            if *val >= i32::MIN as i64 {
-                                Type::new(DataType::new_integer(IntegerRank::Int, true))
-                            } else if *val >= i64::MIN {
-                                Type::new(DataType::new_integer(IntegerRank::Long, true))
-                            } else {
-                                Type::new(DataType::new_integer(IntegerRank::LongLong, true))
-                            }
+                            Type::new(DataType::new_integer(IntegerRank::Long, true))
                         }
                     }
                     IntegerType::Signed(_) => Type::new(DataType::new_integer(IntegerRank::Int, true)),
@@ -437,12 +464,18 @@ impl Type {
     }
 
     /// uac: usual arithmetic conversions
-    pub fn common_datatype_for_uac(x: &Type, y: &Type) -> Result<DataType, CompilerError> {
+    pub fn common_datatype_for_uac(x: &Type, y: &Type, layout: &DataLayout) -> Result<DataType, CompilerError> {
+        if x.datatype.is_error() || y.datatype.is_error() {
+            return Ok(DataType::Error);
+        }
+
         if !x.datatype.is_arithmetic() || !y.datatype.is_arithmetic() {
             return Err(CompilerError {
                 kind: CompilerErrorKind::SemanticError,
                 message: format!("expected arithmetic operands, instead got {}, {}", x, y),
                 span: None,
+                code: Some(ErrorCode::InvalidOperands),
+                ..Default::default()
             });
         }
 
@@ -505,9 +538,9 @@ impl Type {
                 Ok(DataType::new_integer(x_rank, false))
             } else if !y_signed && y_rank > x_rank {
                 Ok(DataType::new_integer(y_rank, false))
-            } else if x_signed && x.size()? > y.size()? {
+            } else if x_signed && x.size(layout)? > y.size(layout)? {
                 Ok(DataType::new_integer(x_rank, true))
-            } else if y_signed && y.size()? > x.size()? {
+            } else if y_signed && y.size(layout)? > x.size(layout)? {
                 Ok(DataType::new_integer(y_rank, true))
             } else if x_signed {
                 Ok(DataType::new_integer(x_rank, false))
@@ -520,7 +553,7 @@ impl Type {
             Err(CompilerError {
                 kind: CompilerErrorKind::InternalError,
                 message: format!("common_type_for_uac: during integer promotion types of operands should've been integers, instead are {}, {}", x, y),
-                span: None
+                span: None, ..Default::default()
             })
         }
     }
@@ -528,29 +561,32 @@ impl Type {
     pub fn check_assignment_conversion(
         target: &Type,
         source: &Type,
+        layout: &DataLayout,
     ) -> Result<AssignmentConversionResult, CompilerError> {
-        if target == source {
-            return Ok(AssignmentConversionResult::Identical);
+        if target == source || target.datatype.is_error() || source.datatype.is_error() {
+            Ok(AssignmentConversionResult::Identical)
         } else if target.datatype.is_arithmetic() && source.datatype.is_arithmetic() {
-            if target.size()? < source.size()? {
-                return Ok(AssignmentConversionResult::CastWithWarning(format!(
+            if target.size(layout)? < source.size(layout)? {
+                Ok(AssignmentConversionResult::CastWithWarning(format!(
                     "narrowing down types from {} to {}, possible precision loss",
                     source, target
-                )));
+                )))
             } else {
-                return Ok(AssignmentConversionResult::Cast);
+                Ok(AssignmentConversionResult::Cast)
             }
         } else {
-            todo!()
+            Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: format!("Cannot convert a value of type {} to {}", source, target),
+                span: None,
+                code: Some(ErrorCode::IncompatibleTypes),
+                ..Default::default()
+            })
         }
     }
 
     pub fn is_boolean_compatible(ty: &Type) -> bool {
-        if ty.datatype.is_scalar() {
-            true
-        } else {
-            todo!()
-        }
+        ty.datatype.is_scalar() || ty.datatype.is_error()
     }
 }
 
@@ -619,6 +655,7 @@ impl fmt::Display for DataType {
             DataType::Typedef { name, actual_type } => {
                 write!(f, "typedef {} = {}", name, actual_type)
             }
+            DataType::Error => write!(f, "<error type>"),
         }
     }
 }
@@ -0,0 +1,323 @@
+//! The ARM64 instructions [`Arm64AsmEmitter`](super::asm::Arm64AsmEmitter) produces, kept
+//! in memory until the end so the [peephole optimiser](super::peephole) can rewrite them.
+//! Each one prints as a line of assembly.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg {
+    /// 32-bit general purpose register
+    W(u8),
+    /// 64-bit general purpose register
+    X(u8),
+    Sp,
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reg::W(number) => write!(f, "w{number}"),
+            Reg::X(number) => write!(f, "x{number}"),
+            Reg::Sp => write!(f, "sp"),
+        }
+    }
+}
+
+/// Condition of a `cset` or `b.cond`, for signed comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cond {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cond {
+    /// The condition that holds exactly when `self` doesn't.
+    pub fn invert(self) -> Cond {
+        match self {
+            Cond::Eq => Cond::Ne,
+            Cond::Ne => Cond::Eq,
+            Cond::Lt => Cond::Ge,
+            Cond::Le => Cond::Gt,
+            Cond::Gt => Cond::Le,
+            Cond::Ge => Cond::Lt,
+        }
+    }
+}
+
+impl fmt::Display for Cond {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Cond::Eq => "eq",
+            Cond::Ne => "ne",
+            Cond::Lt => "lt",
+            Cond::Le => "le",
+            Cond::Gt => "gt",
+            Cond::Ge => "ge",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The last operand of an arithmetic instruction: a register, or an immediate for `add`,
+/// `sub`, `subs` and `cmp`. Immediates are 12 bits, optionally shifted left by 12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand2 {
+    Reg(Reg),
+    Imm(u32),
+}
+
+impl fmt::Display for Operand2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand2::Reg(reg) => write!(f, "{reg}"),
+            Operand2::Imm(imm) if *imm > 0xfff => write!(f, "#{}, lsl #12", imm >> 12),
+            Operand2::Imm(imm) => write!(f, "#{imm}"),
+        }
+    }
+}
+
+/// Three-operand arithmetic and logic instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluOp {
+    Add,
+    Sub,
+    /// `sub` that also sets the flags
+    Subs,
+    Mul,
+    Sdiv,
+    And,
+    Orr,
+    Eor,
+    Lsl,
+    Asr,
+}
+
+impl fmt::Display for AluOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AluOp::Add => "add",
+            AluOp::Sub => "sub",
+            AluOp::Subs => "subs",
+            AluOp::Mul => "mul",
+            AluOp::Sdiv => "sdiv",
+            AluOp::And => "and",
+            AluOp::Orr => "orr",
+            AluOp::Eor => "eor",
+            AluOp::Lsl => "lsl",
+            AluOp::Asr => "asr",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `[base, #offset]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mem {
+    pub base: Reg,
+    pub offset: i64,
+}
+
+impl Mem {
+    /// Whether a load or store of `size` bytes can encode the offset as a scaled unsigned
+    /// 12-bit immediate, the form `ldr` and `str` print as.
+    pub fn is_scaled(&self, size: i64) -> bool {
+        self.offset >= 0 && self.offset % size == 0 && self.offset / size <= 0xfff
+    }
+
+    /// Whether a load or store of `size` bytes can encode the offset at all, either scaled
+    /// or as the signed 9-bit immediate of `ldur` and `stur`.
+    pub fn fits(&self, size: i64) -> bool {
+        self.is_scaled(size) || (-256..=255).contains(&self.offset)
+    }
+
+    /// Whether `ldp` and `stp` of two 64-bit registers can encode the offset, as a signed
+    /// 7-bit immediate scaled by 8.
+    pub fn fits_pair(&self) -> bool {
+        self.offset % 8 == 0 && (-512..=504).contains(&self.offset)
+    }
+}
+
+impl fmt::Display for Mem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, #{}]", self.base, self.offset)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inst {
+    /// A function symbol or a local jump target
+    Label(String),
+    /// An assembler directive such as `.globl _main`
+    Directive(String),
+    /// A `.loc` or `.cfi_*` directive for debug info, which describes the code around it
+    /// without being part of it
+    Debug(String),
+    Alu {
+        op: AluOp,
+        dst: Reg,
+        l: Reg,
+        r: Operand2,
+    },
+    /// `dst = c - a * b`
+    Msub {
+        dst: Reg,
+        a: Reg,
+        b: Reg,
+        c: Reg,
+    },
+    Neg {
+        dst: Reg,
+        src: Reg,
+    },
+    Mvn {
+        dst: Reg,
+        src: Reg,
+    },
+    Cmp {
+        l: Reg,
+        r: Operand2,
+    },
+    /// `dst = cond ? 1 : 0`
+    Cset {
+        dst: Reg,
+        cond: Cond,
+    },
+    Mov {
+        dst: Reg,
+        src: Reg,
+    },
+    /// Sets the low 16 bits and clears the rest
+    MovImm {
+        dst: Reg,
+        imm: u16,
+    },
+    /// Sets the high 16 bits of a 32-bit register and keeps the rest
+    Movk {
+        dst: Reg,
+        imm: u16,
+    },
+    Ldr {
+        dst: Reg,
+        mem: Mem,
+    },
+    Str {
+        src: Reg,
+        mem: Mem,
+    },
+    Ldp {
+        first: Reg,
+        second: Reg,
+        mem: Mem,
+    },
+    Stp {
+        first: Reg,
+        second: Reg,
+        mem: Mem,
+    },
+    /// Jump to a local label, or to a function for a tail call
+    B(String),
+    BCond(Cond, String),
+    Cbz(Reg, String),
+    Bl(String),
+    Ret,
+}
+
+/// How many bytes a load or store of `reg` transfers.
+pub fn access_size(reg: Reg) -> i64 {
+    match reg {
+        Reg::W(_) => 4,
+        Reg::X(_) | Reg::Sp => 8,
+    }
+}
+
+impl Inst {
+    /// The register the instruction writes, if any.
+    pub fn dst(&self) -> Option<Reg> {
+        match self {
+            Inst::Alu { dst, .. }
+            | Inst::Msub { dst, .. }
+            | Inst::Neg { dst, .. }
+            | Inst::Mvn { dst, .. }
+            | Inst::Cset { dst, .. }
+            | Inst::Mov { dst, .. }
+            | Inst::MovImm { dst, .. }
+            | Inst::Movk { dst, .. }
+            | Inst::Ldr { dst, .. } => Some(*dst),
+            _ => None,
+        }
+    }
+
+    /// The registers the instruction reads explicitly. Calls, returns and tail calls also
+    /// read the argument registers.
+    pub fn uses(&self) -> Vec<Reg> {
+        let mut inst = self.clone();
+        inst.uses_mut().into_iter().map(|reg| *reg).collect()
+    }
+
+    pub fn uses_mut(&mut self) -> Vec<&mut Reg> {
+        match self {
+            Inst::Alu { l, r, .. } | Inst::Cmp { l, r } => match r {
+                Operand2::Reg(r) => vec![l, r],
+                Operand2::Imm(_) => vec![l],
+            },
+            Inst::Msub { a, b, c, .. } => vec![a, b, c],
+            Inst::Neg { src, .. } | Inst::Mvn { src, .. } | Inst::Mov { src, .. } => vec![src],
+            Inst::Movk { dst, .. } => vec![dst],
+            Inst::Ldr { mem, .. } | Inst::Ldp { mem, .. } => vec![&mut mem.base],
+            Inst::Str { src, mem } => vec![src, &mut mem.base],
+            Inst::Stp { first, second, mem } => vec![first, second, &mut mem.base],
+            Inst::Cbz(reg, _) => vec![reg],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn sets_flags(&self) -> bool {
+        matches!(self, Inst::Alu { op: AluOp::Subs, .. } | Inst::Cmp { .. })
+    }
+
+    pub fn reads_flags(&self) -> bool {
+        matches!(self, Inst::Cset { .. } | Inst::BCond(..))
+    }
+
+    /// Whether control may arrive at or leave from somewhere other than the next
+    /// instruction.
+    pub fn ends_block(&self) -> bool {
+        matches!(
+            self,
+            Inst::Label(_) | Inst::B(_) | Inst::BCond(..) | Inst::Cbz(..) | Inst::Bl(_) | Inst::Ret
+        )
+    }
+}
+
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inst::Label(label) => write!(f, "{label}:"),
+            Inst::Directive(directive) | Inst::Debug(directive) => write!(f, "\t{directive}"),
+            Inst::Alu { op, dst, l, r } => write!(f, "\t{op}\t{dst}, {l}, {r}"),
+            Inst::Msub { dst, a, b, c } => write!(f, "\tmsub\t{dst}, {a}, {b}, {c}"),
+            Inst::Neg { dst, src } => write!(f, "\tneg\t{dst}, {src}"),
+            Inst::Mvn { dst, src } => write!(f, "\tmvn\t{dst}, {src}"),
+            Inst::Cmp { l, r } => write!(f, "\tcmp\t{l}, {r}"),
+            Inst::Cset { dst, cond } => write!(f, "\tcset\t{dst}, {cond}"),
+            Inst::Mov { dst, src } => write!(f, "\tmov\t{dst}, {src}"),
+            Inst::MovImm { dst, imm } => write!(f, "\tmov\t{dst}, #{imm}"),
+            Inst::Movk { dst, imm } => write!(f, "\tmovk\t{dst}, #{imm}, lsl #16"),
+            Inst::Ldr { dst, mem } if mem.is_scaled(access_size(*dst)) => write!(f, "\tldr\t{dst}, {mem}"),
+            Inst::Ldr { dst, mem } => write!(f, "\tldur\t{dst}, {mem}"),
+            Inst::Str { src, mem } if mem.is_scaled(access_size(*src)) => write!(f, "\tstr\t{src}, {mem}"),
+            Inst::Str { src, mem } => write!(f, "\tstur\t{src}, {mem}"),
+            Inst::Ldp { first, second, mem } => write!(f, "\tldp\t{first}, {second}, {mem}"),
+            Inst::Stp { first, second, mem } => write!(f, "\tstp\t{first}, {second}, {mem}"),
+            Inst::B(target) => write!(f, "\tb\t{target}"),
+            Inst::BCond(cond, target) => write!(f, "\tb.{cond}\t{target}"),
+            Inst::Cbz(reg, target) => write!(f, "\tcbz\t{reg}, {target}"),
+            Inst::Bl(target) => write!(f, "\tbl\t{target}"),
+            Inst::Ret => write!(f, "\tret"),
+        }
+    }
+}
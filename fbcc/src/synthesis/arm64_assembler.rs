@@ -0,0 +1,262 @@
+//! Integrated assembler for the ARM64 backend: encodes the [`Inst`]s of
+//! [`Arm64AsmEmitter::lower`](super::asm::Arm64AsmEmitter::lower) into an [`Object`],
+//! without going through the textual assembly.
+//!
+//! Jumps to local labels are resolved here; calls, and tail calls to other functions, are
+//! left to the linker as relocations.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    core::errors::{CompilerError, CompilerErrorKind},
+    synthesis::{
+        arm64::{AluOp, Cond, Inst, Mem, Operand2, Reg},
+        object::{Architecture, Object, Relocation, RelocationKind, Symbol},
+    },
+};
+
+/// Encodes a translation unit's instructions.
+pub fn assemble(code: &[Inst]) -> Result<Object, CompilerError> {
+    // 1. find every label and which of them are functions, every instruction being 4 bytes
+    let mut labels = HashMap::new();
+    let mut globals = HashSet::new();
+    let mut offset = 0;
+    for inst in code {
+        match inst {
+            Inst::Label(label) => {
+                labels.insert(label.as_str(), offset);
+            }
+            Inst::Directive(directive) => {
+                if let Some(symbol) = directive.strip_prefix(".globl\t") {
+                    globals.insert(symbol);
+                }
+            }
+            Inst::Debug(_) => return Err(error("debug info needs an external assembler".to_string())),
+            _ => offset += 4,
+        }
+    }
+
+    let mut symbols: Vec<Symbol> = code
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Label(label) if globals.contains(label.as_str()) => Some(Symbol {
+                name: label.clone(),
+                offset: labels[label.as_str()],
+                size: 0,
+                global: true,
+            }),
+            _ => None,
+        })
+        .collect();
+    for index in 0..symbols.len() {
+        let end = symbols.get(index + 1).map_or(offset, |next| next.offset);
+        symbols[index].size = end - symbols[index].offset;
+    }
+
+    // 2. encode
+    let mut text = Vec::with_capacity(offset as usize);
+    let mut relocations = Vec::new();
+    for inst in code {
+        let here = text.len() as u64;
+        // words from here to a local label
+        let distance = |label: &str, bits: u32| -> Result<Option<u32>, CompilerError> {
+            if globals.contains(label) {
+                return Ok(None);
+            }
+            let Some(&target) = labels.get(label) else {
+                return Ok(None);
+            };
+            let words = (target as i64 - here as i64) / 4;
+            if words < -(1 << (bits - 1)) || words >= 1 << (bits - 1) {
+                return Err(error(format!("branch to `{label}` is out of range")));
+            }
+            Ok(Some(words as u32 & ((1 << bits) - 1)))
+        };
+        let local =
+            |label: &str, bits: u32| distance(label, bits)?.ok_or_else(|| error(format!("undefined label `{label}`")));
+
+        let word = match inst {
+            Inst::Label(_) | Inst::Directive(_) | Inst::Debug(_) => continue,
+            Inst::B(target) | Inst::Bl(target) => {
+                let (opcode, kind) = match inst {
+                    Inst::B(_) => (0x14000000, RelocationKind::Arm64Jump),
+                    _ => (0x94000000, RelocationKind::Arm64Call),
+                };
+                match distance(target, 26)? {
+                    Some(words) => opcode | words,
+                    None => {
+                        relocations.push(Relocation {
+                            offset: here,
+                            symbol: target.clone(),
+                            kind,
+                        });
+                        opcode
+                    }
+                }
+            }
+            Inst::BCond(cond, target) => 0x54000000 | local(target, 19)? << 5 | condition(*cond),
+            Inst::Cbz(reg, target) => sf(*reg) | 0x34000000 | local(target, 19)? << 5 | number(*reg),
+            inst => encode(inst).ok_or_else(|| error(format!("cannot encode `{}`", inst.to_string().trim())))?,
+        };
+        text.extend(word.to_le_bytes());
+    }
+
+    Ok(Object {
+        architecture: Architecture::Arm64,
+        text,
+        symbols,
+        relocations,
+    })
+}
+
+/// The encoding of an instruction that doesn't refer to a label, if its operands fit.
+fn encode(inst: &Inst) -> Option<u32> {
+    let word = match *inst {
+        Inst::Alu {
+            op: op @ (AluOp::Add | AluOp::Sub | AluOp::Subs),
+            dst,
+            l,
+            r: Operand2::Imm(imm),
+        } => {
+            let opcode = match op {
+                AluOp::Add => 0x11000000,
+                AluOp::Sub => 0x51000000,
+                _ => 0x71000000,
+            };
+            sf(dst) | opcode | immediate(imm)? | number(l) << 5 | number(dst)
+        }
+        Inst::Alu {
+            op,
+            dst,
+            l,
+            r: Operand2::Reg(r),
+        } => {
+            let rm = number(r) << 16 | number(l) << 5 | number(dst);
+            match op {
+                // sp only works with the extended register form, which zero extends all 64 bits
+                AluOp::Add | AluOp::Sub if dst == Reg::Sp || l == Reg::Sp => {
+                    let opcode = if op == AluOp::Add { 0x8b206000 } else { 0xcb206000 };
+                    opcode | rm
+                }
+                AluOp::Add => sf(dst) | 0x0b000000 | rm,
+                AluOp::Sub => sf(dst) | 0x4b000000 | rm,
+                AluOp::Subs => sf(dst) | 0x6b000000 | rm,
+                // madd with the zero register
+                AluOp::Mul => sf(dst) | 0x1b007c00 | rm,
+                AluOp::Sdiv => sf(dst) | 0x1ac00c00 | rm,
+                AluOp::And => sf(dst) | 0x0a000000 | rm,
+                AluOp::Orr => sf(dst) | 0x2a000000 | rm,
+                AluOp::Eor => sf(dst) | 0x4a000000 | rm,
+                AluOp::Lsl => sf(dst) | 0x1ac02000 | rm,
+                AluOp::Asr => sf(dst) | 0x1ac02800 | rm,
+            }
+        }
+        Inst::Alu { .. } => return None,
+        Inst::Msub { dst, a, b, c } => {
+            sf(dst) | 0x1b008000 | number(b) << 16 | number(c) << 10 | number(a) << 5 | number(dst)
+        }
+        // sub and orn from the zero register
+        Inst::Neg { dst, src } => sf(dst) | 0x4b0003e0 | number(src) << 16 | number(dst),
+        Inst::Mvn { dst, src } => sf(dst) | 0x2a2003e0 | number(src) << 16 | number(dst),
+        // subs to the zero register
+        Inst::Cmp { l, r } => match r {
+            Operand2::Imm(imm) => sf(l) | 0x7100001f | immediate(imm)? | number(l) << 5,
+            Operand2::Reg(r) => sf(l) | 0x6b00001f | number(r) << 16 | number(l) << 5,
+        },
+        // csinc from the zero register, on the opposite condition
+        Inst::Cset { dst, cond } => sf(dst) | 0x1a9f07e0 | condition(cond.invert()) << 12 | number(dst),
+        // add #0 when sp is involved, otherwise orr from the zero register
+        Inst::Mov { dst, src } if dst == Reg::Sp || src == Reg::Sp => {
+            sf(dst) | 0x11000000 | number(src) << 5 | number(dst)
+        }
+        Inst::Mov { dst, src } => sf(dst) | 0x2a0003e0 | number(src) << 16 | number(dst),
+        Inst::MovImm { dst, imm } => sf(dst) | 0x52800000 | (imm as u32) << 5 | number(dst),
+        Inst::Movk { dst, imm } => sf(dst) | 0x72a00000 | (imm as u32) << 5 | number(dst),
+        Inst::Ldr { dst, mem } => load_store(dst, mem, 0x400000)?,
+        Inst::Str { src, mem } => load_store(src, mem, 0)?,
+        Inst::Ldp { first, second, mem } => pair(first, second, mem, 0xa9400000)?,
+        Inst::Stp { first, second, mem } => pair(first, second, mem, 0xa9000000)?,
+        Inst::Ret => 0xd65f03c0,
+        Inst::Label(_)
+        | Inst::Directive(_)
+        | Inst::Debug(_)
+        | Inst::B(_)
+        | Inst::BCond(..)
+        | Inst::Cbz(..)
+        | Inst::Bl(_) => return None,
+    };
+    Some(word)
+}
+
+/// `ldr`/`str` with a scaled unsigned offset, or `ldur`/`stur` with an unscaled signed one.
+/// `load` is the bit that tells them apart.
+fn load_store(reg: Reg, mem: Mem, load: u32) -> Option<u32> {
+    let (size, opcode) = match reg {
+        Reg::W(_) => (4, 0xb8000000),
+        Reg::X(_) | Reg::Sp => (8, 0xf8000000),
+    };
+    let operands = number(mem.base) << 5 | number(reg);
+    if mem.is_scaled(size) {
+        Some(opcode | 1 << 24 | load | ((mem.offset / size) as u32) << 10 | operands)
+    } else if mem.fits(size) {
+        Some(opcode | load | (mem.offset as u32 & 0x1ff) << 12 | operands)
+    } else {
+        None
+    }
+}
+
+/// `ldp`/`stp` of two 64-bit registers with a signed offset.
+fn pair(first: Reg, second: Reg, mem: Mem, opcode: u32) -> Option<u32> {
+    if !mem.fits_pair() {
+        return None;
+    }
+    let offset = (mem.offset / 8) as u32 & 0x7f;
+    Some(opcode | offset << 15 | number(second) << 10 | number(mem.base) << 5 | number(first))
+}
+
+/// The 12-bit immediate of `add`, `sub` and `subs`, shifted left by 12 if it has to be.
+fn immediate(imm: u32) -> Option<u32> {
+    if imm <= 0xfff {
+        Some(imm << 10)
+    } else if imm & 0xfff == 0 && imm <= 0xfff000 {
+        Some(1 << 22 | (imm >> 12) << 10)
+    } else {
+        None
+    }
+}
+
+/// The size bit: set for 64-bit registers.
+fn sf(reg: Reg) -> u32 {
+    match reg {
+        Reg::W(_) => 0,
+        Reg::X(_) | Reg::Sp => 1 << 31,
+    }
+}
+
+/// The register field; 31 is sp or the zero register, depending on the instruction.
+fn number(reg: Reg) -> u32 {
+    match reg {
+        Reg::W(number) | Reg::X(number) => number as u32,
+        Reg::Sp => 31,
+    }
+}
+
+fn condition(cond: Cond) -> u32 {
+    match cond {
+        Cond::Eq => 0x0,
+        Cond::Ne => 0x1,
+        Cond::Ge => 0xa,
+        Cond::Lt => 0xb,
+        Cond::Gt => 0xc,
+        Cond::Le => 0xd,
+    }
+}
+
+fn error(message: String) -> CompilerError {
+    CompilerError {
+        kind: CompilerErrorKind::InternalError,
+        message,
+        span: None,
+        ..Default::default()
+    }
+}
@@ -1,215 +1,452 @@
 use crate::{
-    core::errors::{CompilerError, CompilerErrorKind},
-    synthesis::ir::{
-        BinaryOp, IrFunction,
-        IrStatement::{self},
-        Operand, UnaryOp,
+    core::{
+        errors::CompilerError,
+        target::{CallingConvention, ObjectFormat},
+    },
+    synthesis::{
+        arm64::{AluOp, Cond, Inst, Mem, Operand2, Reg},
+        dwarf::{self, DebugInfo},
+        ir::{
+            BinaryOp, IrFunction,
+            IrStatement::{self},
+            Operand, UnaryOp,
+        },
+        legalize::legalize,
+        peephole,
     },
 };
 use std::fmt::Write;
 
-pub struct Arm64AsmEmitter {}
+/// Emits ARM64 assembly, either for Darwin (Mach-O) or for Linux (ELF).
+pub struct Arm64AsmEmitter {
+    format: ObjectFormat,
+    callconv: CallingConvention,
+    /// Run the [peephole optimiser](peephole) over every function
+    peephole: bool,
+    /// Emit line tables, call frame information and variable locations
+    debug: Option<DebugInfo>,
+}
+
+const W9: Reg = Reg::W(9);
+const W10: Reg = Reg::W(10);
+const W11: Reg = Reg::W(11);
+
+/// `[sp, #offset]`
+fn sp(offset: usize) -> Mem {
+    Mem {
+        base: Reg::Sp,
+        offset: offset as i64,
+    }
+}
 
 impl Arm64AsmEmitter {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(format: ObjectFormat, callconv: CallingConvention, peephole: bool) -> Self {
+        Self {
+            format,
+            callconv,
+            peephole,
+            debug: None,
+        }
+    }
+
+    /// Also emits DWARF debug info for the source file `debug` describes.
+    pub fn with_debug_info(self, debug: Option<DebugInfo>) -> Self {
+        Self { debug, ..self }
     }
 
-    pub fn emit(&self, irfuncs: &Vec<IrFunction>) -> Result<String, CompilerError> {
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
         let mut asm = String::new();
-        for function in irfuncs {
-            asm.push_str(self.emit_func(function)?.as_str());
+        if self.format == ObjectFormat::Elf {
+            writeln!(asm, "\t.text").unwrap();
+        }
+        if let Some(debug) = &self.debug {
+            writeln!(asm, "\t{}", debug.file_directive()).unwrap();
+        }
+        for inst in self.lower(irfuncs)? {
+            writeln!(asm, "{inst}").unwrap();
+        }
+        if let Some(debug) = &self.debug {
+            dwarf::emit_sections(&mut asm, debug, self.format, irfuncs);
+        }
+        if self.format == ObjectFormat::Elf {
+            // mark the stack as non-executable, otherwise the linker warns and makes it executable
+            writeln!(asm, "\t.section\t.note.GNU-stack,\"\",%progbits").unwrap();
         }
         Ok(asm)
     }
 
-    fn emit_func(&self, function: &IrFunction) -> Result<String, CompilerError> {
-        let mut asm = String::new();
+    /// The instructions of every function, in order, after the peephole optimiser if it is
+    /// enabled and with every immediate in range.
+    pub fn lower(&self, irfuncs: &[IrFunction]) -> Result<Vec<Inst>, CompilerError> {
+        let mut code = Vec::new();
+        for (index, function) in irfuncs.iter().enumerate() {
+            let mut insts = self.emit_func(function, index)?;
+            if self.peephole {
+                peephole::optimize(&mut insts);
+            }
+            legalize(&mut insts);
+            code.extend(insts);
+        }
+        Ok(code)
+    }
+
+    /// Emits the `index`th function of the translation unit.
+    fn emit_func(&self, function: &IrFunction, index: usize) -> Result<Vec<Inst>, CompilerError> {
+        let mut code = Vec::new();
+        let symbol = self.format.symbol(&function.name);
+        let arg_regs = self.arg_regs();
 
         // mark the function global so the linker (and the C runtime, for `main`) can resolve it
-        writeln!(asm, "\t.globl\t_{}", function.name).unwrap();
-        writeln!(asm, "_{}:", function.name).unwrap();
+        code.push(Inst::Directive(format!(".globl\t{symbol}")));
+        if self.format == ObjectFormat::Elf {
+            code.push(Inst::Directive(format!(".type\t{symbol}, %function")));
+        }
+        code.push(Inst::Label(symbol.clone()));
+        if self.debug.is_some() {
+            code.push(Inst::Label(dwarf::function_begin(self.format, index)));
+        }
+        self.cfi(&mut code, ".cfi_startproc");
 
         // emit prologue
         // 1. allocate stack frame
-        writeln!(asm, "\tsub\tsp, sp, #{}", function.framesize).unwrap();
+        code.push(Inst::Alu {
+            op: AluOp::Sub,
+            dst: Reg::Sp,
+            l: Reg::Sp,
+            r: Operand2::Imm(function.framesize as u32),
+        });
+        self.cfi(&mut code, &format!(".cfi_def_cfa_offset {}", function.framesize));
         // 2. store previous frame record address (x29, x30) on stack
-        writeln!(asm, "\tstp\tx29, x30, [sp, #{}]", function.framesize - 16).unwrap();
+        code.push(Inst::Stp {
+            first: Reg::X(29),
+            second: Reg::X(30),
+            mem: sp(function.framesize - 16),
+        });
         // 3. update (x29, x30) to contain current frame record address
-        writeln!(asm, "\tadd\tx29, sp, #{}", function.framesize - 16).unwrap();
-
-        // currently we only support 8 parameters
-        if function.params.len() > 8 {
-            return Err(CompilerError {
-                kind: CompilerErrorKind::InternalError,
-                message: "function call with more than 8 arguments is not supported".to_string(),
-                span: None,
+        code.push(Inst::Alu {
+            op: AluOp::Add,
+            dst: Reg::X(29),
+            l: Reg::Sp,
+            r: Operand2::Imm(function.framesize as u32 - 16),
+        });
+        // the frame record sits right below the caller's sp
+        self.cfi(&mut code, ".cfi_def_cfa w29, 16");
+        self.cfi(&mut code, ".cfi_offset w30, -8");
+        self.cfi(&mut code, ".cfi_offset w29, -16");
+
+        // store parameters onto stack
+        for (param, reg) in function.params.iter().zip(&arg_regs) {
+            code.push(Inst::Str {
+                src: *reg,
+                mem: sp(function.slot_offset(param)),
             });
         }
 
-        // store parameters onto stack
-        for (index, param) in function.params.iter().enumerate() {
-            writeln!(asm, "\tstr\tw{}, [sp, #{}]", index, function.slot_offset(param)).unwrap();
+        // parameters past the argument registers sit in the caller's outgoing area, which
+        // starts right above our frame record
+        let stack_params = function.params.iter().skip(arg_regs.len());
+        let (offsets, _) = self.callconv.int_stack_args(stack_params.len());
+        for (param, offset) in stack_params.zip(offsets) {
+            code.push(Inst::Ldr {
+                dst: W9,
+                mem: Mem {
+                    base: Reg::X(29),
+                    offset: 16 + offset as i64,
+                },
+            });
+            code.push(Inst::Str {
+                src: W9,
+                mem: sp(function.slot_offset(param)),
+            });
         }
 
-        let mut did_emit_epilogue = false;
-        self.emit_funcbody(function, &mut asm, &mut did_emit_epilogue)?;
+        self.emit_funcbody(function, &mut code)?;
 
-        if !did_emit_epilogue {
-            self.emit_epilogue(function, &mut asm);
+        if function.falls_off_end() {
+            self.emit_operand_to_reg(&Operand::Const(0), Reg::W(0), function, &mut code);
+            self.emit_epilogue(function, &mut code);
         }
 
-        Ok(asm)
+        self.cfi(&mut code, ".cfi_endproc");
+        if self.debug.is_some() {
+            code.push(Inst::Label(dwarf::function_end(self.format, index)));
+        }
+        if self.format == ObjectFormat::Elf {
+            code.push(Inst::Directive(format!(".size\t{0}, .-{0}", symbol)));
+        }
+
+        Ok(code)
     }
 
-    fn emit_epilogue(&self, function: &IrFunction, asm: &mut String) {
+    /// Pushes a call frame information directive, if debug info is enabled.
+    fn cfi(&self, code: &mut Vec<Inst>, directive: &str) {
+        if self.debug.is_some() {
+            code.push(Inst::Debug(directive.to_string()));
+        }
+    }
+
+    /// w0-w7, or however many registers the calling convention passes arguments in.
+    fn arg_regs(&self) -> Vec<Reg> {
+        (0..self.callconv.int_arg_regs().len() as u8).map(Reg::W).collect()
+    }
+
+    fn emit_epilogue(&self, function: &IrFunction, code: &mut Vec<Inst>) {
+        self.emit_frame_teardown(function, code);
+        // 3. return
+        code.push(Inst::Ret);
+        self.cfi(code, ".cfi_restore_state");
+    }
+
+    /// The epilogue up to the `ret`, shared with tail calls, which jump instead. Code may
+    /// follow either, so the caller restores the call frame information after them.
+    fn emit_frame_teardown(&self, function: &IrFunction, code: &mut Vec<Inst>) {
+        self.cfi(code, ".cfi_remember_state");
         // 1. load previous stack frame's record adress into (x29, x30)
-        writeln!(asm, "\tldp\tx29, x30, [sp, #{}]", function.framesize - 16).unwrap();
+        code.push(Inst::Ldp {
+            first: Reg::X(29),
+            second: Reg::X(30),
+            mem: sp(function.framesize - 16),
+        });
+        self.cfi(code, &format!(".cfi_def_cfa sp, {}", function.framesize));
+        self.cfi(code, ".cfi_restore w30");
+        self.cfi(code, ".cfi_restore w29");
         // 2. deallocate stack frame memory
-        writeln!(asm, "\tadd\tsp, sp, #{}", function.framesize).unwrap();
-        // 3. return
-        writeln!(asm, "\tret").unwrap();
+        code.push(Inst::Alu {
+            op: AluOp::Add,
+            dst: Reg::Sp,
+            l: Reg::Sp,
+            r: Operand2::Imm(function.framesize as u32),
+        });
+        self.cfi(code, ".cfi_def_cfa_offset 0");
     }
 
-    fn emit_funcbody(
-        &self,
-        function: &IrFunction,
-        asm: &mut String,
-        did_emit_epilogue: &mut bool,
-    ) -> Result<(), CompilerError> {
+    fn emit_funcbody(&self, function: &IrFunction, code: &mut Vec<Inst>) -> Result<(), CompilerError> {
+        let alu = |op, dst| Inst::Alu {
+            op,
+            dst,
+            l: W9,
+            r: Operand2::Reg(W10),
+        };
         for statement in &function.body {
             match statement {
                 IrStatement::BinaryOp { dst, op, l, r } => {
                     // 1. load left operand
-                    self.emit_operand_to_reg(l, "w9", function, asm);
+                    self.emit_operand_to_reg(l, W9, function, code);
 
                     // 2. load right operand
-                    self.emit_operand_to_reg(r, "w10", function, asm);
+                    self.emit_operand_to_reg(r, W10, function, code);
 
                     // 3. perform binary operation
+                    let compare = |code: &mut Vec<Inst>, cond| {
+                        code.push(alu(AluOp::Subs, W9));
+                        code.push(Inst::Cset { dst: W9, cond });
+                    };
                     match op {
-                        BinaryOp::Add => writeln!(asm, "\tadd\tw9, w9, w10").unwrap(),
-                        BinaryOp::Sub => writeln!(asm, "\tsub\tw9, w9, w10").unwrap(),
-                        BinaryOp::Mul => writeln!(asm, "\tmul\tw9, w9, w10").unwrap(),
-                        BinaryOp::Div => writeln!(asm, "\tsdiv\tw9, w9, w10").unwrap(),
+                        BinaryOp::Add => code.push(alu(AluOp::Add, W9)),
+                        BinaryOp::Sub => code.push(alu(AluOp::Sub, W9)),
+                        BinaryOp::Mul => code.push(alu(AluOp::Mul, W9)),
+                        BinaryOp::Div => code.push(alu(AluOp::Sdiv, W9)),
                         BinaryOp::Mod => {
-                            writeln!(asm, "\tsdiv\tw11, w9, w10").unwrap();
-                            writeln!(asm, "\tmsub\tw9, w11, w10, w9").unwrap();
-                        }
-                        BinaryOp::Lt => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, lt").unwrap();
+                            code.push(alu(AluOp::Sdiv, W11));
+                            code.push(Inst::Msub {
+                                dst: W9,
+                                a: W11,
+                                b: W10,
+                                c: W9,
+                            });
                         }
-                        BinaryOp::Le => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, le").unwrap();
-                        }
-                        BinaryOp::Gt => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, gt").unwrap();
-                        }
-                        BinaryOp::Ge => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, ge").unwrap();
-                        }
-                        BinaryOp::Eq => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, eq").unwrap();
-                        }
-                        BinaryOp::NEq => {
-                            writeln!(asm, "\tsubs\tw9, w9, w10").unwrap();
-                            writeln!(asm, "\tcset\tw9, ne").unwrap();
-                        }
-                        BinaryOp::And => writeln!(asm, "\tand\tw9, w9, w10").unwrap(),
-                        BinaryOp::Or => writeln!(asm, "\torr\tw9, w9, w10").unwrap(),
-                        BinaryOp::Xor => writeln!(asm, "\teor\tw9, w9, w10").unwrap(),
-                        BinaryOp::LShift => writeln!(asm, "\tlsl\tw9, w9, w10").unwrap(),
-                        BinaryOp::RShift => writeln!(asm, "\tasr\tw9, w9, w10").unwrap(),
+                        BinaryOp::Lt => compare(code, Cond::Lt),
+                        BinaryOp::Le => compare(code, Cond::Le),
+                        BinaryOp::Gt => compare(code, Cond::Gt),
+                        BinaryOp::Ge => compare(code, Cond::Ge),
+                        BinaryOp::Eq => compare(code, Cond::Eq),
+                        BinaryOp::NEq => compare(code, Cond::Ne),
+                        BinaryOp::And => code.push(alu(AluOp::And, W9)),
+                        BinaryOp::Or => code.push(alu(AluOp::Orr, W9)),
+                        BinaryOp::Xor => code.push(alu(AluOp::Eor, W9)),
+                        BinaryOp::LShift => code.push(alu(AluOp::Lsl, W9)),
+                        BinaryOp::RShift => code.push(alu(AluOp::Asr, W9)),
                     }
 
                     // 4. store result
-                    writeln!(asm, "\tstr\tw9, [sp, #{}]", function.slot_offset(dst)).unwrap();
+                    code.push(Inst::Str {
+                        src: W9,
+                        mem: sp(function.slot_offset(dst)),
+                    });
                 }
 
                 IrStatement::UnaryOp { dst, op, src } => {
-                    self.emit_operand_to_reg(src, "w9", function, asm);
+                    self.emit_operand_to_reg(src, W9, function, code);
 
                     match op {
-                        UnaryOp::Minus => writeln!(asm, "\tneg\tw9, w9").unwrap(),
-                        UnaryOp::Comp => writeln!(asm, "\tmvn\tw9, w9").unwrap(),
+                        UnaryOp::Minus => code.push(Inst::Neg { dst: W9, src: W9 }),
+                        UnaryOp::Comp => code.push(Inst::Mvn { dst: W9, src: W9 }),
                         UnaryOp::Not => {
-                            writeln!(asm, "\tsubs\tw9, w9, #0").unwrap();
-                            writeln!(asm, "\tcset\tw9, eq").unwrap();
+                            code.push(Inst::Alu {
+                                op: AluOp::Subs,
+                                dst: W9,
+                                l: W9,
+                                r: Operand2::Imm(0),
+                            });
+                            code.push(Inst::Cset {
+                                dst: W9,
+                                cond: Cond::Eq,
+                            });
                         }
                     }
 
-                    writeln!(asm, "\tstr\tw9, [sp, #{}]", function.slot_offset(dst)).unwrap();
+                    code.push(Inst::Str {
+                        src: W9,
+                        mem: sp(function.slot_offset(dst)),
+                    });
                 }
 
                 IrStatement::Copy { dst, src } => {
                     // 1. load src operand into w9
-                    self.emit_operand_to_reg(src, "w9", function, asm);
+                    self.emit_operand_to_reg(src, W9, function, code);
 
                     // 2. store w9 into dst slot
-                    writeln!(asm, "\tstr\tw9, [sp, #{}]", function.slot_offset(dst)).unwrap();
+                    code.push(Inst::Str {
+                        src: W9,
+                        mem: sp(function.slot_offset(dst)),
+                    });
                 }
 
-                IrStatement::Label(label) => writeln!(asm, ".L{}:", label).unwrap(),
-                IrStatement::Jmp(label) => writeln!(asm, "\tb\t.L{}", label).unwrap(),
+                IrStatement::Label(label) => code.push(Inst::Label(self.format.local_label(*label))),
+                IrStatement::Jmp(label) => code.push(Inst::B(self.format.local_label(*label))),
+                IrStatement::Loc(location) => {
+                    if self.debug.is_some() {
+                        code.push(Inst::Debug(dwarf::loc_directive(location)));
+                    }
+                }
 
                 IrStatement::JmpIfZero { cond, target } => {
-                    self.emit_operand_to_reg(cond, "w9", function, asm);
-                    writeln!(asm, "\tcbz\tw9, .L{}", target).unwrap(); // compare and jump to target if zero
+                    self.emit_operand_to_reg(cond, W9, function, code);
+                    // compare and jump to target if zero
+                    code.push(Inst::Cbz(W9, self.format.local_label(*target)));
                 }
 
-                IrStatement::Call { dst, name, args } => {
-                    if args.len() > 8 {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::InternalError,
-                            message: "function call with more than 8 arguments is not supported".to_string(),
-                            span: None,
+                IrStatement::Call { dst, name, args, tail } => {
+                    let arg_regs = self.arg_regs();
+                    let stack_args = args.iter().skip(arg_regs.len());
+                    let (offsets, area) = self.callconv.int_stack_args(stack_args.len());
+
+                    // A tail call hands our frame over to the callee, so its stack arguments
+                    // have to fit in the area our own caller passed us ours in
+                    let stack_params = function.params.len().saturating_sub(arg_regs.len());
+                    let (_, incoming) = self.callconv.int_stack_args(stack_params);
+                    if *tail && area <= incoming {
+                        // 1. Overwrite our incoming stack arguments, right above the frame record
+                        for (arg, offset) in stack_args.zip(offsets) {
+                            self.emit_operand_to_reg(arg, W9, function, code);
+                            code.push(Inst::Str {
+                                src: W9,
+                                mem: Mem {
+                                    base: Reg::X(29),
+                                    offset: 16 + offset as i64,
+                                },
+                            });
+                        }
+
+                        // 2. Load the remaining arguments into w0-w7
+                        for (arg, reg) in args.iter().zip(arg_regs) {
+                            self.emit_operand_to_reg(arg, reg, function, code);
+                        }
+
+                        // 3. Release our frame and jump; the callee returns straight to our caller
+                        self.emit_frame_teardown(function, code);
+                        code.push(Inst::B(self.format.symbol(name)));
+                        self.cfi(code, ".cfi_restore_state");
+                        continue;
+                    }
+
+                    // 1. Allocate the outgoing area for arguments that don't fit in registers;
+                    //    slots are addressed from sp, so their offsets grow by its size meanwhile
+                    if area > 0 {
+                        code.push(Inst::Alu {
+                            op: AluOp::Sub,
+                            dst: Reg::Sp,
+                            l: Reg::Sp,
+                            r: Operand2::Imm(area as u32),
+                        });
+                    }
+                    for (arg, offset) in stack_args.zip(offsets) {
+                        self.emit_operand_to_reg_biased(arg, W9, area, function, code);
+                        code.push(Inst::Str {
+                            src: W9,
+                            mem: sp(offset),
                         });
                     }
 
-                    // 1. Store the arguments in w0-w7 in order
-                    for (index, arg) in args.iter().enumerate() {
-                        let reg = format!("w{index}");
-                        self.emit_operand_to_reg(arg, reg.as_str(), function, asm);
+                    // 2. Store the remaining arguments in w0-w7 in order
+                    for (arg, reg) in args.iter().zip(arg_regs) {
+                        self.emit_operand_to_reg_biased(arg, reg, area, function, code);
                     }
 
-                    // 2. Call the procedure
-                    writeln!(asm, "\tbl\t_{}", name).unwrap();
+                    // 3. Call the procedure and release the outgoing area
+                    code.push(Inst::Bl(self.format.symbol(name)));
+                    if area > 0 {
+                        code.push(Inst::Alu {
+                            op: AluOp::Add,
+                            dst: Reg::Sp,
+                            l: Reg::Sp,
+                            r: Operand2::Imm(area as u32),
+                        });
+                    }
 
-                    // 3. Store the return value onto stack
+                    // 4. Store the return value onto stack
                     if let Some(return_dest) = dst {
-                        writeln!(asm, "\tstr\tw0, [sp, #{}]", function.slot_offset(return_dest)).unwrap();
+                        code.push(Inst::Str {
+                            src: Reg::W(0),
+                            mem: sp(function.slot_offset(return_dest)),
+                        });
                     }
                 }
 
                 IrStatement::Ret(op) => {
-                    self.emit_operand_to_reg(op, "w0", function, asm);
-                    self.emit_epilogue(function, asm);
-                    *did_emit_epilogue = true;
+                    self.emit_operand_to_reg(op, Reg::W(0), function, code);
+                    self.emit_epilogue(function, code);
                 }
             }
         }
         Ok(())
     }
 
-    fn emit_operand_to_reg(&self, operand: &Operand, reg: &str, function: &IrFunction, asm: &mut String) {
+    fn emit_operand_to_reg(&self, operand: &Operand, reg: Reg, function: &IrFunction, code: &mut Vec<Inst>) {
+        self.emit_operand_to_reg_biased(operand, reg, 0, function, code);
+    }
+
+    /// Same as `emit_operand_to_reg`, for when sp sits `bias` bytes below the frame's usual sp.
+    fn emit_operand_to_reg_biased(
+        &self,
+        operand: &Operand,
+        reg: Reg,
+        bias: usize,
+        function: &IrFunction,
+        code: &mut Vec<Inst>,
+    ) {
         match operand {
-            Operand::Var(slot) => writeln!(asm, "\tldr\t{}, [sp, #{}]", reg, function.slot_offset(slot)).unwrap(),
-            Operand::Const(constant) => self.emit_imm_const_to_reg(*constant, reg, asm),
+            Operand::Var(slot) => code.push(Inst::Ldr {
+                dst: reg,
+                mem: sp(function.slot_offset(slot) + bias),
+            }),
+            Operand::Const(constant) => self.emit_imm_const_to_reg(*constant, reg, code),
         }
     }
 
-    fn emit_imm_const_to_reg(&self, constant: i64, reg: &str, asm: &mut String) {
+    fn emit_imm_const_to_reg(&self, constant: i64, reg: Reg, code: &mut Vec<Inst>) {
         let bits = constant as u32;
         let (low, high) = (bits & 0xffff, bits >> 16);
 
-        writeln!(asm, "\tmov\t{}, #{}", reg, low).unwrap();
+        code.push(Inst::MovImm {
+            dst: reg,
+            imm: low as u16,
+        });
         if high != 0 {
-            writeln!(asm, "\tmovk\t{}, #{}, lsl #16", reg, high).unwrap();
+            code.push(Inst::Movk {
+                dst: reg,
+                imm: high as u16,
+            });
         }
     }
 }
@@ -0,0 +1,143 @@
+use std::fmt::Write;
+
+use crate::{
+    core::errors::CompilerError,
+    synthesis::ir::{BinaryOp, IrFunction, IrStatement, Operand, UnaryOp},
+};
+
+/// Emits portable C from the IR: one function per `IrFunction`, an `int` local per slot and
+/// `goto` for jumps, so the host's C compiler can serve as a test oracle for the front end
+/// and the IR independently of our own backends.
+///
+/// The output reproduces what the native backends do rather than what C says: arithmetic
+/// wraps around like the 32-bit machine instructions do, instead of being undefined on
+/// overflow, and shift counts are taken modulo 32.
+pub struct CEmitter;
+
+impl CEmitter {
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
+        let mut c = String::new();
+        writeln!(c, "/* generated by fbcc */").unwrap();
+        writeln!(c).unwrap();
+
+        // prototypes first, so functions can call each other in any order
+        for function in irfuncs {
+            let params: Vec<String> = function.params.iter().map(|param| format!("int {param}")).collect();
+            writeln!(c, "int {}({});", function.name, Self::param_list(params)).unwrap();
+        }
+
+        // functions called but not defined here come from the C library
+        let declared = IrFunction::undefined_callees(irfuncs)?;
+        for (name, arity) in declared {
+            writeln!(c, "int {name}({});", Self::param_list(vec!["int".to_string(); arity])).unwrap();
+        }
+
+        for function in irfuncs {
+            writeln!(c).unwrap();
+            c.push_str(self.emit_func(function).as_str());
+        }
+
+        Ok(c)
+    }
+
+    fn emit_func(&self, function: &IrFunction) -> String {
+        let mut c = String::new();
+
+        let params: Vec<String> = function.params.iter().map(|param| format!("int {param}")).collect();
+        writeln!(c, "int {}({}) {{", function.name, Self::param_list(params.clone())).unwrap();
+
+        // every other slot is a local; zeroed, since reading an uninitialised one is undefined
+        let locals: Vec<String> = (0..function.slots.len())
+            .map(|slot| format!("r{slot}"))
+            .filter(|local| !params.contains(&format!("int {local}")))
+            .collect();
+        if !locals.is_empty() {
+            let locals: Vec<String> = locals.iter().map(|local| format!("{local} = 0")).collect();
+            writeln!(c, "    int {};", locals.join(", ")).unwrap();
+        }
+
+        for statement in &function.body {
+            match statement {
+                IrStatement::BinaryOp { dst, op, l, r } => {
+                    let (l, r) = (Self::operand(l), Self::operand(r));
+                    let expr = match op {
+                        // do the arithmetic unsigned, where overflow wraps, and convert back
+                        BinaryOp::Add => format!("(int)((unsigned){l} + (unsigned){r})"),
+                        BinaryOp::Sub => format!("(int)((unsigned){l} - (unsigned){r})"),
+                        BinaryOp::Mul => format!("(int)((unsigned){l} * (unsigned){r})"),
+                        BinaryOp::Div => format!("{l} / {r}"),
+                        BinaryOp::Mod => format!("{l} % {r}"),
+                        BinaryOp::Lt => format!("{l} < {r}"),
+                        BinaryOp::Le => format!("{l} <= {r}"),
+                        BinaryOp::Gt => format!("{l} > {r}"),
+                        BinaryOp::Ge => format!("{l} >= {r}"),
+                        BinaryOp::Eq => format!("{l} == {r}"),
+                        BinaryOp::NEq => format!("{l} != {r}"),
+                        BinaryOp::And => format!("{l} & {r}"),
+                        BinaryOp::Or => format!("{l} | {r}"),
+                        BinaryOp::Xor => format!("{l} ^ {r}"),
+                        BinaryOp::LShift => format!("(int)((unsigned){l} << ({r} & 31))"),
+                        BinaryOp::RShift => format!("{l} >> ({r} & 31)"),
+                    };
+                    writeln!(c, "    {dst} = {expr};").unwrap();
+                }
+
+                IrStatement::UnaryOp { dst, op, src } => {
+                    let src = Self::operand(src);
+                    let expr = match op {
+                        UnaryOp::Minus => format!("(int)(0u - (unsigned){src})"),
+                        UnaryOp::Comp => format!("~{src}"),
+                        UnaryOp::Not => format!("!{src}"),
+                    };
+                    writeln!(c, "    {dst} = {expr};").unwrap();
+                }
+
+                IrStatement::Copy { dst, src } => writeln!(c, "    {dst} = {};", Self::operand(src)).unwrap(),
+
+                // the empty statement lets a label end the function or precede a declaration
+                IrStatement::Label(label) => writeln!(c, "L{label}:;").unwrap(),
+                IrStatement::Loc(_) => {}
+                IrStatement::Jmp(label) => writeln!(c, "    goto L{label};").unwrap(),
+                IrStatement::JmpIfZero { cond, target } => {
+                    writeln!(c, "    if ({} == 0) goto L{target};", Self::operand(cond)).unwrap()
+                }
+
+                IrStatement::Call { dst, name, args, .. } => {
+                    let args: Vec<String> = args.iter().map(Self::operand).collect();
+                    match dst {
+                        Some(dst) => writeln!(c, "    {dst} = {name}({});", args.join(", ")).unwrap(),
+                        None => writeln!(c, "    {name}({});", args.join(", ")).unwrap(),
+                    }
+                }
+
+                IrStatement::Ret(value) => writeln!(c, "    return {};", Self::operand(value)).unwrap(),
+            }
+        }
+
+        // only `main` returns 0 by itself when it runs off the end in C, so every function does
+        // it explicitly
+        if function.falls_off_end() {
+            writeln!(c, "    return 0;").unwrap();
+        }
+        writeln!(c, "}}").unwrap();
+
+        c
+    }
+
+    /// `params` joined for a parameter list, which is `void` when empty.
+    fn param_list(params: Vec<String>) -> String {
+        match params.is_empty() {
+            true => "void".to_string(),
+            false => params.join(", "),
+        }
+    }
+
+    fn operand(operand: &Operand) -> String {
+        match operand {
+            Operand::Var(slot) => slot.to_string(),
+            // INT_MIN has no literal in C, only the negation of one that doesn't fit an `int`
+            Operand::Const(constant) if *constant as i32 == i32::MIN => "(-2147483647 - 1)".to_string(),
+            Operand::Const(constant) => (*constant as i32).to_string(),
+        }
+    }
+}
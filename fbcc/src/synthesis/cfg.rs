@@ -0,0 +1,397 @@
+//! Control flow graph of an `IrFunction`.
+//!
+//! The linear IR marks block boundaries implicitly with labels and jumps. A [`Cfg`] makes
+//! them explicit: each [`BasicBlock`] holds straight-line statements and ends in exactly one
+//! [`Terminator`] naming its successors by block index. [`Cfg::into_function`] lays the
+//! blocks out again as a linear body for the emitters.
+
+use std::collections::HashMap;
+
+use crate::synthesis::ir::{self, InlineHint, IrFunction, IrStatement, Operand, Slot, SlotID, SourceInfo};
+
+/// Index of a block in [`Cfg::blocks`].
+pub type BlockId = usize;
+
+/// How control leaves a basic block.
+#[derive(Debug, Clone)]
+pub enum Terminator {
+    Jmp(BlockId),
+    /// To `zero` if `cond` is zero, otherwise to `nonzero`
+    Branch {
+        cond: Operand,
+        zero: BlockId,
+        nonzero: BlockId,
+    },
+    Ret(Operand),
+    /// Running off the end of the function without a `return`; only the last block in the
+    /// layout can do this
+    FallOff,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jmp(target) => vec![*target],
+            Terminator::Branch { zero, nonzero, .. } => vec![*zero, *nonzero],
+            Terminator::Ret(_) | Terminator::FallOff => Vec::new(),
+        }
+    }
+
+    /// Operand the terminator reads, if any.
+    pub fn operand(&self) -> Option<&Operand> {
+        match self {
+            Terminator::Branch { cond: operand, .. } | Terminator::Ret(operand) => Some(operand),
+            Terminator::Jmp(_) | Terminator::FallOff => None,
+        }
+    }
+
+    pub fn operand_mut(&mut self) -> Option<&mut Operand> {
+        match self {
+            Terminator::Branch { cond: operand, .. } | Terminator::Ret(operand) => Some(operand),
+            Terminator::Jmp(_) | Terminator::FallOff => None,
+        }
+    }
+
+    /// Rewrites every successor with `map`.
+    pub fn map_successors(&mut self, mut map: impl FnMut(BlockId) -> BlockId) {
+        match self {
+            Terminator::Jmp(target) => *target = map(*target),
+            Terminator::Branch { zero, nonzero, .. } => {
+                *zero = map(*zero);
+                *nonzero = map(*nonzero);
+            }
+            Terminator::Ret(_) | Terminator::FallOff => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    /// Label the block started with in the linear IR, if any
+    pub label: Option<u32>,
+    /// Straight-line statements: no `Label`, `Jmp`, `JmpIfZero` or `Ret`
+    pub statements: Vec<IrStatement>,
+    pub terminator: Terminator,
+}
+
+impl BasicBlock {
+    pub fn successors(&self) -> Vec<BlockId> {
+        self.terminator.successors()
+    }
+
+    /// How many of the statements turn into instructions, for size limits.
+    pub fn size(&self) -> usize {
+        self.statements.iter().filter(|statement| statement.is_code()).count()
+    }
+}
+
+/// An `IrFunction` as a graph of basic blocks. The entry block is always block 0, and the
+/// order of `blocks` is the layout [`Cfg::into_function`] uses.
+pub struct Cfg {
+    pub name: String,
+    pub framesize: usize,
+    pub params: Vec<SlotID>,
+    pub slots: Vec<Slot>,
+    pub blocks: Vec<BasicBlock>,
+    pub inline: InlineHint,
+    pub source: SourceInfo,
+}
+
+impl Cfg {
+    pub const ENTRY: BlockId = 0;
+
+    /// Splits the body at labels and after every jump or return.
+    pub fn from_function(function: IrFunction) -> Self {
+        let body = function.body;
+
+        // 1. find where each block starts, and which block each label opens
+        let mut starts = vec![0];
+        let mut labels = HashMap::new();
+        for (index, statement) in body.iter().enumerate() {
+            match statement {
+                IrStatement::Label(label) => {
+                    // a label right after another label or a jump opens no new block
+                    if *starts.last().unwrap() != index {
+                        starts.push(index);
+                    }
+                    labels.insert(*label, starts.len() - 1);
+                }
+                IrStatement::Jmp(_) | IrStatement::JmpIfZero { .. } | IrStatement::Ret(_) if index + 1 < body.len() => {
+                    starts.push(index + 1);
+                }
+                _ => {}
+            }
+        }
+
+        // 2. move the statements into their blocks and resolve the terminators
+        let count = starts.len();
+        let mut blocks = Vec::with_capacity(count);
+        let mut statements = body.into_iter();
+        for block in 0..count {
+            let length = match starts.get(block + 1) {
+                Some(next) => next - starts[block],
+                None => statements.len(),
+            };
+
+            let mut label = None;
+            let mut straight = Vec::new();
+            let mut terminator = None;
+            for statement in statements.by_ref().take(length) {
+                match statement {
+                    IrStatement::Label(id) => {
+                        label.get_or_insert(id);
+                    }
+                    IrStatement::Jmp(target) => terminator = Some(Terminator::Jmp(labels[&target])),
+                    IrStatement::JmpIfZero { cond, target } => {
+                        terminator = Some(Terminator::Branch {
+                            cond,
+                            zero: labels[&target],
+                            nonzero: block + 1,
+                        })
+                    }
+                    IrStatement::Ret(value) => terminator = Some(Terminator::Ret(value)),
+                    statement => straight.push(statement),
+                }
+            }
+
+            let terminator = terminator.unwrap_or(if block + 1 < count {
+                Terminator::Jmp(block + 1)
+            } else {
+                Terminator::FallOff
+            });
+            blocks.push(BasicBlock {
+                label,
+                statements: straight,
+                terminator,
+            });
+        }
+
+        Cfg {
+            name: function.name,
+            framesize: function.framesize,
+            params: function.params,
+            slots: function.slots,
+            blocks,
+            inline: function.inline,
+            source: function.source,
+        }
+    }
+
+    /// Lays the blocks out in their current order. Jumps to the next block in the layout
+    /// become fall-throughs, and only blocks that are still jumped to keep a label; blocks
+    /// that need one but never had one get a fresh label from `next_label`.
+    pub fn into_function(self, next_label: &mut u32) -> IrFunction {
+        // the block that can fall off the end has to stay last; if that is the entry block,
+        // nothing else is reachable and the other blocks are dropped
+        let mut order: Vec<BlockId> = (0..self.blocks.len()).collect();
+        match order
+            .iter()
+            .position(|block| matches!(self.blocks[*block].terminator, Terminator::FallOff))
+        {
+            Some(Self::ENTRY) => order.truncate(1),
+            Some(position) => {
+                let falloff = order.remove(position);
+                order.push(falloff);
+            }
+            None => {}
+        }
+        let next_in_layout = |position: usize| order.get(position + 1).copied();
+
+        // 1. find the blocks that are jumped to explicitly
+        let mut jumped_to = vec![false; self.blocks.len()];
+        for (position, &block) in order.iter().enumerate() {
+            match &self.blocks[block].terminator {
+                Terminator::Jmp(target) if next_in_layout(position) != Some(*target) => jumped_to[*target] = true,
+                Terminator::Branch { zero, nonzero, .. } => {
+                    jumped_to[*zero] = true;
+                    if next_in_layout(position) != Some(*nonzero) {
+                        jumped_to[*nonzero] = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let labels: Vec<Option<u32>> = self
+            .blocks
+            .iter()
+            .zip(&jumped_to)
+            .map(|(block, jumped_to)| {
+                jumped_to.then(|| {
+                    block.label.unwrap_or_else(|| {
+                        *next_label += 1;
+                        *next_label - 1
+                    })
+                })
+            })
+            .collect();
+
+        // 2. emit the blocks with their terminators spelled out as jumps
+        let mut body = Vec::new();
+        let mut blocks: Vec<Option<BasicBlock>> = self.blocks.into_iter().map(Some).collect();
+        for (position, &id) in order.iter().enumerate() {
+            let block = blocks[id].take().unwrap();
+            if let Some(label) = labels[id] {
+                body.push(IrStatement::Label(label));
+            }
+            body.extend(block.statements);
+
+            let next = next_in_layout(position);
+            match block.terminator {
+                Terminator::Jmp(target) if next == Some(target) => {}
+                Terminator::Jmp(target) => body.push(IrStatement::Jmp(labels[target].unwrap())),
+                Terminator::Branch { cond, zero, nonzero } => {
+                    body.push(IrStatement::JmpIfZero {
+                        cond,
+                        target: labels[zero].unwrap(),
+                    });
+                    if next != Some(nonzero) {
+                        body.push(IrStatement::Jmp(labels[nonzero].unwrap()));
+                    }
+                }
+                Terminator::Ret(value) => body.push(IrStatement::Ret(value)),
+                Terminator::FallOff => {}
+            }
+        }
+
+        IrFunction {
+            name: self.name,
+            framesize: self.framesize,
+            params: self.params,
+            slots: self.slots,
+            body,
+            inline: self.inline,
+            source: self.source,
+        }
+    }
+
+    /// Adds a slot of the same type as `like` to the end of the frame and grows the frame
+    /// to match, like [`IrFunction::new_slot_like`].
+    pub fn new_slot_like(&mut self, like: &SlotID) -> SlotID {
+        ir::new_slot_like(&mut self.slots, &mut self.framesize, like)
+    }
+
+    pub fn successors(&self, block: BlockId) -> Vec<BlockId> {
+        self.blocks[block].successors()
+    }
+
+    /// Predecessors of every block, one entry per edge, so a block that branches to the
+    /// same target both ways is listed twice.
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut preds = vec![Vec::new(); self.blocks.len()];
+        for (block, data) in self.blocks.iter().enumerate() {
+            for successor in data.successors() {
+                preds[successor].push(block);
+            }
+        }
+        preds
+    }
+
+    /// Blocks reachable from the entry, in reverse postorder: every block comes before its
+    /// successors, except along the edges that close loops.
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let mut postorder = Vec::new();
+        let mut visited = vec![false; self.blocks.len()];
+        let mut stack = vec![(Self::ENTRY, 0)];
+        visited[Self::ENTRY] = true;
+        while let Some((block, next)) = stack.pop() {
+            let successors = self.successors(block);
+            if let Some(&successor) = successors.get(next) {
+                stack.push((block, next + 1));
+                if !visited[successor] {
+                    visited[successor] = true;
+                    stack.push((successor, 0));
+                }
+            } else {
+                postorder.push(block);
+            }
+        }
+        postorder.reverse();
+        postorder
+    }
+
+    /// Deletes the blocks that can't be reached from the entry and returns how many there
+    /// were. The remaining blocks keep their relative order but are renumbered.
+    pub fn remove_unreachable(&mut self) -> usize {
+        let mut reachable = vec![false; self.blocks.len()];
+        for block in self.reverse_postorder() {
+            reachable[block] = true;
+        }
+
+        let before = self.blocks.len();
+        self.retain_blocks(&reachable);
+        before - self.blocks.len()
+    }
+
+    /// Makes sure nothing branches to the entry block, by adding an empty block in front of
+    /// it if something does.
+    pub fn split_entry(&mut self) {
+        if self.predecessors()[Self::ENTRY].is_empty() {
+            return;
+        }
+        for block in &mut self.blocks {
+            block.terminator.map_successors(|target| target + 1);
+        }
+        self.blocks.insert(
+            Self::ENTRY,
+            BasicBlock {
+                label: None,
+                statements: Vec::new(),
+                terminator: Terminator::Jmp(Self::ENTRY + 1),
+            },
+        );
+    }
+
+    /// Lays the blocks out in `order`, which lists every block once, and renumbers them to
+    /// match. `order[0]` has to be the entry block.
+    pub fn reorder_blocks(&mut self, order: &[BlockId]) {
+        let mut renumbered = vec![0; self.blocks.len()];
+        for (new, &old) in order.iter().enumerate() {
+            renumbered[old] = new;
+        }
+
+        let mut blocks: Vec<Option<BasicBlock>> = std::mem::take(&mut self.blocks).into_iter().map(Some).collect();
+        self.blocks = order.iter().map(|&old| blocks[old].take().unwrap()).collect();
+        for block in &mut self.blocks {
+            block.terminator.map_successors(|target| renumbered[target]);
+        }
+    }
+
+    /// Deletes the blocks for which `keep` is false; none of the kept blocks may branch to
+    /// them. The remaining blocks keep their relative order, and the returned table maps
+    /// every old block number to its new one.
+    pub fn retain_blocks(&mut self, keep: &[bool]) -> Vec<Option<BlockId>> {
+        let mut renumbered = vec![None; self.blocks.len()];
+        let mut next = 0;
+        for (block, keep) in keep.iter().enumerate() {
+            if *keep {
+                renumbered[block] = Some(next);
+                next += 1;
+            }
+        }
+
+        let mut index = 0;
+        self.blocks.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+        for block in &mut self.blocks {
+            block.terminator.map_successors(|target| renumbered[target].unwrap());
+        }
+        renumbered
+    }
+}
+
+/// First label no function in `irfuncs` uses, for passes that need to mint new ones. Labels
+/// have to be unique across the whole translation unit, not only within one function.
+pub fn first_free_label(irfuncs: &[IrFunction]) -> u32 {
+    irfuncs
+        .iter()
+        .flat_map(|function| &function.body)
+        .filter_map(|statement| match statement {
+            IrStatement::Label(label) => Some(label + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
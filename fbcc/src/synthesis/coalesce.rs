@@ -0,0 +1,253 @@
+//! Copy propagation, copy coalescing and stack slot sharing.
+//!
+//! Lowering computes every expression into a fresh temporary and then copies it into the
+//! variable, and never reuses a slot. [`propagate_copies`] reads the source of a copy
+//! instead of its destination where both still hold the same value. [`coalesce_slots`] then
+//! merges the two sides of a copy into one slot whenever their live ranges don't overlap, so
+//! the value is computed straight into the variable and the copy disappears, and finally
+//! lets any slots whose live ranges don't overlap share their place in the frame.
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::synthesis::{
+    cfg::Cfg,
+    ir::{IrStatement, Operand, Slot, SlotID},
+    liveness::Liveness,
+};
+
+/// Within each block, replaces reads of a copy's destination with its source until either
+/// is written again. The copies themselves are left for dead code elimination.
+pub fn propagate_copies(cfg: &mut Cfg) {
+    let volatile: Vec<bool> = cfg.slots.iter().map(|slot| slot.ty.qualifiers.is_volatile).collect();
+    let is_volatile = |operand: &Operand| matches!(operand, Operand::Var(var) if volatile[var.index()]);
+
+    for block in &mut cfg.blocks {
+        let mut available: HashMap<SlotID, Operand> = HashMap::new();
+        let propagate = |available: &HashMap<SlotID, Operand>, operand: &mut Operand| {
+            if let Operand::Var(var) = operand {
+                if let Some(value) = available.get(var) {
+                    *operand = value.clone();
+                }
+            }
+        };
+
+        for statement in &mut block.statements {
+            for operand in statement.operands_mut() {
+                propagate(&available, operand);
+            }
+
+            let Some(dst) = statement.dst().cloned() else {
+                continue;
+            };
+            available.retain(|var, value| *var != dst && *value != Operand::Var(dst.clone()));
+            if let IrStatement::Copy { src, .. } = statement {
+                if !volatile[dst.index()] && !is_volatile(src) && *src != Operand::Var(dst.clone()) {
+                    available.insert(dst, src.clone());
+                }
+            }
+        }
+
+        if let Some(operand) = block.terminator.operand_mut() {
+            propagate(&available, operand);
+        }
+    }
+}
+
+/// Merges slots that are never live at the same time and lays the frame out again with only
+/// the slots that are left. Returns the number of copies that became redundant and were
+/// removed.
+pub fn coalesce_slots(cfg: &mut Cfg) -> usize {
+    coalesce(cfg, true)
+}
+
+/// Like [`coalesce_slots`], but only merges the two sides of copies, so slots holding
+/// unrelated values stay apart for the passes that still need to tell them apart.
+pub fn coalesce_copies(cfg: &mut Cfg) -> usize {
+    coalesce(cfg, false)
+}
+
+fn coalesce(cfg: &mut Cfg, share: bool) -> usize {
+    let count = cfg.slots.len();
+    let mut interference = interference(cfg);
+    let mut slots = Slots {
+        representative: (0..count).collect(),
+        slots: &cfg.slots,
+    };
+
+    // 1. coalesce the two sides of each copy
+    for statement in cfg.blocks.iter().flat_map(|block| &block.statements) {
+        if let IrStatement::Copy {
+            dst,
+            src: Operand::Var(src),
+        } = statement
+        {
+            slots.try_merge(dst.index(), src.index(), &mut interference);
+        }
+    }
+
+    // 2. share places in the frame among the remaining slots, first fit in slot order, or
+    //    give each its own
+    let mut used = vec![false; count];
+    let references = cfg.blocks.iter().flat_map(|block| {
+        let statements = block.statements.iter();
+        let dsts = statements.clone().filter_map(|statement| statement.dst());
+        let operands = statements
+            .flat_map(|statement| statement.operands())
+            .chain(block.terminator.operand());
+        dsts.chain(operands.filter_map(|operand| match operand {
+            Operand::Var(var) => Some(var),
+            Operand::Const(_) => None,
+        }))
+    });
+    for slot in references.chain(&cfg.params) {
+        used[slot.index()] = true;
+    }
+
+    let mut places: Vec<usize> = Vec::new();
+    for slot in 0..count {
+        let slot = slots.find(slot);
+        if !used[slot] || places.contains(&slot) {
+            continue;
+        }
+        if !share
+            || !places
+                .iter()
+                .any(|place| slots.try_merge(*place, slot, &mut interference))
+        {
+            places.push(slot);
+        }
+    }
+
+    // 3. lay out the frame with one slot per place and rename everything
+    let mut renamed = vec![None; count];
+    let mut layout = Vec::with_capacity(places.len());
+    let mut offset: usize = 0;
+    for (index, place) in places.iter().enumerate() {
+        let template = &cfg.slots[*place];
+        offset = offset.next_multiple_of(template.align);
+        layout.push(Slot {
+            offset,
+            ..template.clone()
+        });
+        offset += template.size;
+        renamed[*place] = Some(index);
+    }
+    let rename = |slot: &mut SlotID| *slot = SlotID::new(renamed[slots.find(slot.index())].unwrap());
+
+    let mut removed = 0;
+    for block in &mut cfg.blocks {
+        for statement in &mut block.statements {
+            if let Some(dst) = statement.dst_mut() {
+                rename(dst);
+            }
+            for operand in statement.operands_mut() {
+                if let Operand::Var(var) = operand {
+                    rename(var);
+                }
+            }
+        }
+        if let Some(Operand::Var(var)) = block.terminator.operand_mut() {
+            rename(var);
+        }
+
+        let before = block.statements.len();
+        block
+            .statements
+            .retain(|statement| !matches!(statement, IrStatement::Copy { dst, src: Operand::Var(src) } if dst == src));
+        removed += before - block.statements.len();
+    }
+    cfg.params.iter_mut().for_each(rename);
+
+    cfg.slots = layout;
+    cfg.framesize = (offset + 16).next_multiple_of(16);
+    removed
+}
+
+/// Union-find over slots; every set is represented by one of its slots.
+struct Slots<'a> {
+    representative: Vec<usize>,
+    slots: &'a [Slot],
+}
+
+impl Slots<'_> {
+    fn find(&self, mut slot: usize) -> usize {
+        while self.representative[slot] != slot {
+            slot = self.representative[slot];
+        }
+        slot
+    }
+
+    /// Merges the sets of `a` and `b` unless they interfere or can't share a slot; the
+    /// merged set interferes with everything either did.
+    fn try_merge(&mut self, a: usize, b: usize, interference: &mut [HashSet<usize>]) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        let (slot_a, slot_b) = (&self.slots[a], &self.slots[b]);
+        if a == b
+            || interference[a].contains(&b)
+            || slot_a.size != slot_b.size
+            || slot_a.align != slot_b.align
+            || slot_a.ty.qualifiers.is_volatile
+            || slot_b.ty.qualifiers.is_volatile
+        {
+            return a == b;
+        }
+
+        self.representative[b] = a;
+        let neighbours = mem::take(&mut interference[b]);
+        for neighbour in &neighbours {
+            interference[*neighbour].remove(&b);
+            interference[*neighbour].insert(a);
+        }
+        interference[a].extend(neighbours);
+        true
+    }
+}
+
+/// Which slots are live at the same time, as the set of slots each one interferes with,
+/// since few are live at once even in functions with thousands of slots. A write interferes
+/// with every other slot live after it, except that a copy doesn't interfere with its source,
+/// since both hold the same value. The parameters are written on entry to the function.
+fn interference(cfg: &Cfg) -> Vec<HashSet<usize>> {
+    let liveness = Liveness::new(cfg);
+    let count = cfg.slots.len();
+    let mut interference = vec![HashSet::new(); count];
+    let mut interfere = |written: usize, live: &[bool], except: Option<usize>| {
+        for other in (0..count).filter(|other| live[*other] && *other != written && Some(*other) != except) {
+            interference[written].insert(other);
+            interference[other].insert(written);
+        }
+    };
+
+    for (block, data) in cfg.blocks.iter().enumerate() {
+        let mut live = liveness.live_out[block].clone();
+        if let Some(Operand::Var(var)) = data.terminator.operand() {
+            live[var.index()] = true;
+        }
+
+        for statement in data.statements.iter().rev() {
+            if let Some(dst) = statement.dst() {
+                let except = match statement {
+                    IrStatement::Copy {
+                        src: Operand::Var(src), ..
+                    } => Some(src.index()),
+                    _ => None,
+                };
+                interfere(dst.index(), &live, except);
+                live[dst.index()] = false;
+            }
+            for operand in statement.operands() {
+                if let Operand::Var(var) = operand {
+                    live[var.index()] = true;
+                }
+            }
+        }
+
+        if block == Cfg::ENTRY {
+            for param in &cfg.params {
+                interfere(param.index(), &live, None);
+            }
+        }
+    }
+    interference
+}
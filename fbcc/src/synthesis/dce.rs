@@ -0,0 +1,79 @@
+//! Dead code elimination.
+//!
+//! Deletes the blocks that can't be reached, and every statement that only computes a value
+//! nobody reads: a write to a slot that isn't live afterwards. Calls stay, since they may
+//! have side effects, but forget their unused result. Accesses to `volatile` slots stay too.
+
+use crate::synthesis::{
+    cfg::Cfg,
+    ir::{IrStatement, Operand},
+    liveness::Liveness,
+};
+
+/// Returns the number of statements removed, counting those in unreachable blocks.
+pub fn eliminate_dead_code(cfg: &mut Cfg) -> usize {
+    let before: usize = cfg.blocks.iter().map(|block| block.size()).sum();
+    cfg.remove_unreachable();
+
+    let volatile: Vec<bool> = cfg.slots.iter().map(|slot| slot.ty.qualifiers.is_volatile).collect();
+    let touches_volatile = |statement: &IrStatement| {
+        statement.dst().is_some_and(|dst| volatile[dst.index()])
+            || statement
+                .operands()
+                .iter()
+                .any(|operand| matches!(operand, Operand::Var(var) if volatile[var.index()]))
+    };
+
+    // removing a statement can end the liveness of the slots it read, in this block and in
+    // its predecessors, so repeat until nothing changes
+    loop {
+        let liveness = Liveness::new(cfg);
+        let mut removed = false;
+        for (block, live_out) in liveness.live_out.into_iter().enumerate() {
+            let data = &mut cfg.blocks[block];
+            let mut live = live_out;
+            if let Some(Operand::Var(var)) = data.terminator.operand() {
+                live[var.index()] = true;
+            }
+
+            let mut kept = Vec::with_capacity(data.statements.len());
+            for mut statement in std::mem::take(&mut data.statements).into_iter().rev() {
+                let dead = match &statement {
+                    IrStatement::Copy {
+                        dst,
+                        src: Operand::Var(src),
+                    } if dst == src => true,
+                    statement => statement.dst().is_some_and(|dst| !live[dst.index()]),
+                };
+
+                if dead && !touches_volatile(&statement) {
+                    match &mut statement {
+                        IrStatement::Call { dst, .. } => *dst = None,
+                        _ => {
+                            removed = true;
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some(dst) = statement.dst() {
+                    live[dst.index()] = false;
+                }
+                for operand in statement.operands() {
+                    if let Operand::Var(var) = operand {
+                        live[var.index()] = true;
+                    }
+                }
+                kept.push(statement);
+            }
+            kept.reverse();
+            data.statements = kept;
+        }
+
+        if !removed {
+            break;
+        }
+    }
+
+    before - cfg.blocks.iter().map(|block| block.size()).sum::<usize>()
+}
@@ -0,0 +1,132 @@
+//! Dominator tree of a [`Cfg`].
+//!
+//! Block `a` dominates block `b` when every path from the entry to `b` passes through `a`.
+//! The immediate dominators are computed with the iterative algorithm from Cooper, Harvey
+//! and Kennedy, "A Simple, Fast Dominance Algorithm".
+
+use crate::synthesis::cfg::{BlockId, Cfg};
+
+pub struct DominatorTree {
+    /// Immediate dominator of every reachable block; the entry is its own
+    idom: Vec<Option<BlockId>>,
+    /// Position of every reachable block in reverse postorder
+    rpo_number: Vec<Option<usize>>,
+    /// Reachable blocks in reverse postorder
+    rpo: Vec<BlockId>,
+    children: Vec<Vec<BlockId>>,
+}
+
+impl DominatorTree {
+    pub fn new(cfg: &Cfg) -> Self {
+        let rpo = cfg.reverse_postorder();
+        let preds = cfg.predecessors();
+
+        let mut rpo_number = vec![None; cfg.blocks.len()];
+        for (number, &block) in rpo.iter().enumerate() {
+            rpo_number[block] = Some(number);
+        }
+
+        let mut idom: Vec<Option<BlockId>> = vec![None; cfg.blocks.len()];
+        idom[Cfg::ENTRY] = Some(Cfg::ENTRY);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in rpo.iter().skip(1) {
+                // unreachable predecessors never get an idom and are skipped
+                let mut processed = preds[block].iter().copied().filter(|pred| idom[*pred].is_some());
+                let mut new_idom = processed.next().unwrap();
+                for pred in processed {
+                    let (mut a, mut b) = (pred, new_idom);
+                    while a != b {
+                        while rpo_number[a] > rpo_number[b] {
+                            a = idom[a].unwrap();
+                        }
+                        while rpo_number[b] > rpo_number[a] {
+                            b = idom[b].unwrap();
+                        }
+                    }
+                    new_idom = a;
+                }
+                if idom[block] != Some(new_idom) {
+                    idom[block] = Some(new_idom);
+                    changed = true;
+                }
+            }
+        }
+
+        let mut children = vec![Vec::new(); cfg.blocks.len()];
+        for &block in rpo.iter().skip(1) {
+            children[idom[block].unwrap()].push(block);
+        }
+
+        DominatorTree {
+            idom,
+            rpo_number,
+            rpo,
+            children,
+        }
+    }
+
+    /// Immediate dominator of `block`; `None` for the entry and for unreachable blocks.
+    pub fn idom(&self, block: BlockId) -> Option<BlockId> {
+        self.idom[block].filter(|idom| *idom != block)
+    }
+
+    /// Blocks `block` immediately dominates, in reverse postorder.
+    pub fn children(&self, block: BlockId) -> &[BlockId] {
+        &self.children[block]
+    }
+
+    pub fn is_reachable(&self, block: BlockId) -> bool {
+        self.idom[block].is_some()
+    }
+
+    /// Whether `a` dominates `b`; every block dominates itself.
+    pub fn dominates(&self, a: BlockId, mut b: BlockId) -> bool {
+        if !self.is_reachable(a) || !self.is_reachable(b) {
+            return false;
+        }
+        while self.rpo_number[b] > self.rpo_number[a] {
+            b = self.idom[b].unwrap();
+        }
+        a == b
+    }
+
+    /// Reachable blocks in reverse postorder, which visits dominators before the blocks
+    /// they dominate.
+    pub fn reverse_postorder(&self) -> &[BlockId] {
+        &self.rpo
+    }
+
+    /// Position of `block` in reverse postorder, `None` if it's unreachable.
+    pub fn rpo_number(&self, block: BlockId) -> Option<usize> {
+        self.rpo_number[block]
+    }
+
+    /// Dominance frontier of every block: the blocks where its dominance ends, i.e. that
+    /// have a predecessor it dominates without strictly dominating them.
+    pub fn dominance_frontiers(&self, cfg: &Cfg) -> Vec<Vec<BlockId>> {
+        let preds = cfg.predecessors();
+        let mut frontiers: Vec<Vec<BlockId>> = vec![Vec::new(); cfg.blocks.len()];
+        for &block in &self.rpo {
+            if preds[block].len() < 2 {
+                continue;
+            }
+            // walk up from every predecessor until reaching the block's immediate dominator;
+            // everything passed on the way has the block in its frontier
+            for &pred in preds[block].iter().filter(|pred| self.is_reachable(**pred)) {
+                let mut runner = pred;
+                while Some(runner) != self.idom(block) {
+                    if frontiers[runner].last() != Some(&block) {
+                        frontiers[runner].push(block);
+                    }
+                    match self.idom(runner) {
+                        Some(idom) => runner = idom,
+                        None => break,
+                    }
+                }
+            }
+        }
+        frontiers
+    }
+}
@@ -0,0 +1,378 @@
+//! DWARF 4 debug information for `-g`, written as assembler directives so the same code
+//! serves every assembly backend that supports it.
+//!
+//! The line table comes from the `.file` and `.loc` directives the backends put in front of
+//! each statement, and the call frame information from their `.cfi_*` directives; the
+//! assembler builds both. This module adds the `.debug_abbrev` and `.debug_info` sections,
+//! which describe each function with its parameters and local variables.
+//!
+//! Every function uses the canonical frame address (the value of sp in the caller) as its
+//! frame base. Both ARM64 and x86-64 frames end exactly there, so a slot at `offset` above
+//! sp lies `framesize - offset` bytes below the frame base.
+
+use std::fmt::Write;
+
+use crate::{
+    analysis::node::Location,
+    core::{
+        target::ObjectFormat,
+        typedefs::{DataType, IntegerRank, Type},
+    },
+    synthesis::ir::IrFunction,
+};
+
+/// The source file being compiled, as the debugger should find it.
+#[derive(Debug, Clone)]
+pub struct DebugInfo {
+    /// Path of the source file, as given on the command line
+    pub file: String,
+    /// Directory the compiler ran in, which a relative `file` is relative to
+    pub directory: String,
+}
+
+impl DebugInfo {
+    /// The `.file` directive that makes `file` file number 1 of the line table.
+    pub fn file_directive(&self) -> String {
+        format!(".file\t1 {}", quote(&self.file))
+    }
+}
+
+/// The `.loc` directive attributing the instructions that follow to `location`.
+pub fn loc_directive(location: &Location) -> String {
+    format!(".loc\t1 {} {}", location.line, location.column)
+}
+
+/// Label at the first instruction of the `index`th function.
+pub fn function_begin(format: ObjectFormat, index: usize) -> String {
+    format!("{}func_begin{index}", private_prefix(format))
+}
+
+/// Label right after the last instruction of the `index`th function.
+pub fn function_end(format: ObjectFormat, index: usize) -> String {
+    format!("{}func_end{index}", private_prefix(format))
+}
+
+// Abbreviation codes, tags, attributes and forms
+const ABBREV_COMPILE_UNIT: u8 = 1;
+const ABBREV_BASE_TYPE: u8 = 2;
+const ABBREV_SUBPROGRAM: u8 = 3;
+const ABBREV_SUBPROGRAM_UNTYPED: u8 = 4;
+const ABBREV_FORMAL_PARAMETER: u8 = 5;
+const ABBREV_VARIABLE: u8 = 6;
+
+const DW_TAG_FORMAL_PARAMETER: u8 = 0x05;
+const DW_TAG_COMPILE_UNIT: u8 = 0x11;
+const DW_TAG_BASE_TYPE: u8 = 0x24;
+const DW_TAG_SUBPROGRAM: u8 = 0x2e;
+const DW_TAG_VARIABLE: u8 = 0x34;
+
+const DW_AT_LOCATION: u8 = 0x02;
+const DW_AT_NAME: u8 = 0x03;
+const DW_AT_BYTE_SIZE: u8 = 0x0b;
+const DW_AT_STMT_LIST: u8 = 0x10;
+const DW_AT_LOW_PC: u8 = 0x11;
+const DW_AT_HIGH_PC: u8 = 0x12;
+const DW_AT_LANGUAGE: u8 = 0x13;
+const DW_AT_COMP_DIR: u8 = 0x1b;
+const DW_AT_PRODUCER: u8 = 0x25;
+const DW_AT_DECL_FILE: u8 = 0x3a;
+const DW_AT_DECL_LINE: u8 = 0x3b;
+const DW_AT_ENCODING: u8 = 0x3e;
+const DW_AT_EXTERNAL: u8 = 0x3f;
+const DW_AT_FRAME_BASE: u8 = 0x40;
+const DW_AT_TYPE: u8 = 0x49;
+
+const DW_FORM_ADDR: u8 = 0x01;
+const DW_FORM_DATA2: u8 = 0x05;
+const DW_FORM_DATA4: u8 = 0x06;
+const DW_FORM_STRING: u8 = 0x08;
+const DW_FORM_DATA1: u8 = 0x0b;
+const DW_FORM_REF4: u8 = 0x13;
+const DW_FORM_SEC_OFFSET: u8 = 0x17;
+const DW_FORM_EXPRLOC: u8 = 0x18;
+const DW_FORM_FLAG_PRESENT: u8 = 0x19;
+
+const DW_LANG_C99: u16 = 0x0c;
+
+const DW_ATE_BOOLEAN: u8 = 0x02;
+const DW_ATE_SIGNED: u8 = 0x05;
+const DW_ATE_SIGNED_CHAR: u8 = 0x06;
+const DW_ATE_UNSIGNED: u8 = 0x08;
+const DW_ATE_UNSIGNED_CHAR: u8 = 0x08;
+
+const DW_OP_FBREG: u8 = 0x91;
+const DW_OP_CALL_FRAME_CFA: u8 = 0x9c;
+
+/// Appends the `.debug_abbrev`, `.debug_info` and `.debug_line` sections describing
+/// `functions` to `asm`. Must come after the code, which has to define the labels of
+/// [`function_begin`] and [`function_end`] for every function.
+pub fn emit_sections(asm: &mut String, debug: &DebugInfo, format: ObjectFormat, functions: &[IrFunction]) {
+    if functions.is_empty() {
+        return;
+    }
+    let label = |name: &str| format!("{}{name}", private_prefix(format));
+    let section = |name: &str| match format {
+        ObjectFormat::MachO => format!(".section\t__DWARF,__{name},regular,debug"),
+        ObjectFormat::Elf | ObjectFormat::Wasm => format!(".section\t.{name},\"\",%progbits"),
+    };
+    // Offsets into other debug sections: ELF relocates them when linking, Mach-O leaves
+    // the debug sections in the object files and only wants the offset
+    let offset = |target: &str, start: &str| match format {
+        ObjectFormat::MachO => format!("{}-{}", label(target), label(start)),
+        ObjectFormat::Elf | ObjectFormat::Wasm => label(target),
+    };
+
+    let mut out = Writer::default();
+
+    out.directive(section("debug_abbrev"));
+    out.label(label("section_abbrev"));
+    emit_abbreviations(&mut out);
+
+    out.directive(section("debug_info"));
+    out.label(label("section_info"));
+    // type references are offsets from the start of the unit's header
+    let unit = label("cu_begin0");
+    out.label(unit.clone());
+    out.directive(format!(
+        ".long\t{}-{}",
+        label("debug_info_end0"),
+        label("debug_info_start0")
+    ));
+    out.label(label("debug_info_start0"));
+    out.directive(".short\t4");
+    out.directive(format!(".long\t{}", offset("section_abbrev", "section_abbrev")));
+    out.byte(8);
+
+    // the compile unit
+    let (first, last) = (function_begin(format, 0), function_end(format, functions.len() - 1));
+    out.uleb(ABBREV_COMPILE_UNIT as u64);
+    out.string(concat!("fbcc ", env!("CARGO_PKG_VERSION")));
+    out.directive(format!(".short\t{DW_LANG_C99}"));
+    out.string(&debug.file);
+    out.directive(format!(".long\t{}", offset("line_table_start0", "section_line")));
+    out.string(&debug.directory);
+    out.directive(format!(".quad\t{first}"));
+    out.directive(format!(".long\t{last}-{first}"));
+
+    // the base types, emitted after the functions that refer to them
+    let mut types: Vec<(&'static str, u8, usize)> = Vec::new();
+    let mut type_ref = |ty: &Type, size: usize| {
+        let (name, encoding) = base_type(ty)?;
+        let index = match types.iter().position(|(other, ..)| *other == name) {
+            Some(index) => index,
+            None => {
+                types.push((name, encoding, size));
+                types.len() - 1
+            }
+        };
+        Some(format!(".long\t{}-{}", label(&format!("type{index}")), unit))
+    };
+
+    for (index, function) in functions.iter().enumerate() {
+        let return_type = function
+            .source
+            .return_type
+            .as_ref()
+            .and_then(|(ty, size)| type_ref(ty, *size));
+        let (begin, end) = (function_begin(format, index), function_end(format, index));
+        out.uleb(if return_type.is_some() {
+            ABBREV_SUBPROGRAM
+        } else {
+            ABBREV_SUBPROGRAM_UNTYPED
+        } as u64);
+        out.directive(format!(".quad\t{begin}"));
+        out.directive(format!(".long\t{end}-{begin}"));
+        out.uleb(1);
+        out.byte(DW_OP_CALL_FRAME_CFA);
+        out.string(&function.name);
+        out.byte(1);
+        out.directive(format!(".long\t{}", function.source.line));
+        if let Some(return_type) = return_type {
+            out.directive(return_type);
+        }
+
+        for (slot_index, slot) in function.slots.iter().enumerate() {
+            let Some(name) = &slot.name else {
+                continue;
+            };
+            // only integer variables for now; the others are left out
+            let Some(ty) = type_ref(&slot.ty, slot.size) else {
+                continue;
+            };
+            let is_param = function.params.iter().any(|param| param.index() == slot_index);
+            out.uleb(if is_param {
+                ABBREV_FORMAL_PARAMETER
+            } else {
+                ABBREV_VARIABLE
+            } as u64);
+            out.string(name);
+            out.directive(ty);
+            let mut location = vec![DW_OP_FBREG];
+            sleb(&mut location, slot.offset as i64 - function.framesize as i64);
+            out.uleb(location.len() as u64);
+            for byte in location {
+                out.byte(byte);
+            }
+        }
+        // end of the subprogram's children
+        out.byte(0);
+    }
+
+    for (index, (name, encoding, size)) in types.iter().enumerate() {
+        out.label(label(&format!("type{index}")));
+        out.uleb(ABBREV_BASE_TYPE as u64);
+        out.string(name);
+        out.byte(*encoding);
+        out.byte(*size as u8);
+    }
+
+    // end of the compile unit's children
+    out.byte(0);
+    out.label(label("debug_info_end0"));
+
+    // the assembler appends the line table the `.loc` directives describe
+    out.directive(section("debug_line"));
+    out.label(label("section_line"));
+    out.label(label("line_table_start0"));
+
+    asm.push_str(&out.0);
+}
+
+fn emit_abbreviations(out: &mut Writer) {
+    let abbreviation = |out: &mut Writer, code: u8, tag: u8, children: bool, attributes: &[(u8, u8)]| {
+        out.uleb(code as u64);
+        out.uleb(tag as u64);
+        out.byte(children as u8);
+        for &(attribute, form) in attributes {
+            out.uleb(attribute as u64);
+            out.uleb(form as u64);
+        }
+        out.byte(0);
+        out.byte(0);
+    };
+    abbreviation(
+        out,
+        ABBREV_COMPILE_UNIT,
+        DW_TAG_COMPILE_UNIT,
+        true,
+        &[
+            (DW_AT_PRODUCER, DW_FORM_STRING),
+            (DW_AT_LANGUAGE, DW_FORM_DATA2),
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_STMT_LIST, DW_FORM_SEC_OFFSET),
+            (DW_AT_COMP_DIR, DW_FORM_STRING),
+            (DW_AT_LOW_PC, DW_FORM_ADDR),
+            (DW_AT_HIGH_PC, DW_FORM_DATA4),
+        ],
+    );
+    abbreviation(
+        out,
+        ABBREV_BASE_TYPE,
+        DW_TAG_BASE_TYPE,
+        false,
+        &[
+            (DW_AT_NAME, DW_FORM_STRING),
+            (DW_AT_ENCODING, DW_FORM_DATA1),
+            (DW_AT_BYTE_SIZE, DW_FORM_DATA1),
+        ],
+    );
+    let subprogram = [
+        (DW_AT_LOW_PC, DW_FORM_ADDR),
+        (DW_AT_HIGH_PC, DW_FORM_DATA4),
+        (DW_AT_FRAME_BASE, DW_FORM_EXPRLOC),
+        (DW_AT_NAME, DW_FORM_STRING),
+        (DW_AT_DECL_FILE, DW_FORM_DATA1),
+        (DW_AT_DECL_LINE, DW_FORM_DATA4),
+        (DW_AT_EXTERNAL, DW_FORM_FLAG_PRESENT),
+        (DW_AT_TYPE, DW_FORM_REF4),
+    ];
+    abbreviation(out, ABBREV_SUBPROGRAM, DW_TAG_SUBPROGRAM, true, &subprogram);
+    abbreviation(
+        out,
+        ABBREV_SUBPROGRAM_UNTYPED,
+        DW_TAG_SUBPROGRAM,
+        true,
+        &subprogram[..subprogram.len() - 1],
+    );
+    let variable = [
+        (DW_AT_NAME, DW_FORM_STRING),
+        (DW_AT_TYPE, DW_FORM_REF4),
+        (DW_AT_LOCATION, DW_FORM_EXPRLOC),
+    ];
+    abbreviation(out, ABBREV_FORMAL_PARAMETER, DW_TAG_FORMAL_PARAMETER, false, &variable);
+    abbreviation(out, ABBREV_VARIABLE, DW_TAG_VARIABLE, false, &variable);
+    // end of the abbreviations
+    out.byte(0);
+}
+
+/// The name and encoding of an integer type, the only ones described so far.
+fn base_type(ty: &Type) -> Option<(&'static str, u8)> {
+    let DataType::Integer { rank, signed } = &ty.datatype else {
+        return None;
+    };
+    Some(match (rank, signed) {
+        (IntegerRank::Bool, _) => ("_Bool", DW_ATE_BOOLEAN),
+        (IntegerRank::Char, true) => ("char", DW_ATE_SIGNED_CHAR),
+        (IntegerRank::Char, false) => ("unsigned char", DW_ATE_UNSIGNED_CHAR),
+        (IntegerRank::Short, true) => ("short", DW_ATE_SIGNED),
+        (IntegerRank::Short, false) => ("unsigned short", DW_ATE_UNSIGNED),
+        (IntegerRank::Int, true) => ("int", DW_ATE_SIGNED),
+        (IntegerRank::Int, false) => ("unsigned int", DW_ATE_UNSIGNED),
+        (IntegerRank::Long, true) => ("long", DW_ATE_SIGNED),
+        (IntegerRank::Long, false) => ("unsigned long", DW_ATE_UNSIGNED),
+        (IntegerRank::LongLong, true) => ("long long", DW_ATE_SIGNED),
+        (IntegerRank::LongLong, false) => ("unsigned long long", DW_ATE_UNSIGNED),
+    })
+}
+
+/// Prefix of labels the assembler keeps out of the symbol table.
+fn private_prefix(format: ObjectFormat) -> &'static str {
+    match format {
+        ObjectFormat::MachO => "L",
+        ObjectFormat::Elf | ObjectFormat::Wasm => ".L",
+    }
+}
+
+/// `s` as a string literal for the assembler.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Appends `value` in signed LEB128.
+fn sleb(bytes: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Collects the lines of the debug sections.
+#[derive(Default)]
+struct Writer(String);
+
+impl Writer {
+    fn directive(&mut self, directive: impl AsRef<str>) {
+        writeln!(self.0, "\t{}", directive.as_ref()).unwrap();
+    }
+
+    fn label(&mut self, label: String) {
+        writeln!(self.0, "{label}:").unwrap();
+    }
+
+    fn byte(&mut self, byte: u8) {
+        self.directive(format!(".byte\t{byte}"));
+    }
+
+    fn uleb(&mut self, value: u64) {
+        self.directive(format!(".uleb128\t{value}"));
+    }
+
+    fn string(&mut self, s: &str) {
+        self.directive(format!(".asciz\t{}", quote(s)));
+    }
+}
@@ -0,0 +1,249 @@
+//! Writes an [`Object`] as a 64-bit little-endian ELF relocatable file: the code in `.text`,
+//! its relocations in `.rela.text`, the functions in `.symtab`, and an empty
+//! `.note.GNU-stack` so the linker keeps the stack non-executable.
+
+use crate::synthesis::object::{Architecture, Object, RelocationKind};
+
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_NOTYPE: u8 = 0;
+const STT_FUNC: u8 = 2;
+
+const R_X86_64_PLT32: u32 = 4;
+const R_AARCH64_JUMP26: u32 = 282;
+const R_AARCH64_CALL26: u32 = 283;
+
+const HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: usize = 64;
+const SYMBOL_SIZE: usize = 24;
+const RELA_SIZE: usize = 24;
+
+// section indices
+const TEXT: u16 = 1;
+const SYMTAB: u32 = 3;
+const STRTAB: u32 = 4;
+const SHSTRTAB: u32 = 5;
+const SECTIONS: usize = 7;
+
+/// The bytes of the object file.
+pub fn write(object: &Object) -> Vec<u8> {
+    // 1. the symbol table: the null symbol, the local functions, then the global ones
+    //    and the undefined ones, since ELF wants locals first
+    let mut strtab = vec![0u8];
+    let mut symtab = vec![0u8; SYMBOL_SIZE];
+    let mut indices = Vec::new();
+    let locals = object.symbols.iter().filter(|symbol| !symbol.global);
+    let globals = object.symbols.iter().filter(|symbol| symbol.global);
+    let mut first_global = 1;
+    for symbol in locals.chain(globals) {
+        let binding = if symbol.global { STB_GLOBAL } else { STB_LOCAL };
+        if !symbol.global {
+            first_global += 1;
+        }
+        let name = string(&mut strtab, &symbol.name);
+        push_symbol(
+            &mut symtab,
+            name,
+            binding << 4 | STT_FUNC,
+            TEXT,
+            symbol.offset,
+            symbol.size,
+        );
+        indices.push(symbol.name.as_str());
+    }
+    for name in object.undefined_symbols() {
+        let offset = string(&mut strtab, name);
+        push_symbol(&mut symtab, offset, STB_GLOBAL << 4 | STT_NOTYPE, 0, 0, 0);
+        indices.push(name);
+    }
+
+    // 2. the relocations
+    let mut rela = Vec::with_capacity(object.relocations.len() * RELA_SIZE);
+    for relocation in &object.relocations {
+        let symbol = indices.iter().position(|name| *name == relocation.symbol).unwrap() as u64 + 1;
+        let (kind, addend) = match relocation.kind {
+            RelocationKind::Arm64Call => (R_AARCH64_CALL26, 0),
+            RelocationKind::Arm64Jump => (R_AARCH64_JUMP26, 0),
+            // the displacement is relative to the end of the instruction, 4 bytes later
+            RelocationKind::X86_64Call => (R_X86_64_PLT32, -4i64),
+        };
+        rela.extend(relocation.offset.to_le_bytes());
+        rela.extend((symbol << 32 | kind as u64).to_le_bytes());
+        rela.extend(addend.to_le_bytes());
+    }
+
+    let mut shstrtab = vec![0u8];
+    let names = [
+        ".text",
+        ".rela.text",
+        ".symtab",
+        ".strtab",
+        ".shstrtab",
+        ".note.GNU-stack",
+    ]
+    .map(|name| string(&mut shstrtab, name));
+
+    // 3. the layout: header, section contents, section headers
+    let (machine, text_align) = match object.architecture {
+        Architecture::Arm64 => (EM_AARCH64, 4),
+        Architecture::X86_64 => (EM_X86_64, 16),
+    };
+    let mut file = vec![0u8; HEADER_SIZE];
+    let text_offset = append(&mut file, &object.text, 16);
+    let rela_offset = append(&mut file, &rela, 8);
+    let symtab_offset = append(&mut file, &symtab, 8);
+    let strtab_offset = append(&mut file, &strtab, 1);
+    let shstrtab_offset = append(&mut file, &shstrtab, 1);
+    let headers_offset = file.len().next_multiple_of(8);
+    file.resize(headers_offset, 0);
+
+    file.extend([0u8; SECTION_HEADER_SIZE]);
+    let sections = [
+        SectionHeader {
+            name: names[0],
+            kind: SHT_PROGBITS,
+            flags: SHF_ALLOC | SHF_EXECINSTR,
+            offset: text_offset,
+            size: object.text.len(),
+            align: text_align,
+            ..Default::default()
+        },
+        SectionHeader {
+            name: names[1],
+            kind: SHT_RELA,
+            flags: SHF_INFO_LINK,
+            offset: rela_offset,
+            size: rela.len(),
+            link: SYMTAB,
+            info: TEXT as u32,
+            align: 8,
+            entsize: RELA_SIZE,
+        },
+        SectionHeader {
+            name: names[2],
+            kind: SHT_SYMTAB,
+            offset: symtab_offset,
+            size: symtab.len(),
+            link: STRTAB,
+            info: first_global,
+            align: 8,
+            entsize: SYMBOL_SIZE,
+            ..Default::default()
+        },
+        SectionHeader {
+            name: names[3],
+            kind: SHT_STRTAB,
+            offset: strtab_offset,
+            size: strtab.len(),
+            align: 1,
+            ..Default::default()
+        },
+        SectionHeader {
+            name: names[4],
+            kind: SHT_STRTAB,
+            offset: shstrtab_offset,
+            size: shstrtab.len(),
+            align: 1,
+            ..Default::default()
+        },
+        SectionHeader {
+            name: names[5],
+            kind: SHT_PROGBITS,
+            offset: shstrtab_offset,
+            align: 1,
+            ..Default::default()
+        },
+    ];
+    for section in sections {
+        section.write(&mut file);
+    }
+
+    // 4. the file header
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend(b"\x7fELF");
+    // 64-bit, little endian, version 1, System V ABI
+    header.extend([2, 1, 1, 0]);
+    header.extend([0u8; 8]);
+    header.extend(1u16.to_le_bytes()); // ET_REL
+    header.extend(machine.to_le_bytes());
+    header.extend(1u32.to_le_bytes());
+    header.extend(0u64.to_le_bytes()); // entry
+    header.extend(0u64.to_le_bytes()); // program headers
+    header.extend((headers_offset as u64).to_le_bytes());
+    header.extend(0u32.to_le_bytes()); // flags
+    header.extend((HEADER_SIZE as u16).to_le_bytes());
+    header.extend(0u16.to_le_bytes());
+    header.extend(0u16.to_le_bytes());
+    header.extend((SECTION_HEADER_SIZE as u16).to_le_bytes());
+    header.extend((SECTIONS as u16).to_le_bytes());
+    header.extend((SHSTRTAB as u16).to_le_bytes());
+    file[..HEADER_SIZE].copy_from_slice(&header);
+
+    file
+}
+
+#[derive(Default)]
+struct SectionHeader {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    offset: usize,
+    size: usize,
+    link: u32,
+    info: u32,
+    align: usize,
+    entsize: usize,
+}
+
+impl SectionHeader {
+    fn write(&self, file: &mut Vec<u8>) {
+        file.extend(self.name.to_le_bytes());
+        file.extend(self.kind.to_le_bytes());
+        file.extend(self.flags.to_le_bytes());
+        // address, zero until linked
+        file.extend(0u64.to_le_bytes());
+        file.extend((self.offset as u64).to_le_bytes());
+        file.extend((self.size as u64).to_le_bytes());
+        file.extend(self.link.to_le_bytes());
+        file.extend(self.info.to_le_bytes());
+        file.extend((self.align as u64).to_le_bytes());
+        file.extend((self.entsize as u64).to_le_bytes());
+    }
+}
+
+fn push_symbol(symtab: &mut Vec<u8>, name: u32, info: u8, section: u16, value: u64, size: u64) {
+    symtab.extend(name.to_le_bytes());
+    symtab.push(info);
+    symtab.push(0);
+    symtab.extend(section.to_le_bytes());
+    symtab.extend(value.to_le_bytes());
+    symtab.extend(size.to_le_bytes());
+}
+
+/// Adds `s` to a string table and returns its offset.
+fn string(table: &mut Vec<u8>, s: &str) -> u32 {
+    let offset = table.len() as u32;
+    table.extend(s.as_bytes());
+    table.push(0);
+    offset
+}
+
+/// Appends `bytes` at the next multiple of `align` and returns their offset.
+fn append(file: &mut Vec<u8>, bytes: &[u8], align: usize) -> usize {
+    let offset = file.len().next_multiple_of(align);
+    file.resize(offset, 0);
+    file.extend(bytes);
+    offset
+}
@@ -0,0 +1,272 @@
+//! Function inlining.
+//!
+//! Replaces calls to small functions defined in the same translation unit with a copy of the
+//! callee's body: the arguments are copied into fresh slots standing in for its parameters,
+//! its labels are renamed, and each `ret` becomes a copy into the call's destination and a
+//! jump past the end of the copy. Functions are visited callees first, so a body has already
+//! had its own calls inlined by the time it is copied into its callers. Functions that take
+//! part in recursion are never inlined.
+
+use std::collections::HashMap;
+
+use crate::{
+    core::errors::{Diagnostic, VecExtensionDiagnosticHelpers},
+    synthesis::{
+        cfg::first_free_label,
+        ir::{InlineHint, IrFunction, IrStatement, Operand, Slot, SlotID},
+    },
+};
+
+/// Largest callee, in statements, inlined without a hint.
+const THRESHOLD: usize = 12;
+
+/// Largest callee, in statements, inlined when it is declared `inline`.
+const INLINE_THRESHOLD: usize = 48;
+
+/// A caller stops growing once it reaches this many statements, except through
+/// `always_inline` callees.
+const CALLER_LIMIT: usize = 2000;
+
+/// Inlines calls across the whole translation unit and returns how many were inlined. Every
+/// call inlined is reported as a remark.
+pub fn inline_functions(functions: &mut [IrFunction], diagnostics: &mut Vec<Diagnostic>) -> usize {
+    let index: HashMap<String, usize> = functions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| (function.name.clone(), index))
+        .collect();
+    let callees: Vec<Vec<usize>> = functions
+        .iter()
+        .map(|function| {
+            function
+                .body
+                .iter()
+                .filter_map(|statement| match statement {
+                    IrStatement::Call { name, .. } => index.get(name).copied(),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect();
+
+    let components = strongly_connected_components(&callees);
+    let mut recursive = vec![false; functions.len()];
+    for component in &components {
+        for &function in component {
+            recursive[function] = component.len() > 1 || callees[function].contains(&function);
+            if recursive[function] && functions[function].inline == InlineHint::Always {
+                diagnostics.warning(
+                    format!(
+                        "`{}` is marked always_inline but is recursive, so calls to it are not inlined",
+                        functions[function].name
+                    ),
+                    None,
+                );
+            }
+        }
+    }
+
+    let mut next_label = first_free_label(functions);
+    let mut inlined = 0;
+    for &caller in components.iter().flatten() {
+        let mut body = Vec::with_capacity(functions[caller].body.len());
+        let mut size = cost(&functions[caller]);
+        for statement in std::mem::take(&mut functions[caller].body) {
+            let IrStatement::Call { dst, name, args, tail } = statement else {
+                body.push(statement);
+                continue;
+            };
+            let callee = match index.get(&name) {
+                Some(&callee) if !recursive[callee] && functions[callee].params.len() == args.len() => callee,
+                _ => {
+                    body.push(IrStatement::Call { dst, name, args, tail });
+                    continue;
+                }
+            };
+
+            let callee_size = cost(&functions[callee]);
+            let worth_it = match functions[callee].inline {
+                InlineHint::Always => true,
+                InlineHint::Never => false,
+                InlineHint::Inline => callee_size <= INLINE_THRESHOLD && size + callee_size <= CALLER_LIMIT,
+                InlineHint::None => callee_size <= THRESHOLD && size + callee_size <= CALLER_LIMIT,
+            };
+            if !worth_it {
+                body.push(IrStatement::Call { dst, name, args, tail });
+                continue;
+            }
+
+            let callee = functions[callee].clone();
+            diagnostics.remark(
+                format!("inlined `{}` into `{}`", callee.name, functions[caller].name),
+                None,
+            );
+            body.extend(splice(&mut functions[caller], &callee, dst, args, &mut next_label));
+            size += callee_size;
+            inlined += 1;
+        }
+        functions[caller].body = body;
+    }
+    inlined
+}
+
+/// Size of a function for the cost model: the statements that turn into instructions.
+fn cost(function: &IrFunction) -> usize {
+    function.body.iter().filter(|statement| statement.is_code()).count()
+}
+
+/// Copies the body of `callee` into `caller` for a call `dst = callee(args)`, giving it its
+/// own slots at the end of the caller's frame and its own labels.
+fn splice(
+    caller: &mut IrFunction,
+    callee: &IrFunction,
+    dst: Option<SlotID>,
+    args: Vec<Operand>,
+    next_label: &mut u32,
+) -> Vec<IrStatement> {
+    let mut fresh_label = || {
+        *next_label += 1;
+        *next_label - 1
+    };
+
+    // 1. a fresh slot for every slot of the callee, which debug info leaves out since the
+    //    caller doesn't declare its variables
+    let mut end = caller
+        .slots
+        .iter()
+        .map(|slot| slot.offset + slot.size)
+        .max()
+        .unwrap_or(0);
+    let first = caller.slots.len();
+    for slot in &callee.slots {
+        let offset = end.next_multiple_of(slot.align);
+        caller.slots.push(Slot {
+            offset,
+            name: None,
+            ..slot.clone()
+        });
+        end = offset + slot.size;
+    }
+    caller.framesize = caller.framesize.max((end + 16).next_multiple_of(16));
+    let slot = |slot: &SlotID| SlotID::new(first + slot.index());
+
+    // 2. a fresh label for every label of the callee, and one to return to
+    let labels: HashMap<u32, u32> = callee
+        .body
+        .iter()
+        .filter_map(|statement| match statement {
+            IrStatement::Label(label) => Some((*label, fresh_label())),
+            _ => None,
+        })
+        .collect();
+    let exit = fresh_label();
+
+    // 3. pass the arguments, then run the renamed body
+    let mut spliced: Vec<IrStatement> = callee
+        .params
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| IrStatement::Copy {
+            dst: slot(param),
+            src: arg,
+        })
+        .collect();
+    for statement in &callee.body {
+        let mut statement = statement.clone();
+        if let Some(dst) = statement.dst_mut() {
+            *dst = slot(dst);
+        }
+        for operand in statement.operands_mut() {
+            if let Operand::Var(var) = operand {
+                *var = slot(var);
+            }
+        }
+        match statement {
+            IrStatement::Label(label) => spliced.push(IrStatement::Label(labels[&label])),
+            IrStatement::Jmp(target) => spliced.push(IrStatement::Jmp(labels[&target])),
+            IrStatement::JmpIfZero { cond, target } => spliced.push(IrStatement::JmpIfZero {
+                cond,
+                target: labels[&target],
+            }),
+            IrStatement::Ret(value) => {
+                if let Some(dst) = &dst {
+                    spliced.push(IrStatement::Copy {
+                        dst: dst.clone(),
+                        src: value,
+                    });
+                }
+                spliced.push(IrStatement::Jmp(exit));
+            }
+            statement => spliced.push(statement),
+        }
+    }
+    spliced.push(IrStatement::Label(exit));
+    spliced
+}
+
+/// Tarjan's algorithm over the call graph. Components come out callees first: every
+/// component is listed after all the components it calls into.
+fn strongly_connected_components(callees: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        callees: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, function: usize) {
+            self.index[function] = Some(self.next);
+            self.lowlink[function] = self.next;
+            self.next += 1;
+            self.stack.push(function);
+            self.on_stack[function] = true;
+
+            for &callee in self.callees[function].iter() {
+                match self.index[callee] {
+                    None => {
+                        self.visit(callee);
+                        self.lowlink[function] = self.lowlink[function].min(self.lowlink[callee]);
+                    }
+                    Some(index) if self.on_stack[callee] => {
+                        self.lowlink[function] = self.lowlink[function].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.lowlink[function]) == self.index[function] {
+                let mut component = Vec::new();
+                loop {
+                    let member = self.stack.pop().unwrap();
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == function {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let count = callees.len();
+    let mut tarjan = Tarjan {
+        callees,
+        index: vec![None; count],
+        lowlink: vec![0; count],
+        on_stack: vec![false; count],
+        stack: Vec::new(),
+        next: 0,
+        components: Vec::new(),
+    };
+    for function in 0..count {
+        if tarjan.index[function].is_none() {
+            tarjan.visit(function);
+        }
+    }
+    tarjan.components
+}
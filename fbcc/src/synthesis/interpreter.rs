@@ -0,0 +1,313 @@
+//! Direct execution of the IR, so programs can be checked without an assembler or the
+//! machine they target.
+//!
+//! Values are 32-bit `int`s like in the backends. Every call gets a fresh frame with one
+//! value per slot of the callee, and slots start out uninitialised: reading one before it
+//! is written is a trap rather than whatever happened to be on the stack.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+
+use crate::synthesis::ir::{BinaryOp, IrFunction, IrStatement, Operand, SlotID, UnaryOp};
+
+/// Nesting depth of calls after which a program is assumed to recurse without end.
+const MAX_CALL_DEPTH: usize = 100_000;
+
+/// Runtime error that stops the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trap {
+    DivisionByZero {
+        function: String,
+    },
+    /// `INT_MIN / -1` and `INT_MIN % -1`, whose result doesn't fit an `int`
+    DivisionOverflow {
+        function: String,
+    },
+    UninitialisedRead {
+        function: String,
+        slot: String,
+    },
+    UndefinedFunction {
+        name: String,
+    },
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    StackOverflow,
+    Io(String),
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trap::DivisionByZero { function } => write!(f, "division by zero in `{function}`"),
+            Trap::DivisionOverflow { function } => write!(f, "overflow in division in `{function}`"),
+            Trap::UninitialisedRead { function, slot } => {
+                write!(f, "read of uninitialised slot {slot} in `{function}`")
+            }
+            Trap::UndefinedFunction { name } => write!(f, "call to undefined function `{name}`"),
+            Trap::ArityMismatch { name, expected, found } => {
+                write!(f, "`{name}` takes {expected} arguments but was called with {found}")
+            }
+            Trap::StackOverflow => write!(f, "stack overflow (more than {MAX_CALL_DEPTH} nested calls)"),
+            Trap::Io(message) => write!(f, "failed to write output: {message}"),
+        }
+    }
+}
+
+/// Why execution of the current call stack stopped early.
+enum Stop {
+    Exit(i32),
+    Trap(Trap),
+}
+
+impl From<Trap> for Stop {
+    fn from(trap: Trap) -> Self {
+        Stop::Trap(trap)
+    }
+}
+
+struct Frame<'a> {
+    function: &'a IrFunction,
+    /// Index of the next statement to execute
+    pc: usize,
+    slots: Vec<Option<i32>>,
+    /// Slot of the caller that receives the return value
+    result: Option<&'a SlotID>,
+}
+
+/// Executes a translation unit's IR; what the program prints goes to `out`.
+pub struct Interpreter<'a, W: Write> {
+    functions: HashMap<&'a str, &'a IrFunction>,
+    /// Position of every label within its function's body
+    labels: HashMap<u32, usize>,
+    out: W,
+}
+
+impl<'a, W: Write> Interpreter<'a, W> {
+    pub fn new(irfuncs: &'a [IrFunction], out: W) -> Self {
+        let mut labels = HashMap::new();
+        for function in irfuncs {
+            for (index, statement) in function.body.iter().enumerate() {
+                if let IrStatement::Label(label) = statement {
+                    labels.insert(*label, index);
+                }
+            }
+        }
+
+        Interpreter {
+            functions: irfuncs
+                .iter()
+                .map(|function| (function.name.as_str(), function))
+                .collect(),
+            labels,
+            out,
+        }
+    }
+
+    /// Runs `main` and returns the program's exit status. As in C, falling off the end of
+    /// `main` means 0.
+    pub fn run_main(&mut self) -> Result<i32, Trap> {
+        match self.call("main", &[]) {
+            Ok(status) => Ok(status.unwrap_or(0)),
+            Err(Stop::Exit(status)) => Ok(status),
+            Err(Stop::Trap(trap)) => Err(trap),
+        }
+    }
+
+    /// Calls `name` with `args` and returns its result; `None` if it ended without `return`.
+    /// A call to `exit` stops execution early, with its status as the result.
+    pub fn run(&mut self, name: &str, args: &[i32]) -> Result<Option<i32>, Trap> {
+        match self.call(name, args) {
+            Ok(result) => Ok(result),
+            Err(Stop::Exit(status)) => Ok(Some(status)),
+            Err(Stop::Trap(trap)) => Err(trap),
+        }
+    }
+
+    /// Gives back the output stream.
+    pub fn into_output(self) -> W {
+        self.out
+    }
+
+    fn call(&mut self, name: &str, args: &[i32]) -> Result<Option<i32>, Stop> {
+        let mut stack: Vec<Frame> = vec![self.frame(name, args, None)?];
+
+        loop {
+            let frame = stack.last_mut().unwrap();
+            let Some(statement) = frame.function.body.get(frame.pc) else {
+                // ran off the end of the function: the caller gets no value
+                let finished = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(caller) => Self::store_result(caller, finished.result, None),
+                    None => return Ok(None),
+                }
+                continue;
+            };
+            frame.pc += 1;
+
+            match statement {
+                IrStatement::BinaryOp { dst, op, l, r } => {
+                    let (l, r) = (Self::read(frame, l)?, Self::read(frame, r)?);
+                    let value = Self::binary(op, l, r, &frame.function.name)?;
+                    frame.slots[dst.index()] = Some(value);
+                }
+
+                IrStatement::UnaryOp { dst, op, src } => {
+                    let src = Self::read(frame, src)?;
+                    let value = match op {
+                        UnaryOp::Minus => src.wrapping_neg(),
+                        UnaryOp::Comp => !src,
+                        UnaryOp::Not => (src == 0) as i32,
+                    };
+                    frame.slots[dst.index()] = Some(value);
+                }
+
+                IrStatement::Copy { dst, src } => {
+                    let value = Self::read(frame, src)?;
+                    frame.slots[dst.index()] = Some(value);
+                }
+
+                IrStatement::Label(_) | IrStatement::Loc(_) => {}
+                IrStatement::Jmp(label) => frame.pc = self.labels[label],
+                IrStatement::JmpIfZero { cond, target } => {
+                    if Self::read(frame, cond)? == 0 {
+                        frame.pc = self.labels[target];
+                    }
+                }
+
+                IrStatement::Call { dst, name, args, .. } => {
+                    let args = args
+                        .iter()
+                        .map(|arg| Self::read(frame, arg))
+                        .collect::<Result<Vec<i32>, Trap>>()?;
+
+                    if self.functions.contains_key(name.as_str()) {
+                        if stack.len() >= MAX_CALL_DEPTH {
+                            return Err(Trap::StackOverflow.into());
+                        }
+                        let callee = self.frame(name, &args, dst.as_ref())?;
+                        stack.push(callee);
+                    } else {
+                        let value = self.builtin(name, &args)?;
+                        Self::store_result(stack.last_mut().unwrap(), dst.as_ref(), Some(value));
+                    }
+                }
+
+                IrStatement::Ret(value) => {
+                    let returned = Some(Self::read(frame, value)?);
+                    let finished = stack.pop().unwrap();
+                    match stack.last_mut() {
+                        Some(caller) => Self::store_result(caller, finished.result, returned),
+                        None => return Ok(returned),
+                    }
+                }
+            }
+        }
+    }
+
+    fn frame(&self, name: &str, args: &[i32], result: Option<&'a SlotID>) -> Result<Frame<'a>, Trap> {
+        let function = *self
+            .functions
+            .get(name)
+            .ok_or_else(|| Trap::UndefinedFunction { name: name.to_string() })?;
+        if function.params.len() != args.len() {
+            return Err(Trap::ArityMismatch {
+                name: name.to_string(),
+                expected: function.params.len(),
+                found: args.len(),
+            });
+        }
+
+        let mut slots = vec![None; function.slots.len()];
+        for (param, arg) in function.params.iter().zip(args) {
+            slots[param.index()] = Some(*arg);
+        }
+
+        Ok(Frame {
+            function,
+            pc: 0,
+            slots,
+            result,
+        })
+    }
+
+    /// Host functions available to every program.
+    fn builtin(&mut self, name: &str, args: &[i32]) -> Result<i32, Stop> {
+        match (name, args) {
+            ("putchar", [ch]) => {
+                self.out
+                    .write_all(&[*ch as u8])
+                    .map_err(|error| Trap::Io(error.to_string()))?;
+                Ok(*ch)
+            }
+            ("exit", [status]) => {
+                self.out.flush().map_err(|error| Trap::Io(error.to_string()))?;
+                Err(Stop::Exit(*status))
+            }
+            ("putchar" | "exit", _) => Err(Trap::ArityMismatch {
+                name: name.to_string(),
+                expected: 1,
+                found: args.len(),
+            }
+            .into()),
+            _ => Err(Trap::UndefinedFunction { name: name.to_string() }.into()),
+        }
+    }
+
+    /// Writes a returned value into the caller's destination slot, if there is one. A call
+    /// that produced no value leaves the slot uninitialised, so using it traps.
+    fn store_result(caller: &mut Frame, result: Option<&SlotID>, value: Option<i32>) {
+        if let Some(slot) = result {
+            caller.slots[slot.index()] = value;
+        }
+    }
+
+    fn read(frame: &Frame, operand: &Operand) -> Result<i32, Trap> {
+        match operand {
+            Operand::Const(constant) => Ok(*constant as i32),
+            Operand::Var(slot) => frame.slots[slot.index()].ok_or_else(|| Trap::UninitialisedRead {
+                function: frame.function.name.clone(),
+                slot: slot.to_string(),
+            }),
+        }
+    }
+
+    fn binary(op: &BinaryOp, l: i32, r: i32, function: &str) -> Result<i32, Trap> {
+        let value = match op {
+            BinaryOp::Add => l.wrapping_add(r),
+            BinaryOp::Sub => l.wrapping_sub(r),
+            BinaryOp::Mul => l.wrapping_mul(r),
+            BinaryOp::Div | BinaryOp::Mod => {
+                if r == 0 {
+                    return Err(Trap::DivisionByZero {
+                        function: function.to_string(),
+                    });
+                }
+                let result = match op {
+                    BinaryOp::Div => l.checked_div(r),
+                    _ => l.checked_rem(r),
+                };
+                result.ok_or_else(|| Trap::DivisionOverflow {
+                    function: function.to_string(),
+                })?
+            }
+            BinaryOp::Lt => (l < r) as i32,
+            BinaryOp::Le => (l <= r) as i32,
+            BinaryOp::Gt => (l > r) as i32,
+            BinaryOp::Ge => (l >= r) as i32,
+            BinaryOp::Eq => (l == r) as i32,
+            BinaryOp::NEq => (l != r) as i32,
+            BinaryOp::And => l & r,
+            BinaryOp::Or => l | r,
+            BinaryOp::Xor => l ^ r,
+            // shift counts are taken modulo 32, like the hardware does
+            BinaryOp::LShift => l.wrapping_shl(r as u32),
+            BinaryOp::RShift => l.wrapping_shr(r as u32),
+        };
+        Ok(value)
+    }
+}
@@ -4,16 +4,19 @@ use std::fmt;
 use crate::{
     analysis::{
         ast::{
-            AssignOperator, BinaryOperator,
+            AssignOperator, AssignOperatorExpression, Attribute, BinaryOperator, BinaryOperatorExpression,
             BlockItem::{self},
+            CallExpression, DeclarationSpecifier,
             Declarator::{self},
-            Expression, ExternalDeclaration, ForInitializer, FunctionDefinition, Initializer, Statement,
-            TranslationUnit, UnaryOperator,
+            Expression, ExternalDeclaration, ForInitializer, FunctionDefinition, FunctionSpecifier,
+            ImplicitCastExpression, Initializer, Statement, TernaryOperatorExpression, TranslationUnit, UnaryOperator,
+            UnaryOperatorExpression,
         },
-        node::{Node, Span},
+        node::{Location, Node, Span},
     },
     core::{
-        errors::{CompilerError, CompilerErrorKind},
+        errors::{CompilerError, CompilerErrorKind, ErrorCode},
+        target::DataLayout,
         typedefs::{Constant, DataType, IntegerRank, IntegerType, Type},
     },
 };
@@ -45,10 +48,21 @@ pub enum UnaryOp {
     Not,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SlotID(usize);
 
-#[derive(Debug, Clone)]
+impl SlotID {
+    /// Position of the slot in [`IrFunction::slots`].
+    pub fn index(&self) -> usize {
+        self.0
+    }
+
+    pub(crate) fn new(index: usize) -> Self {
+        SlotID(index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operand {
     Const(i64),
     Var(SlotID),
@@ -81,23 +95,131 @@ pub enum IrStatement {
         dst: Option<SlotID>,
         name: String,
         args: Vec<Operand>,
+        /// The call is directly followed by a return of its result, so the backend may
+        /// reuse the caller's frame and jump to the callee instead
+        tail: bool,
     },
     Ret(Operand),
+    /// Source position of the statements that follow, for warnings and debug info; executes
+    /// nothing
+    Loc(Location),
+}
+
+impl IrStatement {
+    /// Slot the statement writes, if any.
+    pub fn dst(&self) -> Option<&SlotID> {
+        match self {
+            IrStatement::BinaryOp { dst, .. } | IrStatement::UnaryOp { dst, .. } | IrStatement::Copy { dst, .. } => {
+                Some(dst)
+            }
+            IrStatement::Call { dst, .. } => dst.as_ref(),
+            IrStatement::Label(_)
+            | IrStatement::Jmp(_)
+            | IrStatement::JmpIfZero { .. }
+            | IrStatement::Ret(_)
+            | IrStatement::Loc(_) => None,
+        }
+    }
+
+    pub fn dst_mut(&mut self) -> Option<&mut SlotID> {
+        match self {
+            IrStatement::BinaryOp { dst, .. } | IrStatement::UnaryOp { dst, .. } | IrStatement::Copy { dst, .. } => {
+                Some(dst)
+            }
+            IrStatement::Call { dst, .. } => dst.as_mut(),
+            IrStatement::Label(_)
+            | IrStatement::Jmp(_)
+            | IrStatement::JmpIfZero { .. }
+            | IrStatement::Ret(_)
+            | IrStatement::Loc(_) => None,
+        }
+    }
+
+    /// Operands the statement reads, in evaluation order.
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            IrStatement::BinaryOp { l, r, .. } => vec![l, r],
+            IrStatement::UnaryOp { src, .. } | IrStatement::Copy { src, .. } => vec![src],
+            IrStatement::Call { args, .. } => args.iter().collect(),
+            IrStatement::JmpIfZero { cond: operand, .. } | IrStatement::Ret(operand) => vec![operand],
+            IrStatement::Label(_) | IrStatement::Jmp(_) | IrStatement::Loc(_) => Vec::new(),
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            IrStatement::BinaryOp { l, r, .. } => vec![l, r],
+            IrStatement::UnaryOp { src, .. } | IrStatement::Copy { src, .. } => vec![src],
+            IrStatement::Call { args, .. } => args.iter_mut().collect(),
+            IrStatement::JmpIfZero { cond: operand, .. } | IrStatement::Ret(operand) => vec![operand],
+            IrStatement::Label(_) | IrStatement::Jmp(_) | IrStatement::Loc(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the statement turns into code; labels and source positions don't.
+    pub fn is_code(&self) -> bool {
+        !matches!(self, IrStatement::Label(_) | IrStatement::Loc(_))
+    }
 }
 
+#[derive(Debug, Clone)]
 pub struct Slot {
-    ty: Type,
-    size: usize,
-    align: usize,
-    offset: usize,
+    pub ty: Type,
+    pub size: usize,
+    pub align: usize,
+    pub offset: usize,
+    /// The variable the slot holds, for debug info; temporaries have none
+    pub name: Option<String>,
 }
 
+/// What the source asked for when it comes to inlining calls to a function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InlineHint {
+    #[default]
+    None,
+    /// Declared `inline`
+    Inline,
+    /// `__attribute__((always_inline))`
+    Always,
+    /// `__attribute__((noinline))`, which wins over the other two
+    Never,
+}
+
+impl InlineHint {
+    fn from_specifiers(specifiers: &[Node<DeclarationSpecifier>]) -> Self {
+        let mut hint = InlineHint::None;
+        for specifier in specifiers {
+            hint = match (&specifier.node, hint) {
+                (_, InlineHint::Never) | (DeclarationSpecifier::Attribute(Attribute::NoInline), _) => InlineHint::Never,
+                (_, InlineHint::Always) | (DeclarationSpecifier::Attribute(Attribute::AlwaysInline), _) => {
+                    InlineHint::Always
+                }
+                (DeclarationSpecifier::FunctionSpecifier(FunctionSpecifier::Inline), _) => InlineHint::Inline,
+                (_, hint) => hint,
+            };
+        }
+        hint
+    }
+}
+
+/// Where a function came from in the source, for debug info.
+#[derive(Debug, Clone, Default)]
+pub struct SourceInfo {
+    /// Line of the function's definition
+    pub line: usize,
+    /// The return type and its size in bytes, `None` for `void`
+    pub return_type: Option<(Type, usize)>,
+}
+
+#[derive(Debug, Clone)]
 pub struct IrFunction {
     pub name: String,
     pub framesize: usize,
     pub params: Vec<SlotID>,
     pub slots: Vec<Slot>,
     pub body: Vec<IrStatement>,
+    pub inline: InlineHint,
+    pub source: SourceInfo,
 }
 
 impl IrFunction {
@@ -105,25 +227,77 @@ impl IrFunction {
     pub fn slot_offset(&self, slot: &SlotID) -> usize {
         self.slots[slot.0].offset
     }
+
+    /// Adds a slot of the same type as `like` to the end of the frame and grows the frame
+    /// to match.
+    pub fn new_slot_like(&mut self, like: &SlotID) -> SlotID {
+        new_slot_like(&mut self.slots, &mut self.framesize, like)
+    }
+
+    /// The functions `functions` call without defining, with the number of arguments they
+    /// take, in the order they are first called. Errors if one is called with different
+    /// numbers of arguments, as it gets a single declaration
+    pub fn undefined_callees(functions: &[IrFunction]) -> Result<Vec<(&str, usize)>, CompilerError> {
+        let mut callees: Vec<(&str, usize)> = Vec::new();
+        for function in functions {
+            for statement in &function.body {
+                let IrStatement::Call { name, args, .. } = statement else {
+                    continue;
+                };
+                if functions.iter().any(|function| function.name == *name) {
+                    continue;
+                }
+                match callees.iter().find(|(callee, _)| callee == name) {
+                    Some((_, arity)) if *arity != args.len() => {
+                        return Err(CompilerError {
+                            kind: CompilerErrorKind::InternalError,
+                            message: format!("`{name}` is called with different numbers of arguments"),
+                            span: None,
+                            ..Default::default()
+                        })
+                    }
+                    Some(_) => {}
+                    None => callees.push((name, args.len())),
+                }
+            }
+        }
+        Ok(callees)
+    }
+
+    /// Whether control can run off the end of the body. An earlier `ret` on some other path
+    /// doesn't change that. Every backend then returns 0: that is what running off the end of
+    /// `main` means in C, and for another function, whose value the caller must not use, it
+    /// keeps the result the same on every target instead of whatever the return register held.
+    pub fn falls_off_end(&self) -> bool {
+        !matches!(
+            self.body
+                .iter()
+                .rev()
+                .find(|statement| !matches!(statement, IrStatement::Loc(_))),
+            Some(IrStatement::Ret(_))
+        )
+    }
 }
 
 struct FrameBuilder {
     slots: Vec<Slot>,
     offset: usize,
+    layout: DataLayout,
 }
 
 impl FrameBuilder {
-    fn new() -> Self {
+    fn new(layout: DataLayout) -> Self {
         FrameBuilder {
             slots: Vec::new(),
             offset: 0,
+            layout,
         }
     }
 
-    fn allocate(&mut self, ty: Type) -> Result<SlotID, CompilerError> {
+    fn allocate(&mut self, ty: Type, name: Option<&str>) -> Result<SlotID, CompilerError> {
         let id = self.slots.len();
-        let size = ty.size()?;
-        let align = ty.align()?;
+        let size = ty.size(&self.layout)?;
+        let align = ty.align(&self.layout)?;
 
         self.offset = self.offset.next_multiple_of(align);
 
@@ -132,6 +306,7 @@ impl FrameBuilder {
             size,
             align,
             offset: self.offset,
+            name: name.map(str::to_string),
         };
 
         self.slots.push(slot);
@@ -141,6 +316,23 @@ impl FrameBuilder {
 }
 
 /// Resolve an identifier to its slot, searching scopes innermost-first.
+/// Adds a slot of the same type as `like` to the end of the frame `slots` lay out, and grows
+/// `framesize` to match. Both a function and its [`Cfg`](crate::synthesis::cfg::Cfg) have
+/// their frame in these two fields.
+pub(crate) fn new_slot_like(slots: &mut Vec<Slot>, framesize: &mut usize, like: &SlotID) -> SlotID {
+    let template = &slots[like.0];
+    let end = slots.iter().map(|slot| slot.offset + slot.size).max().unwrap_or(0);
+    let slot = Slot {
+        offset: end.next_multiple_of(template.align),
+        name: None,
+        ..template.clone()
+    };
+
+    *framesize = (slot.offset + slot.size + 16).next_multiple_of(16);
+    slots.push(slot);
+    SlotID(slots.len() - 1)
+}
+
 fn lookup(scopes: &[HashMap<String, SlotID>], name: &str) -> Option<SlotID> {
     scopes.iter().rev().find_map(|scope| scope.get(name)).cloned()
 }
@@ -148,20 +340,27 @@ fn lookup(scopes: &[HashMap<String, SlotID>], name: &str) -> Option<SlotID> {
 pub struct IrEmitter {
     labelcounter: u32,
     loopstack: Vec<(u32, u32)>, // (continue label, break label)
+    layout: DataLayout,
 }
 
 impl IrEmitter {
-    pub fn new() -> Self {
+    pub fn new(layout: DataLayout) -> Self {
         IrEmitter {
             labelcounter: 0,
             loopstack: Vec::new(),
+            layout,
         }
     }
 
+    /// The source position marker for code starting at `span`.
+    fn loc(span: &Span) -> IrStatement {
+        IrStatement::Loc(span.start)
+    }
+
     fn newlabel(&mut self) -> (u32, IrStatement) {
         let label = IrStatement::Label(self.labelcounter);
         self.labelcounter += 1;
-        return (self.labelcounter - 1, label);
+        (self.labelcounter - 1, label)
     }
 
     pub fn emit(&mut self, translation_unit: &TranslationUnit) -> Result<Vec<IrFunction>, CompilerError> {
@@ -171,11 +370,19 @@ impl IrEmitter {
             match &extdecl.node {
                 ExternalDeclaration::FunctionDefinition(function) => functions.push(self.emit_func(function)?),
 
+                // Function declarations only matter to the analyzer
+                ExternalDeclaration::Declaration(declaration)
+                    if declaration.init_declarators.iter().all(|init_decl| {
+                        matches!(init_decl.node.declarator.node, Declarator::FunctionDeclarator(_))
+                    }) => {}
+
                 ExternalDeclaration::Declaration(_) => {
                     return Err(CompilerError {
                         kind: CompilerErrorKind::InternalError,
                         message: "global variables are not implemented in IR yet".to_string(),
                         span: Some(extdecl.span),
+                        code: Some(ErrorCode::Unsupported),
+                        ..Default::default()
                     })
                 }
             }
@@ -190,11 +397,12 @@ impl IrEmitter {
                 kind: CompilerErrorKind::SemanticError,
                 message: "function body must be a compound statement".to_string(),
                 span: Some(function.body.span),
+                ..Default::default()
             });
         };
 
         let mut params: Vec<SlotID> = Vec::new();
-        let mut framebuilder = FrameBuilder::new();
+        let mut framebuilder = FrameBuilder::new(self.layout);
         let mut scopes: Vec<HashMap<String, SlotID>> = Vec::new();
         scopes.push(HashMap::new());
 
@@ -208,8 +416,6 @@ impl IrEmitter {
                 continue;
             }
 
-            let slotid = framebuilder.allocate(paramtype)?;
-
             let paramname: Option<String> = match &param.node.declarator {
                 // A plain parameter name (`int a`) is a DirectDeclarator, not a FunctionDeclarator.
                 Some(declarator) => match &declarator.node {
@@ -219,12 +425,15 @@ impl IrEmitter {
                             kind: CompilerErrorKind::InternalError,
                             message: "function-typed parameters are not yet supported".to_string(),
                             span: Some(param.span),
+                            code: Some(ErrorCode::Unsupported),
+                            ..Default::default()
                         });
                     }
                 },
                 // Unnamed parameter, e.g. a prototype's `int` or `void`.
                 None => None,
             };
+            let slotid = framebuilder.allocate(paramtype, paramname.as_deref())?;
 
             // Only named params get a scope binding; an unnamed param still has a slot.
             if let Some(name) = paramname {
@@ -241,22 +450,34 @@ impl IrEmitter {
         for blockitem in compound_stmt {
             match &blockitem.node {
                 BlockItem::Declaration(declaration) => {
+                    units.push(Self::loc(&blockitem.span));
                     units.extend(self.emit_declaration(declaration, &mut framebuilder, &mut scopes)?);
                 }
                 BlockItem::Statement(stmt) => {
-                    units.extend(self.emit_stmt(&stmt, &blockitem.span, &mut scopes, &mut framebuilder)?);
+                    units.extend(self.emit_stmt(stmt, &blockitem.span, &mut scopes, &mut framebuilder)?);
                 }
             }
         }
 
         scopes.pop();
 
+        let (return_type, _) = Type::from_declaration_specifiers(&function.specifiers)?;
+        let return_type = match return_type.datatype {
+            DataType::Void => None,
+            _ => Some((return_type.clone(), return_type.size(&self.layout)?)),
+        };
+
         let irfunction = IrFunction {
             name: function.declarator.node.identifier.clone(),
             framesize: (framebuilder.offset + 16).next_multiple_of(16),
             params,
             slots: framebuilder.slots,
             body: units,
+            inline: InlineHint::from_specifiers(&function.specifiers),
+            source: SourceInfo {
+                line: function.declarator.span.start.line,
+                return_type,
+            },
         };
 
         Ok(irfunction)
@@ -272,7 +493,7 @@ impl IrEmitter {
         for init_declarator in &declaration.init_declarators {
             if let Declarator::DirectDeclarator(identifier) = &init_declarator.node.declarator.node {
                 let (decltype, _) = Type::from_declaration_specifiers(&declaration.specifiers)?;
-                let slotid = framebuilder.allocate(decltype)?;
+                let slotid = framebuilder.allocate(decltype, Some(identifier))?;
 
                 if let Some(currscope) = scopes.last_mut() {
                     currscope.insert(identifier.clone(), slotid.clone());
@@ -305,6 +526,9 @@ impl IrEmitter {
         framebuilder: &mut FrameBuilder,
     ) -> Result<Vec<IrStatement>, CompilerError> {
         let mut units: Vec<IrStatement> = Vec::new();
+        if !matches!(stmt, Statement::CompoundStatement(_)) {
+            units.push(Self::loc(span));
+        }
         match stmt {
             Statement::CompoundStatement(compound_stmt) => {
                 scopes.push(HashMap::new());
@@ -312,10 +536,11 @@ impl IrEmitter {
                 for blockitem in compound_stmt {
                     match &blockitem.node {
                         BlockItem::Declaration(declaration) => {
-                            self.emit_declaration(declaration, framebuilder, scopes)?;
+                            units.push(Self::loc(&blockitem.span));
+                            units.extend(self.emit_declaration(declaration, framebuilder, scopes)?);
                         }
                         BlockItem::Statement(stmt) => {
-                            units.extend(self.emit_stmt(&stmt, &blockitem.span, scopes, framebuilder)?);
+                            units.extend(self.emit_stmt(stmt, &blockitem.span, scopes, framebuilder)?);
                         }
                     }
                 }
@@ -331,26 +556,41 @@ impl IrEmitter {
             }
 
             Statement::IfStatement(ifstmt) => {
-                let (condresult, cond_ir) = self.emit_expr(&ifstmt.condition.node, scopes, framebuilder)?;
-                units.extend(cond_ir);
+                // An `else if` chain is lowered arm by arm in a loop, as it nests as deep as it
+                // is long. The arms that have an `else` jump past it to labels placed after the
+                // last arm, innermost first
+                let mut ifstmt = &**ifstmt;
+                let mut endifs = Vec::new();
+                loop {
+                    let (condresult, cond_ir) = self.emit_expr(&ifstmt.condition.node, scopes, framebuilder)?;
+                    units.extend(cond_ir);
 
-                let (lelse_id, lelse) = self.newlabel();
+                    let (lelse_id, lelse) = self.newlabel();
 
-                units.push(IrStatement::JmpIfZero {
-                    cond: condresult,
-                    target: lelse_id,
-                });
+                    units.push(IrStatement::JmpIfZero {
+                        cond: condresult,
+                        target: lelse_id,
+                    });
+
+                    units.extend(self.emit_stmt(&ifstmt.if_block.node, &ifstmt.if_block.span, scopes, framebuilder)?);
+                    let Some(else_block) = &ifstmt.else_block else {
+                        units.push(lelse);
+                        break;
+                    };
 
-                units.extend(self.emit_stmt(&ifstmt.if_block.node, &ifstmt.if_block.span, scopes, framebuilder)?);
-                if let Some(else_block) = &ifstmt.else_block {
                     let (lendif_id, lendif) = self.newlabel();
                     units.push(IrStatement::Jmp(lendif_id));
                     units.push(lelse);
-                    units.extend(self.emit_stmt(&else_block.node, &else_block.span, scopes, framebuilder)?);
-                    units.push(lendif);
-                } else {
-                    units.push(lelse);
+                    endifs.push(lendif);
+                    if let Statement::IfStatement(else_if) = &else_block.node {
+                        units.push(Self::loc(&else_block.span));
+                        ifstmt = else_if;
+                    } else {
+                        units.extend(self.emit_stmt(&else_block.node, &else_block.span, scopes, framebuilder)?);
+                        break;
+                    }
                 }
+                units.extend(endifs.into_iter().rev());
             }
 
             Statement::WhileStatement(whilestmt) => {
@@ -360,6 +600,8 @@ impl IrEmitter {
                 let (lend_id, lend) = self.newlabel();
 
                 units.push(lstart);
+                // every iteration tests the condition again
+                units.push(Self::loc(span));
                 units.extend(cond_ir);
                 units.push(IrStatement::JmpIfZero {
                     cond: condresult,
@@ -405,6 +647,7 @@ impl IrEmitter {
                 };
 
                 units.push(start);
+                units.push(Self::loc(span));
 
                 if let Some(condition) = &forstmt.condition {
                     let (condresult, cond_ir) = self.emit_expr(&condition.node, scopes, framebuilder)?;
@@ -426,6 +669,7 @@ impl IrEmitter {
 
                 if let Some(step) = &forstmt.step {
                     units.push(continue_label);
+                    units.push(Self::loc(&step.span));
                     let (_, step_ir) = self.emit_expr(&step.node, scopes, framebuilder)?;
                     units.extend(step_ir);
                 }
@@ -435,11 +679,12 @@ impl IrEmitter {
             }
 
             Statement::BreakStatement => {
-                if self.loopstack.len() <= 0 {
+                if self.loopstack.is_empty() {
                     return Err(CompilerError {
                         kind: CompilerErrorKind::InternalError,
                         message: "Semantic analyzer should've detected stray break statement".to_string(),
                         span: Some(*span),
+                        ..Default::default()
                     });
                 }
 
@@ -447,11 +692,12 @@ impl IrEmitter {
             }
 
             Statement::ContinueStatement => {
-                if self.loopstack.len() <= 0 {
+                if self.loopstack.is_empty() {
                     return Err(CompilerError {
                         kind: CompilerErrorKind::InternalError,
                         message: "Semantic analyzer should've detected stray continue statement".to_string(),
                         span: Some(*span),
+                        ..Default::default()
                     });
                 }
 
@@ -469,6 +715,8 @@ impl IrEmitter {
                     kind: CompilerErrorKind::InternalError,
                     message: "this statement is not supported by IR lowering yet".to_string(),
                     span: Some(*span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
                 })
             }
         }
@@ -481,344 +729,429 @@ impl IrEmitter {
         scopes: &mut Vec<HashMap<String, SlotID>>,
         framebuilder: &mut FrameBuilder,
     ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        // Subexpressions are lowered through here, so the expressions that have some are
+        // lowered by functions of their own and only take up stack while they are lowered
         match expr {
             Expression::Identifier(identifier) => {
                 let slot = lookup(scopes, identifier).ok_or_else(|| CompilerError {
                     kind: CompilerErrorKind::InternalError,
                     message: format!("undeclared identifier `{identifier}` reached IR lowering"),
                     span: None,
+                    ..Default::default()
                 })?;
-                return Ok((Operand::Var(slot), Vec::new()));
+                Ok((Operand::Var(slot), Vec::new()))
             }
 
-            Expression::Constant(constant) => match constant {
-                Constant::Integer(integertype) => match integertype {
-                    IntegerType::Generic(integer) => {
-                        // The backend is 32-bit `int` only; reject constants that
-                        // don't fit instead of silently truncating in codegen.
-                        if i32::try_from(*integer).is_err() {
-                            return Err(CompilerError {
-                                kind: CompilerErrorKind::SemanticError,
-                                message: format!("integer constant `{integer}` does not fit in `int`"),
-                                span: None,
-                            });
-                        }
-                        return Ok((Operand::Const(integer.clone()), Vec::new()));
-                    }
-                    other => {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::InternalError,
-                            message: format!("integer constant `{:?}` is not supported by IR lowering yet", other),
-                            span: None,
-                        })
-                    }
-                },
-                other => {
-                    return Err(CompilerError {
-                        kind: CompilerErrorKind::InternalError,
-                        message: format!("constant `{:?}` is not supported by IR lowering yet", other),
-                        span: None,
-                    })
-                }
-            },
-
-            Expression::UnaryOperator(unaryexpr) => {
-                let (unaryop_result, mut units) = self.emit_expr(&unaryexpr.operand.node, scopes, framebuilder)?;
+            Expression::Constant(constant) => Self::emit_constant(constant),
 
-                let unaryop = match &unaryexpr.operator.node {
-                    // `+x` is a no-op on int; forward the operand as-is.
-                    UnaryOperator::Plus => return Ok((unaryop_result, units)),
-                    UnaryOperator::Minus => UnaryOp::Minus,
-                    UnaryOperator::Complement => UnaryOp::Comp,
-                    UnaryOperator::Negate => UnaryOp::Not,
+            Expression::UnaryOperator(unaryexpr) => self.emit_unary_expr(unaryexpr, scopes, framebuilder),
 
-                    // PostIncrement, PostDecrement, PreIncrement, PreDecrement,
-                    // Address, Indirection
-                    op => {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::InternalError,
-                            message: format!("unary operator `{op}` is not supported by IR lowering yet"),
-                            span: Some(unaryexpr.operator.span),
-                        })
-                    }
-                };
+            Expression::BinaryOperator(binaryexpr) => self.emit_binary_expr(binaryexpr, scopes, framebuilder),
 
-                let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
-                units.push(IrStatement::UnaryOp {
-                    dst: dst.clone(),
-                    op: unaryop,
-                    src: unaryop_result,
-                });
-                return Ok((Operand::Var(dst), units));
-            }
-
-            Expression::BinaryOperator(binaryexpr) => {
-                let (lhs, lhs_ir) = self.emit_expr(&binaryexpr.lhs.node, scopes, framebuilder)?;
-                let (rhs, rhs_ir) = self.emit_expr(&binaryexpr.rhs.node, scopes, framebuilder)?;
+            Expression::AssignOperator(assign_expr) => self.emit_assign_expr(assign_expr, scopes, framebuilder),
 
-                let mut units: Vec<IrStatement>;
+            Expression::TernaryOperator(ternaryopexpr) => self.emit_ternary_expr(ternaryopexpr, scopes, framebuilder),
 
-                match &binaryexpr.operator.node {
-                    BinaryOperator::LogicalAnd => {
-                        let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
-                        let (lfalseid, lfalse) = self.newlabel();
-                        let (lendid, lend) = self.newlabel();
+            Expression::ImplicitCast(cast) => self.emit_implicit_cast(cast, scopes, framebuilder),
 
-                        units = lhs_ir;
+            Expression::Call(callexpr) => self.emit_call_expr(callexpr, scopes, framebuilder),
 
-                        units.push(IrStatement::JmpIfZero {
-                            cond: lhs.clone(),
-                            target: lfalseid,
-                        });
-
-                        units.extend(rhs_ir);
-                        units.push(IrStatement::BinaryOp {
-                            dst: dst.clone(),
-                            op: BinaryOp::NEq,
-                            l: rhs,
-                            r: Operand::Const(0),
-                        });
-                        units.push(IrStatement::Jmp(lendid));
+            Expression::Empty => Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: "empty expression reached IR lowering".to_string(),
+                span: None,
+                ..Default::default()
+            }),
+            _ => Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: format!("emit_expr not implemented for {:?} yet", expr),
+                span: None,
+                ..Default::default()
+            }),
+        }
+    }
 
-                        units.push(lfalse);
-                        units.push(IrStatement::Copy {
-                            dst: dst.clone(),
-                            src: Operand::Const(0),
+    fn emit_constant(constant: &Constant) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        match constant {
+            Constant::Integer(integertype) => match integertype {
+                IntegerType::Generic(integer) => {
+                    // The backend is 32-bit `int` only; reject constants that
+                    // don't fit instead of silently truncating in codegen.
+                    if i32::try_from(*integer).is_err() {
+                        return Err(CompilerError {
+                            kind: CompilerErrorKind::SemanticError,
+                            message: format!("integer constant `{integer}` does not fit in `int`"),
+                            span: None,
+                            code: Some(ErrorCode::ConstantTooLarge),
+                            ..Default::default()
                         });
-
-                        units.push(lend);
-                        return Ok((Operand::Var(dst), units));
                     }
+                    Ok((Operand::Const(*integer), Vec::new()))
+                }
+                other => Err(CompilerError {
+                    kind: CompilerErrorKind::InternalError,
+                    message: format!("integer constant `{:?}` is not supported by IR lowering yet", other),
+                    span: None,
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                }),
+            },
+            other => Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: format!("constant `{:?}` is not supported by IR lowering yet", other),
+                span: None,
+                code: Some(ErrorCode::Unsupported),
+                ..Default::default()
+            }),
+        }
+    }
 
-                    BinaryOperator::LogicalOr => {
-                        let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
-                        let (levalrhsid, levalrhs) = self.newlabel();
-                        let (lendid, lend) = self.newlabel();
-
-                        units = lhs_ir;
+    fn emit_unary_expr(
+        &mut self,
+        unaryexpr: &UnaryOperatorExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let (unaryop_result, mut units) = self.emit_expr(&unaryexpr.operand.node, scopes, framebuilder)?;
+
+        let unaryop = match &unaryexpr.operator.node {
+            // `+x` is a no-op on int; forward the operand as-is.
+            UnaryOperator::Plus => return Ok((unaryop_result, units)),
+            UnaryOperator::Minus => UnaryOp::Minus,
+            UnaryOperator::Complement => UnaryOp::Comp,
+            UnaryOperator::Negate => UnaryOp::Not,
+
+            // PostIncrement, PostDecrement, PreIncrement, PreDecrement,
+            // Address, Indirection
+            op => {
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::InternalError,
+                    message: format!("unary operator `{op}` is not supported by IR lowering yet"),
+                    span: Some(unaryexpr.operator.span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                })
+            }
+        };
 
-                        units.push(IrStatement::JmpIfZero {
-                            cond: lhs.clone(),
-                            target: levalrhsid,
-                        });
+        let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+        units.push(IrStatement::UnaryOp {
+            dst: dst.clone(),
+            op: unaryop,
+            src: unaryop_result,
+        });
+        Ok((Operand::Var(dst), units))
+    }
 
-                        units.push(IrStatement::Copy {
-                            dst: dst.clone(),
-                            src: Operand::Const(1),
-                        });
+    fn emit_binary_expr(
+        &mut self,
+        binaryexpr: &BinaryOperatorExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        // `a + b + c + ...` nests to the left as deep as it is long, so the operators down the
+        // left side are lowered from the innermost one out in a loop
+        let mut chain = Vec::new();
+        let mut innermost = binaryexpr;
+        while let Expression::BinaryOperator(inner) = &innermost.lhs.node {
+            chain.push(innermost);
+            innermost = inner;
+        }
 
-                        units.push(IrStatement::Jmp(lendid));
+        let lhs = self.emit_expr(&innermost.lhs.node, scopes, framebuilder)?;
+        let mut result = self.emit_binary_operator(innermost, lhs, scopes, framebuilder)?;
+        for binaryexpr in chain.into_iter().rev() {
+            result = self.emit_binary_operator(binaryexpr, result, scopes, framebuilder)?;
+        }
+        Ok(result)
+    }
 
-                        units.push(levalrhs);
-                        units.extend(rhs_ir);
-                        units.push(IrStatement::BinaryOp {
-                            dst: dst.clone(),
-                            op: BinaryOp::NEq,
-                            l: rhs.clone(),
-                            r: Operand::Const(0),
-                        });
+    /// Lowers a binary operator whose left operand is already lowered to `lhs` by `lhs_ir`
+    fn emit_binary_operator(
+        &mut self,
+        binaryexpr: &BinaryOperatorExpression,
+        (lhs, lhs_ir): (Operand, Vec<IrStatement>),
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let (rhs, rhs_ir) = self.emit_expr(&binaryexpr.rhs.node, scopes, framebuilder)?;
 
-                        units.push(lend);
-                        return Ok((Operand::Var(dst), units));
-                    }
+        let mut units: Vec<IrStatement>;
 
-                    binary_operator => {
-                        let binaryop = match *binary_operator {
-                            BinaryOperator::Less => BinaryOp::Lt,
-                            BinaryOperator::LessOrEqual => BinaryOp::Le,
-                            BinaryOperator::Greater => BinaryOp::Gt,
-                            BinaryOperator::GreaterOrEqual => BinaryOp::Ge,
-                            BinaryOperator::Equals => BinaryOp::Eq,
-                            BinaryOperator::NotEquals => BinaryOp::NEq,
-                            BinaryOperator::Plus => BinaryOp::Add,
-                            BinaryOperator::Minus => BinaryOp::Sub,
-                            BinaryOperator::Multiply => BinaryOp::Mul,
-                            BinaryOperator::Divide => BinaryOp::Div,
-                            BinaryOperator::Modulo => BinaryOp::Mod,
-                            BinaryOperator::BitwiseAnd => BinaryOp::And,
-                            BinaryOperator::BitwiseOr => BinaryOp::Or,
-                            BinaryOperator::BitwiseXor => BinaryOp::Xor,
-                            BinaryOperator::ShiftLeft => BinaryOp::LShift,
-                            BinaryOperator::ShiftRight => BinaryOp::RShift,
-
-                            _ => unreachable!(),
-                        };
-
-                        let result_slot_id =
-                            framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
-
-                        units = [lhs_ir.as_slice(), rhs_ir.as_slice()].concat();
-                        units.push(IrStatement::BinaryOp {
-                            dst: result_slot_id.clone(),
-                            op: binaryop,
-                            l: lhs,
-                            r: rhs,
-                        });
+        match &binaryexpr.operator.node {
+            BinaryOperator::LogicalAnd => {
+                let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+                let (lfalseid, lfalse) = self.newlabel();
+                let (lendid, lend) = self.newlabel();
 
-                        return Ok((Operand::Var(result_slot_id), units));
-                    }
-                }
-            }
+                units = lhs_ir;
 
-            Expression::AssignOperator(assign_expr) => {
-                let (lhs, lhs_ir) = self.emit_expr(&assign_expr.lhs.node, scopes, framebuilder)?;
-                let (rhs, rhs_ir) = self.emit_expr(&assign_expr.rhs.node, scopes, framebuilder)?;
+                units.push(IrStatement::JmpIfZero {
+                    cond: lhs.clone(),
+                    target: lfalseid,
+                });
 
-                let mut units = [lhs_ir.as_slice(), rhs_ir.as_slice()].concat();
+                units.extend(rhs_ir);
+                units.push(IrStatement::BinaryOp {
+                    dst: dst.clone(),
+                    op: BinaryOp::NEq,
+                    l: rhs,
+                    r: Operand::Const(0),
+                });
+                units.push(IrStatement::Jmp(lendid));
 
-                if let Operand::Var(lhs_slot_id) = &lhs {
-                    match &assign_expr.operator.node {
-                        AssignOperator::Assign => {
-                            units.push(IrStatement::Copy {
-                                dst: lhs_slot_id.clone(),
-                                src: rhs,
-                            });
-                        }
+                units.push(lfalse);
+                units.push(IrStatement::Copy {
+                    dst: dst.clone(),
+                    src: Operand::Const(0),
+                });
 
-                        compound_assign_op => {
-                            let binaryop = match compound_assign_op {
-                                AssignOperator::Assign => unreachable!(),
-
-                                AssignOperator::AssignPlus => BinaryOp::Add,
-                                AssignOperator::AssignMinus => BinaryOp::Sub,
-                                AssignOperator::AssignMultiply => BinaryOp::Mul,
-                                AssignOperator::AssignDivide => BinaryOp::Div,
-                                AssignOperator::AssignModulo => BinaryOp::Mod,
-                                AssignOperator::AssignBitwiseAnd => BinaryOp::And,
-                                AssignOperator::AssignBitwiseOr => BinaryOp::Or,
-                                AssignOperator::AssignBitwiseXor => BinaryOp::Xor,
-                                AssignOperator::AssignShiftLeft => BinaryOp::LShift,
-                                AssignOperator::AssignShiftRight => BinaryOp::RShift,
-                            };
-
-                            units.push(IrStatement::BinaryOp {
-                                dst: lhs_slot_id.clone(),
-                                op: binaryop,
-                                l: lhs.clone(),
-                                r: rhs,
-                            });
-                        }
-                    }
-                    Ok((lhs, units))
-                } else {
-                    Err(CompilerError {
-                        kind: CompilerErrorKind::SemanticError,
-                        message: format!("lhs of an assignment expression must be an lvalue"),
-                        span: Some(assign_expr.operator.span),
-                    })
-                }
+                units.push(lend);
+                Ok((Operand::Var(dst), units))
             }
 
-            Expression::TernaryOperator(ternaryopexpr) => {
-                let (condresult, mut units) = self.emit_expr(&ternaryopexpr.condition.node, scopes, framebuilder)?;
-                let ternary_expr_result_slot_id =
-                    framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
+            BinaryOperator::LogicalOr => {
+                let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+                let (levalrhsid, levalrhs) = self.newlabel();
+                let (lendid, lend) = self.newlabel();
 
-                let (lelse_id, lelse) = self.newlabel();
+                units = lhs_ir;
 
                 units.push(IrStatement::JmpIfZero {
-                    cond: condresult,
-                    target: lelse_id,
+                    cond: lhs.clone(),
+                    target: levalrhsid,
                 });
 
-                let (ifresult, if_ir) = self.emit_expr(&ternaryopexpr.if_expr.node, scopes, framebuilder)?;
-                units.extend(if_ir);
                 units.push(IrStatement::Copy {
-                    dst: ternary_expr_result_slot_id.clone(),
-                    src: ifresult,
+                    dst: dst.clone(),
+                    src: Operand::Const(1),
                 });
 
-                let (lendif_id, lendif) = self.newlabel();
-                units.push(IrStatement::Jmp(lendif_id));
-                units.push(lelse);
+                units.push(IrStatement::Jmp(lendid));
 
-                let (else_result, else_ir) = self.emit_expr(&ternaryopexpr.else_expr.node, scopes, framebuilder)?;
-                units.extend(else_ir);
-                units.push(IrStatement::Copy {
-                    dst: ternary_expr_result_slot_id.clone(),
-                    src: else_result,
+                units.push(levalrhs);
+                units.extend(rhs_ir);
+                units.push(IrStatement::BinaryOp {
+                    dst: dst.clone(),
+                    op: BinaryOp::NEq,
+                    l: rhs.clone(),
+                    r: Operand::Const(0),
                 });
 
-                units.push(lendif);
-                return Ok((Operand::Var(ternary_expr_result_slot_id), units));
+                units.push(lend);
+                Ok((Operand::Var(dst), units))
             }
 
-            Expression::ImplicitCast(cast) => {
-                let (operand, mut units) = self.emit_expr(&cast.expression, scopes, framebuilder)?;
-                match cast.target_type {
-                    // int-width integer: representation is unchanged, forward as-is.
-                    DataType::Integer {
-                        rank: IntegerRank::Int, ..
-                    } => return Ok((operand, units)),
-                    // `(_Bool)x` == `x != 0`; normalizes any int to 0/1.
-                    DataType::Integer {
-                        rank: IntegerRank::Bool,
-                        ..
-                    } => {
-                        let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Bool, false)))?;
-                        units.push(IrStatement::BinaryOp {
-                            dst: dst.clone(),
-                            op: BinaryOp::NEq,
-                            l: operand,
-                            r: Operand::Const(0),
-                        });
-                        return Ok((Operand::Var(dst), units));
-                    }
-                    // Width changes / float conversions need the source type too, which
-                    // `emit_expr` does not yet carry — out of scope for now.
-                    _ => {
+            binary_operator => {
+                let binaryop = match *binary_operator {
+                    BinaryOperator::Less => BinaryOp::Lt,
+                    BinaryOperator::LessOrEqual => BinaryOp::Le,
+                    BinaryOperator::Greater => BinaryOp::Gt,
+                    BinaryOperator::GreaterOrEqual => BinaryOp::Ge,
+                    BinaryOperator::Equals => BinaryOp::Eq,
+                    BinaryOperator::NotEquals => BinaryOp::NEq,
+                    BinaryOperator::Plus => BinaryOp::Add,
+                    BinaryOperator::Minus => BinaryOp::Sub,
+                    BinaryOperator::Multiply => BinaryOp::Mul,
+                    BinaryOperator::Divide => BinaryOp::Div,
+                    BinaryOperator::Modulo => BinaryOp::Mod,
+                    BinaryOperator::BitwiseAnd => BinaryOp::And,
+                    BinaryOperator::BitwiseOr => BinaryOp::Or,
+                    BinaryOperator::BitwiseXor => BinaryOp::Xor,
+                    BinaryOperator::ShiftLeft => BinaryOp::LShift,
+                    BinaryOperator::ShiftRight => BinaryOp::RShift,
+
+                    BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => {
                         return Err(CompilerError {
                             kind: CompilerErrorKind::InternalError,
-                            message: format!("unsupported implicit cast to `{}`", cast.target_type),
-                            span: None,
+                            message: format!("`{binary_operator:?}` should have been lowered with branches"),
+                            span: Some(binaryexpr.operator.span),
+                            ..Default::default()
                         })
                     }
-                }
+                };
+
+                let result_slot_id =
+                    framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+
+                units = [lhs_ir.as_slice(), rhs_ir.as_slice()].concat();
+                units.push(IrStatement::BinaryOp {
+                    dst: result_slot_id.clone(),
+                    op: binaryop,
+                    l: lhs,
+                    r: rhs,
+                });
+
+                Ok((Operand::Var(result_slot_id), units))
             }
+        }
+    }
 
-            Expression::Call(callexpr) => {
-                let funcname = match &callexpr.callee.node {
-                    Expression::Identifier(fname) => fname,
-                    _ => {
-                        return Err(CompilerError {
-                            kind: CompilerErrorKind::InternalError,
-                            message: "callee expressions other than an identifier expression is not supported"
-                                .to_string(),
-                            span: Some(callexpr.callee.span),
-                        })
-                    }
-                };
+    fn emit_assign_expr(
+        &mut self,
+        assign_expr: &AssignOperatorExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let (lhs, lhs_ir) = self.emit_expr(&assign_expr.lhs.node, scopes, framebuilder)?;
+        let (rhs, rhs_ir) = self.emit_expr(&assign_expr.rhs.node, scopes, framebuilder)?;
+
+        let mut units = [lhs_ir.as_slice(), rhs_ir.as_slice()].concat();
+
+        if let Operand::Var(lhs_slot_id) = &lhs {
+            // The operation a compound assignment performs, `None` for a plain `=`
+            let binaryop = match &assign_expr.operator.node {
+                AssignOperator::Assign => None,
+                AssignOperator::AssignPlus => Some(BinaryOp::Add),
+                AssignOperator::AssignMinus => Some(BinaryOp::Sub),
+                AssignOperator::AssignMultiply => Some(BinaryOp::Mul),
+                AssignOperator::AssignDivide => Some(BinaryOp::Div),
+                AssignOperator::AssignModulo => Some(BinaryOp::Mod),
+                AssignOperator::AssignBitwiseAnd => Some(BinaryOp::And),
+                AssignOperator::AssignBitwiseOr => Some(BinaryOp::Or),
+                AssignOperator::AssignBitwiseXor => Some(BinaryOp::Xor),
+                AssignOperator::AssignShiftLeft => Some(BinaryOp::LShift),
+                AssignOperator::AssignShiftRight => Some(BinaryOp::RShift),
+            };
 
-                let mut units: Vec<IrStatement> = Vec::new();
-                let mut args: Vec<Operand> = Vec::new();
+            match binaryop {
+                None => units.push(IrStatement::Copy {
+                    dst: lhs_slot_id.clone(),
+                    src: rhs,
+                }),
+                Some(binaryop) => units.push(IrStatement::BinaryOp {
+                    dst: lhs_slot_id.clone(),
+                    op: binaryop,
+                    l: lhs.clone(),
+                    r: rhs,
+                }),
+            }
+            Ok((lhs, units))
+        } else {
+            Err(CompilerError {
+                kind: CompilerErrorKind::SemanticError,
+                message: "lhs of an assignment expression must be an lvalue".to_string(),
+                span: Some(assign_expr.operator.span),
+                ..Default::default()
+            })
+        }
+    }
 
-                for param in &callexpr.argument_expr_list {
-                    let (argop, arg_ir) = self.emit_expr(&param.node, scopes, framebuilder)?;
-                    units.extend(arg_ir);
-                    args.push(argop);
-                }
+    fn emit_ternary_expr(
+        &mut self,
+        ternaryopexpr: &TernaryOperatorExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let (condresult, mut units) = self.emit_expr(&ternaryopexpr.condition.node, scopes, framebuilder)?;
+        let ternary_expr_result_slot_id =
+            framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+
+        let (lelse_id, lelse) = self.newlabel();
+
+        units.push(IrStatement::JmpIfZero {
+            cond: condresult,
+            target: lelse_id,
+        });
+
+        let (ifresult, if_ir) = self.emit_expr(&ternaryopexpr.if_expr.node, scopes, framebuilder)?;
+        units.extend(if_ir);
+        units.push(IrStatement::Copy {
+            dst: ternary_expr_result_slot_id.clone(),
+            src: ifresult,
+        });
+
+        let (lendif_id, lendif) = self.newlabel();
+        units.push(IrStatement::Jmp(lendif_id));
+        units.push(lelse);
+
+        let (else_result, else_ir) = self.emit_expr(&ternaryopexpr.else_expr.node, scopes, framebuilder)?;
+        units.extend(else_ir);
+        units.push(IrStatement::Copy {
+            dst: ternary_expr_result_slot_id.clone(),
+            src: else_result,
+        });
+
+        units.push(lendif);
+        Ok((Operand::Var(ternary_expr_result_slot_id), units))
+    }
 
-                let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)))?;
-                units.push(IrStatement::Call {
-                    dst: Some(dst.clone()),
-                    name: funcname.clone(),
-                    args,
+    fn emit_implicit_cast(
+        &mut self,
+        cast: &ImplicitCastExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let (operand, mut units) = self.emit_expr(&cast.expression, scopes, framebuilder)?;
+        match cast.target_type {
+            // int-width integer: representation is unchanged, forward as-is.
+            DataType::Integer {
+                rank: IntegerRank::Int, ..
+            } => Ok((operand, units)),
+            // `(_Bool)x` == `x != 0`; normalizes any int to 0/1. The result is an
+            // `int` like every other comparison, as wide as the backends store it.
+            DataType::Integer {
+                rank: IntegerRank::Bool,
+                ..
+            } => {
+                let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+                units.push(IrStatement::BinaryOp {
+                    dst: dst.clone(),
+                    op: BinaryOp::NEq,
+                    l: operand,
+                    r: Operand::Const(0),
                 });
-                return Ok((Operand::Var(dst), units));
+                Ok((Operand::Var(dst), units))
             }
-
-            Expression::Empty => Err(CompilerError {
-                kind: CompilerErrorKind::InternalError,
-                message: "empty expression reached IR lowering".to_string(),
-                span: None,
-            }),
+            // Width changes / float conversions need the source type too, which
+            // `emit_expr` does not yet carry — out of scope for now.
             _ => Err(CompilerError {
                 kind: CompilerErrorKind::InternalError,
-                message: format!("emit_expr not implemented for {:?} yet", expr),
+                message: format!("unsupported implicit cast to `{}`", cast.target_type),
                 span: None,
+                code: Some(ErrorCode::Unsupported),
+                ..Default::default()
             }),
         }
     }
+
+    fn emit_call_expr(
+        &mut self,
+        callexpr: &CallExpression,
+        scopes: &mut Vec<HashMap<String, SlotID>>,
+        framebuilder: &mut FrameBuilder,
+    ) -> Result<(Operand, Vec<IrStatement>), CompilerError> {
+        let funcname = match &callexpr.callee.node {
+            Expression::Identifier(fname) => fname,
+            _ => {
+                return Err(CompilerError {
+                    kind: CompilerErrorKind::InternalError,
+                    message: "callee expressions other than an identifier expression is not supported".to_string(),
+                    span: Some(callexpr.callee.span),
+                    code: Some(ErrorCode::Unsupported),
+                    ..Default::default()
+                })
+            }
+        };
+
+        let mut units: Vec<IrStatement> = Vec::new();
+        let mut args: Vec<Operand> = Vec::new();
+
+        for param in &callexpr.argument_expr_list {
+            let (argop, arg_ir) = self.emit_expr(&param.node, scopes, framebuilder)?;
+            units.extend(arg_ir);
+            args.push(argop);
+        }
+
+        let dst = framebuilder.allocate(Type::new(DataType::new_integer(IntegerRank::Int, true)), None)?;
+        units.push(IrStatement::Call {
+            dst: Some(dst.clone()),
+            name: funcname.clone(),
+            args,
+            tail: false,
+        });
+        Ok((Operand::Var(dst), units))
+    }
 }
 
 // -----------------------------------------------------------
@@ -884,10 +1217,13 @@ impl fmt::Display for IrStatement {
             IrStatement::Label(id) => write!(f, "L{id}:"),
             IrStatement::Jmp(target) => write!(f, "jmp L{target}"),
             IrStatement::JmpIfZero { cond, target } => write!(f, "jz {cond}, L{target}"),
-            IrStatement::Call { dst, name, args } => {
+            IrStatement::Call { dst, name, args, tail } => {
                 if let Some(dst) = dst {
                     write!(f, "{dst} = ")?;
                 }
+                if *tail {
+                    write!(f, "tail ")?;
+                }
                 write!(f, "call {name}(")?;
                 for (i, arg) in args.iter().enumerate() {
                     if i > 0 {
@@ -898,6 +1234,7 @@ impl fmt::Display for IrStatement {
                 write!(f, ")")
             }
             IrStatement::Ret(value) => write!(f, "ret {value}"),
+            IrStatement::Loc(location) => write!(f, "# line {location}"),
         }
     }
 }
@@ -912,7 +1249,10 @@ impl fmt::Display for IrFunction {
             }
             write!(f, "{param}")?;
         }
-        writeln!(f, ") [frame={}]:", self.framesize)?;
+        match self.inline {
+            InlineHint::None => writeln!(f, ") [frame={}]:", self.framesize)?,
+            hint => writeln!(f, ") [frame={}, {:?}]:", self.framesize, hint)?,
+        }
 
         // Body: statements indented, labels kept at the margin so they stand out.
         for statement in &self.body {
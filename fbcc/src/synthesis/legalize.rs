@@ -0,0 +1,139 @@
+//! Rewrites ARM64 instructions whose immediates don't fit their encoding. The emitter
+//! addresses every slot from sp and allocates the whole frame with one `sub`, which only
+//! assembles while the frame is small: `add` and `sub` take 12-bit immediates (optionally
+//! shifted by 12), `ldr` and `str` reach 16 KiB above their base and `ldp` and `stp` only
+//! 504 bytes.
+//!
+//! Runs last, after the [peephole optimiser](super::peephole), since the code it produces
+//! goes through x16, the intra-procedure-call scratch register nothing else uses.
+
+use crate::synthesis::arm64::{access_size, AluOp, Inst, Mem, Operand2, Reg};
+
+const SCRATCH: Reg = Reg::X(16);
+
+/// Legalises the instructions of one function.
+pub fn legalize(code: &mut Vec<Inst>) {
+    let mut legal = Vec::with_capacity(code.len());
+    // how far above sp the frame record lies once the prologue has set up x29, and right now
+    let mut frame_record = None;
+    let mut x29 = None;
+
+    for inst in code.drain(..) {
+        match inst.clone() {
+            Inst::Alu {
+                op: op @ (AluOp::Add | AluOp::Sub),
+                dst,
+                l,
+                r: Operand2::Imm(imm),
+            } => add_immediate(&mut legal, op, dst, l, imm),
+            Inst::Ldr { dst, mem } => {
+                let mem = address(&mut legal, mem, x29, |mem| mem.fits(access_size(dst)));
+                legal.push(Inst::Ldr { dst, mem });
+            }
+            Inst::Str { src, mem } => {
+                let mem = address(&mut legal, mem, x29, |mem| mem.fits(access_size(src)));
+                legal.push(Inst::Str { src, mem });
+            }
+            Inst::Ldp { first, second, mem } => {
+                let mem = address(&mut legal, mem, x29, Mem::fits_pair);
+                legal.push(Inst::Ldp { first, second, mem });
+            }
+            Inst::Stp { first, second, mem } => {
+                let mem = address(&mut legal, mem, x29, Mem::fits_pair);
+                legal.push(Inst::Stp { first, second, mem });
+            }
+            inst => legal.push(inst),
+        }
+
+        // follow sp and x29, so slots can be addressed from x29 instead
+        match inst {
+            Inst::Alu {
+                op: AluOp::Add,
+                dst: Reg::X(29),
+                l: Reg::Sp,
+                r: Operand2::Imm(imm),
+            } => {
+                frame_record = Some(imm as i64);
+                x29 = frame_record;
+            }
+            Inst::Alu {
+                op,
+                dst: Reg::Sp,
+                l: Reg::Sp,
+                r: Operand2::Imm(imm),
+            } => {
+                let imm = imm as i64;
+                x29 = x29.map(|x29| if op == AluOp::Sub { x29 + imm } else { x29 - imm });
+            }
+            Inst::Ldp { first: Reg::X(29), .. } => x29 = None,
+            // every jump to a label happens with the frame fully set up and no outgoing
+            // arguments allocated
+            Inst::Label(_) => x29 = frame_record,
+            _ => {}
+        }
+    }
+    *code = legal;
+}
+
+/// Pushes `dst = l op imm`, split in two if `imm` needs more than 12 bits and computed
+/// through the scratch register if it needs more than 24.
+fn add_immediate(code: &mut Vec<Inst>, op: AluOp, dst: Reg, l: Reg, imm: u32) {
+    let alu = |l, r| Inst::Alu { op, dst, l, r };
+    let (high, low) = (imm & !0xfff, imm & 0xfff);
+    if high == 0 || low == 0 && high <= 0xfff000 {
+        code.push(alu(l, Operand2::Imm(imm)));
+    } else if high <= 0xfff000 {
+        code.push(alu(l, Operand2::Imm(high)));
+        code.push(alu(dst, Operand2::Imm(low)));
+    } else {
+        let Reg::X(scratch) = SCRATCH else { unreachable!() };
+        code.push(Inst::MovImm {
+            dst: Reg::W(scratch),
+            imm: imm as u16,
+        });
+        code.push(Inst::Movk {
+            dst: Reg::W(scratch),
+            imm: (imm >> 16) as u16,
+        });
+        code.push(alu(l, Operand2::Reg(SCRATCH)));
+    }
+}
+
+/// An address the access can encode, for the same memory as `mem`. `x29` is how far the
+/// frame record lies above sp, while it is set up.
+fn address(code: &mut Vec<Inst>, mem: Mem, x29: Option<i64>, fits: impl Fn(&Mem) -> bool) -> Mem {
+    if fits(&mem) {
+        return mem;
+    }
+
+    // slots near the top of a large frame are closer to the frame record
+    if let (Reg::Sp, Some(x29)) = (mem.base, x29) {
+        let relative = Mem {
+            base: Reg::X(29),
+            offset: mem.offset - x29,
+        };
+        if fits(&relative) {
+            return relative;
+        }
+    }
+
+    // otherwise add the part of the offset that doesn't fit to the base in x16
+    let low = Mem {
+        base: SCRATCH,
+        offset: mem.offset & 0xfff,
+    };
+    if mem.offset >= 0 && fits(&low) {
+        add_immediate(code, AluOp::Add, SCRATCH, mem.base, (mem.offset & !0xfff) as u32);
+        return low;
+    }
+    let (op, offset) = if mem.offset < 0 {
+        (AluOp::Sub, -mem.offset)
+    } else {
+        (AluOp::Add, mem.offset)
+    };
+    add_immediate(code, op, SCRATCH, mem.base, offset as u32);
+    Mem {
+        base: SCRATCH,
+        offset: 0,
+    }
+}
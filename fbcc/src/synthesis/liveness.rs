@@ -0,0 +1,72 @@
+//! Liveness of the slots of a [`Cfg`].
+//!
+//! A slot is live at a point if some path from there reads it before writing it. Liveness is
+//! a backward dataflow problem, solved per block and refined to single statements by walking
+//! a block backwards from its live-out set.
+
+use crate::synthesis::{
+    cfg::{BlockId, Cfg},
+    ir::Operand,
+};
+
+pub struct Liveness {
+    /// Slots live on entry to each block
+    pub live_in: Vec<Vec<bool>>,
+    /// Slots live on exit from each block
+    pub live_out: Vec<Vec<bool>>,
+}
+
+impl Liveness {
+    pub fn new(cfg: &Cfg) -> Self {
+        // 1. slots read before being written in each block, and slots written in it
+        let mut upward = vec![vec![false; cfg.slots.len()]; cfg.blocks.len()];
+        let mut written = vec![vec![false; cfg.slots.len()]; cfg.blocks.len()];
+        for (block, data) in cfg.blocks.iter().enumerate() {
+            let mut read = |operand: &Operand, written: &[bool]| {
+                if let Operand::Var(var) = operand {
+                    if !written[var.index()] {
+                        upward[block][var.index()] = true;
+                    }
+                }
+            };
+            for statement in &data.statements {
+                for operand in statement.operands() {
+                    read(operand, &written[block]);
+                }
+                if let Some(dst) = statement.dst() {
+                    written[block][dst.index()] = true;
+                }
+            }
+            if let Some(operand) = data.terminator.operand() {
+                read(operand, &written[block]);
+            }
+        }
+
+        // 2. iterate live_out = ∪ live_in of the successors and
+        //    live_in = upward ∪ (live_out − written) to a fixed point, in postorder so that
+        //    most successors are done before their predecessors
+        let mut live_in = upward;
+        let mut live_out = vec![vec![false; cfg.slots.len()]; cfg.blocks.len()];
+        let postorder: Vec<BlockId> = cfg.reverse_postorder().into_iter().rev().collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in &postorder {
+                for successor in cfg.successors(block) {
+                    for slot in 0..cfg.slots.len() {
+                        if !live_in[successor][slot] || live_out[block][slot] {
+                            continue;
+                        }
+                        live_out[block][slot] = true;
+                        if !written[block][slot] && !live_in[block][slot] {
+                            live_in[block][slot] = true;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        Liveness { live_in, live_out }
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Write;
+
+use crate::{
+    core::errors::CompilerError,
+    synthesis::ir::{BinaryOp, IrFunction, IrStatement, Operand, UnaryOp},
+};
+
+/// Emits textual LLVM IR (opaque pointers, so LLVM 15 or newer).
+///
+/// Every slot becomes an `alloca` in the entry block, read and written with `load`/`store`;
+/// `opt -passes=mem2reg` turns them into SSA values. Labels become basic blocks, and code
+/// after a jump that no label leads to is put in a block of its own, as LLVM requires every
+/// block to end in exactly one terminator.
+pub struct LlvmIrEmitter {
+    triple: &'static str,
+}
+
+/// Per-function numbering of the values and blocks that don't come from the IR.
+struct FunctionState {
+    temps: usize,
+    blocks: usize,
+    /// Whether the current block already ended in a terminator
+    terminated: bool,
+}
+
+impl FunctionState {
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("%t{}", self.temps - 1)
+    }
+
+    fn block(&mut self) -> String {
+        self.blocks += 1;
+        format!("b{}", self.blocks - 1)
+    }
+}
+
+impl LlvmIrEmitter {
+    pub fn new(triple: &'static str) -> Self {
+        Self { triple }
+    }
+
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
+        let mut ll = String::new();
+        writeln!(ll, "; ModuleID = 'fbcc'").unwrap();
+        writeln!(ll, "target triple = \"{}\"", self.triple).unwrap();
+
+        for function in irfuncs {
+            writeln!(ll).unwrap();
+            ll.push_str(self.emit_func(function).as_str());
+        }
+
+        // functions called but not defined here are declared with the signature of the call
+        let declared = IrFunction::undefined_callees(irfuncs)?;
+        if !declared.is_empty() {
+            writeln!(ll).unwrap();
+        }
+        for (name, arity) in declared {
+            writeln!(ll, "declare i32 @{name}({})", vec!["i32"; arity].join(", ")).unwrap();
+        }
+
+        Ok(ll)
+    }
+
+    fn emit_func(&self, function: &IrFunction) -> String {
+        let mut ll = String::new();
+        let mut state = FunctionState {
+            temps: 0,
+            blocks: 0,
+            terminated: false,
+        };
+
+        let params: Vec<String> = (0..function.params.len())
+            .map(|index| format!("i32 %a{index}"))
+            .collect();
+        writeln!(ll, "define i32 @{}({}) {{", function.name, params.join(", ")).unwrap();
+        writeln!(ll, "entry:").unwrap();
+
+        // 1. a stack slot for every IR slot, so that the body only ever loads and stores
+        for slot in 0..function.slots.len() {
+            writeln!(ll, "  %r{slot} = alloca i32, align 4").unwrap();
+        }
+
+        // 2. spill the incoming arguments into their slots
+        for (index, param) in function.params.iter().enumerate() {
+            writeln!(ll, "  store i32 %a{index}, ptr %{param}, align 4").unwrap();
+        }
+
+        for statement in &function.body {
+            // code following a terminator is unreachable but still needs a block
+            if state.terminated && statement.is_code() {
+                let block = state.block();
+                writeln!(ll, "{block}:").unwrap();
+                state.terminated = false;
+            }
+
+            match statement {
+                IrStatement::BinaryOp { dst, op, l, r } => {
+                    let l = self.emit_operand(l, &mut state, &mut ll);
+                    let r = self.emit_operand(r, &mut state, &mut ll);
+
+                    let predicate = match op {
+                        BinaryOp::Lt => Some("slt"),
+                        BinaryOp::Le => Some("sle"),
+                        BinaryOp::Gt => Some("sgt"),
+                        BinaryOp::Ge => Some("sge"),
+                        BinaryOp::Eq => Some("eq"),
+                        BinaryOp::NEq => Some("ne"),
+                        _ => None,
+                    };
+                    let result = match predicate {
+                        // comparisons give an `i1`, which C wants as an `int` 0 or 1
+                        Some(predicate) => self.emit_compare(predicate, &l, &r, &mut state, &mut ll),
+                        None => {
+                            // shifting by 32 or more is poison in LLVM, so the count wraps like
+                            // it does on the machine and in the interpreter
+                            let r = if matches!(op, BinaryOp::LShift | BinaryOp::RShift) {
+                                let count = state.temp();
+                                writeln!(ll, "  {count} = and i32 {r}, 31").unwrap();
+                                count
+                            } else {
+                                r
+                            };
+                            let instruction = match op {
+                                BinaryOp::Add => "add",
+                                BinaryOp::Sub => "sub",
+                                BinaryOp::Mul => "mul",
+                                BinaryOp::Div => "sdiv",
+                                BinaryOp::Mod => "srem",
+                                BinaryOp::And => "and",
+                                BinaryOp::Or => "or",
+                                BinaryOp::Xor => "xor",
+                                BinaryOp::LShift => "shl",
+                                BinaryOp::RShift => "ashr",
+                                _ => unreachable!("comparisons are handled above"),
+                            };
+                            let result = state.temp();
+                            writeln!(ll, "  {result} = {instruction} i32 {l}, {r}").unwrap();
+                            result
+                        }
+                    };
+                    writeln!(ll, "  store i32 {result}, ptr %{dst}, align 4").unwrap();
+                }
+
+                IrStatement::UnaryOp { dst, op, src } => {
+                    let src = self.emit_operand(src, &mut state, &mut ll);
+                    let result = match op {
+                        UnaryOp::Minus => {
+                            let result = state.temp();
+                            writeln!(ll, "  {result} = sub i32 0, {src}").unwrap();
+                            result
+                        }
+                        UnaryOp::Comp => {
+                            let result = state.temp();
+                            writeln!(ll, "  {result} = xor i32 {src}, -1").unwrap();
+                            result
+                        }
+                        UnaryOp::Not => self.emit_compare("eq", &src, "0", &mut state, &mut ll),
+                    };
+                    writeln!(ll, "  store i32 {result}, ptr %{dst}, align 4").unwrap();
+                }
+
+                IrStatement::Copy { dst, src } => {
+                    let src = self.emit_operand(src, &mut state, &mut ll);
+                    writeln!(ll, "  store i32 {src}, ptr %{dst}, align 4").unwrap();
+                }
+
+                IrStatement::Label(label) => {
+                    // falling into a label is an explicit branch in LLVM
+                    if !state.terminated {
+                        writeln!(ll, "  br label %L{label}").unwrap();
+                    }
+                    writeln!(ll, "L{label}:").unwrap();
+                    state.terminated = false;
+                }
+                IrStatement::Loc(_) => {}
+
+                IrStatement::Jmp(label) => {
+                    writeln!(ll, "  br label %L{label}").unwrap();
+                    state.terminated = true;
+                }
+
+                IrStatement::JmpIfZero { cond, target } => {
+                    let cond = self.emit_operand(cond, &mut state, &mut ll);
+                    let is_zero = state.temp();
+                    writeln!(ll, "  {is_zero} = icmp eq i32 {cond}, 0").unwrap();
+
+                    let fallthrough = state.block();
+                    writeln!(ll, "  br i1 {is_zero}, label %L{target}, label %{fallthrough}").unwrap();
+                    writeln!(ll, "{fallthrough}:").unwrap();
+                }
+
+                IrStatement::Call { dst, name, args, tail } => {
+                    let args: Vec<String> = args
+                        .iter()
+                        .map(|arg| format!("i32 {}", self.emit_operand(arg, &mut state, &mut ll)))
+                        .collect();
+                    let result = state.temp();
+                    let marker = if *tail { "tail " } else { "" };
+                    writeln!(ll, "  {result} = {marker}call i32 @{name}({})", args.join(", ")).unwrap();
+                    if let Some(dst) = dst {
+                        writeln!(ll, "  store i32 {result}, ptr %{dst}, align 4").unwrap();
+                    }
+                }
+
+                IrStatement::Ret(value) => {
+                    let value = self.emit_operand(value, &mut state, &mut ll);
+                    writeln!(ll, "  ret i32 {value}").unwrap();
+                    state.terminated = true;
+                }
+            }
+        }
+
+        // a block has to end in a terminator, so running off the end returns 0 explicitly
+        if !state.terminated {
+            writeln!(ll, "  ret i32 0").unwrap();
+        }
+        writeln!(ll, "}}").unwrap();
+
+        ll
+    }
+
+    /// Compares `l` with `r` and widens the `i1` result to an `i32`.
+    fn emit_compare(&self, predicate: &str, l: &str, r: &str, state: &mut FunctionState, ll: &mut String) -> String {
+        let flag = state.temp();
+        writeln!(ll, "  {flag} = icmp {predicate} i32 {l}, {r}").unwrap();
+        let result = state.temp();
+        writeln!(ll, "  {result} = zext i1 {flag} to i32").unwrap();
+        result
+    }
+
+    /// Returns the LLVM value for an operand, loading it from its slot first if needed.
+    fn emit_operand(&self, operand: &Operand, state: &mut FunctionState, ll: &mut String) -> String {
+        match operand {
+            Operand::Var(slot) => {
+                let value = state.temp();
+                writeln!(ll, "  {value} = load i32, ptr %{slot}, align 4").unwrap();
+                value
+            }
+            Operand::Const(constant) => (*constant as i32).to_string(),
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! Loop optimisations.
+//!
+//! Each pass visits the natural loops found by [`LoopNest`] innermost first, with the
+//! analyses recomputed for every loop. Slots are plain variables rather than SSA values, so
+//! statements can be copied or moved without renaming anything.
+//!
+//! - [`rotate_loops`] copies the exit test at the top of a loop to its bottom. Every
+//!   iteration then branches once instead of twice, and the test left at the top becomes a
+//!   guard: once past it, the body runs at least once.
+//! - [`hoist_invariants`] moves computations whose operands don't change inside a loop into
+//!   a preheader that runs once before the loop.
+//! - [`reduce_strength`] replaces the product of an induction variable and a constant with a
+//!   second variable that grows by a multiple of the constant whenever the first one grows.
+//! - [`unroll_loops`] replaces small loops that run a known number of times with that many
+//!   copies of their body.
+
+use crate::synthesis::{
+    cfg::{BasicBlock, BlockId, Cfg, Terminator},
+    dominators::DominatorTree,
+    ir::{BinaryOp, IrStatement, Operand, SlotID, UnaryOp},
+    liveness::Liveness,
+    loops::{Loop, LoopNest},
+    sccp::{fold_binary, fold_unary},
+};
+
+/// Largest loop header, in statements, copied to the bottom of the loop.
+const ROTATE_LIMIT: usize = 8;
+
+/// Most iterations a loop may run to be unrolled.
+const UNROLL_TRIPS: usize = 16;
+
+/// Most statements an unrolled loop may grow to.
+const UNROLL_LIMIT: usize = 64;
+
+/// A block a pass added, to be laid out right before or after `anchor` once the pass is
+/// done; until then block numbers have to stay put.
+struct Placement {
+    block: BlockId,
+    anchor: BlockId,
+    after: bool,
+}
+
+/// Rotates top-tested loops into bottom-tested ones. Returns the number of loops rotated.
+pub fn rotate_loops(cfg: &mut Cfg) -> usize {
+    for_each_loop(cfg, |cfg, nest, index, _, _| rotate(cfg, &nest.loops[index]) as usize)
+}
+
+/// Moves loop-invariant computations into preheaders. Returns the number of statements
+/// moved.
+pub fn hoist_invariants(cfg: &mut Cfg) -> usize {
+    for_each_loop(cfg, |cfg, nest, index, domtree, placements| {
+        hoist(cfg, &nest.loops[index], domtree, placements)
+    })
+}
+
+/// Turns multiplications of induction variables by constants into additions. Returns the
+/// number of multiplications replaced.
+pub fn reduce_strength(cfg: &mut Cfg) -> usize {
+    for_each_loop(cfg, |cfg, nest, index, _, placements| {
+        reduce(cfg, &nest.loops[index], placements)
+    })
+}
+
+/// Fully unrolls small innermost loops with a constant trip count. Returns the number of
+/// loops unrolled.
+pub fn unroll_loops(cfg: &mut Cfg) -> usize {
+    for_each_loop(cfg, |cfg, nest, index, domtree, placements| {
+        unroll(cfg, nest, index, domtree, placements) as usize
+    })
+}
+
+/// Calls `visit` once for every loop, innermost first, and sums up what it returns. Blocks
+/// `visit` adds are laid out where it asks for once every loop has been visited.
+fn for_each_loop(
+    cfg: &mut Cfg,
+    mut visit: impl FnMut(&mut Cfg, &LoopNest, usize, &DominatorTree, &mut Vec<Placement>) -> usize,
+) -> usize {
+    // a preheader in front of a loop at the very start would become the new entry block
+    cfg.split_entry();
+
+    let mut visited: Vec<BlockId> = Vec::new();
+    let mut placements = Vec::new();
+    let mut total = 0;
+    loop {
+        let domtree = DominatorTree::new(cfg);
+        let nest = LoopNest::new(cfg, &domtree);
+        // loops come after the loops enclosing them, so the last one not visited yet has
+        // no unvisited loops inside it
+        let Some(index) = (0..nest.loops.len())
+            .rev()
+            .find(|index| !visited.contains(&nest.loops[*index].header))
+        else {
+            break;
+        };
+        visited.push(nest.loops[index].header);
+        total += visit(cfg, &nest, index, &domtree, &mut placements);
+    }
+
+    if !placements.is_empty() {
+        let mut order: Vec<BlockId> = (0..cfg.blocks.len()).collect();
+        for placement in placements {
+            order.retain(|block| *block != placement.block);
+            let anchor = order.iter().position(|block| *block == placement.anchor).unwrap();
+            order.insert(anchor + placement.after as usize, placement.block);
+        }
+        cfg.reorder_blocks(&order);
+    }
+    total
+}
+
+/// The block that runs right before the loop every time it is entered, with the header as
+/// its only successor. One is added if the loop doesn't have one.
+fn preheader(cfg: &mut Cfg, lp: &Loop, placements: &mut Vec<Placement>) -> BlockId {
+    let entries: Vec<BlockId> = cfg.predecessors()[lp.header]
+        .iter()
+        .copied()
+        .filter(|pred| !lp.contains(*pred))
+        .collect();
+    if let [entry] = entries[..] {
+        if cfg.successors(entry) == [lp.header] {
+            return entry;
+        }
+    }
+
+    let preheader = cfg.blocks.len();
+    cfg.blocks.push(BasicBlock {
+        label: None,
+        statements: Vec::new(),
+        terminator: Terminator::Jmp(lp.header),
+    });
+    for entry in entries {
+        cfg.blocks[entry]
+            .terminator
+            .map_successors(|target| if target == lp.header { preheader } else { target });
+    }
+    placements.push(Placement {
+        block: preheader,
+        anchor: lp.header,
+        after: false,
+    });
+    preheader
+}
+
+/// How many statements of the loop write each slot.
+fn definitions(cfg: &Cfg, lp: &Loop) -> Vec<usize> {
+    let mut definitions = vec![0; cfg.slots.len()];
+    for &block in &lp.blocks {
+        for statement in &cfg.blocks[block].statements {
+            if let Some(dst) = statement.dst() {
+                definitions[dst.index()] += 1;
+            }
+        }
+    }
+    definitions
+}
+
+fn rotate(cfg: &mut Cfg, lp: &Loop) -> bool {
+    let header = lp.header;
+    let [latch] = lp.latches[..] else {
+        return false;
+    };
+    let Terminator::Branch {
+        cond: Operand::Var(cond),
+        zero,
+        nonzero,
+    } = cfg.blocks[header].terminator.clone()
+    else {
+        return false;
+    };
+    if latch == header
+        || !matches!(cfg.blocks[latch].terminator, Terminator::Jmp(_))
+        || cfg.blocks[header].size() > ROTATE_LIMIT
+    {
+        return false;
+    }
+    let (body, exit, exit_on_zero) = match (lp.contains(zero), lp.contains(nonzero)) {
+        (true, false) => (zero, nonzero, false),
+        (false, true) => (nonzero, zero, true),
+        _ => return false,
+    };
+
+    // The latch branches back to the body on zero, so that it can fall through to the
+    // exit. A test that leaves on zero has to be inverted for that: flip the comparison
+    // computing it if nothing else reads its result, or negate it otherwise.
+    let mut test = cfg.blocks[header].statements.clone();
+    let mut cond = cond;
+    if exit_on_zero {
+        let liveness = Liveness::new(cfg);
+        let read_later = liveness.live_in[body][cond.index()] || liveness.live_in[exit][cond.index()];
+        let flipped = match test.last_mut() {
+            Some(IrStatement::BinaryOp { dst, op, .. }) if *dst == cond && !read_later => {
+                match inverse_comparison(op) {
+                    Some(inverse) => {
+                        *op = inverse;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        };
+        if !flipped {
+            let negated = cfg.new_slot_like(&cond);
+            test.push(IrStatement::UnaryOp {
+                dst: negated.clone(),
+                op: UnaryOp::Not,
+                src: Operand::Var(cond),
+            });
+            cond = negated;
+        }
+    }
+
+    let latch = &mut cfg.blocks[latch];
+    latch.statements.extend(test);
+    latch.terminator = Terminator::Branch {
+        cond: Operand::Var(cond),
+        zero: body,
+        nonzero: exit,
+    };
+    true
+}
+
+/// The comparison that is true exactly when `op` is false.
+fn inverse_comparison(op: &BinaryOp) -> Option<BinaryOp> {
+    let inverse = match op {
+        BinaryOp::Lt => BinaryOp::Ge,
+        BinaryOp::Le => BinaryOp::Gt,
+        BinaryOp::Gt => BinaryOp::Le,
+        BinaryOp::Ge => BinaryOp::Lt,
+        BinaryOp::Eq => BinaryOp::NEq,
+        BinaryOp::NEq => BinaryOp::Eq,
+        _ => return None,
+    };
+    Some(inverse)
+}
+
+fn hoist(cfg: &mut Cfg, lp: &Loop, domtree: &DominatorTree, placements: &mut Vec<Placement>) -> usize {
+    let volatile: Vec<bool> = cfg.slots.iter().map(|slot| slot.ty.qualifiers.is_volatile).collect();
+    let liveness = Liveness::new(cfg);
+    let mut definitions = definitions(cfg, lp);
+
+    let exiting: Vec<BlockId> = lp
+        .blocks
+        .iter()
+        .copied()
+        .filter(|block| cfg.successors(*block).iter().any(|successor| !lp.contains(*successor)))
+        .collect();
+    let live_at_exit: Vec<bool> = (0..cfg.slots.len())
+        .map(|slot| {
+            exiting.iter().any(|block| {
+                cfg.successors(*block)
+                    .iter()
+                    .any(|successor| !lp.contains(*successor) && liveness.live_in[*successor][slot])
+            })
+        })
+        .collect();
+
+    // A statement is invariant if it computes the same value in every iteration: all its
+    // operands come from outside the loop, and it is the only write to its destination,
+    // which no iteration reads before writing it. Running it before the loop instead has
+    // to be safe as well: either it runs whenever the loop is left anyway, or it can't trap
+    // and its result isn't needed after the loop.
+    let invariant = |statement: &IrStatement, block: BlockId, definitions: &[usize]| {
+        let Some(dst) = statement.dst() else {
+            return false;
+        };
+        let operands_invariant = statement.operands().iter().all(|operand| match operand {
+            Operand::Const(_) => true,
+            Operand::Var(var) => definitions[var.index()] == 0 && !volatile[var.index()],
+        });
+        let may_trap = matches!(
+            statement,
+            IrStatement::BinaryOp { op: BinaryOp::Div | BinaryOp::Mod, r, .. } if !matches!(r, Operand::Const(r) if *r != 0 && *r != -1)
+        );
+        !matches!(statement, IrStatement::Call { .. })
+            && operands_invariant
+            && definitions[dst.index()] == 1
+            && !volatile[dst.index()]
+            && !liveness.live_in[lp.header][dst.index()]
+            && (exiting.iter().all(|exit| domtree.dominates(block, *exit)) || !may_trap && !live_at_exit[dst.index()])
+    };
+
+    // hoisting a statement can make the ones reading its result invariant too
+    let mut hoisted = Vec::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in domtree.reverse_postorder().iter().filter(|block| lp.contains(**block)) {
+            let mut index = 0;
+            while index < cfg.blocks[block].statements.len() {
+                if invariant(&cfg.blocks[block].statements[index], block, &definitions) {
+                    let statement = cfg.blocks[block].statements.remove(index);
+                    definitions[statement.dst().unwrap().index()] -= 1;
+                    hoisted.push(statement);
+                    changed = true;
+                } else {
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    let count = hoisted.len();
+    if count > 0 {
+        let preheader = preheader(cfg, lp, placements);
+        cfg.blocks[preheader].statements.extend(hoisted);
+    }
+    count
+}
+
+fn reduce(cfg: &mut Cfg, lp: &Loop, placements: &mut Vec<Placement>) -> usize {
+    let volatile: Vec<bool> = cfg.slots.iter().map(|slot| slot.ty.qualifiers.is_volatile).collect();
+    let definitions = definitions(cfg, lp);
+
+    // 1. basic induction variables: written once in the loop, by adding a constant
+    let mut steps: Vec<Option<i32>> = vec![None; cfg.slots.len()];
+    for &block in &lp.blocks {
+        for statement in &cfg.blocks[block].statements {
+            let IrStatement::BinaryOp { dst, op, l, r } = statement else {
+                continue;
+            };
+            let step = match (op, l, r) {
+                (BinaryOp::Add, Operand::Var(var), Operand::Const(step))
+                | (BinaryOp::Add, Operand::Const(step), Operand::Var(var))
+                    if var == dst =>
+                {
+                    *step as i32
+                }
+                (BinaryOp::Sub, Operand::Var(var), Operand::Const(step)) if var == dst => (*step as i32).wrapping_neg(),
+                _ => continue,
+            };
+            if definitions[dst.index()] == 1 && !volatile[dst.index()] {
+                steps[dst.index()] = Some(step);
+            }
+        }
+    }
+
+    // 2. replace `d = i * k` with `d = s`, where `s` starts out as `i * k` and grows by
+    //    `step * k` right after `i` grows by `step`; shifts by a constant count multiply
+    let mut reduced: Vec<(SlotID, i32, SlotID)> = Vec::new();
+    let mut count = 0;
+    for &block in &lp.blocks {
+        for index in 0..cfg.blocks[block].statements.len() {
+            let IrStatement::BinaryOp { dst, op, l, r } = &cfg.blocks[block].statements[index] else {
+                continue;
+            };
+            let (var, factor) = match (op, l, r) {
+                (BinaryOp::Mul, Operand::Var(var), Operand::Const(factor))
+                | (BinaryOp::Mul, Operand::Const(factor), Operand::Var(var)) => (var, *factor as i32),
+                (BinaryOp::LShift, Operand::Var(var), Operand::Const(count @ 0..=31)) => (var, 1i32 << count),
+                _ => continue,
+            };
+            if steps[var.index()].is_none() || var == dst {
+                continue;
+            }
+
+            let (dst, var) = (dst.clone(), var.clone());
+            let scaled = match reduced.iter().find(|(iv, k, _)| *iv == var && *k == factor) {
+                Some((_, _, scaled)) => scaled.clone(),
+                None => {
+                    let scaled = cfg.new_slot_like(&var);
+                    reduced.push((var, factor, scaled.clone()));
+                    scaled
+                }
+            };
+            cfg.blocks[block].statements[index] = IrStatement::Copy {
+                dst,
+                src: Operand::Var(scaled),
+            };
+            count += 1;
+        }
+    }
+    if reduced.is_empty() {
+        return 0;
+    }
+
+    let preheader = preheader(cfg, lp, placements);
+    for (var, factor, scaled) in reduced {
+        cfg.blocks[preheader].statements.push(IrStatement::BinaryOp {
+            dst: scaled.clone(),
+            op: BinaryOp::Mul,
+            l: Operand::Var(var.clone()),
+            r: Operand::Const(factor as i64),
+        });
+
+        let step = steps[var.index()].unwrap();
+        let (block, index) = lp
+            .blocks
+            .iter()
+            .find_map(|&block| {
+                let statements = &cfg.blocks[block].statements;
+                let index = statements.iter().position(|statement| statement.dst() == Some(&var))?;
+                Some((block, index))
+            })
+            .unwrap();
+        cfg.blocks[block].statements.insert(
+            index + 1,
+            IrStatement::BinaryOp {
+                dst: scaled.clone(),
+                op: BinaryOp::Add,
+                l: Operand::Var(scaled),
+                r: Operand::Const(step.wrapping_mul(factor) as i64),
+            },
+        );
+    }
+    count
+}
+
+fn unroll(
+    cfg: &mut Cfg,
+    nest: &LoopNest,
+    index: usize,
+    domtree: &DominatorTree,
+    placements: &mut Vec<Placement>,
+) -> bool {
+    let lp = &nest.loops[index];
+    let [latch] = lp.latches[..] else {
+        return false;
+    };
+    let Terminator::Branch { cond, zero, nonzero } = cfg.blocks[latch].terminator.clone() else {
+        return false;
+    };
+    let continue_on_zero = match (zero == lp.header, nonzero == lp.header) {
+        (true, false) if !lp.contains(nonzero) => true,
+        (false, true) if !lp.contains(zero) => false,
+        _ => return false,
+    };
+    let exit = if continue_on_zero { nonzero } else { zero };
+    if nest.loops.iter().any(|other| other.parent == Some(index)) {
+        return false;
+    }
+    let size: usize = lp.blocks.iter().map(|block| cfg.blocks[*block].size()).sum();
+
+    // 1. the induction variable the test depends on: written once per iteration, by
+    //    adding a constant, and set to a constant before the loop
+    let definitions = definitions(cfg, lp);
+    let Some((var, step, update)) = lp.blocks.iter().find_map(|&block| {
+        cfg.blocks[block]
+            .statements
+            .iter()
+            .find_map(|statement| match statement {
+                IrStatement::BinaryOp {
+                    dst,
+                    op: op @ (BinaryOp::Add | BinaryOp::Sub),
+                    l: Operand::Var(var),
+                    r: Operand::Const(step),
+                } if var == dst
+                    && definitions[dst.index()] == 1
+                    && !cfg.slots[dst.index()].ty.qualifiers.is_volatile
+                    && domtree.dominates(block, latch) =>
+                {
+                    let step = *step as i32;
+                    let step = if matches!(op, BinaryOp::Sub) {
+                        step.wrapping_neg()
+                    } else {
+                        step
+                    };
+                    Some((dst.clone(), step, block))
+                }
+                _ => None,
+            })
+    }) else {
+        return false;
+    };
+    let Some(initial) = value_on_entry(cfg, lp, &var) else {
+        return false;
+    };
+
+    // 2. run the latch with only the induction variable known until the test says to leave
+    let mut value = initial;
+    let mut trips = None;
+    for trip in 1..=UNROLL_TRIPS {
+        if update != latch {
+            value = value.wrapping_add(step);
+        }
+        let mut known = vec![None; cfg.slots.len()];
+        known[var.index()] = Some(value);
+        for statement in &cfg.blocks[latch].statements {
+            evaluate(statement, &mut known);
+        }
+        value = known[var.index()].unwrap_or(value);
+        let test = match &cond {
+            Operand::Const(constant) => Some(*constant as i32),
+            Operand::Var(var) => known[var.index()],
+        };
+        match test {
+            Some(test) if (test == 0) == continue_on_zero => continue,
+            Some(_) => {
+                trips = Some(trip);
+                break;
+            }
+            None => return false,
+        }
+    }
+    let Some(trips) = trips else {
+        return false;
+    };
+    if size * trips > UNROLL_LIMIT {
+        return false;
+    }
+
+    // 3. chain `trips` copies of the loop, each latch going on to the next copy's header
+    //    and the last one leaving
+    let blocks = lp.blocks.clone();
+    let position = |block: BlockId| blocks.binary_search(&block).unwrap();
+    let mut last = *blocks.last().unwrap();
+    let mut headers = vec![lp.header];
+    let mut latches = vec![latch];
+    for _ in 1..trips {
+        let first = cfg.blocks.len();
+        let renumber = |block: BlockId| match blocks.binary_search(&block) {
+            Ok(position) => first + position,
+            Err(_) => block,
+        };
+        for &block in &blocks {
+            let mut copy = BasicBlock {
+                label: None,
+                ..cfg.blocks[block].clone()
+            };
+            copy.terminator.map_successors(renumber);
+            cfg.blocks.push(copy);
+            placements.push(Placement {
+                block: renumber(block),
+                anchor: last,
+                after: true,
+            });
+            last = renumber(block);
+        }
+        headers.push(first + position(lp.header));
+        latches.push(first + position(latch));
+    }
+    for (copy, &latch) in latches.iter().enumerate() {
+        cfg.blocks[latch].terminator = Terminator::Jmp(headers.get(copy + 1).copied().unwrap_or(exit));
+    }
+    true
+}
+
+/// The constant `var` holds when the loop is entered, found by walking back from the one
+/// block that enters it as long as there is only one way back.
+fn value_on_entry(cfg: &Cfg, lp: &Loop, var: &SlotID) -> Option<i32> {
+    let preds = cfg.predecessors();
+    let [mut block] = preds[lp.header]
+        .iter()
+        .copied()
+        .filter(|pred| !lp.contains(*pred))
+        .collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+
+    let mut visited = vec![false; cfg.blocks.len()];
+    while !visited[block] {
+        visited[block] = true;
+        let definition = cfg.blocks[block]
+            .statements
+            .iter()
+            .rev()
+            .find(|statement| statement.dst() == Some(var));
+        match definition {
+            Some(IrStatement::Copy {
+                src: Operand::Const(value),
+                ..
+            }) => return Some(*value as i32),
+            Some(_) => return None,
+            None => match preds[block][..] {
+                [pred] => block = pred,
+                _ => return None,
+            },
+        }
+    }
+    None
+}
+
+/// Folds `statement` if its operands are `known`, and forgets its destination otherwise.
+fn evaluate(statement: &IrStatement, known: &mut [Option<i32>]) {
+    let value = |operand: &Operand| match operand {
+        Operand::Const(constant) => Some(*constant as i32),
+        Operand::Var(var) => known[var.index()],
+    };
+    let result = match statement {
+        IrStatement::BinaryOp { op, l, r, .. } => value(l).zip(value(r)).and_then(|(l, r)| fold_binary(op, l, r)),
+        IrStatement::UnaryOp { op, src, .. } => value(src).map(|src| fold_unary(op, src)),
+        IrStatement::Copy { src, .. } => value(src),
+        _ => None,
+    };
+    if let Some(dst) = statement.dst() {
+        known[dst.index()] = result;
+    }
+}
@@ -0,0 +1,125 @@
+//! Natural loops of a [`Cfg`] and how they nest.
+//!
+//! A back edge is an edge whose target dominates its source; the target is the loop's
+//! header, and the loop is every block that reaches the source without passing through the
+//! header. Back edges to the same header make up one loop.
+
+use crate::synthesis::{
+    cfg::{BlockId, Cfg},
+    dominators::DominatorTree,
+};
+
+pub struct Loop {
+    pub header: BlockId,
+    /// Sources of the back edges to the header
+    pub latches: Vec<BlockId>,
+    /// Every block of the loop, header and nested loops included, in ascending order
+    pub blocks: Vec<BlockId>,
+    /// Innermost loop enclosing this one
+    pub parent: Option<usize>,
+    /// 1 for outermost loops
+    pub depth: usize,
+}
+
+impl Loop {
+    pub fn contains(&self, block: BlockId) -> bool {
+        self.blocks.binary_search(&block).is_ok()
+    }
+}
+
+pub struct LoopNest {
+    /// Loops ordered so that every loop comes after the loops enclosing it
+    pub loops: Vec<Loop>,
+    /// Innermost loop of every block
+    innermost: Vec<Option<usize>>,
+    /// Whether every retreating edge is a back edge; if not, some cycle has more than one
+    /// entry and isn't a natural loop
+    reducible: bool,
+}
+
+impl LoopNest {
+    pub fn new(cfg: &Cfg, domtree: &DominatorTree) -> Self {
+        let preds = cfg.predecessors();
+        let mut loops: Vec<Loop> = Vec::new();
+        let mut reducible = true;
+
+        // 1. collect the natural loop of every header, visiting headers in reverse postorder
+        //    so that outer loops are found before the loops they contain
+        for &header in domtree.reverse_postorder() {
+            let mut latches = Vec::new();
+            for &pred in &preds[header] {
+                if !domtree.is_reachable(pred) || domtree.rpo_number(pred) < domtree.rpo_number(header) {
+                    continue;
+                }
+                if domtree.dominates(header, pred) {
+                    if !latches.contains(&pred) {
+                        latches.push(pred);
+                    }
+                } else {
+                    reducible = false;
+                }
+            }
+            if latches.is_empty() {
+                continue;
+            }
+
+            let mut inside = vec![false; cfg.blocks.len()];
+            inside[header] = true;
+            let mut worklist = latches.clone();
+            while let Some(block) = worklist.pop() {
+                if inside[block] {
+                    continue;
+                }
+                inside[block] = true;
+                worklist.extend(preds[block].iter().filter(|pred| domtree.is_reachable(**pred)));
+            }
+
+            loops.push(Loop {
+                header,
+                latches,
+                blocks: (0..cfg.blocks.len()).filter(|block| inside[*block]).collect(),
+                parent: None,
+                depth: 1,
+            });
+        }
+
+        // 2. a loop's parent is the innermost earlier loop that contains its header
+        let mut innermost = vec![None; cfg.blocks.len()];
+        for index in 0..loops.len() {
+            let parent = (0..index)
+                .rev()
+                .find(|outer| loops[*outer].contains(loops[index].header));
+            if let Some(parent) = parent {
+                loops[index].parent = Some(parent);
+                loops[index].depth = loops[parent].depth + 1;
+            }
+            for &block in &loops[index].blocks {
+                innermost[block] = Some(index);
+            }
+        }
+
+        LoopNest {
+            loops,
+            innermost,
+            reducible,
+        }
+    }
+
+    /// Index of the innermost loop containing `block`.
+    pub fn innermost_loop(&self, block: BlockId) -> Option<usize> {
+        self.innermost[block]
+    }
+
+    pub fn is_loop_header(&self, block: BlockId) -> bool {
+        self.innermost[block].is_some_and(|index| self.loops[index].header == block)
+    }
+
+    /// Number of loops around `block`, 0 outside of any loop.
+    pub fn depth(&self, block: BlockId) -> usize {
+        self.innermost[block].map_or(0, |index| self.loops[index].depth)
+    }
+
+    pub fn is_reducible(&self) -> bool {
+        self.reducible
+    }
+}
@@ -0,0 +1,184 @@
+//! Writes an [`Object`] as a 64-bit Mach-O relocatable file for ARM64 macOS: one segment
+//! with the `__TEXT,__text` section and its relocations, and the functions in the symbol
+//! table.
+
+use crate::synthesis::object::{Architecture, Object, RelocationKind};
+
+const MH_MAGIC_64: u32 = 0xfeedfacf;
+const MH_OBJECT: u32 = 0x1;
+const CPU_TYPE_ARM64: u32 = 0x0100000c;
+
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_SYMTAB: u32 = 0x2;
+const LC_DYSYMTAB: u32 = 0xb;
+const LC_BUILD_VERSION: u32 = 0x32;
+
+const PLATFORM_MACOS: u32 = 1;
+/// macOS 11.0, the first release for Apple silicon
+const MINIMUM_MACOS: u32 = 11 << 16;
+
+const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x80000000;
+const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x400;
+
+const N_EXT: u8 = 0x1;
+const N_SECT: u8 = 0xe;
+
+const ARM64_RELOC_BRANCH26: u32 = 2;
+
+const HEADER_SIZE: usize = 32;
+const SEGMENT_SIZE: usize = 72;
+const SECTION_SIZE: usize = 80;
+const SYMTAB_SIZE: usize = 24;
+const DYSYMTAB_SIZE: usize = 80;
+const BUILD_VERSION_SIZE: usize = 24;
+const COMMANDS_SIZE: usize = SEGMENT_SIZE + SECTION_SIZE + BUILD_VERSION_SIZE + SYMTAB_SIZE + DYSYMTAB_SIZE;
+
+/// The bytes of the object file.
+pub fn write(object: &Object) -> Vec<u8> {
+    debug_assert_eq!(object.architecture, Architecture::Arm64);
+
+    // 1. the symbol table: local functions, then the global ones, then the undefined ones,
+    //    as the dynamic symbol table command describes them
+    let mut strtab = vec![0u8];
+    let mut symtab = Vec::new();
+    let mut indices = Vec::new();
+    let mut locals: Vec<_> = object.symbols.iter().filter(|symbol| !symbol.global).collect();
+    let mut globals: Vec<_> = object.symbols.iter().filter(|symbol| symbol.global).collect();
+    locals.sort_by(|a, b| a.name.cmp(&b.name));
+    globals.sort_by(|a, b| a.name.cmp(&b.name));
+    for symbol in locals.iter().chain(&globals) {
+        let kind = if symbol.global { N_SECT | N_EXT } else { N_SECT };
+        push_symbol(&mut symtab, &mut strtab, &symbol.name, kind, 1, symbol.offset);
+        indices.push(symbol.name.as_str());
+    }
+    let undefined = object.undefined_symbols();
+    for name in &undefined {
+        push_symbol(&mut symtab, &mut strtab, name, N_EXT, 0, 0);
+        indices.push(name);
+    }
+
+    // 2. the relocations, all branches to a symbol
+    let mut relocations = Vec::new();
+    for relocation in &object.relocations {
+        let symbol = indices.iter().position(|name| *name == relocation.symbol).unwrap() as u32;
+        let kind = match relocation.kind {
+            RelocationKind::Arm64Call | RelocationKind::Arm64Jump => ARM64_RELOC_BRANCH26,
+            RelocationKind::X86_64Call => unreachable!("x86-64 code in an ARM64 object file"),
+        };
+        // pc-relative, 4 bytes long, against a symbol rather than a section
+        let info = symbol | 1 << 24 | 2 << 25 | 1 << 27 | kind << 28;
+        relocations.extend((relocation.offset as u32).to_le_bytes());
+        relocations.extend(info.to_le_bytes());
+    }
+
+    // 3. the layout: header, load commands, code, relocations, symbols, strings
+    let mut file = vec![0u8; HEADER_SIZE + COMMANDS_SIZE];
+    let text_offset = append(&mut file, &object.text, 4);
+    let relocations_offset = append(&mut file, &relocations, 4);
+    let symtab_offset = append(&mut file, &symtab, 8);
+    let strtab_offset = append(&mut file, &strtab, 1);
+    file.resize(file.len().next_multiple_of(8), 0);
+
+    let mut header = Vec::with_capacity(HEADER_SIZE + COMMANDS_SIZE);
+    for field in [MH_MAGIC_64, CPU_TYPE_ARM64, 0, MH_OBJECT, 4, COMMANDS_SIZE as u32, 0, 0] {
+        header.extend(field.to_le_bytes());
+    }
+
+    let text_size = object.text.len() as u64;
+    header.extend(LC_SEGMENT_64.to_le_bytes());
+    header.extend(((SEGMENT_SIZE + SECTION_SIZE) as u32).to_le_bytes());
+    // object files have a single unnamed segment
+    header.extend([0u8; 16]);
+    for field in [0, text_size, text_offset as u64, text_size] {
+        header.extend(field.to_le_bytes());
+    }
+    // protections rwx, one section, no flags
+    for field in [7u32, 7, 1, 0] {
+        header.extend(field.to_le_bytes());
+    }
+    header.extend(name16("__text"));
+    header.extend(name16("__TEXT"));
+    header.extend(0u64.to_le_bytes());
+    header.extend(text_size.to_le_bytes());
+    for field in [
+        text_offset as u32,
+        // 2^2 byte alignment
+        2,
+        relocations_offset as u32,
+        object.relocations.len() as u32,
+        S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS,
+        0,
+        0,
+        0,
+    ] {
+        header.extend(field.to_le_bytes());
+    }
+
+    for field in [
+        LC_BUILD_VERSION,
+        BUILD_VERSION_SIZE as u32,
+        PLATFORM_MACOS,
+        MINIMUM_MACOS,
+        0,
+        0,
+    ] {
+        header.extend(field.to_le_bytes());
+    }
+
+    let symbols = indices.len() as u32;
+    for field in [
+        LC_SYMTAB,
+        SYMTAB_SIZE as u32,
+        symtab_offset as u32,
+        symbols,
+        strtab_offset as u32,
+        strtab.len() as u32,
+    ] {
+        header.extend(field.to_le_bytes());
+    }
+
+    let (locals, globals) = (locals.len() as u32, globals.len() as u32);
+    let mut dysymtab = vec![
+        LC_DYSYMTAB,
+        DYSYMTAB_SIZE as u32,
+        0,
+        locals,
+        locals,
+        globals,
+        locals + globals,
+        undefined.len() as u32,
+    ];
+    // no indirect symbols, tables of contents, modules or external relocations
+    dysymtab.resize(DYSYMTAB_SIZE / 4, 0);
+    for field in dysymtab {
+        header.extend(field.to_le_bytes());
+    }
+
+    file[..HEADER_SIZE + COMMANDS_SIZE].copy_from_slice(&header);
+    file
+}
+
+fn push_symbol(symtab: &mut Vec<u8>, strtab: &mut Vec<u8>, name: &str, kind: u8, section: u8, value: u64) {
+    symtab.extend((strtab.len() as u32).to_le_bytes());
+    strtab.extend(name.as_bytes());
+    strtab.push(0);
+    symtab.push(kind);
+    symtab.push(section);
+    symtab.extend(0u16.to_le_bytes());
+    symtab.extend(value.to_le_bytes());
+}
+
+/// A section or segment name, padded with zeros to 16 bytes.
+fn name16(name: &str) -> [u8; 16] {
+    let mut padded = [0u8; 16];
+    padded[..name.len()].copy_from_slice(name.as_bytes());
+    padded
+}
+
+/// Appends `bytes` at the next multiple of `align` and returns their offset.
+fn append(file: &mut Vec<u8>, bytes: &[u8], align: usize) -> usize {
+    let offset = file.len().next_multiple_of(align);
+    file.resize(offset, 0);
+    file.extend(bytes);
+    offset
+}
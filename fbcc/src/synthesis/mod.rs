@@ -1,2 +1,28 @@
+pub mod arm64;
+pub mod arm64_assembler;
 pub mod asm;
+pub mod c;
+pub mod cfg;
+pub mod coalesce;
+pub mod dce;
+pub mod dominators;
+pub mod dwarf;
+pub mod elf;
+pub mod inline;
+pub mod interpreter;
 pub mod ir;
+pub mod legalize;
+pub mod liveness;
+pub mod llvm;
+pub mod loopopt;
+pub mod loops;
+pub mod macho;
+pub mod object;
+pub mod peephole;
+pub mod riscv64;
+pub mod sccp;
+pub mod ssa;
+pub mod tailcall;
+pub mod wasm;
+pub mod x86_64;
+pub mod x86_64_assembler;
@@ -0,0 +1,88 @@
+//! Relocatable object files, as produced by the integrated assemblers for ARM64 and x86-64
+//! and written out by [`elf`](super::elf) or [`macho`](super::macho).
+//!
+//! The backends only emit code, so an object has a single text section. Jumps within it are
+//! resolved by the assembler; what's left for the linker are calls and tail calls to
+//! functions, which may live in another object file or library.
+
+use crate::core::{
+    errors::{CompilerError, CompilerErrorKind},
+    target::ObjectFormat,
+};
+use crate::synthesis::{elf, macho};
+
+/// Instruction set of an object file's code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Architecture {
+    Arm64,
+    X86_64,
+}
+
+/// A function defined in the text section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// Offset of the first instruction in the text section
+    pub offset: u64,
+    pub size: u64,
+    /// Visible to other object files (`.globl`)
+    pub global: bool,
+}
+
+/// How the linker patches the address of a symbol into an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationKind {
+    /// ARM64 `bl`: a 26-bit word offset
+    Arm64Call,
+    /// ARM64 `b` to another function, for tail calls
+    Arm64Jump,
+    /// x86-64 `call`: a 32-bit offset from the end of the instruction
+    X86_64Call,
+}
+
+/// A place in the text section that refers to `symbol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// Offset of the instruction, or of the x86-64 displacement, in the text section
+    pub offset: u64,
+    pub symbol: String,
+    pub kind: RelocationKind,
+}
+
+/// The contents of a relocatable object file, independent of its format.
+#[derive(Debug, Clone)]
+pub struct Object {
+    pub architecture: Architecture,
+    pub text: Vec<u8>,
+    pub symbols: Vec<Symbol>,
+    pub relocations: Vec<Relocation>,
+}
+
+impl Object {
+    /// Symbols the relocations refer to that the object doesn't define, sorted by name.
+    pub fn undefined_symbols(&self) -> Vec<&str> {
+        let mut undefined: Vec<&str> = self
+            .relocations
+            .iter()
+            .map(|relocation| relocation.symbol.as_str())
+            .filter(|name| !self.symbols.iter().any(|symbol| symbol.name == *name))
+            .collect();
+        undefined.sort_unstable();
+        undefined.dedup();
+        undefined
+    }
+
+    /// The object file in `format`.
+    pub fn write(&self, format: ObjectFormat) -> Result<Vec<u8>, CompilerError> {
+        match (format, self.architecture) {
+            (ObjectFormat::Elf, _) => Ok(elf::write(self)),
+            (ObjectFormat::MachO, Architecture::Arm64) => Ok(macho::write(self)),
+            _ => Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: format!("cannot write {:?} code as a {format:?} object file", self.architecture),
+                span: None,
+                ..Default::default()
+            }),
+        }
+    }
+}
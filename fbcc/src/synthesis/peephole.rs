@@ -0,0 +1,403 @@
+//! Peephole optimiser for the ARM64 backend.
+//!
+//! The emitter handles one IR statement at a time: it loads every operand from its stack
+//! slot into a scratch register, materialises every constant with `mov`, and stores every
+//! result straight back. These rewrites clean up after it, over and over until none of them
+//! applies anymore:
+//!
+//! - store-to-load forwarding: a load from a slot a register still holds becomes a `mov`,
+//!   or disappears if it is the same register, and stores to slots nothing ever loads from
+//!   are deleted
+//! - `mov` of a constant that is only added, subtracted or compared becomes an immediate
+//! - `mov` chains: instructions read the source of a `mov` instead of its destination
+//! - compare and branch fusion: a `cset` tested by `cbz` becomes a `b.cond`, and a `cset`
+//!   compared with zero hands its condition on to whoever reads the flags
+//! - instructions whose results nothing reads are deleted
+//!
+//! A scratch register never carries a value from one block to another, so where a block
+//! ends only the argument registers are live.
+
+use crate::synthesis::arm64::{AluOp, Cond, Inst, Mem, Operand2, Reg};
+
+/// Optimises the instructions of one function.
+pub fn optimize(code: &mut Vec<Inst>) {
+    loop {
+        let changed = forward_loads(code)
+            | use_immediates(code)
+            | propagate_moves(code)
+            | fuse_compares(code)
+            | remove_dead(code)
+            | remove_dead_stores(code);
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Whether `reg` may be read after `code[index]` before it is written again.
+fn is_live_after(code: &[Inst], index: usize, reg: Reg) -> bool {
+    let Reg::W(number) = reg else {
+        return true;
+    };
+    for inst in &code[index + 1..] {
+        if inst.uses().contains(&reg) {
+            return true;
+        }
+        if inst.dst() == Some(reg) {
+            return false;
+        }
+        if inst.ends_block() {
+            break;
+        }
+    }
+    // arguments of a call or tail call, or the return value
+    number < 8
+}
+
+/// Whether the flags may be read after `code[index]` before they are set again.
+fn flags_live_after(code: &[Inst], index: usize) -> bool {
+    for inst in &code[index + 1..] {
+        if inst.reads_flags() {
+            return true;
+        }
+        if inst.sets_flags() || inst.ends_block() {
+            return false;
+        }
+    }
+    false
+}
+
+/// Replaces loads from slots whose value a register still holds, since a store to the slot
+/// or a load from it earlier in the block.
+fn forward_loads(code: &mut Vec<Inst>) -> bool {
+    let mut held: Vec<(Mem, Reg)> = Vec::new();
+    let mut changed = false;
+    let mut forwarded = Vec::with_capacity(code.len());
+    for mut inst in code.drain(..) {
+        let mut loaded = None;
+        if let Inst::Ldr { dst, mem } = inst {
+            loaded = Some((mem, dst));
+            if let Some(&(_, reg)) = held.iter().find(|(slot, _)| *slot == mem) {
+                changed = true;
+                if reg == dst {
+                    continue;
+                }
+                inst = Inst::Mov { dst, src: reg };
+            }
+        }
+
+        if inst.ends_block() || inst.dst() == Some(Reg::Sp) {
+            held.clear();
+        }
+        if let Some(dst) = inst.dst() {
+            held.retain(|(_, reg)| *reg != dst);
+        }
+        match inst {
+            Inst::Str { src, mem } => {
+                clobber(&mut held, mem, 4);
+                if matches!(src, Reg::W(_)) {
+                    held.push((mem, src));
+                }
+            }
+            Inst::Stp { mem, .. } => clobber(&mut held, mem, 16),
+            _ => {}
+        }
+        if let Some((mem, Reg::W(number))) = loaded {
+            held.push((mem, Reg::W(number)));
+        }
+        forwarded.push(inst);
+    }
+    *code = forwarded;
+    changed
+}
+
+/// Forgets the slots a store of `size` bytes to `mem` may overwrite. Stores relative to
+/// another base register may overwrite anything.
+fn clobber(held: &mut Vec<(Mem, Reg)>, mem: Mem, size: i64) {
+    held.retain(|(slot, _)| {
+        slot.base == mem.base && (slot.offset + 4 <= mem.offset || mem.offset + size <= slot.offset)
+    });
+}
+
+/// Turns a constant moved into a register just to be added, subtracted or compared into an
+/// immediate operand of that instruction.
+fn use_immediates(code: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    let mut index = 0;
+    while index < code.len() {
+        let Inst::MovImm { dst: reg, imm: low } = code[index] else {
+            index += 1;
+            continue;
+        };
+        let (value, user) = match code.get(index + 1) {
+            Some(Inst::Movk { dst, imm: high }) if *dst == reg => ((*high as u32) << 16 | low as u32, index + 2),
+            _ => (low as u32, index + 1),
+        };
+
+        let only_use = |dst: Option<Reg>| dst == Some(reg) || !is_live_after(code, user, reg);
+        let rewritten = match code.get(user) {
+            Some(&Inst::Alu {
+                op,
+                dst,
+                l,
+                r: Operand2::Reg(r),
+            }) if r == reg && l != reg && only_use(Some(dst)) => {
+                immediate(op, value as i32).map(|(op, imm)| Inst::Alu {
+                    op,
+                    dst,
+                    l,
+                    r: Operand2::Imm(imm),
+                })
+            }
+            Some(&Inst::Cmp { l, r: Operand2::Reg(r) }) if r == reg && l != reg && only_use(None) => {
+                immediate(AluOp::Subs, value as i32).map(|(_, imm)| Inst::Cmp {
+                    l,
+                    r: Operand2::Imm(imm),
+                })
+            }
+            _ => None,
+        };
+        match rewritten {
+            Some(inst) => {
+                code[user] = inst;
+                code.drain(index..user);
+                changed = true;
+            }
+            None => index += 1,
+        }
+    }
+    changed
+}
+
+/// `op` with `value` as an immediate operand, if it fits in 12 bits, possibly by turning an
+/// addition into a subtraction or the other way round.
+fn immediate(op: AluOp, value: i32) -> Option<(AluOp, u32)> {
+    match (op, value) {
+        (AluOp::Add | AluOp::Sub | AluOp::Subs, 0..=4095) => Some((op, value as u32)),
+        (AluOp::Add, -4095..=-1) => Some((AluOp::Sub, value.unsigned_abs())),
+        (AluOp::Sub, -4095..=-1) => Some((AluOp::Add, value.unsigned_abs())),
+        _ => None,
+    }
+}
+
+/// Makes the instructions after `mov dst, src` read `src` instead of `dst` as long as both
+/// keep their values, which leaves the `mov` itself dead once nothing else reads `dst`.
+fn propagate_moves(code: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    let mut index = 0;
+    while index < code.len() {
+        let Inst::Mov { dst, src } = code[index] else {
+            index += 1;
+            continue;
+        };
+        if dst == src {
+            code.remove(index);
+            changed = true;
+            continue;
+        }
+
+        for inst in &mut code[index + 1..] {
+            // `movk` reads the register it writes, which can't be renamed apart
+            if matches!(inst, Inst::Movk { .. }) {
+                break;
+            }
+            for reg in inst.uses_mut() {
+                if *reg == dst {
+                    *reg = src;
+                    changed = true;
+                }
+            }
+            if inst.dst() == Some(dst) || inst.dst() == Some(src) || inst.ends_block() {
+                break;
+            }
+        }
+        index += 1;
+    }
+    changed
+}
+
+/// Branches on the flags a comparison set instead of on the `cset` result computed from
+/// them.
+fn fuse_compares(code: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    let mut redundant = Vec::new();
+    for index in 0..code.len() {
+        match code[index] {
+            // cset t, c; cbz t, L  ->  b.!c L
+            Inst::Cbz(reg, ref target) => {
+                if let Some(cond) = cset_before(code, index, reg) {
+                    code[index] = Inst::BCond(cond.invert(), target.clone());
+                    changed = true;
+                }
+            }
+            // cset t, c; cmp t, #0; b.ne L  ->  b.c L, as long as every reader of the flags
+            // only asks whether t is zero
+            Inst::Cmp {
+                l: reg,
+                r: Operand2::Imm(0),
+            } => {
+                let Some(cond) = cset_before(code, index, reg) else {
+                    continue;
+                };
+                let mut readers = Vec::new();
+                for (offset, inst) in code[index + 1..].iter().enumerate() {
+                    if inst.reads_flags() {
+                        readers.push(index + 1 + offset);
+                    }
+                    if inst.sets_flags() || inst.ends_block() {
+                        break;
+                    }
+                }
+                let zero_tests = readers.iter().all(|reader| {
+                    matches!(
+                        code[*reader],
+                        Inst::Cset {
+                            cond: Cond::Eq | Cond::Ne,
+                            ..
+                        } | Inst::BCond(Cond::Eq | Cond::Ne, _)
+                    )
+                });
+                if !zero_tests {
+                    continue;
+                }
+                for reader in readers {
+                    let (Inst::Cset { cond: tested, .. } | Inst::BCond(tested, _)) = &mut code[reader] else {
+                        unreachable!();
+                    };
+                    *tested = if *tested == Cond::Ne { cond } else { cond.invert() };
+                }
+                redundant.push(index);
+            }
+            _ => {}
+        }
+    }
+
+    for index in redundant.into_iter().rev() {
+        code.remove(index);
+        changed = true;
+    }
+    changed
+}
+
+/// The condition of the `cset` that last wrote `reg` before `code[index]` in the same
+/// block, provided the flags it was computed from haven't changed since.
+fn cset_before(code: &[Inst], index: usize, reg: Reg) -> Option<Cond> {
+    for inst in code[..index].iter().rev() {
+        if inst.dst() == Some(reg) {
+            return match inst {
+                Inst::Cset { cond, .. } => Some(*cond),
+                _ => None,
+            };
+        }
+        if inst.sets_flags() || inst.ends_block() {
+            return None;
+        }
+    }
+    None
+}
+
+/// Deletes instructions that only write a register nothing reads, and turns `subs` whose
+/// difference nothing reads into `cmp`.
+fn remove_dead(code: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    for index in (0..code.len()).rev() {
+        match code[index] {
+            Inst::Cmp { .. } if !flags_live_after(code, index) => {
+                code.remove(index);
+                changed = true;
+            }
+            Inst::Alu {
+                op: AluOp::Subs,
+                dst,
+                l,
+                r,
+            } if !is_live_after(code, index, dst) => {
+                if flags_live_after(code, index) {
+                    code[index] = Inst::Cmp { l, r };
+                } else {
+                    code.remove(index);
+                }
+                changed = true;
+            }
+            ref inst => {
+                if let Some(dst @ Reg::W(_)) = inst.dst() {
+                    if !is_live_after(code, index, dst) {
+                        code.remove(index);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// Deletes stores to stack slots no instruction in the function loads from.
+fn remove_dead_stores(code: &mut Vec<Inst>) -> bool {
+    // Addresses are taken relative to sp on entry, since sp moves down to allocate the
+    // frame and outgoing arguments: the slots are the frame's lowest `frame` bytes, below
+    // the frame record, and outgoing arguments lie below them
+    let mut frame: i64 = 0;
+    let mut depth: i64 = 0;
+    let mut addresses = Vec::with_capacity(code.len());
+    for inst in code.iter() {
+        match *inst {
+            Inst::Label(_) => depth = frame,
+            Inst::Alu {
+                op: AluOp::Sub,
+                dst: Reg::Sp,
+                r: Operand2::Imm(size),
+                ..
+            } => {
+                depth += size as i64;
+                if frame == 0 {
+                    frame = depth;
+                }
+            }
+            Inst::Alu {
+                op: AluOp::Add,
+                dst: Reg::Sp,
+                r: Operand2::Imm(size),
+                ..
+            } => depth -= size as i64,
+            _ => {}
+        }
+        let address = match inst {
+            Inst::Ldr { mem, .. } | Inst::Str { mem, .. } | Inst::Ldp { mem, .. } | Inst::Stp { mem, .. }
+                if mem.base == Reg::Sp =>
+            {
+                Some(mem.offset - depth)
+            }
+            _ => None,
+        };
+        addresses.push(address);
+    }
+
+    let loaded: Vec<(i64, i64)> = code
+        .iter()
+        .zip(&addresses)
+        .filter_map(|(inst, address)| {
+            let size = match inst {
+                Inst::Ldr { dst: Reg::W(_), .. } => 4,
+                Inst::Ldr { .. } => 8,
+                Inst::Ldp { .. } => 16,
+                _ => return None,
+            };
+            address.map(|address| (address, address + size))
+        })
+        .collect();
+
+    let before = code.len();
+    let mut index = 0;
+    code.retain(|inst| {
+        let address = addresses[index];
+        index += 1;
+        let Some(address) = address else {
+            return true;
+        };
+        !matches!(inst, Inst::Str { src: Reg::W(_), .. })
+            || address < -frame
+            || loaded.iter().any(|(start, end)| *start < address + 4 && address < *end)
+    });
+    code.len() != before
+}
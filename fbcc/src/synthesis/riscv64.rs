@@ -0,0 +1,403 @@
+use crate::{
+    core::{
+        errors::CompilerError,
+        target::{CallingConvention, ObjectFormat},
+    },
+    synthesis::ir::{
+        BinaryOp, IrFunction,
+        IrStatement::{self},
+        Operand, UnaryOp,
+    },
+};
+use std::fmt::Write;
+
+/// Reach of a conditional branch (B-type immediate): +-4 KiB from the branch itself.
+const BRANCH_RANGE: i64 = 4096;
+
+/// Largest offset usable as an I/S-type 12-bit signed immediate.
+const IMM12_MAX: usize = 2047;
+
+/// Part of a function body. Conditional branches stay symbolic until every label's address
+/// is known, so that far targets can get the long form.
+enum Chunk {
+    Code(String),
+    Label(u32),
+    BranchIfZero { reg: &'static str, target: u32 },
+}
+
+impl Chunk {
+    /// Upper bound on the size of the chunk in bytes, assuming no compressed instructions.
+    fn size(&self, long: bool) -> i64 {
+        match self {
+            // `call` expands to `auipc` + `jalr`, everything else is a single instruction
+            Chunk::Code(code) => code
+                .lines()
+                .map(|line| if line.starts_with("\tcall") { 8 } else { 4 })
+                .sum(),
+            Chunk::Label(_) => 0,
+            Chunk::BranchIfZero { .. } => {
+                if long {
+                    8
+                } else {
+                    4
+                }
+            }
+        }
+    }
+}
+
+/// Function body under construction.
+struct Chunks(Vec<Chunk>);
+
+impl Chunks {
+    /// Buffer that straight-line instructions are appended to.
+    fn text(&mut self) -> &mut String {
+        if !matches!(self.0.last(), Some(Chunk::Code(_))) {
+            self.0.push(Chunk::Code(String::new()));
+        }
+        match self.0.last_mut() {
+            Some(Chunk::Code(code)) => code,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Emits RV64GC assembly for GNU as, following the LP64D calling convention.
+pub struct Riscv64AsmEmitter {
+    format: ObjectFormat,
+    callconv: CallingConvention,
+}
+
+impl Riscv64AsmEmitter {
+    pub fn new(format: ObjectFormat, callconv: CallingConvention) -> Self {
+        Self { format, callconv }
+    }
+
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
+        let mut asm = String::new();
+        writeln!(asm, "\t.text").unwrap();
+        for function in irfuncs {
+            asm.push_str(self.emit_func(function)?.as_str());
+        }
+        if self.format == ObjectFormat::Elf {
+            // mark the stack as non-executable, otherwise the linker warns and makes it executable
+            writeln!(asm, "\t.section\t.note.GNU-stack,\"\",@progbits").unwrap();
+        }
+        Ok(asm)
+    }
+
+    fn emit_func(&self, function: &IrFunction) -> Result<String, CompilerError> {
+        let mut asm = String::new();
+        let symbol = self.format.symbol(&function.name);
+        let arg_regs = self.callconv.int_arg_regs();
+
+        writeln!(asm, "\t.globl\t{}", symbol).unwrap();
+        if self.format == ObjectFormat::Elf {
+            writeln!(asm, "\t.type\t{}, @function", symbol).unwrap();
+        }
+        writeln!(asm, "{}:", symbol).unwrap();
+
+        // emit prologue
+        // 1. allocate stack frame
+        self.emit_sp_adjust(-(function.framesize as i64), &mut asm);
+        // 2. store return address and caller's frame pointer at the top of the frame
+        self.emit_slot_access("sd", "ra", function.framesize - 8, "sp", &mut asm);
+        self.emit_slot_access("sd", "s0", function.framesize - 16, "sp", &mut asm);
+        // 3. point s0 at the caller's sp, where our incoming stack arguments start
+        self.emit_add_imm("s0", "sp", function.framesize as i64, &mut asm);
+
+        // store parameters onto stack
+        for (param, reg) in function.params.iter().zip(arg_regs) {
+            self.emit_slot_access("sw", reg, function.slot_offset(param), "sp", &mut asm);
+        }
+
+        let stack_params = function.params.iter().skip(arg_regs.len());
+        let (offsets, _) = self.callconv.int_stack_args(stack_params.len());
+        for (param, offset) in stack_params.zip(offsets) {
+            self.emit_slot_access("lw", "t0", offset, "s0", &mut asm);
+            self.emit_slot_access("sw", "t0", function.slot_offset(param), "sp", &mut asm);
+        }
+
+        let mut body = Chunks(Vec::new());
+        self.emit_funcbody(function, &mut body)?;
+
+        if function.falls_off_end() {
+            self.emit_operand_to_reg(&Operand::Const(0), "a0", function, body.text());
+            self.emit_epilogue(function, body.text());
+        }
+
+        self.emit_chunks(body, &mut asm);
+
+        if self.format == ObjectFormat::Elf {
+            writeln!(asm, "\t.size\t{0}, .-{0}", symbol).unwrap();
+        }
+
+        Ok(asm)
+    }
+
+    fn emit_epilogue(&self, function: &IrFunction, asm: &mut String) {
+        // 1. restore return address and caller's frame pointer
+        self.emit_slot_access("ld", "ra", function.framesize - 8, "sp", asm);
+        self.emit_slot_access("ld", "s0", function.framesize - 16, "sp", asm);
+        // 2. deallocate stack frame memory
+        self.emit_sp_adjust(function.framesize as i64, asm);
+        // 3. return
+        writeln!(asm, "\tret").unwrap();
+    }
+
+    fn emit_funcbody(&self, function: &IrFunction, body: &mut Chunks) -> Result<(), CompilerError> {
+        for statement in &function.body {
+            match statement {
+                IrStatement::BinaryOp { dst, op, l, r } => {
+                    let asm = body.text();
+
+                    // 1. load left operand
+                    self.emit_operand_to_reg(l, "t0", function, asm);
+
+                    // 2. load right operand
+                    self.emit_operand_to_reg(r, "t1", function, asm);
+
+                    // 3. perform binary operation; the `w` forms operate on (and sign-extend)
+                    //    the low 32 bits, matching `int`
+                    match op {
+                        BinaryOp::Add => writeln!(asm, "\taddw\tt0, t0, t1").unwrap(),
+                        BinaryOp::Sub => writeln!(asm, "\tsubw\tt0, t0, t1").unwrap(),
+                        BinaryOp::Mul => writeln!(asm, "\tmulw\tt0, t0, t1").unwrap(),
+                        BinaryOp::Div => writeln!(asm, "\tdivw\tt0, t0, t1").unwrap(),
+                        BinaryOp::Mod => writeln!(asm, "\tremw\tt0, t0, t1").unwrap(),
+                        BinaryOp::Lt => writeln!(asm, "\tslt\tt0, t0, t1").unwrap(),
+                        BinaryOp::Le => {
+                            writeln!(asm, "\tslt\tt0, t1, t0").unwrap();
+                            writeln!(asm, "\txori\tt0, t0, 1").unwrap();
+                        }
+                        BinaryOp::Gt => writeln!(asm, "\tslt\tt0, t1, t0").unwrap(),
+                        BinaryOp::Ge => {
+                            writeln!(asm, "\tslt\tt0, t0, t1").unwrap();
+                            writeln!(asm, "\txori\tt0, t0, 1").unwrap();
+                        }
+                        BinaryOp::Eq => {
+                            writeln!(asm, "\tsub\tt0, t0, t1").unwrap();
+                            writeln!(asm, "\tseqz\tt0, t0").unwrap();
+                        }
+                        BinaryOp::NEq => {
+                            writeln!(asm, "\tsub\tt0, t0, t1").unwrap();
+                            writeln!(asm, "\tsnez\tt0, t0").unwrap();
+                        }
+                        BinaryOp::And => writeln!(asm, "\tand\tt0, t0, t1").unwrap(),
+                        BinaryOp::Or => writeln!(asm, "\tor\tt0, t0, t1").unwrap(),
+                        BinaryOp::Xor => writeln!(asm, "\txor\tt0, t0, t1").unwrap(),
+                        BinaryOp::LShift => writeln!(asm, "\tsllw\tt0, t0, t1").unwrap(),
+                        BinaryOp::RShift => writeln!(asm, "\tsraw\tt0, t0, t1").unwrap(),
+                    }
+
+                    // 4. store result
+                    self.emit_slot_access("sw", "t0", function.slot_offset(dst), "sp", asm);
+                }
+
+                IrStatement::UnaryOp { dst, op, src } => {
+                    let asm = body.text();
+                    self.emit_operand_to_reg(src, "t0", function, asm);
+
+                    match op {
+                        UnaryOp::Minus => writeln!(asm, "\tnegw\tt0, t0").unwrap(),
+                        UnaryOp::Comp => writeln!(asm, "\tnot\tt0, t0").unwrap(),
+                        UnaryOp::Not => writeln!(asm, "\tseqz\tt0, t0").unwrap(),
+                    }
+
+                    self.emit_slot_access("sw", "t0", function.slot_offset(dst), "sp", asm);
+                }
+
+                IrStatement::Copy { dst, src } => {
+                    let asm = body.text();
+
+                    // 1. load src operand into t0
+                    self.emit_operand_to_reg(src, "t0", function, asm);
+
+                    // 2. store t0 into dst slot
+                    self.emit_slot_access("sw", "t0", function.slot_offset(dst), "sp", asm);
+                }
+
+                IrStatement::Label(label) => body.0.push(Chunk::Label(*label)),
+                IrStatement::Loc(_) => {}
+                // `j` reaches +-1 MiB, which no function we generate comes close to
+                IrStatement::Jmp(label) => writeln!(body.text(), "\tj\t{}", self.format.local_label(*label)).unwrap(),
+
+                IrStatement::JmpIfZero { cond, target } => {
+                    self.emit_operand_to_reg(cond, "t0", function, body.text());
+                    body.0.push(Chunk::BranchIfZero {
+                        reg: "t0",
+                        target: *target,
+                    });
+                }
+
+                IrStatement::Call { dst, name, args, .. } => {
+                    let asm = body.text();
+                    let arg_regs = self.callconv.int_arg_regs();
+
+                    // 1. Allocate the outgoing area for arguments that don't fit in registers;
+                    //    slots are addressed from sp, so their offsets grow by its size meanwhile
+                    let stack_args = args.iter().skip(arg_regs.len());
+                    let (offsets, area) = self.callconv.int_stack_args(stack_args.len());
+                    if area > 0 {
+                        self.emit_sp_adjust(-(area as i64), asm);
+                    }
+                    for (arg, offset) in stack_args.zip(offsets) {
+                        self.emit_operand_to_reg_biased(arg, "t0", area, function, asm);
+                        self.emit_slot_access("sd", "t0", offset, "sp", asm);
+                    }
+
+                    // 2. Store the remaining arguments in a0-a7 in order
+                    for (arg, reg) in args.iter().zip(arg_regs) {
+                        self.emit_operand_to_reg_biased(arg, reg, area, function, asm);
+                    }
+
+                    // 3. Call the procedure and release the outgoing area
+                    writeln!(asm, "\tcall\t{}", self.format.symbol(name)).unwrap();
+                    if area > 0 {
+                        self.emit_sp_adjust(area as i64, asm);
+                    }
+
+                    // 4. Store the return value onto stack
+                    if let Some(return_dest) = dst {
+                        self.emit_slot_access("sw", "a0", function.slot_offset(return_dest), "sp", asm);
+                    }
+                }
+
+                IrStatement::Ret(op) => {
+                    let asm = body.text();
+                    self.emit_operand_to_reg(op, "a0", function, asm);
+                    self.emit_epilogue(function, asm);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lays out the function body and prints it. A `beqz` only reaches +-4 KiB, so branches
+    /// to labels further away become `bnez` over an unconditional `j`. Making a branch long
+    /// only ever moves labels further apart, so this settles after a few rounds.
+    fn emit_chunks(&self, body: Chunks, asm: &mut String) {
+        let mut long = vec![false; body.0.len()];
+
+        loop {
+            let mut labels = std::collections::HashMap::new();
+            let mut addresses = Vec::with_capacity(body.0.len());
+            let mut address = 0;
+            for (index, chunk) in body.0.iter().enumerate() {
+                if let Chunk::Label(label) = chunk {
+                    labels.insert(*label, address);
+                }
+                addresses.push(address);
+                address += chunk.size(long[index]);
+            }
+
+            let mut changed = false;
+            for (index, chunk) in body.0.iter().enumerate() {
+                if let Chunk::BranchIfZero { target, .. } = chunk {
+                    let distance = labels[target] - addresses[index];
+                    if !long[index] && !(-BRANCH_RANGE..BRANCH_RANGE).contains(&distance) {
+                        long[index] = true;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        for (chunk, long) in body.0.iter().zip(long) {
+            match chunk {
+                Chunk::Code(code) => asm.push_str(code),
+                Chunk::Label(label) => writeln!(asm, "{}:", self.format.local_label(*label)).unwrap(),
+                Chunk::BranchIfZero { reg, target } => {
+                    let target = self.format.local_label(*target);
+                    if long {
+                        writeln!(asm, "\tbnez\t{}, 1f", reg).unwrap();
+                        writeln!(asm, "\tj\t{}", target).unwrap();
+                        writeln!(asm, "1:").unwrap();
+                    } else {
+                        writeln!(asm, "\tbeqz\t{}, {}", reg, target).unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds `amount` to sp, going through a scratch register when it doesn't fit `addi`.
+    fn emit_sp_adjust(&self, amount: i64, asm: &mut String) {
+        self.emit_add_imm("sp", "sp", amount, asm);
+    }
+
+    fn emit_add_imm(&self, dst: &str, src: &str, amount: i64, asm: &mut String) {
+        if (-2048..=IMM12_MAX as i64).contains(&amount) {
+            writeln!(asm, "\taddi\t{}, {}, {}", dst, src, amount).unwrap();
+        } else {
+            self.emit_imm_const_to_reg(amount, "t2", asm);
+            writeln!(asm, "\tadd\t{}, {}, t2", dst, src).unwrap();
+        }
+    }
+
+    /// Emits a load or store (`op`) of `reg` at `base + offset`, computing the address in a
+    /// scratch register when the offset doesn't fit the 12-bit immediate.
+    fn emit_slot_access(&self, op: &str, reg: &str, offset: usize, base: &str, asm: &mut String) {
+        if offset <= IMM12_MAX {
+            writeln!(asm, "\t{}\t{}, {}({})", op, reg, offset, base).unwrap();
+        } else {
+            self.emit_imm_const_to_reg(offset as i64, "t2", asm);
+            writeln!(asm, "\tadd\tt2, {}, t2", base).unwrap();
+            writeln!(asm, "\t{}\t{}, 0(t2)", op, reg).unwrap();
+        }
+    }
+
+    fn emit_operand_to_reg(&self, operand: &Operand, reg: &str, function: &IrFunction, asm: &mut String) {
+        self.emit_operand_to_reg_biased(operand, reg, 0, function, asm);
+    }
+
+    /// Same as `emit_operand_to_reg`, for when sp sits `bias` bytes below the frame's usual sp.
+    fn emit_operand_to_reg_biased(
+        &self,
+        operand: &Operand,
+        reg: &str,
+        bias: usize,
+        function: &IrFunction,
+        asm: &mut String,
+    ) {
+        match operand {
+            Operand::Var(slot) => self.emit_slot_access("lw", reg, function.slot_offset(slot) + bias, "sp", asm),
+            Operand::Const(constant) => self.emit_imm_const_to_reg(*constant, reg, asm),
+        }
+    }
+
+    /// Materialises `constant` the way the `li` pseudo-instruction expands: `addi` for 12-bit
+    /// values, `lui` + `addiw` for 32-bit ones, and for wider values the upper bits built
+    /// recursively, shifted into place and topped up with the low 12 bits.
+    fn emit_imm_const_to_reg(&self, constant: i64, reg: &str, asm: &mut String) {
+        // sign-extend the low 12 bits, which is how `addi`/`addiw` read their immediate
+        let lo12 = (constant << 52) >> 52;
+
+        if (-2048..=2047).contains(&constant) {
+            writeln!(asm, "\taddi\t{}, zero, {}", reg, constant).unwrap();
+        } else if i32::try_from(constant).is_ok() {
+            // `lui` sign-extends bit 31; `addiw` wraps within 32 bits, which fixes up values
+            // just below 2^31 whose rounded upper part would otherwise overflow
+            let hi20 = ((constant + 0x800) >> 12) & 0xfffff;
+            writeln!(asm, "\tlui\t{}, {}", reg, hi20).unwrap();
+            if lo12 != 0 {
+                writeln!(asm, "\taddiw\t{}, {}, {}", reg, reg, lo12).unwrap();
+            }
+        } else {
+            let hi52 = (constant as u64).wrapping_add(0x800) >> 12;
+            let shift = 12 + hi52.trailing_zeros();
+            // sign-extend what is left of the upper part from its remaining width
+            let upper = ((hi52 >> (shift - 12)) << shift) as i64 >> shift;
+
+            self.emit_imm_const_to_reg(upper, reg, asm);
+            writeln!(asm, "\tslli\t{}, {}, {}", reg, reg, shift).unwrap();
+            if lo12 != 0 {
+                writeln!(asm, "\taddi\t{}, {}, {}", reg, reg, lo12).unwrap();
+            }
+        }
+    }
+}
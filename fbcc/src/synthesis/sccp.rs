@@ -0,0 +1,345 @@
+//! Sparse conditional constant propagation, from Wegman and Zadeck, "Constant Propagation
+//! with Conditional Branches".
+//!
+//! Runs on SSA form. Every value starts out unknown and is only lowered to a constant, or to
+//! varying, once a statement computing it is found to be executable; branches on constants
+//! only make one of their edges executable. So code that can't run doesn't spoil the values
+//! flowing out of it, which finds more constants than folding and propagating separately.
+//!
+//! Arithmetic folds with the semantics of C's 32-bit `int`. Operations whose behaviour is
+//! undefined, like division by zero, are left to run time with a warning.
+
+use std::collections::HashSet;
+
+use crate::{
+    analysis::node::{Location, Span},
+    core::errors::{Diagnostic, VecExtensionDiagnosticHelpers},
+    synthesis::{
+        cfg::{BlockId, Cfg, Terminator},
+        ir::{BinaryOp, IrStatement, Operand, SlotID, UnaryOp},
+        ssa::{is_promotable, SsaFunction},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    /// Not computed by any executable statement yet
+    Unknown,
+    Const(i32),
+    /// Has more than one value, or one that isn't known at compile time
+    Varying,
+}
+
+impl Value {
+    fn meet(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Unknown, value) | (value, Value::Unknown) => value,
+            (Value::Const(a), Value::Const(b)) if a == b => Value::Const(a),
+            _ => Value::Varying,
+        }
+    }
+}
+
+/// Folds the constants in `ssa`, replaces branches on constants with jumps and deletes the
+/// blocks that can no longer run.
+pub fn propagate_constants(ssa: &mut SsaFunction, diagnostics: &mut Vec<Diagnostic>) {
+    let mut sccp = Sccp::new(ssa);
+    sccp.solve(ssa);
+    sccp.rewrite(ssa, diagnostics);
+    ssa.remove_unreachable();
+}
+
+struct Sccp {
+    values: Vec<Value>,
+    /// Blocks reading each value
+    users: Vec<Vec<BlockId>>,
+    executable: Vec<bool>,
+    executable_edges: HashSet<(BlockId, BlockId)>,
+    worklist: Vec<BlockId>,
+    queued: Vec<bool>,
+}
+
+impl Sccp {
+    fn new(ssa: &SsaFunction) -> Self {
+        let cfg = &ssa.cfg;
+
+        // only values with a single definition are tracked; parameters, slots that stay in
+        // memory and reads of undefined variables are varying from the start
+        let mut definitions = vec![0; cfg.slots.len()];
+        for phi in ssa.phis.iter().flatten() {
+            definitions[phi.dst.index()] += 1;
+        }
+        for statement in cfg.blocks.iter().flat_map(|block| &block.statements) {
+            if let Some(dst) = statement.dst() {
+                definitions[dst.index()] += 1;
+            }
+        }
+        let mut values: Vec<Value> = cfg
+            .slots
+            .iter()
+            .zip(definitions)
+            .map(|(slot, definitions)| {
+                if definitions == 1 && is_promotable(slot) {
+                    Value::Unknown
+                } else {
+                    Value::Varying
+                }
+            })
+            .collect();
+        for param in &cfg.params {
+            values[param.index()] = Value::Varying;
+        }
+
+        let mut users = vec![Vec::new(); cfg.slots.len()];
+        for (block, data) in cfg.blocks.iter().enumerate() {
+            let phi_args = ssa.phis[block]
+                .iter()
+                .flat_map(|phi| &phi.args)
+                .filter_map(|(_, arg)| arg.as_ref());
+            let operands = data.statements.iter().flat_map(|statement| statement.operands());
+            for operand in phi_args.chain(operands).chain(data.terminator.operand()) {
+                if let Operand::Var(var) = operand {
+                    if users[var.index()].last() != Some(&block) {
+                        users[var.index()].push(block);
+                    }
+                }
+            }
+        }
+
+        Sccp {
+            values,
+            users,
+            executable: vec![false; cfg.blocks.len()],
+            executable_edges: HashSet::new(),
+            worklist: Vec::new(),
+            queued: vec![false; cfg.blocks.len()],
+        }
+    }
+
+    fn value(&self, operand: &Operand) -> Value {
+        match operand {
+            Operand::Const(constant) => Value::Const(*constant as i32),
+            Operand::Var(var) => self.values[var.index()],
+        }
+    }
+
+    fn push(&mut self, block: BlockId) {
+        if !self.queued[block] {
+            self.queued[block] = true;
+            self.worklist.push(block);
+        }
+    }
+
+    fn update(&mut self, slot: usize, value: Value) {
+        if self.values[slot] == value || self.values[slot] == Value::Varying {
+            return;
+        }
+        self.values[slot] = value;
+        for index in 0..self.users[slot].len() {
+            let user = self.users[slot][index];
+            if self.executable[user] {
+                self.push(user);
+            }
+        }
+    }
+
+    fn mark_edge(&mut self, from: BlockId, to: BlockId) {
+        if self.executable_edges.insert((from, to)) {
+            self.executable[to] = true;
+            self.push(to);
+        }
+    }
+
+    /// Evaluates the executable blocks until no value or edge changes anymore. A block is
+    /// evaluated again whenever one of the values it reads changes or one of its incoming
+    /// edges becomes executable.
+    fn solve(&mut self, ssa: &SsaFunction) {
+        self.executable[Cfg::ENTRY] = true;
+        self.push(Cfg::ENTRY);
+
+        while let Some(block) = self.worklist.pop() {
+            self.queued[block] = false;
+
+            for phi in &ssa.phis[block] {
+                let value = phi
+                    .args
+                    .iter()
+                    .filter(|(pred, _)| self.executable_edges.contains(&(*pred, block)))
+                    .filter_map(|(_, arg)| arg.as_ref())
+                    .fold(Value::Unknown, |value, arg| value.meet(self.value(arg)));
+                self.update(phi.dst.index(), value);
+            }
+
+            for statement in &ssa.cfg.blocks[block].statements {
+                let value = match statement {
+                    IrStatement::BinaryOp { op, l, r, .. } => match (self.value(l), self.value(r)) {
+                        (Value::Varying, _) | (_, Value::Varying) => Value::Varying,
+                        (Value::Const(l), Value::Const(r)) => {
+                            fold_binary(op, l, r).map_or(Value::Varying, Value::Const)
+                        }
+                        _ => Value::Unknown,
+                    },
+                    IrStatement::UnaryOp { op, src, .. } => match self.value(src) {
+                        Value::Const(src) => Value::Const(fold_unary(op, src)),
+                        value => value,
+                    },
+                    IrStatement::Copy { src, .. } => self.value(src),
+                    _ => Value::Varying,
+                };
+                if let Some(dst) = statement.dst() {
+                    self.update(dst.index(), value);
+                }
+            }
+
+            match &ssa.cfg.blocks[block].terminator {
+                Terminator::Jmp(target) => self.mark_edge(block, *target),
+                Terminator::Branch { cond, zero, nonzero } => match self.value(cond) {
+                    Value::Unknown => {}
+                    Value::Const(0) => self.mark_edge(block, *zero),
+                    Value::Const(_) => self.mark_edge(block, *nonzero),
+                    Value::Varying => {
+                        self.mark_edge(block, *zero);
+                        self.mark_edge(block, *nonzero);
+                    }
+                },
+                Terminator::Ret(_) | Terminator::FallOff => {}
+            }
+        }
+    }
+
+    /// Substitutes the constants, drops the statements and phis that computed them and turns
+    /// branches with a single executable edge into jumps. Blocks that never became executable
+    /// are left unreachable.
+    fn rewrite(&self, ssa: &mut SsaFunction, diagnostics: &mut Vec<Diagnostic>) {
+        let substitute = |operand: &mut Operand| {
+            if let Operand::Var(var) = operand {
+                if let Value::Const(constant) = self.values[var.index()] {
+                    *operand = Operand::Const(constant as i64);
+                }
+            }
+        };
+        let is_constant = |dst: &SlotID| matches!(self.values[dst.index()], Value::Const(_));
+        // of the statement the warnings are about
+        let mut location = None;
+
+        for block in 0..ssa.cfg.blocks.len() {
+            // constants are substituted everywhere, since their definitions go away
+            let data = &mut ssa.cfg.blocks[block];
+            for phi in &mut ssa.phis[block] {
+                phi.args
+                    .iter_mut()
+                    .filter_map(|(_, arg)| arg.as_mut())
+                    .for_each(substitute);
+            }
+            for statement in &mut data.statements {
+                statement.operands_mut().into_iter().for_each(substitute);
+            }
+            if let Some(operand) = data.terminator.operand_mut() {
+                substitute(operand);
+            }
+
+            if !self.executable[block] {
+                continue;
+            }
+
+            ssa.phis[block].retain(|phi| !is_constant(&phi.dst));
+            data.statements.retain(|statement| match statement {
+                IrStatement::Call { .. } => true,
+                statement => !statement.dst().is_some_and(is_constant),
+            });
+            for statement in &data.statements {
+                if let IrStatement::Loc(at) = statement {
+                    location = Some(*at);
+                }
+                warn_undefined(statement, location, &ssa.cfg.name, diagnostics);
+            }
+
+            if let Terminator::Branch { zero, nonzero, .. } = data.terminator {
+                let taken = match (
+                    self.executable_edges.contains(&(block, zero)),
+                    self.executable_edges.contains(&(block, nonzero)),
+                ) {
+                    (true, false) => Some((zero, nonzero)),
+                    (false, true) => Some((nonzero, zero)),
+                    _ => None,
+                };
+                if let Some((target, dead)) = taken {
+                    data.terminator = Terminator::Jmp(target);
+                    for phi in &mut ssa.phis[dead] {
+                        phi.args.retain(|(pred, _)| *pred != block);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Folds `l op r`, or returns `None` if the result is undefined in C.
+pub(crate) fn fold_binary(op: &BinaryOp, l: i32, r: i32) -> Option<i32> {
+    let value = match op {
+        BinaryOp::Add => l.wrapping_add(r),
+        BinaryOp::Sub => l.wrapping_sub(r),
+        BinaryOp::Mul => l.wrapping_mul(r),
+        // `checked_*` fails exactly for the undefined cases: a zero divisor and INT_MIN / -1
+        BinaryOp::Div => l.checked_div(r)?,
+        BinaryOp::Mod => l.checked_rem(r)?,
+        BinaryOp::Lt => (l < r) as i32,
+        BinaryOp::Le => (l <= r) as i32,
+        BinaryOp::Gt => (l > r) as i32,
+        BinaryOp::Ge => (l >= r) as i32,
+        BinaryOp::Eq => (l == r) as i32,
+        BinaryOp::NEq => (l != r) as i32,
+        BinaryOp::And => l & r,
+        BinaryOp::Or => l | r,
+        BinaryOp::Xor => l ^ r,
+        BinaryOp::LShift => l.checked_shl(shift_count(r)?)?,
+        BinaryOp::RShift => l.checked_shr(shift_count(r)?)?,
+    };
+    Some(value)
+}
+
+/// Shifting by a negative count or by the width of the type or more is undefined.
+fn shift_count(count: i32) -> Option<u32> {
+    u32::try_from(count).ok().filter(|count| *count < 32)
+}
+
+pub(crate) fn fold_unary(op: &UnaryOp, src: i32) -> i32 {
+    match op {
+        UnaryOp::Minus => src.wrapping_neg(),
+        UnaryOp::Comp => !src,
+        UnaryOp::Not => (src == 0) as i32,
+    }
+}
+
+/// Warns about operations that weren't folded because C leaves their result undefined. A
+/// constant zero divisor or shift count out of range is undefined whatever the other operand.
+/// The warning points at `location`, where the statement of the operation starts.
+fn warn_undefined(
+    statement: &IrStatement,
+    location: Option<Location>,
+    function: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let IrStatement::BinaryOp {
+        op,
+        l,
+        r: Operand::Const(r),
+        ..
+    } = statement
+    else {
+        return;
+    };
+    let r = *r as i32;
+
+    let message = match (op, l) {
+        (BinaryOp::Div | BinaryOp::Mod, _) if r == 0 => format!("division by zero in `{function}`"),
+        (BinaryOp::Div | BinaryOp::Mod, Operand::Const(l)) if fold_binary(op, *l as i32, r).is_none() => {
+            format!("`{l} {op} {r}` overflows in `{function}`")
+        }
+        (BinaryOp::LShift | BinaryOp::RShift, _) if shift_count(r).is_none() => {
+            format!("shift count {r} is out of range for `int` in `{function}`")
+        }
+        _ => return,
+    };
+    diagnostics.warning(message, location.map(|at| Span::new(at, at)));
+}
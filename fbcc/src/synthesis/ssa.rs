@@ -0,0 +1,330 @@
+//! Static single assignment form.
+//!
+//! [`SsaFunction::construct`] promotes every slot that holds a plain scalar to SSA values,
+//! like LLVM's mem2reg: each definition gets a fresh slot that is never written again, and
+//! where definitions from different paths meet, a [`Phi`] selects among them. Phis are only
+//! placed in the iterated dominance frontier of a variable's definitions, and only where the
+//! variable is live (pruned SSA), as described by Cytron et al., "Efficiently Computing
+//! Static Single Assignment Form and the Control Dependence Graph".
+//!
+//! [`SsaFunction::destruct`] turns the phis back into copies at the end of the predecessors,
+//! so the result can be laid out as an ordinary `IrFunction` any of the emitters can take.
+
+use crate::synthesis::{
+    cfg::{BasicBlock, BlockId, Cfg, Terminator},
+    dominators::DominatorTree,
+    ir::{IrStatement, Operand, Slot, SlotID},
+    liveness::Liveness,
+};
+
+/// `dst` takes the value of the argument for the predecessor control came from.
+#[derive(Debug, Clone)]
+pub struct Phi {
+    pub dst: SlotID,
+    /// The promoted slot this phi is a version of
+    pub var: SlotID,
+    /// One argument per predecessor; `None` where the variable is undefined on that edge
+    pub args: Vec<(BlockId, Option<Operand>)>,
+}
+
+pub struct SsaFunction {
+    pub cfg: Cfg,
+    /// Phis at the start of every block
+    pub phis: Vec<Vec<Phi>>,
+}
+
+/// Whether a slot can be promoted to SSA values. No IR statement takes the address of a slot,
+/// so this only rules out slots whose accesses have to stay in memory.
+pub fn is_promotable(slot: &Slot) -> bool {
+    slot.ty.datatype.is_scalar() && !slot.ty.qualifiers.is_volatile
+}
+
+impl SsaFunction {
+    pub fn construct(mut cfg: Cfg) -> Self {
+        normalize(&mut cfg);
+
+        let domtree = DominatorTree::new(&cfg);
+        let frontiers = domtree.dominance_frontiers(&cfg);
+        let promotable: Vec<bool> = cfg.slots.iter().map(is_promotable).collect();
+        let live_in = Liveness::new(&cfg).live_in;
+
+        // 1. place phis in the iterated dominance frontier of each variable's definitions,
+        //    wherever the variable is live
+        let mut defsites: Vec<Vec<BlockId>> = vec![Vec::new(); cfg.slots.len()];
+        for (block, data) in cfg.blocks.iter().enumerate() {
+            for statement in &data.statements {
+                if let Some(dst) = statement.dst() {
+                    if defsites[dst.index()].last() != Some(&block) {
+                        defsites[dst.index()].push(block);
+                    }
+                }
+            }
+        }
+
+        let mut phis: Vec<Vec<Phi>> = vec![Vec::new(); cfg.blocks.len()];
+        for (var, sites) in defsites.into_iter().enumerate() {
+            if !promotable[var] {
+                continue;
+            }
+            let mut has_phi = vec![false; cfg.blocks.len()];
+            let mut queued = vec![false; cfg.blocks.len()];
+            for &site in &sites {
+                queued[site] = true;
+            }
+            let mut worklist = sites;
+            while let Some(block) = worklist.pop() {
+                for &frontier in &frontiers[block] {
+                    if has_phi[frontier] || !live_in[frontier][var] {
+                        continue;
+                    }
+                    has_phi[frontier] = true;
+                    phis[frontier].push(Phi {
+                        dst: SlotID::new(var),
+                        var: SlotID::new(var),
+                        args: Vec::new(),
+                    });
+                    if !queued[frontier] {
+                        queued[frontier] = true;
+                        worklist.push(frontier);
+                    }
+                }
+            }
+        }
+
+        // 2. rename: walk the dominator tree with a stack of reaching values per variable.
+        //    Parameters start out with their incoming value, everything else is undefined
+        let mut ssa = SsaFunction { cfg, phis };
+        let mut stacks: Vec<Vec<Operand>> = vec![Vec::new(); ssa.cfg.slots.len()];
+        for param in &ssa.cfg.params {
+            stacks[param.index()].push(Operand::Var(param.clone()));
+        }
+
+        enum Visit {
+            Enter(BlockId),
+            Exit(Vec<SlotID>),
+        }
+        let mut visits = vec![Visit::Enter(Cfg::ENTRY)];
+        while let Some(visit) = visits.pop() {
+            match visit {
+                Visit::Enter(block) => {
+                    let pushed = ssa.rename_block(block, &promotable, &mut stacks);
+                    visits.push(Visit::Exit(pushed));
+                    visits.extend(domtree.children(block).iter().rev().map(|child| Visit::Enter(*child)));
+                }
+                Visit::Exit(pushed) => {
+                    for var in pushed {
+                        stacks[var.index()].pop();
+                    }
+                }
+            }
+        }
+
+        ssa
+    }
+
+    /// Renames the definitions and uses in `block` and fills in its successors' phi
+    /// arguments. Returns the variables whose stacks got a new value.
+    fn rename_block(&mut self, block: BlockId, promotable: &[bool], stacks: &mut [Vec<Operand>]) -> Vec<SlotID> {
+        let mut pushed = Vec::new();
+
+        for index in 0..self.phis[block].len() {
+            let var = self.phis[block][index].var.clone();
+            let dst = self.cfg.new_slot_like(&var);
+            stacks[var.index()].push(Operand::Var(dst.clone()));
+            pushed.push(var);
+            self.phis[block][index].dst = dst;
+        }
+
+        let current = |stacks: &[Vec<Operand>], operand: &mut Operand| {
+            if let Operand::Var(var) = operand {
+                if let Some(value) = promotable
+                    .get(var.index())
+                    .filter(|promotable| **promotable)
+                    .and_then(|_| stacks[var.index()].last())
+                {
+                    *operand = value.clone();
+                }
+            }
+        };
+
+        let statements = std::mem::take(&mut self.cfg.blocks[block].statements);
+        let mut renamed = Vec::with_capacity(statements.len());
+        for mut statement in statements {
+            for operand in statement.operands_mut() {
+                current(stacks, operand);
+            }
+
+            let Some(var) = statement.dst().filter(|dst| promotable[dst.index()]).cloned() else {
+                renamed.push(statement);
+                continue;
+            };
+            match statement {
+                // a copy of a constant or of another SSA value needs no slot of its own: the
+                // variable simply has that value from here on
+                IrStatement::Copy { src, .. } if is_value(&src, promotable) => {
+                    stacks[var.index()].push(src);
+                }
+                mut statement => {
+                    let dst = self.cfg.new_slot_like(&var);
+                    *statement.dst_mut().unwrap() = dst.clone();
+                    stacks[var.index()].push(Operand::Var(dst));
+                    renamed.push(statement);
+                }
+            }
+            pushed.push(var);
+        }
+        self.cfg.blocks[block].statements = renamed;
+
+        if let Some(operand) = self.cfg.blocks[block].terminator.operand_mut() {
+            current(stacks, operand);
+        }
+
+        for successor in self.cfg.successors(block) {
+            for phi in &mut self.phis[successor] {
+                let value = stacks[phi.var.index()].last().cloned();
+                phi.args.push((block, value));
+            }
+        }
+
+        pushed
+    }
+
+    /// Deletes the blocks that can't be reached from the entry, along with the phi arguments
+    /// for their edges, and returns how many there were.
+    pub fn remove_unreachable(&mut self) -> usize {
+        let mut reachable = vec![false; self.cfg.blocks.len()];
+        for block in self.cfg.reverse_postorder() {
+            reachable[block] = true;
+        }
+
+        let renumbered = self.cfg.retain_blocks(&reachable);
+        let mut index = 0;
+        self.phis.retain(|_| {
+            index += 1;
+            reachable[index - 1]
+        });
+        for phi in self.phis.iter_mut().flatten() {
+            phi.args = std::mem::take(&mut phi.args)
+                .into_iter()
+                .filter_map(|(pred, arg)| Some((renumbered[pred]?, arg)))
+                .collect();
+        }
+        renumbered.iter().filter(|block| block.is_none()).count()
+    }
+
+    /// Replaces the phis with copies at the end of the predecessors. Edges from a block with
+    /// several successors to a block with phis are split first, so the copies only run on
+    /// the edge they belong to.
+    pub fn destruct(mut self) -> Cfg {
+        // 1. split the critical edges into blocks with phis
+        for block in 0..self.cfg.blocks.len() {
+            let Terminator::Branch { zero, nonzero, .. } = self.cfg.blocks[block].terminator else {
+                continue;
+            };
+            for successor in [zero, nonzero] {
+                if self.phis[successor].is_empty() {
+                    continue;
+                }
+                let split = self.cfg.blocks.len();
+                self.cfg.blocks.push(BasicBlock {
+                    label: None,
+                    statements: Vec::new(),
+                    terminator: Terminator::Jmp(successor),
+                });
+                self.phis.push(Vec::new());
+                if let Terminator::Branch { zero, nonzero, .. } = &mut self.cfg.blocks[block].terminator {
+                    if *zero == successor {
+                        *zero = split;
+                    } else {
+                        *nonzero = split;
+                    }
+                }
+                for phi in &mut self.phis[successor] {
+                    for (pred, _) in &mut phi.args {
+                        if *pred == block {
+                            *pred = split;
+                        }
+                    }
+                }
+            }
+        }
+
+        // 2. gather the copies each predecessor has to make; they all happen at once, so
+        //    they have to be ordered such that no copy overwrites a value another one reads
+        let mut copies: Vec<Vec<(SlotID, Operand)>> = vec![Vec::new(); self.cfg.blocks.len()];
+        for phi in self.phis.into_iter().flatten() {
+            for (pred, arg) in phi.args {
+                if let Some(arg) = arg.filter(|arg| *arg != Operand::Var(phi.dst.clone())) {
+                    copies[pred].push((phi.dst.clone(), arg));
+                }
+            }
+        }
+
+        let mut scratch = None;
+        for (block, copies) in copies.into_iter().enumerate() {
+            let sequential = sequentialize(copies, &mut self.cfg, &mut scratch);
+            self.cfg.blocks[block].statements.extend(sequential);
+        }
+
+        self.cfg
+    }
+}
+
+/// Orders a parallel copy so that every source is read before it is overwritten. A cycle of
+/// copies, like a swap, has no such order; it is broken by saving one of its values in
+/// `scratch` first. One scratch slot is enough for a whole function, because each cycle is
+/// finished before the next one is broken.
+fn sequentialize(mut pending: Vec<(SlotID, Operand)>, cfg: &mut Cfg, scratch: &mut Option<SlotID>) -> Vec<IrStatement> {
+    let mut sequential = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|(dst, _)| {
+            !pending
+                .iter()
+                .any(|(_, src)| matches!(src, Operand::Var(var) if var == dst))
+        });
+
+        if let Some(index) = ready {
+            let (dst, src) = pending.remove(index);
+            sequential.push(IrStatement::Copy { dst, src });
+        } else {
+            let saved = pending[0].0.clone();
+            let temp = scratch.get_or_insert_with(|| cfg.new_slot_like(&saved)).clone();
+            sequential.push(IrStatement::Copy {
+                dst: temp.clone(),
+                src: Operand::Var(saved.clone()),
+            });
+            for (_, src) in &mut pending {
+                if *src == Operand::Var(saved.clone()) {
+                    *src = Operand::Var(temp.clone());
+                }
+            }
+        }
+    }
+    sequential
+}
+
+/// Prepares the graph for SSA: drops unreachable blocks, turns branches whose targets are
+/// the same into jumps so that no two edges connect the same blocks, and gives the function
+/// an entry block without predecessors, where the initial values are defined.
+fn normalize(cfg: &mut Cfg) {
+    cfg.remove_unreachable();
+
+    for block in &mut cfg.blocks {
+        if let Terminator::Branch { zero, nonzero, .. } = block.terminator {
+            if zero == nonzero {
+                block.terminator = Terminator::Jmp(zero);
+            }
+        }
+    }
+
+    cfg.split_entry();
+}
+
+/// Whether `operand` stays the same for the rest of the function: a constant or an SSA value.
+fn is_value(operand: &Operand, promotable: &[bool]) -> bool {
+    match operand {
+        Operand::Const(_) => true,
+        // versions are created past the end of `promotable`
+        Operand::Var(var) => promotable.get(var.index()).is_none_or(|promotable| *promotable),
+    }
+}
@@ -0,0 +1,90 @@
+//! Tail calls.
+//!
+//! A call whose result is returned straight away leaves the caller nothing to do afterwards.
+//! When it calls the function itself, the arguments are copied into the parameters and the
+//! call becomes a jump back to the top of the body, so accumulator-style recursion runs in
+//! constant stack space on every backend. Other such calls are marked `tail`, and a backend
+//! that can hand the caller's frame over to the callee jumps to it instead of calling it.
+
+use crate::synthesis::ir::{IrFunction, IrStatement, Operand};
+
+/// Returns the number of calls turned into loops or marked as tail calls.
+pub fn optimize_tail_calls(function: &mut IrFunction, next_label: &mut u32) -> usize {
+    // 1. find the calls directly followed by a return of their result; labels and source
+    //    locations in between don't execute
+    let tail_calls: Vec<usize> = (0..function.body.len())
+        .filter(|&index| {
+            let IrStatement::Call { dst: Some(dst), .. } = &function.body[index] else {
+                return false;
+            };
+            let next = function.body[index + 1..].iter().find(|statement| statement.is_code());
+            matches!(next, Some(IrStatement::Ret(Operand::Var(var))) if var == dst)
+        })
+        .collect();
+    if tail_calls.is_empty() {
+        return 0;
+    }
+
+    // 2. loop back to the top for calls to the function itself, mark the others
+    let mut entry = None;
+    let mut body = Vec::with_capacity(function.body.len());
+    for (index, statement) in std::mem::take(&mut function.body).into_iter().enumerate() {
+        match statement {
+            IrStatement::Call { name, args, .. }
+                if tail_calls.contains(&index) && name == function.name && args.len() == function.params.len() =>
+            {
+                let entry = *entry.get_or_insert_with(|| {
+                    *next_label += 1;
+                    *next_label - 1
+                });
+                body.extend(assign_parameters(function, args));
+                body.push(IrStatement::Jmp(entry));
+            }
+            IrStatement::Call { dst, name, args, .. } if tail_calls.contains(&index) => {
+                body.push(IrStatement::Call {
+                    dst,
+                    name,
+                    args,
+                    tail: true,
+                });
+            }
+            statement => body.push(statement),
+        }
+    }
+    if let Some(entry) = entry {
+        body.insert(0, IrStatement::Label(entry));
+    }
+    function.body = body;
+
+    tail_calls.len()
+}
+
+/// Copies `args` into the parameters of `function` as if all at once: an argument that
+/// reads a parameter assigned before it is saved in a fresh slot first.
+fn assign_parameters(function: &mut IrFunction, args: Vec<Operand>) -> Vec<IrStatement> {
+    let params = function.params.clone();
+    let mut saves = Vec::new();
+    let mut copies = Vec::new();
+    for (position, (param, arg)) in params.iter().zip(args).enumerate() {
+        match arg {
+            Operand::Var(var) if var == *param => {}
+            Operand::Var(var) if params[..position].contains(&var) => {
+                let saved = function.new_slot_like(&var);
+                saves.push(IrStatement::Copy {
+                    dst: saved.clone(),
+                    src: Operand::Var(var),
+                });
+                copies.push(IrStatement::Copy {
+                    dst: param.clone(),
+                    src: Operand::Var(saved),
+                });
+            }
+            arg => copies.push(IrStatement::Copy {
+                dst: param.clone(),
+                src: arg,
+            }),
+        }
+    }
+    saves.extend(copies);
+    saves
+}
@@ -0,0 +1,369 @@
+//! WebAssembly text format (WAT) backend.
+//!
+//! Wasm has no `goto`, so the flat `Label`/`Jmp`/`JmpIfZero` control flow of the IR is
+//! turned into a CFG and then structured into `block`/`loop`/`if` following the
+//! dominator-tree algorithm from Norman Ramsey's "Beyond Relooper" (ICFP 2022), the same idea
+//! as LLVM's stackifier: every loop header gets a `loop`, and every block with more than one
+//! forward predecessor gets a `block` ending right before it, opened by its immediate
+//! dominator. This works for any reducible CFG, which is all that structured C produces.
+//!
+//! Every slot becomes a Wasm local and every function is exported. The IR cannot take the
+//! address of a slot yet; once it can, those slots have to live in a shadow stack in linear
+//! memory instead, since locals have no address.
+
+use std::fmt::Write;
+
+use crate::{
+    core::errors::{CompilerError, CompilerErrorKind},
+    synthesis::{
+        cfg::{BlockId, Cfg, Terminator},
+        dominators::DominatorTree,
+        ir::{BinaryOp, IrFunction, IrStatement, Operand, SlotID, UnaryOp},
+        loops::LoopNest,
+    },
+};
+
+/// Enclosing construct a `br` can target, innermost last.
+#[derive(PartialEq)]
+enum Context {
+    /// `br` continues the loop headed by the block
+    LoopHeadedBy(BlockId),
+    /// `br` exits to the code of the block following the `block`
+    BlockFollowedBy(BlockId),
+    /// Only counts towards branch depths
+    IfThenElse,
+}
+
+/// The CFG of a function with the analyses the structuring needs.
+struct Structure {
+    cfg: Cfg,
+    domtree: DominatorTree,
+    loops: LoopNest,
+    /// Number of forward edges into each block
+    forward_preds: Vec<usize>,
+}
+
+impl Structure {
+    fn new(function: &IrFunction) -> Result<Self, CompilerError> {
+        let cfg = Cfg::from_function(function.clone());
+        let domtree = DominatorTree::new(&cfg);
+        let loops = LoopNest::new(&cfg, &domtree);
+
+        // a cycle with more than one entry can't be expressed by any nesting of Wasm constructs
+        if !loops.is_reducible() {
+            return Err(CompilerError {
+                kind: CompilerErrorKind::InternalError,
+                message: format!(
+                    "irreducible control flow in `{}` cannot be expressed in WebAssembly",
+                    function.name
+                ),
+                span: None,
+                ..Default::default()
+            });
+        }
+
+        let mut structure = Structure {
+            forward_preds: vec![0; cfg.blocks.len()],
+            cfg,
+            domtree,
+            loops,
+        };
+        for &block in structure.domtree.reverse_postorder() {
+            for successor in structure.cfg.successors(block) {
+                if !structure.is_backward(block, successor) {
+                    structure.forward_preds[successor] += 1;
+                }
+            }
+        }
+        Ok(structure)
+    }
+
+    /// Whether the edge from `from` to `to` closes a loop.
+    fn is_backward(&self, from: BlockId, to: BlockId) -> bool {
+        self.domtree.rpo_number(to) <= self.domtree.rpo_number(from)
+    }
+
+    /// Children in the dominator tree with several forward predecessors, which need a `block`
+    /// to be branched to. Ordered with the last one in reverse postorder first, because that
+    /// one's `block` is opened first and so encloses the others.
+    fn merge_children(&self, block: BlockId) -> Vec<BlockId> {
+        let mut merges: Vec<BlockId> = self
+            .domtree
+            .children(block)
+            .iter()
+            .copied()
+            .filter(|child| self.forward_preds[*child] > 1)
+            .collect();
+        merges.sort_by_key(|child| std::cmp::Reverse(self.domtree.rpo_number(*child)));
+        merges
+    }
+}
+
+/// Emits a WebAssembly module in text format, one exported function per `IrFunction`.
+pub struct WatEmitter;
+
+impl WatEmitter {
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
+        let mut wat = String::new();
+        writeln!(wat, "(module").unwrap();
+
+        // functions called but not defined here are imported from the host
+        let imports = IrFunction::undefined_callees(irfuncs)?;
+        for (name, arity) in imports {
+            writeln!(
+                wat,
+                "  (import \"env\" \"{name}\" (func ${name}{} (result i32)))",
+                " (param i32)".repeat(arity)
+            )
+            .unwrap();
+        }
+
+        for function in irfuncs {
+            wat.push_str(self.emit_func(function)?.as_str());
+        }
+
+        writeln!(wat, ")").unwrap();
+        Ok(wat)
+    }
+
+    fn emit_func(&self, function: &IrFunction) -> Result<String, CompilerError> {
+        let mut wat = String::new();
+        let structure = Structure::new(function)?;
+
+        write!(wat, "  (func ${0} (export \"{0}\")", function.name).unwrap();
+        for param in &function.params {
+            write!(wat, " (param {} i32)", Self::local(param)).unwrap();
+        }
+        writeln!(wat, " (result i32)").unwrap();
+
+        let params: Vec<String> = function.params.iter().map(Self::local).collect();
+        for slot in 0..function.slots.len() {
+            let local = format!("$r{slot}");
+            if !params.contains(&local) {
+                writeln!(wat, "    (local {local} i32)").unwrap();
+            }
+        }
+
+        let mut context = Vec::new();
+        self.emit_tree(&structure, Cfg::ENTRY, &mut context, 2, &mut wat);
+        // every path has returned by now, but the validator only knows that about the code
+        // right after a `br` or `return`, not after an `if` whose arms both branch away
+        writeln!(wat, "    unreachable").unwrap();
+        writeln!(wat, "  )").unwrap();
+
+        Ok(wat)
+    }
+
+    /// Emits `block` along with every block it dominates.
+    fn emit_tree(
+        &self,
+        structure: &Structure,
+        block: BlockId,
+        context: &mut Vec<Context>,
+        depth: usize,
+        wat: &mut String,
+    ) {
+        let merges = structure.merge_children(block);
+        if structure.loops.is_loop_header(block) {
+            Self::line(wat, depth, "loop");
+            context.push(Context::LoopHeadedBy(block));
+            self.emit_within(structure, block, &merges, context, depth + 1, wat);
+            context.pop();
+            Self::line(wat, depth, "end");
+        } else {
+            self.emit_within(structure, block, &merges, context, depth, wat);
+        }
+    }
+
+    /// Emits `block` wrapped in one `block` per merge node in `merges`, each followed by the
+    /// code of that merge node.
+    fn emit_within(
+        &self,
+        structure: &Structure,
+        block: BlockId,
+        merges: &[BlockId],
+        context: &mut Vec<Context>,
+        depth: usize,
+        wat: &mut String,
+    ) {
+        let Some((&merge, inner)) = merges.split_first() else {
+            for statement in &structure.cfg.blocks[block].statements {
+                self.emit_statement(statement, depth, wat);
+            }
+            self.emit_terminator(structure, block, context, depth, wat);
+            return;
+        };
+
+        Self::line(wat, depth, "block");
+        context.push(Context::BlockFollowedBy(merge));
+        self.emit_within(structure, block, inner, context, depth + 1, wat);
+        context.pop();
+        Self::line(wat, depth, "end");
+        self.emit_tree(structure, merge, context, depth, wat);
+    }
+
+    fn emit_terminator(
+        &self,
+        structure: &Structure,
+        block: BlockId,
+        context: &mut Vec<Context>,
+        depth: usize,
+        wat: &mut String,
+    ) {
+        match &structure.cfg.blocks[block].terminator {
+            Terminator::Jmp(target) => self.emit_branch(structure, block, *target, context, depth, wat),
+
+            Terminator::Branch { cond, zero, nonzero } => {
+                self.emit_operand(cond, depth, wat);
+
+                // an arm that only needs a `br` folds into a `br_if`, the other arm follows it
+                if let Some(label) = Self::branch_depth(structure, block, *nonzero, context) {
+                    Self::line(wat, depth, &format!("br_if {label}"));
+                    self.emit_branch(structure, block, *zero, context, depth, wat);
+                    return;
+                }
+                if let Some(label) = Self::branch_depth(structure, block, *zero, context) {
+                    Self::line(wat, depth, "i32.eqz");
+                    Self::line(wat, depth, &format!("br_if {label}"));
+                    self.emit_branch(structure, block, *nonzero, context, depth, wat);
+                    return;
+                }
+
+                Self::line(wat, depth, "if");
+                context.push(Context::IfThenElse);
+                self.emit_branch(structure, block, *nonzero, context, depth + 1, wat);
+                Self::line(wat, depth, "else");
+                self.emit_branch(structure, block, *zero, context, depth + 1, wat);
+                context.pop();
+                Self::line(wat, depth, "end");
+            }
+
+            Terminator::Ret(value) => {
+                self.emit_operand(value, depth, wat);
+                Self::line(wat, depth, "return");
+            }
+
+            // a function can't end without its `i32` result on the stack, so running off the end
+            // returns 0 explicitly
+            Terminator::FallOff => {
+                Self::line(wat, depth, "i32.const 0");
+                Self::line(wat, depth, "return");
+            }
+        }
+    }
+
+    /// Emits control transfer from `from` to `to`: a `br` to the enclosing construct when
+    /// `to` has one, otherwise the code of `to` itself, which `from` then dominates.
+    fn emit_branch(
+        &self,
+        structure: &Structure,
+        from: BlockId,
+        to: BlockId,
+        context: &mut Vec<Context>,
+        depth: usize,
+        wat: &mut String,
+    ) {
+        match Self::branch_depth(structure, from, to, context) {
+            Some(label) => Self::line(wat, depth, &format!("br {label}")),
+            None => self.emit_tree(structure, to, context, depth, wat),
+        }
+    }
+
+    /// Relative depth of the construct a branch from `from` to `to` has to target, if any.
+    fn branch_depth(structure: &Structure, from: BlockId, to: BlockId, context: &[Context]) -> Option<usize> {
+        let target = if structure.is_backward(from, to) {
+            Context::LoopHeadedBy(to)
+        } else if structure.forward_preds[to] > 1 {
+            Context::BlockFollowedBy(to)
+        } else {
+            return None;
+        };
+        context.iter().rev().position(|construct| *construct == target)
+    }
+
+    fn emit_statement(&self, statement: &IrStatement, depth: usize, wat: &mut String) {
+        match statement {
+            IrStatement::BinaryOp { dst, op, l, r } => {
+                self.emit_operand(l, depth, wat);
+                self.emit_operand(r, depth, wat);
+
+                let instruction = match op {
+                    BinaryOp::Add => "i32.add",
+                    BinaryOp::Sub => "i32.sub",
+                    BinaryOp::Mul => "i32.mul",
+                    BinaryOp::Div => "i32.div_s",
+                    BinaryOp::Mod => "i32.rem_s",
+                    BinaryOp::Lt => "i32.lt_s",
+                    BinaryOp::Le => "i32.le_s",
+                    BinaryOp::Gt => "i32.gt_s",
+                    BinaryOp::Ge => "i32.ge_s",
+                    BinaryOp::Eq => "i32.eq",
+                    BinaryOp::NEq => "i32.ne",
+                    BinaryOp::And => "i32.and",
+                    BinaryOp::Or => "i32.or",
+                    BinaryOp::Xor => "i32.xor",
+                    BinaryOp::LShift => "i32.shl",
+                    BinaryOp::RShift => "i32.shr_s",
+                };
+                Self::line(wat, depth, instruction);
+                Self::line(wat, depth, &format!("local.set {}", Self::local(dst)));
+            }
+
+            IrStatement::UnaryOp { dst, op, src } => {
+                match op {
+                    UnaryOp::Minus => {
+                        Self::line(wat, depth, "i32.const 0");
+                        self.emit_operand(src, depth, wat);
+                        Self::line(wat, depth, "i32.sub");
+                    }
+                    UnaryOp::Comp => {
+                        self.emit_operand(src, depth, wat);
+                        Self::line(wat, depth, "i32.const -1");
+                        Self::line(wat, depth, "i32.xor");
+                    }
+                    UnaryOp::Not => {
+                        self.emit_operand(src, depth, wat);
+                        Self::line(wat, depth, "i32.eqz");
+                    }
+                }
+                Self::line(wat, depth, &format!("local.set {}", Self::local(dst)));
+            }
+
+            IrStatement::Copy { dst, src } => {
+                self.emit_operand(src, depth, wat);
+                Self::line(wat, depth, &format!("local.set {}", Self::local(dst)));
+            }
+
+            IrStatement::Call { dst, name, args, .. } => {
+                for arg in args {
+                    self.emit_operand(arg, depth, wat);
+                }
+                Self::line(wat, depth, &format!("call ${name}"));
+                match dst {
+                    Some(dst) => Self::line(wat, depth, &format!("local.set {}", Self::local(dst))),
+                    None => Self::line(wat, depth, "drop"),
+                }
+            }
+
+            // control flow is carried by the block terminators
+            IrStatement::Label(_) | IrStatement::Jmp(_) | IrStatement::JmpIfZero { .. } | IrStatement::Ret(_) => {}
+            IrStatement::Loc(_) => {}
+        }
+    }
+
+    fn emit_operand(&self, operand: &Operand, depth: usize, wat: &mut String) {
+        match operand {
+            Operand::Var(slot) => Self::line(wat, depth, &format!("local.get {}", Self::local(slot))),
+            Operand::Const(constant) => Self::line(wat, depth, &format!("i32.const {}", *constant as i32)),
+        }
+    }
+
+    /// Name of the local holding a slot, matching the `rN` of the IR dump.
+    fn local(slot: &SlotID) -> String {
+        format!("${slot}")
+    }
+
+    fn line(wat: &mut String, depth: usize, instruction: &str) {
+        writeln!(wat, "{}{}", "  ".repeat(depth), instruction).unwrap();
+    }
+}
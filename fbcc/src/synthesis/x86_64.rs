@@ -0,0 +1,296 @@
+use crate::{
+    core::{
+        errors::CompilerError,
+        target::{CallingConvention, ObjectFormat},
+    },
+    synthesis::{
+        dwarf::{self, DebugInfo},
+        ir::{
+            BinaryOp, IrFunction,
+            IrStatement::{self},
+            Operand, UnaryOp,
+        },
+    },
+};
+use std::fmt::Write;
+
+/// Emits GAS/AT&T syntax x86-64 assembly (System V calling convention).
+pub struct X86_64AsmEmitter {
+    format: ObjectFormat,
+    callconv: CallingConvention,
+    /// Emit line tables, call frame information and variable locations
+    debug: Option<DebugInfo>,
+}
+
+impl X86_64AsmEmitter {
+    pub fn new(format: ObjectFormat, callconv: CallingConvention) -> Self {
+        Self {
+            format,
+            callconv,
+            debug: None,
+        }
+    }
+
+    /// Also emits DWARF debug info for the source file `debug` describes.
+    pub fn with_debug_info(self, debug: Option<DebugInfo>) -> Self {
+        Self { debug, ..self }
+    }
+
+    pub fn emit(&self, irfuncs: &[IrFunction]) -> Result<String, CompilerError> {
+        let mut asm = String::new();
+        writeln!(asm, "\t.text").unwrap();
+        if let Some(debug) = &self.debug {
+            writeln!(asm, "\t{}", debug.file_directive()).unwrap();
+        }
+        for (index, function) in irfuncs.iter().enumerate() {
+            asm.push_str(self.emit_func(function, index)?.as_str());
+        }
+        if let Some(debug) = &self.debug {
+            dwarf::emit_sections(&mut asm, debug, self.format, irfuncs);
+        }
+        if self.format == ObjectFormat::Elf {
+            // mark the stack as non-executable, otherwise the linker warns and makes it executable
+            writeln!(asm, "\t.section\t.note.GNU-stack,\"\",@progbits").unwrap();
+        }
+        Ok(asm)
+    }
+
+    /// Emits the `index`th function of the translation unit.
+    fn emit_func(&self, function: &IrFunction, index: usize) -> Result<String, CompilerError> {
+        let mut asm = String::new();
+        let symbol = self.format.symbol(&function.name);
+        let arg_regs = self.callconv.int_arg_regs();
+
+        writeln!(asm, "\t.globl\t{}", symbol).unwrap();
+        if self.format == ObjectFormat::Elf {
+            writeln!(asm, "\t.type\t{}, @function", symbol).unwrap();
+        }
+        writeln!(asm, "{}:", symbol).unwrap();
+        if self.debug.is_some() {
+            writeln!(asm, "{}:", dwarf::function_begin(self.format, index)).unwrap();
+        }
+        self.cfi(&mut asm, ".cfi_startproc");
+
+        // emit prologue
+        // 1. save caller's frame pointer; this also realigns sp to 16 bytes after the `call`
+        writeln!(asm, "\tpushq\t%rbp").unwrap();
+        self.cfi(&mut asm, ".cfi_def_cfa_offset 16");
+        self.cfi(&mut asm, ".cfi_offset %rbp, -16");
+        // 2. set up our own frame pointer
+        writeln!(asm, "\tmovq\t%rsp, %rbp").unwrap();
+        self.cfi(&mut asm, ".cfi_def_cfa_register %rbp");
+        // 3. allocate the slot area; `framesize` already reserves 16 bytes for the frame
+        //    record, which on x86-64 is the return address and the saved %rbp
+        if Self::locals_size(function) > 0 {
+            writeln!(asm, "\tsubq\t${}, %rsp", Self::locals_size(function)).unwrap();
+        }
+
+        // store parameters onto stack
+        for (param, reg) in function.params.iter().zip(arg_regs) {
+            writeln!(asm, "\tmovl\t{}, {}(%rsp)", reg, function.slot_offset(param)).unwrap();
+        }
+
+        // the rest were passed on the stack, above the return address and the saved %rbp
+        let stack_params = function.params.iter().skip(arg_regs.len());
+        let (offsets, _) = self.callconv.int_stack_args(stack_params.len());
+        for (param, offset) in stack_params.zip(offsets) {
+            writeln!(asm, "\tmovl\t{}(%rbp), %eax", offset + 16).unwrap();
+            writeln!(asm, "\tmovl\t%eax, {}(%rsp)", function.slot_offset(param)).unwrap();
+        }
+
+        self.emit_funcbody(function, &mut asm)?;
+
+        if function.falls_off_end() {
+            writeln!(asm, "\tmovl\t$0, %eax").unwrap();
+            self.emit_epilogue(&mut asm);
+        }
+
+        self.cfi(&mut asm, ".cfi_endproc");
+        if self.debug.is_some() {
+            writeln!(asm, "{}:", dwarf::function_end(self.format, index)).unwrap();
+        }
+        if self.format == ObjectFormat::Elf {
+            writeln!(asm, "\t.size\t{0}, .-{0}", symbol).unwrap();
+        }
+
+        Ok(asm)
+    }
+
+    /// Bytes of stack below the saved %rbp, kept a multiple of 16 so calls see an aligned sp.
+    fn locals_size(function: &IrFunction) -> usize {
+        function.framesize - 16
+    }
+
+    /// Pushes a call frame information directive, if debug info is enabled.
+    fn cfi(&self, asm: &mut String, directive: &str) {
+        if self.debug.is_some() {
+            writeln!(asm, "\t{directive}").unwrap();
+        }
+    }
+
+    fn emit_epilogue(&self, asm: &mut String) {
+        // code may follow the `ret`, with the frame still set up
+        self.cfi(asm, ".cfi_remember_state");
+        // 1. deallocate stack frame memory
+        writeln!(asm, "\tmovq\t%rbp, %rsp").unwrap();
+        // 2. restore caller's frame pointer
+        writeln!(asm, "\tpopq\t%rbp").unwrap();
+        self.cfi(asm, ".cfi_def_cfa %rsp, 8");
+        // 3. return
+        writeln!(asm, "\tret").unwrap();
+        self.cfi(asm, ".cfi_restore_state");
+    }
+
+    fn emit_funcbody(&self, function: &IrFunction, asm: &mut String) -> Result<(), CompilerError> {
+        for statement in &function.body {
+            match statement {
+                IrStatement::BinaryOp { dst, op, l, r } => {
+                    // 1. load left operand
+                    self.emit_operand_to_reg(l, "%eax", function, asm);
+
+                    // 2. load right operand
+                    self.emit_operand_to_reg(r, "%ecx", function, asm);
+
+                    // 3. perform binary operation
+                    match op {
+                        BinaryOp::Add => writeln!(asm, "\taddl\t%ecx, %eax").unwrap(),
+                        BinaryOp::Sub => writeln!(asm, "\tsubl\t%ecx, %eax").unwrap(),
+                        BinaryOp::Mul => writeln!(asm, "\timull\t%ecx, %eax").unwrap(),
+                        BinaryOp::Div => {
+                            // sign-extend eax into edx:eax, quotient ends up in eax
+                            writeln!(asm, "\tcltd").unwrap();
+                            writeln!(asm, "\tidivl\t%ecx").unwrap();
+                        }
+                        BinaryOp::Mod => {
+                            // same as division, but the remainder is left in edx
+                            writeln!(asm, "\tcltd").unwrap();
+                            writeln!(asm, "\tidivl\t%ecx").unwrap();
+                            writeln!(asm, "\tmovl\t%edx, %eax").unwrap();
+                        }
+                        BinaryOp::Lt => self.emit_compare("setl", asm),
+                        BinaryOp::Le => self.emit_compare("setle", asm),
+                        BinaryOp::Gt => self.emit_compare("setg", asm),
+                        BinaryOp::Ge => self.emit_compare("setge", asm),
+                        BinaryOp::Eq => self.emit_compare("sete", asm),
+                        BinaryOp::NEq => self.emit_compare("setne", asm),
+                        BinaryOp::And => writeln!(asm, "\tandl\t%ecx, %eax").unwrap(),
+                        BinaryOp::Or => writeln!(asm, "\torl\t%ecx, %eax").unwrap(),
+                        BinaryOp::Xor => writeln!(asm, "\txorl\t%ecx, %eax").unwrap(),
+                        // variable shift counts must live in %cl
+                        BinaryOp::LShift => writeln!(asm, "\tsall\t%cl, %eax").unwrap(),
+                        BinaryOp::RShift => writeln!(asm, "\tsarl\t%cl, %eax").unwrap(),
+                    }
+
+                    // 4. store result
+                    writeln!(asm, "\tmovl\t%eax, {}(%rsp)", function.slot_offset(dst)).unwrap();
+                }
+
+                IrStatement::UnaryOp { dst, op, src } => {
+                    self.emit_operand_to_reg(src, "%eax", function, asm);
+
+                    match op {
+                        UnaryOp::Minus => writeln!(asm, "\tnegl\t%eax").unwrap(),
+                        UnaryOp::Comp => writeln!(asm, "\tnotl\t%eax").unwrap(),
+                        UnaryOp::Not => {
+                            writeln!(asm, "\tcmpl\t$0, %eax").unwrap();
+                            writeln!(asm, "\tsete\t%al").unwrap();
+                            writeln!(asm, "\tmovzbl\t%al, %eax").unwrap();
+                        }
+                    }
+
+                    writeln!(asm, "\tmovl\t%eax, {}(%rsp)", function.slot_offset(dst)).unwrap();
+                }
+
+                IrStatement::Copy { dst, src } => {
+                    // 1. load src operand into eax
+                    self.emit_operand_to_reg(src, "%eax", function, asm);
+
+                    // 2. store eax into dst slot
+                    writeln!(asm, "\tmovl\t%eax, {}(%rsp)", function.slot_offset(dst)).unwrap();
+                }
+
+                IrStatement::Label(label) => writeln!(asm, "{}:", self.format.local_label(*label)).unwrap(),
+                IrStatement::Jmp(label) => writeln!(asm, "\tjmp\t{}", self.format.local_label(*label)).unwrap(),
+                IrStatement::Loc(location) => {
+                    if self.debug.is_some() {
+                        writeln!(asm, "\t{}", dwarf::loc_directive(location)).unwrap();
+                    }
+                }
+
+                IrStatement::JmpIfZero { cond, target } => {
+                    self.emit_operand_to_reg(cond, "%eax", function, asm);
+                    writeln!(asm, "\ttestl\t%eax, %eax").unwrap();
+                    writeln!(asm, "\tje\t{}", self.format.local_label(*target)).unwrap();
+                }
+
+                IrStatement::Call { dst, name, args, .. } => {
+                    let arg_regs = self.callconv.int_arg_regs();
+
+                    // 1. Allocate the outgoing area for arguments that don't fit in registers;
+                    //    slots are addressed from %rsp, so their offsets grow by its size meanwhile
+                    let stack_args = args.iter().skip(arg_regs.len());
+                    let (offsets, area) = self.callconv.int_stack_args(stack_args.len());
+                    if area > 0 {
+                        writeln!(asm, "\tsubq\t${}, %rsp", area).unwrap();
+                    }
+                    for (arg, offset) in stack_args.zip(offsets) {
+                        self.emit_operand_to_reg_biased(arg, "%eax", area, function, asm);
+                        writeln!(asm, "\tmovl\t%eax, {}(%rsp)", offset).unwrap();
+                    }
+
+                    // 2. Store the remaining arguments in edi, esi, edx, ecx, r8d, r9d in order
+                    for (arg, reg) in args.iter().zip(arg_regs) {
+                        self.emit_operand_to_reg_biased(arg, reg, area, function, asm);
+                    }
+
+                    // 3. Call the procedure (the frame keeps sp 16-byte aligned at this point, and
+                    //    the outgoing area is a multiple of 16) and release the outgoing area
+                    writeln!(asm, "\tcall\t{}", self.format.symbol(name)).unwrap();
+                    if area > 0 {
+                        writeln!(asm, "\taddq\t${}, %rsp", area).unwrap();
+                    }
+
+                    // 4. Store the return value onto stack
+                    if let Some(return_dest) = dst {
+                        writeln!(asm, "\tmovl\t%eax, {}(%rsp)", function.slot_offset(return_dest)).unwrap();
+                    }
+                }
+
+                IrStatement::Ret(op) => {
+                    self.emit_operand_to_reg(op, "%eax", function, asm);
+                    self.emit_epilogue(asm);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares eax against ecx and materialises the flag selected by `setcc` as 0/1 in eax.
+    fn emit_compare(&self, setcc: &str, asm: &mut String) {
+        writeln!(asm, "\tcmpl\t%ecx, %eax").unwrap();
+        writeln!(asm, "\t{}\t%al", setcc).unwrap();
+        writeln!(asm, "\tmovzbl\t%al, %eax").unwrap();
+    }
+
+    fn emit_operand_to_reg(&self, operand: &Operand, reg: &str, function: &IrFunction, asm: &mut String) {
+        self.emit_operand_to_reg_biased(operand, reg, 0, function, asm);
+    }
+
+    /// Same as `emit_operand_to_reg`, for when %rsp sits `bias` bytes below the frame's usual %rsp.
+    fn emit_operand_to_reg_biased(
+        &self,
+        operand: &Operand,
+        reg: &str,
+        bias: usize,
+        function: &IrFunction,
+        asm: &mut String,
+    ) {
+        match operand {
+            Operand::Var(slot) => {
+                writeln!(asm, "\tmovl\t{}(%rsp), {}", function.slot_offset(slot) + bias, reg).unwrap()
+            }
+            // `movl` takes a full 32-bit immediate, no need to split it like on ARM64
+            Operand::Const(constant) => writeln!(asm, "\tmovl\t${}, {}", *constant as i32, reg).unwrap(),
+        }
+    }
+}
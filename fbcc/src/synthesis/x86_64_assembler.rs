@@ -0,0 +1,304 @@
+//! Integrated assembler for the x86-64 backend: encodes the AT&T syntax assembly
+//! [`X86_64AsmEmitter`](super::x86_64::X86_64AsmEmitter) prints into an [`Object`].
+//!
+//! Only the instructions the backend emits are understood. Jumps always take a 32-bit
+//! displacement, so every instruction's size is known before the labels are.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    core::errors::{CompilerError, CompilerErrorKind},
+    synthesis::object::{Architecture, Object, Relocation, RelocationKind, Symbol},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// A register by number, with its width in bytes
+    Reg(u8, u8),
+    Imm(i64),
+    /// `disp(%base)`
+    Mem {
+        base: u8,
+        disp: i32,
+    },
+    /// A label or function
+    Symbol(String),
+}
+
+/// An instruction, with the label or function it jumps to, if any; the displacement to it
+/// is the last four bytes.
+struct Encoded {
+    bytes: Vec<u8>,
+    target: Option<String>,
+}
+
+/// Assembles the output of the x86-64 backend.
+pub fn assemble(asm: &str) -> Result<Object, CompilerError> {
+    // 1. encode every instruction, leaving the displacements to labels for later
+    let mut labels = HashMap::new();
+    let mut globals = HashSet::new();
+    let mut text = Vec::new();
+    let mut fixups = Vec::new();
+    for line in asm.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(label) = line.strip_suffix(':') {
+            labels.insert(label.to_string(), text.len() as u64);
+            continue;
+        }
+        let (mnemonic, operands) = line.split_once('\t').unwrap_or((line, ""));
+        if let Some(directive) = mnemonic.strip_prefix('.') {
+            match directive {
+                "globl" => {
+                    globals.insert(operands.to_string());
+                }
+                "text" | "type" | "size" | "section" => {}
+                _ => return Err(error(format!("debug info needs an external assembler: `{line}`"))),
+            }
+            continue;
+        }
+        let operands = operands
+            .split(", ")
+            .filter(|operand| !operand.is_empty())
+            .map(operand)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| error(format!("cannot parse `{line}`")))?;
+        let encoded = encode(mnemonic, &operands).ok_or_else(|| error(format!("cannot encode `{line}`")))?;
+        text.extend(encoded.bytes);
+        if let Some(target) = encoded.target {
+            // the displacement is always the last four bytes
+            fixups.push((text.len() as u64 - 4, target));
+        }
+    }
+
+    // 2. patch in the displacements to local labels, and relocate the rest
+    let mut relocations = Vec::new();
+    for (offset, target) in fixups {
+        match labels.get(&target) {
+            Some(&label) if !globals.contains(&target) => {
+                let displacement = (label as i64 - (offset as i64 + 4)) as i32;
+                text[offset as usize..offset as usize + 4].copy_from_slice(&displacement.to_le_bytes());
+            }
+            _ => relocations.push(Relocation {
+                offset,
+                symbol: target,
+                kind: RelocationKind::X86_64Call,
+            }),
+        }
+    }
+
+    let mut symbols: Vec<Symbol> = globals
+        .iter()
+        .filter_map(|name| {
+            Some(Symbol {
+                name: name.clone(),
+                offset: *labels.get(name)?,
+                size: 0,
+                global: true,
+            })
+        })
+        .collect();
+    symbols.sort_by_key(|symbol| symbol.offset);
+    for index in 0..symbols.len() {
+        let end = symbols.get(index + 1).map_or(text.len() as u64, |next| next.offset);
+        symbols[index].size = end - symbols[index].offset;
+    }
+
+    Ok(Object {
+        architecture: Architecture::X86_64,
+        text,
+        symbols,
+        relocations,
+    })
+}
+
+fn operand(operand: &str) -> Option<Operand> {
+    if let Some(name) = operand.strip_prefix('%') {
+        let (number, size) = register(name)?;
+        Some(Operand::Reg(number, size))
+    } else if let Some(imm) = operand.strip_prefix('$') {
+        Some(Operand::Imm(imm.parse().ok()?))
+    } else if let Some((disp, base)) = operand.strip_suffix(')').and_then(|operand| operand.split_once("(%")) {
+        let (base, 8) = register(base)? else {
+            return None;
+        };
+        let disp = if disp.is_empty() { 0 } else { disp.parse().ok()? };
+        Some(Operand::Mem { base, disp })
+    } else {
+        Some(Operand::Symbol(operand.to_string()))
+    }
+}
+
+fn register(name: &str) -> Option<(u8, u8)> {
+    const NAMES: [[&str; 8]; 3] = [
+        ["al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil"],
+        ["eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi"],
+        ["rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi"],
+    ];
+    for (names, size) in NAMES.iter().zip([1, 4, 8]) {
+        if let Some(number) = names.iter().position(|other| *other == name) {
+            return Some((number as u8, size));
+        }
+    }
+    // r8-r15 with their `d`, `b` and no suffix
+    let rest = name.strip_prefix('r')?;
+    let (number, size) = match rest.strip_suffix('d') {
+        Some(number) => (number, 4),
+        None => match rest.strip_suffix('b') {
+            Some(number) => (number, 1),
+            None => (rest, 8),
+        },
+    };
+    let number: u8 = number.parse().ok()?;
+    (8..16).contains(&number).then_some((number, size))
+}
+
+/// Encodes one instruction; `operands` are in AT&T order, source first.
+fn encode(mnemonic: &str, operands: &[Operand]) -> Option<Encoded> {
+    use Operand::{Imm, Mem, Reg, Symbol};
+
+    let plain = |bytes: Vec<u8>| Some(Encoded { bytes, target: None });
+    let jump = |opcode: &[u8], target: &str| {
+        let mut bytes = opcode.to_vec();
+        bytes.extend([0; 4]);
+        Some(Encoded {
+            bytes,
+            target: Some(target.to_string()),
+        })
+    };
+
+    match (mnemonic, operands) {
+        ("ret", []) => plain(vec![0xc3]),
+        ("cltd", []) => plain(vec![0x99]),
+        ("pushq", [Reg(reg, 8)]) => plain(rex(false, 0, 0, *reg).into_iter().chain([0x50 + (reg & 7)]).collect()),
+        ("popq", [Reg(reg, 8)]) => plain(rex(false, 0, 0, *reg).into_iter().chain([0x58 + (reg & 7)]).collect()),
+        ("call", [Symbol(target)]) => jump(&[0xe8], target),
+        ("jmp", [Symbol(target)]) => jump(&[0xe9], target),
+        (setcc, [Reg(0, 1)]) if setcc.starts_with("set") => plain(vec![0x0f, 0x90 | condition(&setcc[3..])?, 0xc0]),
+        (jcc, [Symbol(target)]) if jcc.starts_with('j') => jump(&[0x0f, 0x80 | condition(&jcc[1..])?], target),
+        ("movzbl", [Reg(src, 1), Reg(dst, 4)]) => plain(with_modrm(false, &[0x0f, 0xb6], *dst, &Reg(*src, 1))),
+        ("movl", [Imm(imm), Reg(dst, 4)]) => {
+            let mut bytes: Vec<u8> = rex(false, 0, 0, *dst).into_iter().collect();
+            bytes.push(0xb8 + (dst & 7));
+            bytes.extend((*imm as i32).to_le_bytes());
+            plain(bytes)
+        }
+        ("movl" | "movq", [src @ (Reg(..) | Mem { .. }), dst @ (Reg(..) | Mem { .. })]) => {
+            let wide = mnemonic == "movq";
+            match (src, dst) {
+                (Reg(src, _), _) => plain(with_modrm(wide, &[0x89], *src, dst)),
+                (_, Reg(dst, _)) => plain(with_modrm(wide, &[0x8b], *dst, src)),
+                _ => None,
+            }
+        }
+        ("subq" | "addq", [Imm(imm), dst @ Reg(_, 8)]) => {
+            let extension = if mnemonic == "subq" { 5 } else { 0 };
+            let mut bytes = with_modrm(true, &[0x81], extension, dst);
+            bytes.extend(i32::try_from(*imm).ok()?.to_le_bytes());
+            plain(bytes)
+        }
+        ("cmpl", [Imm(imm), dst @ Reg(_, 4)]) => {
+            let mut bytes = with_modrm(false, &[0x81], 7, dst);
+            bytes.extend((*imm as i32).to_le_bytes());
+            plain(bytes)
+        }
+        ("addl" | "subl" | "andl" | "orl" | "xorl" | "cmpl" | "testl", [Reg(src, 4), dst @ Reg(_, 4)]) => {
+            let opcode = match mnemonic {
+                "addl" => 0x01,
+                "subl" => 0x29,
+                "andl" => 0x21,
+                "orl" => 0x09,
+                "xorl" => 0x31,
+                "cmpl" => 0x39,
+                _ => 0x85,
+            };
+            plain(with_modrm(false, &[opcode], *src, dst))
+        }
+        ("imull", [src @ Reg(_, 4), Reg(dst, 4)]) => plain(with_modrm(false, &[0x0f, 0xaf], *dst, src)),
+        // the group 3 and shift instructions pick the operation with the reg field
+        ("idivl" | "negl" | "notl", [dst @ Reg(_, 4)]) => {
+            let extension = match mnemonic {
+                "idivl" => 7,
+                "negl" => 3,
+                _ => 2,
+            };
+            plain(with_modrm(false, &[0xf7], extension, dst))
+        }
+        ("sall" | "sarl", [Reg(1, 1), dst @ Reg(_, 4)]) => {
+            let extension = if mnemonic == "sall" { 4 } else { 7 };
+            plain(with_modrm(false, &[0xd3], extension, dst))
+        }
+        _ => None,
+    }
+}
+
+/// `opcode` with a ModRM byte for register `reg` (or an opcode extension) and the register
+/// or memory operand `rm`, and the REX prefix they need.
+fn with_modrm(wide: bool, opcode: &[u8], reg: u8, rm: &Operand) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    match *rm {
+        Operand::Reg(number, size) => {
+            bytes.extend(rex(wide, reg, 0, number));
+            // spl, bpl, sil and dil need a REX prefix to not mean ah, ch, dh and bh
+            if size == 1 && (4..8).contains(&number) && bytes.is_empty() {
+                bytes.push(0x40);
+            }
+            bytes.extend(opcode);
+            bytes.push(0xc0 | (reg & 7) << 3 | (number & 7));
+        }
+        Operand::Mem { base, disp } => {
+            bytes.extend(rex(wide, reg, 0, base));
+            bytes.extend(opcode);
+            // rbp and r13 always take a displacement, rsp and r12 always a SIB byte
+            let mode = if disp == 0 && base & 7 != 5 {
+                0
+            } else if i8::try_from(disp).is_ok() {
+                1
+            } else {
+                2
+            };
+            bytes.push(mode << 6 | (reg & 7) << 3 | (base & 7));
+            if base & 7 == 4 {
+                bytes.push(0x24);
+            }
+            match mode {
+                1 => bytes.push(disp as u8),
+                2 => bytes.extend(disp.to_le_bytes()),
+                _ => {}
+            }
+        }
+        Operand::Imm(_) | Operand::Symbol(_) => unreachable!("not a ModRM operand"),
+    }
+    bytes
+}
+
+/// The REX prefix for a 64-bit operation or registers r8-r15 in the reg, index or rm/base
+/// fields, if one is needed.
+fn rex(wide: bool, reg: u8, index: u8, rm: u8) -> Option<u8> {
+    let rex = 0x40 | (wide as u8) << 3 | (reg >> 3) << 2 | (index >> 3) << 1 | rm >> 3;
+    (rex != 0x40).then_some(rex)
+}
+
+/// The condition code of a `jcc` or `setcc`, for the conditions the backend uses.
+fn condition(name: &str) -> Option<u8> {
+    Some(match name {
+        "e" => 0x4,
+        "ne" => 0x5,
+        "l" => 0xc,
+        "ge" => 0xd,
+        "le" => 0xe,
+        "g" => 0xf,
+        _ => return None,
+    })
+}
+
+fn error(message: String) -> CompilerError {
+    CompilerError {
+        kind: CompilerErrorKind::InternalError,
+        message,
+        span: None,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,57 @@
+//! Checks the ELF flavour of the ARM64 backend: the symbol directives in the text it generates
+//! and the standard AAPCS64 layout of stack arguments, which Apple's variant packs tighter.
+//! `tests/backends.rs` runs the programs in `tests/backend` under `qemu-aarch64`.
+
+mod common;
+
+use common::{assembly, backend_sources, function};
+use fbcc::compiler::Optimizations;
+use fbcc::core::target::{Aarch64AppleDarwin, Aarch64LinuxGnu};
+
+#[test]
+fn every_function_has_a_type_and_a_size() {
+    for (name, source) in backend_sources() {
+        let asm = assembly(&source, Optimizations::default(), &Aarch64LinuxGnu);
+        for label in asm.lines().filter_map(|line| line.strip_prefix("\t.globl\t")) {
+            let directives = [
+                format!("\t.globl\t{label}\n\t.type\t{label}, %function\n"),
+                format!("\n{label}:\n"),
+                format!("\t.size\t{label}, .-{label}\n"),
+            ];
+            let at: Vec<Option<usize>> = directives.iter().map(|directive| asm.find(directive)).collect();
+            assert!(at.iter().all(Option::is_some) && at.is_sorted(), "{name}: {label}");
+        }
+    }
+}
+
+#[test]
+fn arguments_after_the_eighth_take_8_byte_slots() {
+    let source = "int weigh(int a, int b, int c, int d, int e, int f, int g, int h, int i, int j) {\n\
+                      return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h + 9 * i + 10 * j;\n\
+                  }\n\
+                  int main() { return weigh(1, 2, 3, 4, 5, 6, 7, 8, 9, 10); }";
+    let asm = assembly(source, Optimizations::default(), &Aarch64LinuxGnu);
+
+    // the callee finds them above its frame record, 8 bytes apart
+    let weigh = function(&asm, "weigh");
+    assert!(
+        weigh.contains("\tldr\tw9, [x29, #16]\n") && weigh.contains("\tldr\tw9, [x29, #24]\n"),
+        "{weigh}"
+    );
+
+    // the caller stores them 8 bytes apart at the bottom of its frame, and takes them off again
+    let main = function(&asm, "main");
+    let call = main.find("\tbl\tweigh\n").unwrap();
+    let before = &main[..call];
+    assert!(before.contains("\tsub\tsp, sp, #16\n"), "{main}");
+    assert!(
+        before.contains("\tstr\tw9, [sp, #0]\n") && before.contains("\tstr\tw9, [sp, #8]\n"),
+        "{main}"
+    );
+    assert!(main[call..].contains("\tadd\tsp, sp, #16\n"), "{main}");
+
+    // where Apple's variant gives each only the 4 bytes of an `int`
+    let apple = assembly(source, Optimizations::default(), &Aarch64AppleDarwin);
+    assert!(apple.contains("\tstr\tw9, [sp, #4]\n"), "{apple}");
+    assert!(apple.contains("\tldr\tw9, [x29, #20]\n"), "{apple}");
+}
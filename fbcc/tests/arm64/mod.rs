@@ -0,0 +1,151 @@
+//! A small simulator for the ARM64 instructions the backend emits, since the host can't run
+//! ARM64 code.
+
+use std::collections::HashMap;
+
+use fbcc::synthesis::arm64::{AluOp, Cond, Inst, Operand2, Reg};
+
+/// The instructions of `function`, from its label up to the next function's.
+pub fn function<'a>(code: &'a [Inst], name: &str) -> &'a [Inst] {
+    let start = code
+        .iter()
+        .position(|inst| *inst == Inst::Label(format!("_{name}")))
+        .unwrap()
+        + 1;
+    let end = code[start..]
+        .iter()
+        .position(|inst| matches!(inst, Inst::Directive(_)))
+        .map_or(code.len(), |end| start + end);
+    &code[start..end]
+}
+
+/// Runs `main` and returns its exit status.
+pub fn run(code: &[Inst]) -> u8 {
+    const RETURN: u64 = u64::MAX;
+    let labels: HashMap<&str, usize> = code
+        .iter()
+        .enumerate()
+        .filter_map(|(index, inst)| match inst {
+            Inst::Label(label) => Some((label.as_str(), index)),
+            _ => None,
+        })
+        .collect();
+
+    let mut regs = [0u64; 32];
+    let mut memory = vec![0u8; 1 << 20];
+    let mut sp = memory.len() as u64;
+    let mut compared = (0i32, 0i32);
+    regs[30] = RETURN;
+
+    let read = |regs: &[u64; 32], sp: u64, reg: Reg| match reg {
+        Reg::W(number) => regs[number as usize] as u32 as u64,
+        Reg::X(number) => regs[number as usize],
+        Reg::Sp => sp,
+    };
+    let operand = |regs: &[u64; 32], sp: u64, operand: Operand2| match operand {
+        Operand2::Reg(reg) => read(regs, sp, reg),
+        Operand2::Imm(imm) => imm as u64,
+    };
+    let holds = |(l, r): (i32, i32), cond: Cond| match cond {
+        Cond::Eq => l == r,
+        Cond::Ne => l != r,
+        Cond::Lt => l < r,
+        Cond::Le => l <= r,
+        Cond::Gt => l > r,
+        Cond::Ge => l >= r,
+    };
+
+    let mut pc = labels["_main"];
+    for _ in 0..10_000_000 {
+        let mut next = pc + 1;
+        let mut write = None;
+        match &code[pc] {
+            Inst::Label(_) | Inst::Directive(_) | Inst::Debug(_) => {}
+            Inst::Alu { op, dst, l, r } => {
+                let (a, b) = (read(&regs, sp, *l), operand(&regs, sp, *r));
+                let (x, y) = (a as i32, b as i32);
+                let value = match op {
+                    AluOp::Add => a.wrapping_add(b),
+                    AluOp::Sub => a.wrapping_sub(b),
+                    AluOp::Subs => {
+                        compared = (x, y);
+                        a.wrapping_sub(b)
+                    }
+                    AluOp::Mul => x.wrapping_mul(y) as u64,
+                    AluOp::Sdiv if y == 0 => 0,
+                    AluOp::Sdiv => x.wrapping_div(y) as u64,
+                    AluOp::And => a & b,
+                    AluOp::Orr => a | b,
+                    AluOp::Eor => a ^ b,
+                    AluOp::Lsl => x.wrapping_shl(y as u32) as u64,
+                    AluOp::Asr => x.wrapping_shr(y as u32) as u64,
+                };
+                write = Some((*dst, value));
+            }
+            Inst::Msub { dst, a, b, c } => {
+                let [a, b, c] = [*a, *b, *c].map(|reg| read(&regs, sp, reg) as i32);
+                write = Some((*dst, c.wrapping_sub(a.wrapping_mul(b)) as u64));
+            }
+            Inst::Neg { dst, src } => write = Some((*dst, (read(&regs, sp, *src) as i32).wrapping_neg() as u64)),
+            Inst::Mvn { dst, src } => write = Some((*dst, !read(&regs, sp, *src))),
+            Inst::Cmp { l, r } => compared = (read(&regs, sp, *l) as i32, operand(&regs, sp, *r) as i32),
+            Inst::Cset { dst, cond } => write = Some((*dst, holds(compared, *cond) as u64)),
+            Inst::Mov { dst, src } => write = Some((*dst, read(&regs, sp, *src))),
+            Inst::MovImm { dst, imm } => write = Some((*dst, *imm as u64)),
+            Inst::Movk { dst, imm } => write = Some((*dst, read(&regs, sp, *dst) & 0xffff | (*imm as u64) << 16)),
+            Inst::Ldr { dst, mem } => {
+                let address = read(&regs, sp, mem.base).wrapping_add(mem.offset as u64) as usize;
+                let size = if matches!(dst, Reg::W(_)) { 4 } else { 8 };
+                let mut bytes = [0u8; 8];
+                bytes[..size].copy_from_slice(&memory[address..address + size]);
+                write = Some((*dst, u64::from_le_bytes(bytes)));
+            }
+            Inst::Str { src, mem } => {
+                let address = read(&regs, sp, mem.base).wrapping_add(mem.offset as u64) as usize;
+                let size = if matches!(src, Reg::W(_)) { 4 } else { 8 };
+                let bytes = read(&regs, sp, *src).to_le_bytes();
+                memory[address..address + size].copy_from_slice(&bytes[..size]);
+            }
+            Inst::Ldp { first, second, mem } => {
+                let address = read(&regs, sp, mem.base).wrapping_add(mem.offset as u64) as usize;
+                for (reg, address) in [(first, address), (second, address + 8)] {
+                    let value = u64::from_le_bytes(memory[address..address + 8].try_into().unwrap());
+                    let Reg::X(number) = reg else { unreachable!() };
+                    regs[*number as usize] = value;
+                }
+            }
+            Inst::Stp { first, second, mem } => {
+                let address = read(&regs, sp, mem.base).wrapping_add(mem.offset as u64) as usize;
+                for (reg, address) in [(first, address), (second, address + 8)] {
+                    let bytes = read(&regs, sp, *reg).to_le_bytes();
+                    memory[address..address + 8].copy_from_slice(&bytes);
+                }
+            }
+            Inst::B(target) => next = labels[target.as_str()],
+            Inst::BCond(cond, target) => {
+                if holds(compared, *cond) {
+                    next = labels[target.as_str()];
+                }
+            }
+            Inst::Cbz(reg, target) => {
+                if read(&regs, sp, *reg) == 0 {
+                    next = labels[target.as_str()];
+                }
+            }
+            Inst::Bl(target) => {
+                regs[30] = next as u64;
+                next = labels[target.as_str()];
+            }
+            Inst::Ret if regs[30] == RETURN => return regs[0] as u8,
+            Inst::Ret => next = regs[30] as usize,
+        }
+        match write {
+            Some((Reg::W(number), value)) => regs[number as usize] = value as u32 as u64,
+            Some((Reg::X(number), value)) => regs[number as usize] = value,
+            Some((Reg::Sp, value)) => sp = value,
+            None => {}
+        }
+        pc = next;
+    }
+    panic!("the program doesn't terminate");
+}
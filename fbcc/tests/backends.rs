@@ -0,0 +1,184 @@
+//! Checks what every backend does alike: a function that runs off its end returns 0, and with
+//! the tools that build and run its code installed, every program in `tests/backend` runs to
+//! its expected exit status.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use common::{each_backend_program, emit, exit_status, installed, opaque_pointers, output, Emit};
+use fbcc::compiler::{Compiler, Optimizations};
+use fbcc::core::target::{Aarch64LinuxGnu, Riscv64LinuxGnu, Target, Wasm32UnknownUnknown, X86_64LinuxGnu};
+
+/// The text of `text` from the first `start` up to the next `end` after it.
+fn between<'a>(text: &'a str, start: &str, end: &str) -> &'a str {
+    let from = text.find(start).unwrap_or_else(|| panic!("no `{start}` in {text}"));
+    let to = text[from..].find(end).map_or(text.len(), |to| from + to);
+    &text[from..to]
+}
+
+/// How to run the code a backend makes of a program
+struct Runner {
+    /// Names the files the code is written to
+    label: &'static str,
+    emit: Emit,
+    target: &'static dyn Target,
+    extension: &'static str,
+    /// Whether this host can run the code once the tools are installed
+    host: bool,
+    tools: &'static [&'static str],
+    /// Runs the code in a file and returns its exit status
+    run: fn(&Path) -> u8,
+}
+
+/// Links the code at `path` with `cc` and `flags`, runs it under `emulator` if there is one,
+/// and returns its exit status.
+fn link_and_run(cc: &str, flags: &[&str], emulator: Option<&str>, path: &Path) -> u8 {
+    let binary = path.with_extension("");
+    output(Command::new(cc).args(flags).arg(path).arg("-o").arg(&binary));
+    let status = match emulator {
+        Some(emulator) => exit_status(Command::new(emulator).arg(&binary)),
+        None => exit_status(&mut Command::new(&binary)),
+    };
+    fs::remove_file(binary).unwrap();
+    status
+}
+
+#[test]
+fn falling_off_the_end_returns_zero() {
+    let source = "int bump(int x) {\n\
+                      if (x > 100)\n\
+                          return x;\n\
+                      x = x + 1;\n\
+                  }\n\
+                  int main() { return bump(7); }";
+
+    // each backend, where `bump` starts and ends in what it makes of it, how it returns, and
+    // how it returns 0
+    let backends: [(Emit, &dyn Target, [&str; 4]); 6] = [
+        (
+            Compiler::compile,
+            &Aarch64LinuxGnu,
+            ["\nbump:\n", "\t.size\t", "\tret\n", "\tmov\tw0, #0\n\tldp\tx29, x30, "],
+        ),
+        (
+            Compiler::compile,
+            &X86_64LinuxGnu,
+            [
+                "\nbump:\n",
+                "\t.size\t",
+                "\tret\n",
+                "\tmovl\t$0, %eax\n\tmovq\t%rbp, %rsp\n",
+            ],
+        ),
+        (
+            Compiler::compile,
+            &Riscv64LinuxGnu,
+            ["\nbump:\n", "\t.size\t", "\tret\n", "\taddi\ta0, zero, 0\n\tld\tra, "],
+        ),
+        (
+            Compiler::compile,
+            &Wasm32UnknownUnknown,
+            ["(func $bump ", "\n  )\n", "return\n", "i32.const 0\n      return\n"],
+        ),
+        (
+            Compiler::compile_llvm_ir,
+            &X86_64LinuxGnu,
+            ["define i32 @bump(", "\n}\n", "\n  ret i32 ", "\n  ret i32 0"],
+        ),
+        (
+            Compiler::compile_c,
+            &X86_64LinuxGnu,
+            ["int bump(int r0) {", "\n}\n", "\n    return ", "\n    return 0;"],
+        ),
+    ];
+
+    for (compile, target, [start, end, ret, ret_zero]) in backends {
+        let text = emit(compile, source, Optimizations::default(), target);
+        let bump = between(&text, start, end);
+
+        // the path that runs off the end returns 0 through a return of its own
+        let returns: Vec<usize> = bump.match_indices(ret).map(|(at, _)| at).collect();
+        assert_eq!(returns.len(), 2, "{}: {bump}", target.triple());
+        assert!(
+            bump[returns[0] + ret.len()..].contains(ret_zero),
+            "{}: {bump}",
+            target.triple()
+        );
+    }
+}
+
+#[test]
+fn backend_programs_run() {
+    let is_x86_64_linux = cfg!(all(target_arch = "x86_64", target_os = "linux"));
+    let runners = [
+        Runner {
+            label: "aarch64",
+            emit: Compiler::compile,
+            target: &Aarch64LinuxGnu,
+            extension: "s",
+            host: true,
+            tools: &["aarch64-linux-gnu-gcc", "qemu-aarch64"],
+            run: |path| link_and_run("aarch64-linux-gnu-gcc", &["-static"], Some("qemu-aarch64"), path),
+        },
+        Runner {
+            label: "x86_64",
+            emit: Compiler::compile,
+            target: &X86_64LinuxGnu,
+            extension: "s",
+            host: is_x86_64_linux,
+            tools: &["cc"],
+            run: |path| link_and_run("cc", &[], None, path),
+        },
+        Runner {
+            label: "riscv64",
+            emit: Compiler::compile,
+            target: &Riscv64LinuxGnu,
+            extension: "s",
+            host: true,
+            tools: &["riscv64-linux-gnu-gcc", "qemu-riscv64"],
+            run: |path| link_and_run("riscv64-linux-gnu-gcc", &["-static"], Some("qemu-riscv64"), path),
+        },
+        Runner {
+            label: "lli",
+            emit: Compiler::compile_llvm_ir,
+            target: &X86_64LinuxGnu,
+            extension: "ll",
+            host: is_x86_64_linux,
+            tools: &["lli"],
+            run: |path| exit_status(Command::new("lli").args(opaque_pointers("lli")).arg(path)),
+        },
+        Runner {
+            label: "c",
+            emit: Compiler::compile_c,
+            target: &X86_64LinuxGnu,
+            extension: "c",
+            host: true,
+            tools: &["cc"],
+            run: |path| link_and_run("cc", &["-std=c99", "-pedantic-errors", "-w"], None, path),
+        },
+    ];
+
+    for runner in runners {
+        if !runner.host || !runner.tools.iter().all(|tool| installed(tool)) {
+            eprintln!(
+                "skipped {}: needs {} on a host that can run it",
+                runner.label,
+                runner.tools.join(" and ")
+            );
+            continue;
+        }
+
+        each_backend_program(
+            runner.label,
+            runner.emit,
+            runner.target,
+            runner.extension,
+            |path, name, expected| {
+                assert_eq!((runner.run)(path), expected, "{}: {name}", runner.label);
+            },
+        );
+    }
+}
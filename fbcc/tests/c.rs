@@ -0,0 +1,34 @@
+//! Checks the text the C backend generates. `tests/backends.rs` builds the C of every program
+//! in `tests/backend` as standard C99 and runs it.
+
+mod common;
+
+use common::emit;
+use fbcc::compiler::{Compiler, Optimizations};
+use fbcc::core::target::X86_64LinuxGnu;
+
+fn c(source: &str, optimizations: Optimizations) -> String {
+    emit(Compiler::compile_c, source, optimizations, &X86_64LinuxGnu)
+}
+
+#[test]
+fn functions_are_declared_before_any_is_defined() {
+    let source = "int even(int n);\n\
+                  int odd(int n) { if (n == 0) return 0; return even(n - 1); }\n\
+                  int even(int n) { if (n == 0) return 1; return odd(n - 1); }\n\
+                  int main() { return even(10); }";
+    let c = c(source, Optimizations::default());
+    let first_body = c.find(") {\n").unwrap();
+    for prototype in ["int odd(int r0);\n", "int even(int r0);\n", "int main(void);\n"] {
+        assert!(c[..first_body].contains(prototype), "{prototype}: {c}");
+    }
+}
+
+#[test]
+fn arithmetic_wraps_like_the_machine() {
+    let source = "int main() { int x = 2147483647; int y = -x; int z = x * 3 + 1; return z << 33; }";
+    let c = c(source, Optimizations::default());
+    assert!(c.contains(" = (int)(0u - (unsigned)r0);\n"), "{c}");
+    assert!(c.contains(" = (int)((unsigned)r0 * (unsigned)3);\n"), "{c}");
+    assert!(c.contains(" << (33 & 31));\n"), "{c}");
+}
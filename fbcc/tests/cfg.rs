@@ -0,0 +1,83 @@
+//! Checks the CFG construction by running programs before and after a round trip through
+//! it, and the analyses on it against loops whose shape is known.
+
+mod common;
+
+use common::{backend_sources, lower, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::cfg::{first_free_label, Cfg};
+use fbcc::synthesis::dominators::DominatorTree;
+use fbcc::synthesis::ir::IrFunction;
+use fbcc::synthesis::loops::LoopNest;
+
+#[test]
+fn round_trip_preserves_behaviour() {
+    for (name, source) in backend_sources() {
+        let ir = lower(&source, Optimizations::default());
+        let expected = run(&ir);
+
+        let mut next_label = first_free_label(&ir);
+        let round_tripped: Vec<IrFunction> = ir
+            .iter()
+            .map(|function| {
+                let mut cfg = Cfg::from_function(function.clone());
+                cfg.remove_unreachable();
+                cfg.into_function(&mut next_label)
+            })
+            .collect();
+
+        assert_eq!(run(&round_tripped), expected, "{name}");
+    }
+}
+
+#[test]
+fn code_after_return_is_unreachable() {
+    let ir = lower(
+        "int main() { int a = 1; return a; a = 2; return a; }",
+        Optimizations::default(),
+    );
+    let mut cfg = Cfg::from_function(ir.into_iter().next().unwrap());
+    assert_eq!(cfg.blocks.len(), 2);
+    assert_eq!(cfg.remove_unreachable(), 1);
+    assert_eq!(cfg.blocks.len(), 1);
+}
+
+#[test]
+fn nested_loops() {
+    let ir = lower(
+        "int main() {\n\
+             int total = 0;\n\
+             for (int i = 0; i < 4; i += 1) {\n\
+                 int j = 0;\n\
+                 while (j < i) { total += j; j += 1; }\n\
+             }\n\
+             while (total > 100) total -= 1;\n\
+             return total;\n\
+         }",
+        Optimizations::default(),
+    );
+    let cfg = Cfg::from_function(ir.into_iter().next().unwrap());
+    let domtree = DominatorTree::new(&cfg);
+    let nest = LoopNest::new(&cfg, &domtree);
+
+    assert!(nest.is_reducible());
+    assert_eq!(nest.loops.len(), 3);
+
+    let depths: Vec<usize> = nest.loops.iter().map(|l| l.depth).collect();
+    assert_eq!(depths, [1, 2, 1]);
+
+    let (outer, inner) = (&nest.loops[0], &nest.loops[1]);
+    assert_eq!(inner.parent, Some(0));
+    assert!(inner.blocks.iter().all(|block| outer.contains(*block)));
+    assert!(!outer.contains(nest.loops[2].header));
+
+    // headers dominate their loops, and the entry dominates everything reachable
+    for l in &nest.loops {
+        assert!(l.blocks.iter().all(|block| domtree.dominates(l.header, *block)));
+        assert!(nest.is_loop_header(l.header));
+    }
+    for &block in domtree.reverse_postorder() {
+        assert!(domtree.dominates(Cfg::ENTRY, block));
+    }
+    assert_eq!(nest.depth(inner.header), 2);
+}
@@ -0,0 +1,64 @@
+//! Checks copy propagation and slot coalescing: copies into variables disappear, frames
+//! shrink, and the programs in `tests/backend` still compute the same results.
+
+mod common;
+
+use common::{backend_sources, lower, only, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::ir::IrStatement;
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let unoptimized = lower(&source, Optimizations::default());
+        let expected = run(&unoptimized);
+
+        for optimizations in [only(|o| o.coalesce = true), Optimizations::level(1)] {
+            let ir = lower(&source, optimizations);
+            assert_eq!(run(&ir), expected, "{name}");
+            for (before, after) in unoptimized.iter().zip(&ir) {
+                assert!(after.framesize <= before.framesize, "{name}: {}", after.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn assignments_write_straight_into_the_variable() {
+    let source = "int f(int a, int b) { int x = a + b; int y = x * 2; x = y - a; return x; }\n\
+                  int main() { return f(3, 4); }";
+    let before = lower(source, Optimizations::default());
+    let after = lower(source, only(|o| o.coalesce = true));
+
+    assert_eq!(run(&after), 11);
+    assert!(after[0].framesize < before[0].framesize);
+    assert!(
+        !after[0]
+            .body
+            .iter()
+            .any(|statement| matches!(statement, IrStatement::Copy { .. })),
+        "{}",
+        after[0]
+    );
+}
+
+#[test]
+fn overlapping_values_keep_their_own_slots() {
+    // `a` and `b` are swapped through `t`, so at some point all three are needed
+    let source = "int main() {\n\
+                      int a = 1;\n\
+                      int b = 2;\n\
+                      for (int i = 0; i < 3; i += 1) { int t = a; a = b; b = t; }\n\
+                      return a * 10 + b;\n\
+                  }";
+    assert_eq!(run(&lower(source, only(|o| o.coalesce = true))), 21);
+    assert_eq!(run(&lower(source, Optimizations::level(1))), 21);
+}
+
+#[test]
+fn parameters_stay_apart() {
+    // `b` is dead on entry, but its slot is still written by the prologue
+    let source = "int f(int a, int b, int c) { b = a + c; return a - b + c * 2; }\n\
+                  int main() { return f(5, 100, 7); }";
+    assert_eq!(run(&lower(source, only(|o| o.coalesce = true))), 7);
+}
@@ -0,0 +1,170 @@
+//! What the tests of the passes and backends share: compiling a program to IR with some set
+//! of passes, running that IR, the programs in `tests/backend`, and running the code of the
+//! backends with tools the host may not have.
+
+// Every test uses only some of these
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, process};
+
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::errors::{CompilerError, Diagnostic};
+use fbcc::core::target::{Aarch64AppleDarwin, Target};
+use fbcc::synthesis::interpreter::Interpreter;
+use fbcc::synthesis::ir::IrFunction;
+
+/// The IR of `source`, which has to compile, after the passes `optimizations` enables, with
+/// the diagnostics they gave.
+pub fn compile_ir(source: &str, optimizations: Optimizations) -> (Vec<Diagnostic>, Vec<IrFunction>) {
+    let (diagnostics, result) = Compiler::compile_ir(source, &CompilerOptions::new(optimizations), &Aarch64AppleDarwin);
+    let ir = result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message));
+    (diagnostics, ir)
+}
+
+/// The IR of `source`, which has to compile, after the passes `optimizations` enables.
+pub fn lower(source: &str, optimizations: Optimizations) -> Vec<IrFunction> {
+    compile_ir(source, optimizations).1
+}
+
+/// What `main` of `ir` returns, which it has to without trapping.
+pub fn run(ir: &[IrFunction]) -> i32 {
+    Interpreter::new(ir, Vec::new()).run_main().unwrap()
+}
+
+/// Only the optimizations that `enable` turns on, as in `only(|o| o.dce = true)`.
+pub fn only(enable: impl FnOnce(&mut Optimizations)) -> Optimizations {
+    let mut optimizations = Optimizations::default();
+    enable(&mut optimizations);
+    optimizations
+}
+
+/// A way to compile a program to text, like [`Compiler::compile`] or
+/// [`Compiler::compile_llvm_ir`]
+pub type Emit = fn(&str, &CompilerOptions, &dyn Target) -> (Vec<Diagnostic>, Result<String, Vec<CompilerError>>);
+
+/// What `emit` makes of `source` for `target`, which has to compile.
+pub fn emit(emit: Emit, source: &str, optimizations: Optimizations, target: &dyn Target) -> String {
+    let (_, result) = emit(source, &CompilerOptions::new(optimizations), target);
+    result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message))
+}
+
+/// The assembly of `source` for `target`, which has to compile.
+pub fn assembly(source: &str, optimizations: Optimizations, target: &dyn Target) -> String {
+    emit(Compiler::compile, source, optimizations, target)
+}
+
+/// The path and source of every program in `tests/backend`, in the order of their numbers.
+pub fn backend_sources() -> Vec<(String, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/backend");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    paths
+        .into_iter()
+        .map(|path| (path.display().to_string(), fs::read_to_string(&path).unwrap()))
+        .collect()
+}
+
+/// The exit status a program in `tests/backend` expects, from its `// expect: N` line.
+pub fn expected_status(name: &str, source: &str) -> u8 {
+    source
+        .lines()
+        .find_map(|line| line.strip_prefix("// expect: "))
+        .and_then(|expected| expected.trim().parse().ok())
+        .unwrap_or_else(|| panic!("{name} has no `// expect:` line"))
+}
+
+/// Writes what `emit` makes of every program in `tests/backend` at `-O0` and `-O2` for
+/// `target` to a file with the extension `extension`, and hands it to `check` with the
+/// program's name and expected exit status. `label` keeps the files apart from those of other
+/// tests.
+pub fn each_backend_program(
+    label: &str,
+    emit: Emit,
+    target: &dyn Target,
+    extension: &str,
+    check: impl Fn(&Path, &str, u8),
+) {
+    for (name, source) in backend_sources() {
+        let expected = expected_status(&name, &source);
+        for level in [0, 2] {
+            let stem = format!("{label}-{}-O{level}", name.rsplit('/').next().unwrap());
+            let path = scratch(&format!("{stem}.{extension}"));
+            fs::write(&path, self::emit(emit, &source, Optimizations::level(level), target)).unwrap();
+            check(&path, &format!("{name} at -O{level}"), expected);
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
+
+/// The text of the function `name` in ELF assembly, from its label up to its `.size`.
+pub fn function<'a>(asm: &'a str, name: &str) -> &'a str {
+    let start = asm
+        .find(&format!("\n{name}:\n"))
+        .unwrap_or_else(|| panic!("no function `{name}`"));
+    let end = asm[start..].find("\t.size\t").map_or(asm.len(), |end| start + end);
+    &asm[start + 1..end]
+}
+
+/// Whether `program` can be run. The tests that need a tool like an emulator or LLVM skip
+/// when it isn't installed, since the rest of the suite runs without them.
+pub fn installed(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// A path in the temporary directory for a file called `name`, which no other test process
+/// uses.
+pub fn scratch(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("fbcc-tests-{}-{name}", process::id()))
+}
+
+/// The flags that make the LLVM tool `program` read opaque pointers, which LLVM 14 only does
+/// when asked to.
+pub fn opaque_pointers(program: &str) -> Vec<&'static str> {
+    let version = output(Command::new(program).arg("--version"));
+    let major = version
+        .split("LLVM version ")
+        .nth(1)
+        .and_then(|version| version.split('.').next())
+        .and_then(|major| major.parse::<u32>().ok())
+        .unwrap_or_else(|| panic!("cannot tell the version of {program}: {version}"));
+    if major < 15 {
+        vec!["-opaque-pointers"]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Runs `command`, which has to succeed, and returns its output.
+pub fn output(command: &mut Command) -> String {
+    let output = command
+        .output()
+        .unwrap_or_else(|error| panic!("cannot run {command:?}: {error}"));
+    assert!(
+        output.status.success(),
+        "{command:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Runs `command` and returns its exit status, which it has to exit with.
+pub fn exit_status(command: &mut Command) -> u8 {
+    let status = command
+        .status()
+        .unwrap_or_else(|error| panic!("cannot run {command:?}: {error}"));
+    let code = status
+        .code()
+        .unwrap_or_else(|| panic!("{command:?} was killed: {status}"));
+    code as u8
+}
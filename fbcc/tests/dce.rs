@@ -0,0 +1,85 @@
+//! Checks dead code elimination: what it removes, what it must keep, and that it doesn't
+//! change what the programs in `tests/backend` compute.
+
+mod common;
+
+use common::{backend_sources, lower, only, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::cfg::Cfg;
+use fbcc::synthesis::dce::eliminate_dead_code;
+use fbcc::synthesis::ir::{IrStatement, Operand};
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = run(&lower(&source, Optimizations::default()));
+        for optimizations in [only(|o| o.dce = true), Optimizations::level(1)] {
+            let ir = lower(&source, optimizations);
+            assert_eq!(run(&ir), expected, "{name}");
+
+            // every label that is left is jumped to
+            let body = ir.iter().flat_map(|function| &function.body);
+            for statement in body.clone() {
+                if let IrStatement::Label(label) = statement {
+                    assert!(
+                        body.clone().any(|statement| matches!(
+                            statement,
+                            IrStatement::Jmp(target) | IrStatement::JmpIfZero { target, .. } if target == label
+                        )),
+                        "{name}: L{label} is unused"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn unused_results_and_unreachable_code() {
+    let source = "int f(int a) { int unused = a * 3; a + 1; return a; a = 5; return 7; }\n\
+                  int main() { return f(2); }";
+    let function = lower(source, Optimizations::default()).remove(0);
+    let mut cfg = Cfg::from_function(function);
+
+    // `a * 3`, the copy into `unused`, `a + 1` and the assignment after the return
+    assert_eq!(eliminate_dead_code(&mut cfg), 4);
+    assert_eq!(cfg.blocks.len(), 1);
+    assert!(cfg.blocks[0].statements.is_empty());
+}
+
+#[test]
+fn dead_stores_across_blocks() {
+    // the first store to `x` is overwritten on both paths before it is read
+    let source = "int main() {\n\
+                      int c = 1;\n\
+                      int x = 10;\n\
+                      if (c) x = 1; else x = 2;\n\
+                      return x;\n\
+                  }";
+    let body = &lower(source, only(|o| o.dce = true))[0].body;
+    assert!(
+        !body.iter().any(|statement| matches!(
+            statement,
+            IrStatement::Copy {
+                src: Operand::Const(10),
+                ..
+            }
+        )),
+        "{body:?}"
+    );
+}
+
+#[test]
+fn calls_are_kept() {
+    let source = "int g(int a) { return a; }\nint main() { int x = g(1); g(2); return 0; }";
+    let body = &lower(source, only(|o| o.dce = true))[1].body;
+    let calls: Vec<_> = body
+        .iter()
+        .filter_map(|statement| match statement {
+            IrStatement::Call { dst, .. } => Some(dst),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls.len(), 2, "{body:?}");
+    assert!(calls.iter().all(|dst| dst.is_none()), "{body:?}");
+}
@@ -0,0 +1,159 @@
+//! Checks what errors carry besides their message: the stable error code, labels pointing at
+//! related declarations, and fix-its that make the program compile once applied.
+
+use fbcc::analysis::node::{Location, Span};
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations, Session};
+use fbcc::core::errors::{apply_fixits, CompilerError, ErrorCode, FixIt, Severity};
+use fbcc::core::target::Aarch64AppleDarwin;
+
+/// The errors of checking `source`, which has to parse
+fn analyze(source: &str) -> Vec<CompilerError> {
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let mut translation_unit = session.parse(source).unwrap();
+    session.analyze(&mut translation_unit).unwrap_err()
+}
+
+/// The start of every label of `error`, as `(line, column)`
+fn labels(error: &CompilerError) -> Vec<(usize, usize)> {
+    error
+        .labels
+        .iter()
+        .map(|label| (label.span.start.line, label.span.start.column))
+        .collect()
+}
+
+#[test]
+fn error_codes_round_trip() {
+    for (number, code) in (1..).zip(ErrorCode::ALL) {
+        assert_eq!(code as u32, number, "codes are numbered without gaps");
+        let printed = code.to_string();
+        assert_eq!(printed, format!("E{number:04}"));
+        assert_eq!(printed.parse::<ErrorCode>(), Ok(code));
+        assert_eq!(printed[1..].parse::<ErrorCode>(), Ok(code));
+        assert!(!code.explanation().trim().is_empty());
+    }
+    for invalid in ["E0000", "E1", "E00007", "X0007", "E9999", ""] {
+        assert!(invalid.parse::<ErrorCode>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn redefinitions_point_at_the_previous_definition() {
+    let errors = analyze(
+        "int f() { return 0; }
+int f() { return 1; }
+int main() {
+    int x = 1;
+    int x = 2;
+    return x;
+}
+",
+    );
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert!(errors.iter().all(|error| error.code == Some(ErrorCode::Redefinition)));
+    assert_eq!(labels(&errors[0]), [(1, 5)]);
+    assert_eq!(labels(&errors[1]), [(4, 9)]);
+    assert_eq!(errors[1].message, "Redefinition of `x`");
+    assert_eq!(errors[1].labels[0].message, "previous definition is here");
+}
+
+#[test]
+fn conflicting_declarations_and_calls_point_at_the_declaration() {
+    let errors = analyze(
+        "int add(int a, int b);
+long add(int a, int b);
+int main() { return add(1); }
+",
+    );
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert_eq!(errors[0].code, Some(ErrorCode::ConflictingTypes));
+    assert_eq!(labels(&errors[0]), [(1, 5)]);
+    assert_eq!(errors[1].code, Some(ErrorCode::ArgumentCount));
+    assert_eq!(labels(&errors[1]), [(1, 5)]);
+    assert_eq!(errors[1].labels[0].message, "`add` is declared here");
+}
+
+#[test]
+fn semantic_errors_have_codes() {
+    for (source, code) in [
+        ("int main() { return y; }", ErrorCode::UndeclaredIdentifier),
+        ("int main() { break; return 0; }", ErrorCode::JumpOutsideLoop),
+        ("int main() { 1 = 2; return 0; }", ErrorCode::NotAssignable),
+        ("int main() { int f = 0; return f(); }", ErrorCode::NotAFunction),
+        ("void g() {} int main() { return g() + 1; }", ErrorCode::InvalidOperands),
+        ("signed unsigned int x;", ErrorCode::InvalidTypeSpecifiers),
+    ] {
+        let errors = analyze(source);
+        assert_eq!(errors[0].code, Some(code), "{source}: {errors:?}");
+    }
+}
+
+#[test]
+fn missing_punctuation_comes_with_a_fix_it() {
+    let source = "int main() {
+    int x = (1 + 2
+    return x
+}
+";
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let errors = session.parse(source).unwrap_err();
+    assert!(errors.iter().all(|error| error.code == Some(ErrorCode::ExpectedToken)));
+    let fixits: Vec<FixIt> = errors.into_iter().flat_map(|error| error.fixits).collect();
+    assert_eq!(fixits.len(), 1, "{fixits:?}");
+    assert_eq!(fixits[0].message, "insert `)`");
+
+    // Applying the fix-its of each round of errors eventually gives a program that compiles
+    let mut source = source.to_string();
+    for _ in 0..3 {
+        let (_, result) = Compiler::compile(&source, &CompilerOptions::default(), &Aarch64AppleDarwin);
+        match result {
+            Ok(_) => break,
+            Err(errors) => {
+                let fixits: Vec<FixIt> = errors.into_iter().flat_map(|error| error.fixits).collect();
+                assert!(!fixits.is_empty(), "no fix-it for:\n{source}");
+                source = apply_fixits(&source, &fixits);
+            }
+        }
+    }
+    assert_eq!(source, "int main() {\n    int x = (1 + 2);\n    return x;\n}\n");
+}
+
+#[test]
+fn fix_its_are_applied_in_source_order() {
+    let at = |line, column| Span::new(Location::new(line, column), Location::new(line, column));
+    let fixits = [
+        FixIt::insert(at(2, 6), ";"),
+        FixIt::insert(at(1, 4), ")"),
+        // out of range, so left out
+        FixIt::insert(at(9, 1), "}"),
+        // overlaps the first replacement
+        FixIt {
+            span: Span::new(Location::new(2, 2), Location::new(2, 4)),
+            replacement: String::new(),
+            message: "remove `ea`".to_string(),
+        },
+        FixIt {
+            span: Span::new(Location::new(2, 1), Location::new(2, 3)),
+            replacement: "cr".to_string(),
+            message: "replace `br` with `cr`".to_string(),
+        },
+    ];
+    assert_eq!(apply_fixits("f(1\nbreak\n", &fixits), "f(1)\ncreak;\n");
+}
+
+#[test]
+fn inlining_is_reported_as_a_remark() {
+    let source = "int square(int x) { return x * x; }
+int main() { return square(3); }
+";
+    let options = CompilerOptions::new(Optimizations::level(1));
+    let (diagnostics, result) = Compiler::compile_ir(source, &options, &Aarch64AppleDarwin);
+    assert!(result.is_ok());
+    assert!(
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Remark
+                && diagnostic.message == "inlined `square` into `main`"),
+        "{diagnostics:?}"
+    );
+}
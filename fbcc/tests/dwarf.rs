@@ -0,0 +1,119 @@
+//! Checks the debug info `-g` adds: `.loc` directives for the statements, call frame
+//! information for every function, the variables' locations, and that none of it changes
+//! the code itself.
+
+mod common;
+
+use common::backend_sources;
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::target::{Aarch64AppleDarwin, Aarch64LinuxGnu, Target, X86_64LinuxGnu};
+use fbcc::synthesis::dwarf::DebugInfo;
+
+const SOURCE: &str = "int add(int a, int b) {
+    int sum = a + b;
+    return sum;
+}
+
+int main() {
+    int x = 3;
+    int y = add(x, 4);
+    for (int i = 0; i < 3; i += 1) {
+        y = y + i;
+    }
+    return y;
+}
+";
+
+fn compile(source: &str, optimizations: &Optimizations, debug: bool, target: &dyn Target) -> String {
+    let debug = debug.then(|| DebugInfo {
+        file: "test.c".to_string(),
+        directory: "/src".to_string(),
+    });
+    let (_, result) = Compiler::compile(
+        source,
+        &CompilerOptions::new(optimizations.clone()).with_debug_info(debug),
+        target,
+    );
+    result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message))
+}
+
+/// The lines of the assembly that turn into code, without the debug directives, the labels
+/// they refer to and the sections after the code.
+fn code(asm: &str) -> Vec<&str> {
+    let end = [
+        "\t.section\t__DWARF",
+        "\t.section\t.debug_",
+        "\t.section\t.note.GNU-stack",
+    ]
+    .iter()
+    .filter_map(|section| asm.find(section))
+    .min()
+    .unwrap_or(asm.len());
+    asm[..end]
+        .lines()
+        .filter(|line| {
+            !line.starts_with("\t.loc")
+                && !line.starts_with("\t.cfi_")
+                && !line.starts_with("\t.file")
+                && !line.trim_start_matches('.').starts_with("Lfunc_")
+        })
+        .collect()
+}
+
+fn count(asm: &str, directive: &str) -> usize {
+    asm.lines().filter(|line| line.trim() == directive).count()
+}
+
+#[test]
+fn code_is_unchanged() {
+    let targets: [&dyn Target; 3] = [&Aarch64AppleDarwin, &Aarch64LinuxGnu, &X86_64LinuxGnu];
+    for (name, source) in backend_sources() {
+        for optimizations in [Optimizations::default(), Optimizations::level(1)] {
+            for target in targets {
+                let plain = compile(&source, &optimizations, false, target);
+                let debug = compile(&source, &optimizations, true, target);
+                assert_eq!(code(&debug), code(&plain), "{name} {}", target.triple());
+            }
+        }
+    }
+}
+
+#[test]
+fn statements_have_lines() {
+    let asm = compile(SOURCE, &Optimizations::default(), true, &Aarch64AppleDarwin);
+    assert!(asm.starts_with("\t.file\t1 \"test.c\"\n"), "{asm}");
+    // the declarations, the call, the loop's test, body and step, and the returns
+    for loc in ["2 5", "3 5", "7 5", "8 5", "9 5", "10 9", "9 28", "12 5"] {
+        assert!(asm.contains(&format!("\t.loc\t1 {loc}\n")), "{loc}: {asm}");
+    }
+}
+
+#[test]
+fn functions_have_call_frame_information() {
+    let asm = compile(SOURCE, &Optimizations::default(), true, &Aarch64AppleDarwin);
+    assert_eq!(count(&asm, ".cfi_startproc"), 2);
+    assert_eq!(count(&asm, ".cfi_endproc"), 2);
+    assert_eq!(count(&asm, ".cfi_def_cfa w29, 16"), 2);
+    assert_eq!(count(&asm, ".cfi_remember_state"), count(&asm, ".cfi_restore_state"));
+
+    let asm = compile(SOURCE, &Optimizations::default(), true, &X86_64LinuxGnu);
+    assert_eq!(count(&asm, ".cfi_startproc"), 2);
+    assert_eq!(count(&asm, ".cfi_def_cfa_register %rbp"), 2);
+
+    // nothing without -g
+    let asm = compile(SOURCE, &Optimizations::default(), false, &Aarch64AppleDarwin);
+    assert!(!asm.contains(".cfi_") && !asm.contains(".loc") && !asm.contains("__DWARF"));
+}
+
+#[test]
+fn variables_have_locations() {
+    let asm = compile(SOURCE, &Optimizations::default(), true, &Aarch64AppleDarwin);
+    let info = asm.split("__debug_info").nth(1).unwrap();
+    for name in ["add", "a", "b", "sum", "main", "x", "y", "i", "int"] {
+        assert!(info.contains(&format!("\t.asciz\t\"{name}\"\n")), "{name}: {info}");
+    }
+    // `sum` is 8 bytes above sp in `add`'s 32 byte frame: DW_OP_fbreg -24
+    let sum = info.split("\"sum\"").nth(1).unwrap();
+    let location: Vec<&str> = sum.lines().skip(2).take(3).collect();
+    assert_eq!(location, ["\t.uleb128\t2", "\t.byte\t145", "\t.byte\t104"]);
+}
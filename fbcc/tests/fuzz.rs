@@ -0,0 +1,311 @@
+//! Property tests that [`Compiler::compile`] never panics, whatever it is given: malformed
+//! files have to end in diagnostics. The inputs are arbitrary text, soups of C tokens, and
+//! the programs of `tests/backend` with tokens deleted, duplicated and swapped in.
+//!
+//! `fuzz/` has a `cargo fuzz` target that runs the same check for as long as it is left to.
+
+mod common;
+
+use std::panic;
+use std::sync::OnceLock;
+use std::thread;
+
+use common::backend_sources;
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::target::{target_from_triple, Aarch64AppleDarwin, SUPPORTED_TRIPLES};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+const TOKENS: &[&str] = &[
+    "int",
+    "char",
+    "long",
+    "short",
+    "unsigned",
+    "signed",
+    "void",
+    "float",
+    "double",
+    "_Bool",
+    "_Complex",
+    "const",
+    "static",
+    "extern",
+    "inline",
+    "_Noreturn",
+    "typedef",
+    "struct",
+    "union",
+    "enum",
+    "if",
+    "else",
+    "while",
+    "do",
+    "for",
+    "switch",
+    "case",
+    "default",
+    "break",
+    "continue",
+    "return",
+    "goto",
+    "sizeof",
+    "main",
+    "x",
+    "y",
+    "f",
+    "0",
+    "1",
+    "42",
+    "2147483648",
+    "99999999999999999999",
+    "1.5",
+    "'a'",
+    "\"s\"",
+    "(",
+    ")",
+    "{",
+    "}",
+    "[",
+    "]",
+    ";",
+    ",",
+    ":",
+    "?",
+    "=",
+    "+=",
+    "<<=",
+    "+",
+    "-",
+    "*",
+    "/",
+    "%",
+    "<<",
+    ">>",
+    "&",
+    "|",
+    "^",
+    "~",
+    "!",
+    "&&",
+    "||",
+    "==",
+    "!=",
+    "<",
+    ">=",
+    "++",
+    "--",
+    ".",
+    "->",
+    "...",
+    "#",
+    "@",
+    "$",
+    "\\",
+];
+
+/// The programs of `tests/backend`, split into tokens at whitespace
+fn corpus() -> &'static [Vec<String>] {
+    static CORPUS: OnceLock<Vec<Vec<String>>> = OnceLock::new();
+    CORPUS.get_or_init(|| {
+        backend_sources()
+            .into_iter()
+            .map(|(_, source)| source.split_whitespace().map(str::to_string).collect())
+            .collect()
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Delete(usize),
+    Duplicate(usize),
+    Insert(usize, &'static str),
+}
+
+/// A corpus program with a few edits
+fn mutated_program() -> impl Strategy<Value = String> {
+    let edit = prop_oneof![
+        any::<usize>().prop_map(Edit::Delete),
+        any::<usize>().prop_map(Edit::Duplicate),
+        (any::<usize>(), select(TOKENS)).prop_map(|(at, token)| Edit::Insert(at, token)),
+    ];
+    (any::<prop::sample::Index>(), prop::collection::vec(edit, 1..4)).prop_map(|(program, edits)| {
+        let mut tokens = program.get(corpus()).clone();
+        for edit in edits {
+            let len = tokens.len().max(1);
+            match edit {
+                Edit::Delete(at) if !tokens.is_empty() => {
+                    tokens.remove(at % len);
+                }
+                Edit::Duplicate(at) if !tokens.is_empty() => {
+                    let token = tokens[at % len].clone();
+                    tokens.insert(at % len, token);
+                }
+                Edit::Insert(at, token) => tokens.insert(at % (tokens.len() + 1), token.to_string()),
+                _ => {}
+            }
+        }
+        tokens.join(" ")
+    })
+}
+
+fn token_soup() -> impl Strategy<Value = String> {
+    prop::collection::vec(select(TOKENS), 0..48).prop_map(|tokens| tokens.join(" "))
+}
+
+/// Compiles `source` for every target, with and without optimisations, and fails if any of
+/// the compilations panics
+fn compiles_without_panicking(source: &str) -> Result<(), TestCaseError> {
+    for triple in SUPPORTED_TRIPLES {
+        let target = target_from_triple(triple).unwrap();
+        for level in [0, 1] {
+            let options = CompilerOptions::new(Optimizations::level(level));
+            let compile = || Compiler::compile(source, &options, target.as_ref());
+            let compiled = panic::catch_unwind(panic::AssertUnwindSafe(compile));
+            prop_assert!(compiled.is_ok(), "{triple} -O{level} panicked on:\n{source}");
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn arbitrary_text_does_not_panic(source in any::<String>()) {
+        compiles_without_panicking(&source)?;
+    }
+
+    #[test]
+    fn token_soup_does_not_panic(source in token_soup()) {
+        compiles_without_panicking(&source)?;
+    }
+
+    #[test]
+    fn mutated_programs_do_not_panic(source in mutated_program()) {
+        compiles_without_panicking(&source)?;
+    }
+}
+
+/// Inputs that used to panic
+#[test]
+fn known_crashers_do_not_panic() {
+    let crashers = [
+        "int helper(int x);",
+        "_Complex x;",
+        "inline int x;",
+        "int main() { goto end; }",
+        "int main() { int x = {1}; return x; }",
+        "int main() { return sizeof(int); }",
+        "int main() { int x; return &x; }",
+        "int main() { return 99999999999999999999; }",
+        "int 1x;",
+        "int",
+        "int main(",
+        "'}",
+    ];
+    for source in crashers {
+        compiles_without_panicking(source).unwrap();
+    }
+}
+
+/// Nesting past the parser's limit is an error, not a stack overflow, and nesting up to it
+/// compiles at every optimization level: the first depth that doesn't compile is the one
+/// past the limit, and it is past what C11 (5.2.4.1) requires. Chains of left-associative
+/// operators and of `else if` don't nest, and compile at any length. The compiler gets the stack
+/// the command line driver gives it, as a debug build needs more than a test thread has.
+#[test]
+fn deep_nesting_is_an_error() {
+    // each form, and how deep C11 requires it to go
+    type Form = fn(usize) -> String;
+    let nested: [(Form, usize); 8] = [
+        (
+            |depth| format!("int main() {{ return {}1{}; }}", "(".repeat(depth), ")".repeat(depth)),
+            63,
+        ),
+        (
+            |depth| format!("int main() {{ {}{} return 0; }}", "{".repeat(depth), "}".repeat(depth)),
+            127,
+        ),
+        (|depth| format!("int main() {{ return {}1; }}", "- ".repeat(depth)), 63),
+        (
+            |depth| format!("int main() {{ int x = 0; x{}; return x; }}", "=x".repeat(depth)),
+            63,
+        ),
+        (
+            |depth| format!("int main() {{ return {}1; }}", "1 ? 1 : ".repeat(depth)),
+            63,
+        ),
+        (
+            |depth| {
+                format!(
+                    "int main() {{ int x = 0; {}x = 1; return x; }}",
+                    "if (1) ".repeat(depth)
+                )
+            },
+            127,
+        ),
+        (
+            |depth| {
+                format!(
+                    "int main() {{ int x = 0; {}x = 1; return x; }}",
+                    "while (x) ".repeat(depth)
+                )
+            },
+            127,
+        ),
+        (
+            |depth| {
+                format!(
+                    "int f(int x) {{ return x; }} int main() {{ return {}1{}; }}",
+                    "f(".repeat(depth),
+                    ")".repeat(depth)
+                )
+            },
+            63,
+        ),
+    ];
+    let chained: [Form; 2] = [
+        |length| format!("int main() {{ int x = 1; return x{}; }}", "+x".repeat(length)),
+        |length| {
+            format!(
+                "int main() {{ int x = 0; if (x) x = 1; {}else x = 2; return x; }}",
+                "else if (x) x = 1; ".repeat(length)
+            )
+        },
+    ];
+
+    let compiler = thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(move || {
+        for level in [0, 2] {
+            let options = CompilerOptions::new(Optimizations::level(level));
+            let compile = |source: String| Compiler::compile(&source, &options, &Aarch64AppleDarwin).1;
+
+            for (form, required) in nested {
+                if let Err(errors) = compile(form(required)) {
+                    panic!("{:?} at -O{level}", errors[0].message);
+                }
+
+                // the depths that compile go up to the deepest, so it is found by bisection
+                let (mut deepest, mut past) = (required, 10_000);
+                while past - deepest > 1 {
+                    let depth = (deepest + past) / 2;
+                    match compile(form(depth)) {
+                        Ok(_) => deepest = depth,
+                        Err(_) => past = depth,
+                    }
+                }
+                for depth in [deepest + 1, 10_000] {
+                    let errors = compile(form(depth)).unwrap_err();
+                    assert!(errors[0].message.starts_with("Nesting is too deep"), "{errors:?}");
+                }
+            }
+
+            for form in chained {
+                if let Err(errors) = compile(form(1_000)) {
+                    panic!("{:?} at -O{level}", errors[0].message);
+                }
+            }
+        }
+    });
+    compiler.unwrap().join().unwrap();
+}
@@ -0,0 +1,124 @@
+//! Checks the inliner: which calls it replaces, which it must leave alone, and that the
+//! programs in `tests/backend` still compute the same results.
+
+mod common;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use common::{backend_sources, compile_ir, lower, only, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::ir::{IrFunction, IrStatement};
+
+/// Names of the functions `function` still calls.
+fn calls(function: &IrFunction) -> Vec<&str> {
+    function
+        .body
+        .iter()
+        .filter_map(|statement| match statement {
+            IrStatement::Call { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = run(&lower(&source, Optimizations::default()));
+        for optimizations in [only(|o| o.inline = true), Optimizations::level(1)] {
+            assert_eq!(run(&lower(&source, optimizations)), expected, "{name}");
+        }
+    }
+}
+
+#[test]
+fn nested_calls_disappear() {
+    let source =
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/backend/09_nested_calls.c")).unwrap();
+    let ir = lower(&source, only(|o| o.inline = true));
+    let main = ir.iter().find(|function| function.name == "main").unwrap();
+    assert!(calls(main).is_empty(), "{main}");
+    assert_eq!(run(&ir), 21);
+
+    // with constant propagation on top, nothing is left to compute
+    let ir = lower(&source, Optimizations::level(1));
+    let main = ir.iter().find(|function| function.name == "main").unwrap();
+    assert_eq!(main.body.len(), 1, "{main}");
+}
+
+#[test]
+fn recursion_is_not_inlined() {
+    let source = "int fact(int n) { if (n <= 1) return 1; return n * fact(n - 1); }\n\
+                  int main() { return fact(5); }";
+    let ir = lower(source, only(|o| o.inline = true));
+    assert_eq!(calls(&ir[0]), ["fact"]);
+    assert_eq!(calls(&ir[1]), ["fact"]);
+    assert_eq!(run(&ir), 120);
+
+    // mutual recursion neither
+    let source = "int odd(int n) { if (n == 0) return 0; return 1 - odd(n - 1) + odd(n - 1) - odd(n - 1); }\n\
+                  int main() { return odd(3); }";
+    let ir = lower(source, only(|o| o.inline = true));
+    assert!(calls(&ir[0]).iter().all(|name| *name == "odd"));
+    assert_eq!(calls(&ir[1]), ["odd"]);
+}
+
+#[test]
+fn hints_and_attributes() {
+    // twenty-odd statements: too big without a hint, small enough for `inline`
+    let body = "{ int s = 0; s += a * 3; s -= a / 2; s += a % 5; s *= 2; s += a * a; s -= 7; s += a << 1; \
+                s ^= a; s += a * 3; s -= a / 3; return s; }";
+    let program = |specifiers: &str| format!("{specifiers} int f(int a) {body}\nint main() {{ return f(9) % 100; }}");
+
+    let plain = lower(&program(""), only(|o| o.inline = true));
+    assert_eq!(calls(&plain[1]), ["f"]);
+
+    let expected = run(&plain);
+    for specifiers in [
+        "inline",
+        "__attribute__((always_inline))",
+        "__attribute__((unused, __always_inline__))",
+    ] {
+        let ir = lower(&program(specifiers), only(|o| o.inline = true));
+        assert!(calls(&ir[1]).is_empty(), "{specifiers}: {}", ir[1]);
+        assert_eq!(run(&ir), expected, "{specifiers}");
+    }
+
+    let ir = lower(
+        "__attribute__((noinline)) int one() { return 1; }\nint main() { return one(); }",
+        only(|o| o.inline = true),
+    );
+    assert_eq!(calls(&ir[1]), ["one"]);
+}
+
+#[test]
+fn every_copy_gets_its_own_labels() {
+    let source = "int sum(int n) { int s = 0; while (n > 0) { s += n; n -= 1; } return s; }\n\
+                  int main() { return sum(3) + sum(4) * sum(2); }";
+    let ir = lower(source, only(|o| o.inline = true));
+    assert!(calls(&ir[1]).is_empty(), "{}", ir[1]);
+    assert_eq!(run(&ir), 6 + 10 * 3);
+
+    let mut labels = HashSet::new();
+    for statement in ir.iter().flat_map(|function| &function.body) {
+        if let IrStatement::Label(label) = statement {
+            assert!(labels.insert(*label), "L{label} is defined twice");
+        }
+    }
+}
+
+#[test]
+fn recursive_always_inline_warns() {
+    let source = "__attribute__((always_inline)) int f(int n) { if (n) return f(n - 1); return 0; }\n\
+                  int main() { return f(2); }";
+    let (diagnostics, ir) = compile_ir(source, only(|o| o.inline = true));
+    assert_eq!(calls(&ir[1]), ["f"]);
+    assert!(
+        diagnostics
+            .iter()
+            .any(|warning| warning.message.contains("always_inline")),
+        "{diagnostics:?}"
+    );
+}
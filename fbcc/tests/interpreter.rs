@@ -0,0 +1,76 @@
+//! Runs the programs in `tests/backend` with the IR interpreter, so the backend suite's
+//! expectations are checked on any machine.
+
+mod common;
+
+use common::{backend_sources, expected_status, lower};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::interpreter::{Interpreter, Trap};
+use fbcc::synthesis::ir::{InlineHint, IrFunction, IrStatement, Operand};
+
+fn run(source: &str) -> Result<i32, Trap> {
+    Interpreter::new(&lower(source, Optimizations::default()), Vec::new()).run_main()
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = expected_status(&name, &source);
+        let status = run(&source).unwrap_or_else(|trap| panic!("{name} trapped: {trap}"));
+        assert_eq!(status & 0xff, i32::from(expected), "{name}");
+    }
+}
+
+#[test]
+fn division_by_zero_traps() {
+    let trap = run("int f(int a, int b) { return a % b; }\nint main() { return f(7, 0); }");
+    assert_eq!(
+        trap,
+        Err(Trap::DivisionByZero {
+            function: "f".to_string()
+        })
+    );
+}
+
+#[test]
+fn uninitialised_read_traps() {
+    let trap = run("int main() { int x; int y = 1; return x + y; }");
+    assert!(matches!(trap, Err(Trap::UninitialisedRead { .. })), "{trap:?}");
+}
+
+#[test]
+fn runaway_recursion_traps() {
+    let trap = run("int f(int n) { return f(n + 1); }\nint main() { return f(0); }");
+    assert_eq!(trap, Err(Trap::StackOverflow));
+}
+
+#[test]
+fn builtins() {
+    // there is no way to declare `putchar` and `exit` in C yet, so build the IR by hand
+    let call = |name: &str, arg: i64| IrStatement::Call {
+        dst: None,
+        name: name.to_string(),
+        args: vec![Operand::Const(arg)],
+        tail: false,
+    };
+    let main = IrFunction {
+        name: "main".to_string(),
+        framesize: 16,
+        params: Vec::new(),
+        slots: Vec::new(),
+        inline: InlineHint::None,
+        source: Default::default(),
+        body: vec![
+            call("putchar", 'o' as i64),
+            call("putchar", 'k' as i64),
+            call("exit", 3),
+            call("putchar", '!' as i64),
+            IrStatement::Ret(Operand::Const(0)),
+        ],
+    };
+
+    let ir = [main];
+    let mut interpreter = Interpreter::new(&ir, Vec::new());
+    assert_eq!(interpreter.run_main(), Ok(3));
+    assert_eq!(interpreter.into_output(), b"ok");
+}
@@ -0,0 +1,156 @@
+//! Checks that functions with large stack frames lower to ARM64 code that both assembles,
+//! with every immediate in range, and still computes the same result on the simulator in
+//! [`arm64`].
+
+mod arm64;
+mod common;
+
+use arm64::{function, run};
+use fbcc::compiler::Optimizations;
+use fbcc::core::target::{CallingConvention, ObjectFormat};
+use fbcc::synthesis::arm64::{access_size, AluOp, Inst, Mem, Operand2, Reg};
+use fbcc::synthesis::asm::Arm64AsmEmitter;
+use fbcc::synthesis::legalize::legalize;
+
+fn lower(source: &str, peephole: bool) -> Vec<Inst> {
+    let ir = common::lower(source, Optimizations::default());
+    Arm64AsmEmitter::new(ObjectFormat::MachO, CallingConvention::AppleArm64, peephole)
+        .lower(&ir)
+        .unwrap()
+}
+
+/// A function with `count` locals, so a frame of about `8 * count` bytes at `-O0`, that
+/// returns the sum of a few of them.
+fn wide(count: usize) -> (String, u8) {
+    let mut source = String::from("int wide(int x) {\n");
+    let mut sum = 0;
+    for i in 0..count {
+        source += &format!("    int v{i} = x + {};\n", i % 7);
+    }
+    source += "    int s = 0;\n";
+    for i in (0..count).step_by(97).chain([count - 1]) {
+        source += &format!("    s += v{i};\n");
+        sum += 1 + i % 7;
+    }
+    source += "    return s;\n}\nint main() { return wide(1) & 255; }\n";
+    (source, sum as u8)
+}
+
+fn is_legal(inst: &Inst) -> bool {
+    match inst {
+        Inst::Alu {
+            r: Operand2::Imm(imm), ..
+        }
+        | Inst::Cmp {
+            r: Operand2::Imm(imm), ..
+        } => *imm <= 0xfff || imm & 0xfff == 0 && *imm <= 0xfff000,
+        Inst::Ldr { dst: reg, mem } | Inst::Str { src: reg, mem } => mem.fits(access_size(*reg)),
+        Inst::Ldp { mem, .. } | Inst::Stp { mem, .. } => mem.fits_pair(),
+        _ => true,
+    }
+}
+
+#[test]
+fn large_frames() {
+    let (source, expected) = wide(6000);
+    for peephole in [false, true] {
+        let code = lower(&source, peephole);
+        assert!(
+            code.iter().all(is_legal),
+            "{:#?}",
+            code.iter().find(|inst| !is_legal(inst))
+        );
+        assert_eq!(run(&code), expected);
+
+        // the last slots are closest to the frame record
+        let wide = function(&code, "wide");
+        assert!(wide.iter().any(|inst| matches!(
+            inst,
+            Inst::Ldr {
+                mem: Mem {
+                    base: Reg::X(29),
+                    offset: ..0
+                },
+                ..
+            }
+        )));
+    }
+}
+
+#[test]
+fn small_frames_are_untouched() {
+    let (source, expected) = wide(50);
+    let code = lower(&source, false);
+    let wide = function(&code, "wide");
+    assert_eq!(
+        wide[0],
+        Inst::Alu {
+            op: AluOp::Sub,
+            dst: Reg::Sp,
+            l: Reg::Sp,
+            r: Operand2::Imm(432)
+        }
+    );
+    assert!(!wide.iter().any(|inst| inst.uses().contains(&Reg::X(16))));
+    assert_eq!(run(&code), expected);
+}
+
+#[test]
+fn huge_immediates_go_through_x16() {
+    let mut code = vec![
+        Inst::Alu {
+            op: AluOp::Sub,
+            dst: Reg::Sp,
+            l: Reg::Sp,
+            r: Operand2::Imm(0x1234560),
+        },
+        Inst::Str {
+            src: Reg::W(9),
+            mem: Mem {
+                base: Reg::Sp,
+                offset: 0x1234550,
+            },
+        },
+    ];
+    legalize(&mut code);
+    assert_eq!(
+        code,
+        [
+            Inst::MovImm {
+                dst: Reg::W(16),
+                imm: 0x4560
+            },
+            Inst::Movk {
+                dst: Reg::W(16),
+                imm: 0x123
+            },
+            Inst::Alu {
+                op: AluOp::Sub,
+                dst: Reg::Sp,
+                l: Reg::Sp,
+                r: Operand2::Reg(Reg::X(16))
+            },
+            Inst::MovImm {
+                dst: Reg::W(16),
+                imm: 0x4000
+            },
+            Inst::Movk {
+                dst: Reg::W(16),
+                imm: 0x123
+            },
+            Inst::Alu {
+                op: AluOp::Add,
+                dst: Reg::X(16),
+                l: Reg::Sp,
+                r: Operand2::Reg(Reg::X(16))
+            },
+            Inst::Str {
+                src: Reg::W(9),
+                mem: Mem {
+                    base: Reg::X(16),
+                    offset: 0x550
+                }
+            },
+        ]
+    );
+}
@@ -0,0 +1,99 @@
+//! Checks the LLVM IR backend: the text it generates, and with LLVM installed, that `opt`
+//! accepts the module of every program in `tests/backend`. `tests/backends.rs` runs them with
+//! `lli`.
+
+mod common;
+
+use std::process::Command;
+
+use common::{each_backend_program, emit, installed, opaque_pointers};
+use fbcc::compiler::{Compiler, Optimizations};
+use fbcc::core::target::X86_64LinuxGnu;
+use fbcc::synthesis::ir::{InlineHint, IrFunction, IrStatement, Operand};
+use fbcc::synthesis::llvm::LlvmIrEmitter;
+
+fn llvm_ir(source: &str, optimizations: Optimizations) -> String {
+    emit(Compiler::compile_llvm_ir, source, optimizations, &X86_64LinuxGnu)
+}
+
+#[test]
+fn modules_name_the_target() {
+    let ll = llvm_ir("int main() { return 0; }", Optimizations::default());
+    assert!(ll.contains("target triple = \"x86_64-linux-gnu\"\n"), "{ll}");
+}
+
+#[test]
+fn shift_counts_wrap_like_the_machine() {
+    let ll = llvm_ir("int main() { int x = 33; return 1 << x; }", Optimizations::default());
+    let mask = ll.find(", 31\n").unwrap_or_else(|| panic!("{ll}"));
+    assert!(ll[mask..].contains(" = shl i32 1, %t"), "{ll}");
+}
+
+#[test]
+fn sibling_calls_are_marked_tail() {
+    let source = "int sum(int n, int acc) { if (n == 0) return acc; return sum(n - 1, acc + n); }\n\
+                  int offset_sum(int n) { return sum(n, n + 1); }\n\
+                  int main() { return offset_sum(3); }";
+    let ll = llvm_ir(source, Optimizations::level(1));
+    assert!(ll.contains(" = tail call i32 @sum("), "{ll}");
+    assert!(!llvm_ir(source, Optimizations::default()).contains("tail call"));
+}
+
+#[test]
+fn undefined_functions_are_declared() {
+    // there is no way to declare `putchar` in C yet, so build the IR by hand
+    let call = |args| IrStatement::Call {
+        dst: None,
+        name: "putchar".to_string(),
+        args,
+        tail: false,
+    };
+    let main = |body| IrFunction {
+        name: "main".to_string(),
+        framesize: 16,
+        params: Vec::new(),
+        slots: Vec::new(),
+        inline: InlineHint::None,
+        source: Default::default(),
+        body,
+    };
+
+    let ll = LlvmIrEmitter::new("x86_64-linux-gnu")
+        .emit(&[main(vec![
+            call(vec![Operand::Const(104)]),
+            call(vec![Operand::Const(105)]),
+            IrStatement::Ret(Operand::Const(0)),
+        ])])
+        .unwrap();
+    assert_eq!(ll.matches("declare i32 @putchar(i32)\n").count(), 1, "{ll}");
+
+    // a declaration has a single signature
+    let mismatched = main(vec![
+        call(vec![Operand::Const(104)]),
+        call(vec![Operand::Const(104), Operand::Const(105)]),
+        IrStatement::Ret(Operand::Const(0)),
+    ]);
+    assert!(LlvmIrEmitter::new("x86_64-linux-gnu").emit(&[mismatched]).is_err());
+}
+
+#[test]
+fn backend_programs_verify() {
+    if !installed("opt") {
+        eprintln!("skipped: needs opt");
+        return;
+    }
+
+    let flags = opaque_pointers("opt");
+    each_backend_program(
+        "opt",
+        Compiler::compile_llvm_ir,
+        &X86_64LinuxGnu,
+        "ll",
+        |path, name, _| {
+            let mut opt = Command::new("opt");
+            opt.args(&flags).args(["-verify", "-disable-output"]).arg(path);
+            let status = opt.status().unwrap();
+            assert!(status.success(), "{name}: {status}");
+        },
+    );
+}
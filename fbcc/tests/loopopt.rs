@@ -0,0 +1,192 @@
+//! Checks the loop optimisations: what each one does to a loop, that it leaves alone what
+//! it must, and that the programs in `tests/backend` still compute the same results.
+
+mod common;
+
+use common::{backend_sources, lower, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::ir::{BinaryOp, IrFunction, IrStatement};
+
+/// The statements from the first label some later jump goes back to, up to that jump.
+fn loop_body(function: &IrFunction) -> &[IrStatement] {
+    let body = &function.body;
+    for (end, statement) in body.iter().enumerate() {
+        let target = match statement {
+            IrStatement::Jmp(target) | IrStatement::JmpIfZero { target, .. } => target,
+            _ => continue,
+        };
+        if let Some(start) = body[..end]
+            .iter()
+            .position(|statement| matches!(statement, IrStatement::Label(label) if label == target))
+        {
+            return &body[start..=end];
+        }
+    }
+    &[]
+}
+
+fn count(statements: &[IrStatement], matches: impl Fn(&IrStatement) -> bool) -> usize {
+    statements.iter().filter(|statement| matches(statement)).count()
+}
+
+fn is_jump(statement: &IrStatement) -> bool {
+    matches!(statement, IrStatement::Jmp(_) | IrStatement::JmpIfZero { .. })
+}
+
+fn is_multiply(statement: &IrStatement) -> bool {
+    matches!(
+        statement,
+        IrStatement::BinaryOp {
+            op: BinaryOp::Mul | BinaryOp::LShift,
+            ..
+        }
+    )
+}
+
+const SCALED: &str = "int scaled(int n, int k) { int s = 0; int i = 0; \
+                      while (i < n) { s += i * 4 + (k * 7 + n); i += 1; } return s; }\n\
+                      int main() { return scaled(5, 2) - scaled(0, 9); }";
+
+#[test]
+fn backend_programs() {
+    let each = [
+        Optimizations {
+            rotate: true,
+            ..Default::default()
+        },
+        Optimizations {
+            licm: true,
+            ..Default::default()
+        },
+        Optimizations {
+            coalesce: true,
+            strength_reduction: true,
+            ..Default::default()
+        },
+        Optimizations {
+            coalesce: true,
+            rotate: true,
+            unroll: true,
+            ..Default::default()
+        },
+        Optimizations::level(1),
+        Optimizations {
+            unroll: true,
+            ..Optimizations::level(1)
+        },
+    ];
+    for (name, source) in backend_sources() {
+        let expected = run(&lower(&source, Optimizations::default()));
+        for optimizations in each.clone() {
+            assert_eq!(
+                run(&lower(&source, optimizations.clone())),
+                expected,
+                "{name} with {optimizations:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn invariants_are_hoisted() {
+    let before = lower(SCALED, Optimizations::default());
+    let ir = lower(
+        SCALED,
+        Optimizations {
+            licm: true,
+            ..Default::default()
+        },
+    );
+    // `k * 7 + n` leaves the loop, `i * 4` stays
+    assert_eq!(count(loop_body(&before[0]), is_multiply), 2, "{}", before[0]);
+    assert_eq!(count(loop_body(&ir[0]), is_multiply), 1, "{}", ir[0]);
+    assert_eq!(run(&ir), 135);
+}
+
+#[test]
+fn possible_traps_stay_guarded() {
+    // hoisting `100 / d` would divide by zero before the loop checks `d`
+    let source = "int guarded(int n, int d) { int s = 0; \
+                  for (int i = 0; i < n; i += 1) { if (d != 0) s += 100 / d; s += i; } return s; }\n\
+                  int main() { return guarded(4, 0) + guarded(0, 0); }";
+    let ir = lower(source, Optimizations::level(1));
+    let divides = |statement: &IrStatement| matches!(statement, IrStatement::BinaryOp { op: BinaryOp::Div, .. });
+    assert_eq!(count(loop_body(&ir[0]), divides), 1, "{}", ir[0]);
+    assert_eq!(run(&ir), 6);
+}
+
+#[test]
+fn hoisted_conditions_keep_int_slots() {
+    // `d != 0` is converted to `_Bool` and, hoisted, stays live across the loop. The backends
+    // load and store every slot as an `int`, so a 1-byte slot packed in before another one
+    // would read that one's bytes along with its own
+    let source = "int guarded(int n, int d) { int s = 0; \
+                  for (int i = 0; i < n; i += 1) { if (d != 0) s += 100 / d; s += i << 2; } return s; }\n\
+                  int main() { return guarded(4, 0) + guarded(3, 50); }";
+    let ir = lower(source, Optimizations::level(1));
+    for function in &ir {
+        assert!(
+            function.slots.iter().all(|slot| slot.size == 4),
+            "{function}: {:?}",
+            function.slots
+        );
+    }
+    assert_eq!(run(&ir), 42);
+}
+
+#[test]
+fn multiplications_become_additions() {
+    let ir = lower(
+        SCALED,
+        Optimizations {
+            coalesce: true,
+            licm: true,
+            strength_reduction: true,
+            ..Default::default()
+        },
+    );
+    assert_eq!(count(loop_body(&ir[0]), is_multiply), 0, "{}", ir[0]);
+    assert_eq!(run(&ir), 135);
+
+    // shifts by a constant too
+    let source = "int main() { int s = 0; for (int i = 3; i < 9; i += 2) s += i << 3; return s; }";
+    let ir = lower(source, Optimizations::level(1));
+    assert_eq!(count(loop_body(&ir[0]), is_multiply), 0, "{}", ir[0]);
+    assert_eq!(run(&ir), (3 + 5 + 7) * 8);
+}
+
+#[test]
+fn rotated_loops_branch_once() {
+    let before = lower(SCALED, Optimizations::default());
+    let ir = lower(
+        SCALED,
+        Optimizations {
+            rotate: true,
+            ..Default::default()
+        },
+    );
+    assert_eq!(count(loop_body(&before[0]), is_jump), 2, "{}", before[0]);
+    assert_eq!(count(loop_body(&ir[0]), is_jump), 1, "{}", ir[0]);
+    assert_eq!(run(&ir), 135);
+}
+
+#[test]
+fn constant_trip_loops_are_unrolled() {
+    let source = "int main() { int s = 0; for (int i = 0; i < 5; i += 1) s = s * 3 + i; return s; }";
+    let unrolled = Optimizations {
+        unroll: true,
+        ..Optimizations::level(1)
+    };
+    let ir = lower(source, unrolled.clone());
+    assert!(loop_body(&ir[0]).is_empty(), "{}", ir[0]);
+    assert_eq!(run(&ir), 58);
+
+    // unknown trip counts and long loops stay loops
+    for source in [
+        "int f(int n) { int s = 0; for (int i = 0; i < n; i += 1) s += i; return s; }\nint main() { return f(5); }",
+        "int main() { int s = 0; for (int i = 0; i < 1000; i += 1) s ^= i; return s; }",
+    ] {
+        let ir = lower(source, unrolled.clone());
+        assert!(!loop_body(&ir[0]).is_empty(), "{}", ir[0]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Checks the integrated assemblers behind `-c`: instruction encodings against the bytes
+//! llvm-mc produces, the symbols and relocations of the objects, the ELF and Mach-O headers,
+//! and that every backend program assembles.
+
+mod common;
+
+use common::backend_sources;
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::target::{Aarch64AppleDarwin, Aarch64LinuxGnu, Riscv64LinuxGnu, Target, X86_64LinuxGnu};
+use fbcc::synthesis::arm64::{AluOp, Cond, Inst, Mem, Operand2, Reg};
+use fbcc::synthesis::arm64_assembler;
+use fbcc::synthesis::asm::Arm64AsmEmitter;
+use fbcc::synthesis::object::{Object, RelocationKind};
+use fbcc::synthesis::x86_64_assembler;
+
+const SOURCE: &str = "int factorial(int n) {
+    int result = 1;
+    while (n > 1) {
+        result = result * n;
+        n = n - 1;
+    }
+    return result;
+}
+
+int twice(int n) {
+    return factorial(n) + factorial(n);
+}
+
+int main() {
+    return twice(3);
+}
+";
+
+fn object(source: &str, target: &dyn Target) -> Vec<u8> {
+    let (_, result) = Compiler::compile_object(source, &CompilerOptions::new(Optimizations::default()), target);
+    result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn arm64_encodings_match_the_assembler() {
+    let w = Reg::W;
+    let alu = |op, dst, l, r| Inst::Alu { op, dst, l, r };
+    let cases = [
+        (alu(AluOp::Add, w(0), w(0), Operand2::Reg(w(1))), 0x0b010000),
+        (alu(AluOp::Sub, Reg::Sp, Reg::Sp, Operand2::Imm(16)), 0xd10043ff),
+        (alu(AluOp::Sub, Reg::Sp, Reg::Sp, Operand2::Imm(0x3000)), 0xd1400fff),
+        (
+            alu(AluOp::Add, Reg::X(16), Reg::Sp, Operand2::Reg(Reg::X(16))),
+            0x8b3063f0,
+        ),
+        (alu(AluOp::Subs, w(9), w(10), Operand2::Reg(w(11))), 0x6b0b0149),
+        (alu(AluOp::Mul, w(0), w(1), Operand2::Reg(w(2))), 0x1b027c20),
+        (alu(AluOp::Sdiv, w(3), w(4), Operand2::Reg(w(5))), 0x1ac50c83),
+        (alu(AluOp::Eor, w(0), w(0), Operand2::Reg(w(1))), 0x4a010000),
+        (alu(AluOp::Asr, w(0), w(0), Operand2::Reg(w(1))), 0x1ac12800),
+        (
+            Inst::Msub {
+                dst: w(0),
+                a: w(1),
+                b: w(2),
+                c: w(3),
+            },
+            0x1b028c20,
+        ),
+        (Inst::Neg { dst: w(0), src: w(1) }, 0x4b0103e0),
+        (Inst::Mvn { dst: w(0), src: w(1) }, 0x2a2103e0),
+        (
+            Inst::Cmp {
+                l: w(8),
+                r: Operand2::Imm(10),
+            },
+            0x7100291f,
+        ),
+        (
+            Inst::Cset {
+                dst: w(0),
+                cond: Cond::Lt,
+            },
+            0x1a9fa7e0,
+        ),
+        (
+            Inst::Mov {
+                dst: Reg::X(29),
+                src: Reg::Sp,
+            },
+            0x910003fd,
+        ),
+        (Inst::Mov { dst: w(0), src: w(19) }, 0x2a1303e0),
+        (
+            Inst::MovImm {
+                dst: w(16),
+                imm: 0x1234,
+            },
+            0x52824690,
+        ),
+        (Inst::Movk { dst: w(16), imm: 5 }, 0x72a000b0),
+        (
+            Inst::Ldr {
+                dst: w(0),
+                mem: Mem {
+                    base: Reg::Sp,
+                    offset: 12,
+                },
+            },
+            0xb9400fe0,
+        ),
+        (
+            Inst::Str {
+                src: Reg::X(1),
+                mem: Mem {
+                    base: Reg::X(29),
+                    offset: -8,
+                },
+            },
+            0xf81f83a1,
+        ),
+        (
+            Inst::Ldp {
+                first: Reg::X(29),
+                second: Reg::X(30),
+                mem: Mem {
+                    base: Reg::Sp,
+                    offset: 16,
+                },
+            },
+            0xa9417bfd,
+        ),
+        (
+            Inst::Stp {
+                first: Reg::X(29),
+                second: Reg::X(30),
+                mem: Mem {
+                    base: Reg::Sp,
+                    offset: 0,
+                },
+            },
+            0xa9007bfd,
+        ),
+        (Inst::Ret, 0xd65f03c0),
+    ];
+    for (inst, word) in cases {
+        let object = arm64_assembler::assemble(std::slice::from_ref(&inst)).unwrap();
+        assert_eq!(object.text, u32::to_le_bytes(word), "{inst}");
+    }
+}
+
+#[test]
+fn x86_64_encodings_match_the_assembler() {
+    let cases: [(&str, &[u8]); 19] = [
+        ("pushq\t%rbp", &[0x55]),
+        ("movq\t%rsp, %rbp", &[0x48, 0x89, 0xe5]),
+        // always the 32-bit immediate, where llvm-mc picks the 8-bit one
+        ("subq\t$32, %rsp", &[0x48, 0x81, 0xec, 0x20, 0, 0, 0]),
+        ("movl\t%edi, 4(%rsp)", &[0x89, 0x7c, 0x24, 0x04]),
+        ("movl\t300(%rsp), %eax", &[0x8b, 0x84, 0x24, 0x2c, 0x01, 0, 0]),
+        ("movl\t$42, %r8d", &[0x41, 0xb8, 0x2a, 0, 0, 0]),
+        ("movl\t%eax, %r9d", &[0x41, 0x89, 0xc1]),
+        ("addl\t%ecx, %eax", &[0x01, 0xc8]),
+        ("imull\t%ecx, %eax", &[0x0f, 0xaf, 0xc1]),
+        ("cltd", &[0x99]),
+        ("idivl\t%ecx", &[0xf7, 0xf9]),
+        ("negl\t%eax", &[0xf7, 0xd8]),
+        ("sarl\t%cl, %eax", &[0xd3, 0xf8]),
+        ("cmpl\t%ecx, %eax", &[0x39, 0xc8]),
+        ("setl\t%al", &[0x0f, 0x9c, 0xc0]),
+        ("movzbl\t%al, %eax", &[0x0f, 0xb6, 0xc0]),
+        ("testl\t%eax, %eax", &[0x85, 0xc0]),
+        ("popq\t%rbp", &[0x5d]),
+        ("ret", &[0xc3]),
+    ];
+    for (line, bytes) in cases {
+        let object = x86_64_assembler::assemble(&format!("\t{line}\n")).unwrap();
+        assert_eq!(object.text, bytes, "{line}");
+    }
+}
+
+/// The IR of `SOURCE` assembled for a target, before it's written out.
+fn assembled(target: &dyn Target) -> Object {
+    let (_, ir) = Compiler::compile_ir(SOURCE, &CompilerOptions::new(Optimizations::default()), target);
+    let ir = ir.unwrap();
+    match target.triple() {
+        "x86_64-linux-gnu" => {
+            let asm = target.emit_asm(&ir, &Optimizations::default(), None).unwrap();
+            x86_64_assembler::assemble(&asm).unwrap()
+        }
+        _ => {
+            let emitter = Arm64AsmEmitter::new(target.object_format(), target.calling_convention(), false);
+            arm64_assembler::assemble(&emitter.lower(&ir).unwrap()).unwrap()
+        }
+    }
+}
+
+#[test]
+fn calls_are_relocated_and_jumps_resolved() {
+    for (target, prefix, call) in [
+        (&Aarch64AppleDarwin as &dyn Target, "_", RelocationKind::Arm64Call),
+        (&Aarch64LinuxGnu, "", RelocationKind::Arm64Call),
+        (&X86_64LinuxGnu, "", RelocationKind::X86_64Call),
+    ] {
+        let object = assembled(target);
+        let names: Vec<String> = object.symbols.iter().map(|symbol| symbol.name.clone()).collect();
+        assert_eq!(
+            names,
+            ["factorial", "twice", "main"].map(|name| format!("{prefix}{name}"))
+        );
+        assert!(object.symbols.iter().all(|symbol| symbol.global && symbol.size > 0));
+        let end = object.symbols.last().map(|symbol| symbol.offset + symbol.size);
+        assert_eq!(end, Some(object.text.len() as u64));
+
+        // the two calls to factorial and the one to twice; the loop's jumps are resolved
+        let relocations: Vec<(&str, RelocationKind)> = object
+            .relocations
+            .iter()
+            .map(|relocation| (relocation.symbol.strip_prefix(prefix).unwrap(), relocation.kind))
+            .collect();
+        assert_eq!(
+            relocations,
+            [("factorial", call), ("factorial", call), ("twice", call)],
+            "{}",
+            target.triple()
+        );
+        assert!(object.undefined_symbols().is_empty());
+    }
+}
+
+#[test]
+fn elf_objects_have_symbols_and_relocations() {
+    for (target, machine, relocation) in [(&Aarch64LinuxGnu as &dyn Target, 183, 283), (&X86_64LinuxGnu, 62, 4)] {
+        let elf = object(SOURCE, target);
+        assert_eq!(&elf[..6], b"\x7fELF\x02\x01");
+        assert_eq!(u16_at(&elf, 16), 1, "ET_REL");
+        assert_eq!(u16_at(&elf, 18), machine);
+
+        let sections = u64::from_le_bytes(elf[40..48].try_into().unwrap()) as usize;
+        let section = |index: usize| sections + index * 64;
+        let field =
+            |index: usize, offset: usize| u64::from_le_bytes(elf[section(index) + offset..][..8].try_into().unwrap());
+        assert_eq!(u16_at(&elf, 60), 7);
+
+        // .rela.text: three calls against symbols 1 and 2, factorial and twice
+        assert_eq!(u32_at(&elf, section(2) + 4), 4, "SHT_RELA");
+        let (offset, size) = (field(2, 24) as usize, field(2, 32) as usize);
+        assert_eq!(size, 3 * 24);
+        let infos: Vec<u64> = (0..3)
+            .map(|index| u64::from_le_bytes(elf[offset + index * 24 + 8..][..8].try_into().unwrap()))
+            .collect();
+        assert_eq!(
+            infos,
+            [1 << 32 | relocation, 1 << 32 | relocation, 2 << 32 | relocation]
+        );
+
+        // .symtab: the null symbol and the three functions
+        assert_eq!(u32_at(&elf, section(3) + 4), 2, "SHT_SYMTAB");
+        assert_eq!(field(3, 32), 4 * 24);
+    }
+}
+
+#[test]
+fn macho_objects_have_symbols_and_relocations() {
+    let macho = object(SOURCE, &Aarch64AppleDarwin);
+    assert_eq!(u32_at(&macho, 0), 0xfeedfacf);
+    assert_eq!(u32_at(&macho, 4), 0x0100000c, "CPU_TYPE_ARM64");
+    assert_eq!(u32_at(&macho, 12), 1, "MH_OBJECT");
+    assert_eq!(u32_at(&macho, 16), 4);
+
+    // the __text section of the segment command, and its three branch relocations
+    let text = 32 + 72;
+    assert_eq!(&macho[text..text + 6], b"__text");
+    let (relocations, count) = (u32_at(&macho, text + 56) as usize, u32_at(&macho, text + 60));
+    assert_eq!(count, 3);
+    for index in 0..3 {
+        let info = u32_at(&macho, relocations + index * 8 + 4);
+        assert_eq!(info >> 28, 2, "ARM64_RELOC_BRANCH26");
+        assert_eq!(info >> 24 & 0xf, 0b1101, "pc-relative, 4 bytes, extern");
+    }
+
+    // the symbol table: _factorial, _main and _twice, all defined and external
+    let symtab = 32 + 72 + 80 + 24;
+    assert_eq!(u32_at(&macho, symtab), 0x2, "LC_SYMTAB");
+    let (symbols, count, strings) = (
+        u32_at(&macho, symtab + 8) as usize,
+        u32_at(&macho, symtab + 12) as usize,
+        u32_at(&macho, symtab + 16) as usize,
+    );
+    let names: Vec<&str> = (0..count)
+        .map(|index| {
+            let symbol = symbols + index * 16;
+            assert_eq!(macho[symbol + 4], 0xf, "N_SECT | N_EXT");
+            let name = &macho[strings + u32_at(&macho, symbol) as usize..];
+            std::str::from_utf8(&name[..name.iter().position(|&byte| byte == 0).unwrap()]).unwrap()
+        })
+        .collect();
+    assert_eq!(names, ["_factorial", "_main", "_twice"]);
+}
+
+#[test]
+fn every_program_assembles() {
+    let targets: [&dyn Target; 3] = [&Aarch64AppleDarwin, &Aarch64LinuxGnu, &X86_64LinuxGnu];
+    for (name, source) in backend_sources() {
+        for optimizations in [Optimizations::default(), Optimizations::level(1)] {
+            for target in targets {
+                let (_, result) =
+                    Compiler::compile_object(&source, &CompilerOptions::new(optimizations.clone()), target);
+                if let Err(error) = result {
+                    panic!("{name} {}: {}", target.triple(), error[0].message);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn targets_without_an_assembler_are_an_error() {
+    let (_, result) = Compiler::compile_object(
+        SOURCE,
+        &CompilerOptions::new(Optimizations::default()),
+        &Riscv64LinuxGnu,
+    );
+    assert!(result.unwrap_err()[0].message.contains("no integrated assembler"));
+}
@@ -0,0 +1,96 @@
+//! Checks the ARM64 peephole optimiser, running the code it produces on the simulator in
+//! [`arm64`].
+
+mod arm64;
+mod common;
+
+use arm64::{function, run};
+use common::{backend_sources, expected_status};
+use fbcc::compiler::Optimizations;
+use fbcc::core::target::{CallingConvention, ObjectFormat};
+use fbcc::synthesis::arm64::{AluOp, Inst, Operand2, Reg};
+use fbcc::synthesis::asm::Arm64AsmEmitter;
+
+fn lower(source: &str, optimizations: &Optimizations) -> Vec<Inst> {
+    let ir = common::lower(source, optimizations.clone());
+    Arm64AsmEmitter::new(
+        ObjectFormat::MachO,
+        CallingConvention::AppleArm64,
+        optimizations.peephole,
+    )
+    .lower(&ir)
+    .unwrap()
+}
+
+fn with_peephole(optimizations: Optimizations) -> Optimizations {
+    Optimizations {
+        peephole: true,
+        ..optimizations
+    }
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = expected_status(&name, &source);
+
+        let plain = lower(&source, &Optimizations::default());
+        assert_eq!(run(&plain), expected, "{name}");
+        for optimizations in [Optimizations::default(), Optimizations::level(1)] {
+            let optimized = lower(&source, &with_peephole(optimizations));
+            assert_eq!(run(&optimized), expected, "{name}");
+            assert!(optimized.len() <= plain.len(), "{name}");
+        }
+    }
+}
+
+#[test]
+fn loads_are_forwarded() {
+    let source = "int main() { int a = 3; int b = a + 4; int c = b * 2; return c - b; }";
+    let loads = |code: &[Inst]| {
+        function(code, "main")
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Ldr { dst: Reg::W(_), .. }))
+            .count()
+    };
+    let plain = lower(source, &Optimizations::default());
+    let code = lower(source, &with_peephole(Optimizations::default()));
+    // only `b` is reloaded, because `w9` has been overwritten by then
+    assert_eq!(loads(&plain), 7);
+    assert_eq!(loads(&code), 1, "{:#?}", function(&code, "main"));
+    assert_eq!(run(&code), 7);
+}
+
+#[test]
+fn compares_branch_directly() {
+    let source = "int main() { int i = 0; int s = 0; while (i < 10) { if (i != 4) s += i; i += 1; } return s; }";
+    let code = lower(source, &with_peephole(Optimizations::default()));
+    let main = function(&code, "main");
+    assert!(
+        !main
+            .iter()
+            .any(|inst| matches!(inst, Inst::Cset { .. } | Inst::Cbz(..))),
+        "{main:#?}"
+    );
+    assert_eq!(main.iter().filter(|inst| matches!(inst, Inst::BCond(..))).count(), 2);
+    assert_eq!(run(&code), 41);
+}
+
+#[test]
+fn small_constants_are_immediates() {
+    let source = "int f(int x) { return x + 4095 - (x - 7); }\nint main() { return f(2) + 100000 - 100000; }";
+    let code = lower(source, &with_peephole(Optimizations::default()));
+    let f = function(&code, "f");
+    assert!(!f.iter().any(|inst| matches!(inst, Inst::MovImm { .. })), "{f:#?}");
+    assert!(f.contains(&Inst::Alu {
+        op: AluOp::Add,
+        dst: Reg::W(9),
+        l: Reg::W(0),
+        r: Operand2::Imm(4095)
+    }));
+    // too big for an immediate
+    assert!(function(&code, "main")
+        .iter()
+        .any(|inst| matches!(inst, Inst::Movk { .. })));
+    assert_eq!(run(&code), (4095 + 7) as u8);
+}
@@ -0,0 +1,107 @@
+//! Checks that a compilation reports every error in a translation unit: the parser picks up
+//! again after a syntax error, and the analyzer after a semantic one without reporting what
+//! follows from it.
+
+use fbcc::compiler::{Compiler, CompilerOptions, Session};
+use fbcc::core::errors::{CompilerError, CompilerErrorKind};
+use fbcc::core::target::Aarch64AppleDarwin;
+
+/// The lines of the errors, in order
+fn lines(errors: &[CompilerError]) -> Vec<usize> {
+    errors.iter().map(|error| error.span.unwrap().start.line).collect()
+}
+
+#[test]
+fn syntax_errors_are_all_reported() {
+    let source = "int helper(int x) {
+    int y = x +;
+    return y
+}
+
+int main() {
+    int a = 1;
+    a = a * ;
+    return a;
+}
+";
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let errors = session.parse(source).unwrap_err();
+    assert_eq!(lines(&errors), [2, 4, 8], "{errors:?}");
+    assert!(errors
+        .iter()
+        .all(|error| matches!(error.kind, CompilerErrorKind::SyntaxError)));
+}
+
+#[test]
+fn parsing_resumes_after_a_broken_declaration() {
+    // the broken function is skipped as a whole, body and all, and so is the stray `}`
+    let source = "int broken( { return 0; }
+int fine() { return 1; }
+}
+int also_broken() { return 2 + ; }
+int x = ;
+int last() { return 3; }
+";
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let errors = session.parse(source).unwrap_err();
+    assert_eq!(lines(&errors), [1, 4, 5], "{errors:?}");
+}
+
+#[test]
+fn semantic_errors_do_not_cascade() {
+    let source = "int main() {
+    int a = 1;
+    int b = undefined + 2;
+    int c = b + 1;
+    c = -(c + nope);
+    if (missing) { c = 0; }
+    return c + other(a, b);
+}
+";
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let mut translation_unit = session.parse(source).unwrap();
+    let errors = session.analyze(&mut translation_unit).unwrap_err();
+
+    // one error for each unknown name, and nothing for the expressions around them
+    assert_eq!(lines(&errors), [3, 5, 6, 7], "{errors:?}");
+    for (error, name) in errors.iter().zip(["undefined", "nope", "missing", "other"]) {
+        assert!(error.message.contains(&format!("'{name}'")), "{}", error.message);
+    }
+}
+
+#[test]
+fn compile_returns_every_error_up_to_the_limit() {
+    let source = "int main() {
+    int a = x;
+    int b = y;
+    int c = z;
+    return a + b + c;
+}
+";
+    let (_, result) = Compiler::compile(source, &CompilerOptions::default(), &Aarch64AppleDarwin);
+    assert_eq!(result.unwrap_err().len(), 3);
+
+    let options = CompilerOptions::default().with_error_limit(Some(2));
+    let (_, result) = Compiler::compile(source, &options, &Aarch64AppleDarwin);
+    let errors = result.unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(errors[2].message.starts_with("too many errors"));
+}
+
+#[test]
+fn unsupported_and_conflicting_declarations_are_errors() {
+    let source = "int f(int x);
+long f(int x);
+int f(int x) { return x; }
+int main() {
+    return sizeof(int) + f(1);
+}
+";
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let mut translation_unit = session.parse(source).unwrap();
+    let errors = session.analyze(&mut translation_unit).unwrap_err();
+
+    assert_eq!(lines(&errors), [2, 5], "{errors:?}");
+    assert!(errors[0].message.starts_with("Conflicting types for `f`"));
+    assert!(errors[1].message.contains("`sizeof`"));
+}
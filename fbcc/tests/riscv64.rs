@@ -0,0 +1,74 @@
+//! Checks the RISC-V backend: the LP64D calling convention, the epilogues and the relaxation
+//! of far branches in the text it generates. `tests/backends.rs` runs the programs in
+//! `tests/backend` under `qemu-riscv64`.
+
+mod common;
+
+use common::{assembly, backend_sources, function};
+use fbcc::compiler::Optimizations;
+use fbcc::core::target::Riscv64LinuxGnu;
+
+#[test]
+fn every_function_ends_in_an_epilogue() {
+    for (name, source) in backend_sources() {
+        let asm = assembly(&source, Optimizations::default(), &Riscv64LinuxGnu);
+        for label in asm.lines().filter_map(|line| line.strip_prefix("\t.globl\t")) {
+            let lines: Vec<&str> = function(&asm, label).lines().collect();
+            assert!(lines[lines.len() - 4].starts_with("\tld\tra, "), "{name}: {label}");
+            assert!(
+                lines[lines.len() - 2].starts_with("\taddi\tsp, sp, "),
+                "{name}: {label}"
+            );
+            assert_eq!(lines[lines.len() - 1], "\tret", "{name}: {label}");
+        }
+    }
+}
+
+#[test]
+fn arguments_after_the_eighth_go_on_the_stack() {
+    let source = "int weigh(int a, int b, int c, int d, int e, int f, int g, int h, int i, int j) {\n\
+                      return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h + 9 * i + 10 * j;\n\
+                  }\n\
+                  int main() { return weigh(1, 2, 3, 4, 5, 6, 7, 8, 9, 10); }";
+    let asm = assembly(source, Optimizations::default(), &Riscv64LinuxGnu);
+
+    // the callee finds them where the caller's sp was, which is its frame pointer
+    let weigh = function(&asm, "weigh");
+    for register in ["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7"] {
+        assert!(weigh.contains(&format!("\tsw\t{register}, ")), "{register}: {weigh}");
+    }
+    assert!(
+        weigh.contains("\tlw\tt0, 0(s0)\n") && weigh.contains("\tlw\tt0, 8(s0)\n"),
+        "{weigh}"
+    );
+
+    // the caller stores them in 8-byte slots at the bottom of its frame, and takes them off
+    // again
+    let main = function(&asm, "main");
+    let call = main.find("\tcall\tweigh\n").unwrap();
+    let before = &main[..call];
+    assert!(before.contains("\taddi\tsp, sp, -16\n"), "{main}");
+    assert!(
+        before.contains("\tsd\tt0, 0(sp)\n") && before.contains("\tsd\tt0, 8(sp)\n"),
+        "{main}"
+    );
+    assert!(main[call..].contains("\taddi\tsp, sp, 16\n"), "{main}");
+}
+
+#[test]
+fn far_branches_are_relaxed() {
+    // the body of the `if` is too long for `beqz` to jump over
+    let body = "x = x * 3 + 1; ".repeat(300);
+    let source = format!("int main() {{ int x = 1; if (x) {{ {body} }} return x; }}");
+    let asm = assembly(&source, Optimizations::default(), &Riscv64LinuxGnu);
+    assert!(!asm.contains("\tbeqz\t"), "{asm}");
+    assert!(asm.contains("\tbnez\tt0, 1f\n\tj\t.L0\n1:\n"), "{asm}");
+
+    // a short one still branches directly
+    let asm = assembly(
+        "int main() { int x = 1; if (x) x = 2; return x; }",
+        Optimizations::default(),
+        &Riscv64LinuxGnu,
+    );
+    assert!(asm.contains("\tbeqz\tt0, .L0\n"), "{asm}");
+}
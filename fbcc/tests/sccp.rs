@@ -0,0 +1,145 @@
+//! Checks sparse conditional constant propagation: what it folds, what it must leave alone,
+//! and that `-O1` doesn't change what the programs in `tests/backend` compute.
+
+mod common;
+
+use common::{backend_sources, compile_ir, lower, run};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::interpreter::Interpreter;
+use fbcc::synthesis::ir::{IrStatement, Operand};
+
+fn main_body(source: &str) -> Vec<IrStatement> {
+    lower(source, Optimizations::level(1))
+        .into_iter()
+        .find(|function| function.name == "main")
+        .unwrap()
+        .body
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = run(&lower(&source, Optimizations::default()));
+        let optimized = run(&lower(&source, Optimizations::level(1)));
+        assert_eq!(optimized, expected, "{name}");
+    }
+}
+
+#[test]
+fn straight_line_code_folds_completely() {
+    let body = main_body("int main() { int x = 6; int y = x * 7; int z = -(y / 4) % 3 + (y << 2); return z; }");
+    assert!(
+        matches!(body.as_slice(), [IrStatement::Ret(Operand::Const(167))]),
+        "{body:?}"
+    );
+}
+
+#[test]
+fn branches_on_constants_are_resolved() {
+    let body = main_body(
+        "int f(int a) { return a; }\n\
+         int main() { int x = 3; if (x > 2) return 1; else return f(x); }",
+    );
+    assert!(
+        matches!(body.as_slice(), [IrStatement::Ret(Operand::Const(1))]),
+        "{body:?}"
+    );
+}
+
+#[test]
+fn constants_flow_around_loops() {
+    // `i` only changes on a path that is never taken, so it stays 1 in the loop and after it
+    let body = main_body(
+        "int main() {\n\
+             int i = 1;\n\
+             int n = 0;\n\
+             while (n < 10) { if (i != 1) i = 2; n += 1; }\n\
+             return i;\n\
+         }",
+    );
+    assert!(
+        matches!(body.last(), Some(IrStatement::Ret(Operand::Const(1)))),
+        "{body:?}"
+    );
+    assert!(!body.iter().any(|statement| matches!(
+        statement,
+        IrStatement::Copy {
+            src: Operand::Const(2),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn parameters_are_not_constants() {
+    // nothing is known about `n` inside `count`, so the loop it bounds can't be folded away
+    let source = "int count(int n) { int s = 0; int i = 0; while (i < n) { s += 2; i += 1; } return s; }\n\
+                  int main() { return count(5); }";
+    let ir = lower(source, Optimizations::level(1));
+    assert!(
+        !ir[0]
+            .body
+            .iter()
+            .any(|statement| matches!(statement, IrStatement::Ret(Operand::Const(_)))),
+        "{}",
+        ir[0]
+    );
+    let mut interpreter = Interpreter::new(&ir, Vec::new());
+    assert_eq!(interpreter.run("count", &[5]), Ok(Some(10)), "{}", ir[0]);
+    assert_eq!(interpreter.run("count", &[0]), Ok(Some(0)), "{}", ir[0]);
+}
+
+#[test]
+fn arithmetic_wraps_like_int() {
+    let body = main_body("int main() { int x = 2147483647; return x + 1 == -2147483647 - 1; }");
+    assert!(
+        matches!(body.as_slice(), [IrStatement::Ret(Operand::Const(1))]),
+        "{body:?}"
+    );
+}
+
+#[test]
+fn undefined_operations_are_left_alone() {
+    for (source, warning) in [
+        ("int main() { int x = 0; return 7 / x; }", "division by zero"),
+        ("int main() { int x = 0; return 7 % x; }", "division by zero"),
+        (
+            "int main() { int x = -2147483647 - 1; return x / -1; }",
+            "`-2147483648 / -1` overflows",
+        ),
+        ("int main() { int x = 32; return 1 << x; }", "shift count 32"),
+    ] {
+        let (diagnostics, ir) = compile_ir(source, Optimizations::level(1));
+        assert!(
+            diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.message.contains(warning)),
+            "{source}: {diagnostics:?}"
+        );
+        assert!(
+            ir[0]
+                .body
+                .iter()
+                .any(|statement| matches!(statement, IrStatement::BinaryOp { .. })),
+            "{source}"
+        );
+    }
+
+    // code that never runs doesn't warn
+    let (diagnostics, _) = compile_ir(
+        "int main() { int x = 0; if (x) return 1 / x; return 0; }",
+        Optimizations::level(1),
+    );
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn warnings_point_at_the_statement() {
+    let source = "int main() {\n    int x = 0;\n    int y = 3;\n    if (y)\n        y = 7 / x;\n    return y << 40;\n}";
+    let (diagnostics, _) = compile_ir(source, Optimizations::level(1));
+    let locations: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.span.map(|span| (span.start.line, span.start.column)))
+        .collect();
+    assert_eq!(locations, [Some((5, 9)), Some((6, 5))], "{diagnostics:?}");
+}
@@ -0,0 +1,102 @@
+//! Checks the staged [`Session`] API: every stage hands back its result, and running the
+//! stages one by one gives the same output as [`Compiler::compile`].
+
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations, Session};
+use fbcc::core::errors::{CompilerError, CompilerErrorKind};
+use fbcc::core::target::{Aarch64AppleDarwin, X86_64LinuxGnu};
+use fbcc::synthesis::ir::IrStatement;
+
+const SOURCE: &str = "int square(int x) {
+    return x * x;
+}
+
+int main() {
+    int unused = 7;
+    int y = square(3);
+    return y;
+}
+";
+
+#[test]
+fn stages_hand_back_their_results() {
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+
+    let mut translation_unit = session.parse(SOURCE).unwrap();
+    assert_eq!(translation_unit.external_declarations.len(), 2);
+
+    let symboltable = session.analyze(&mut translation_unit).unwrap();
+    let square = symboltable.lookup("square", 0).unwrap();
+    assert_eq!(square.typeinfo.to_string(), "signed int(signed int)");
+    assert!(symboltable.lookup_all("unused").is_some());
+
+    let ir = session.lower(&translation_unit).unwrap();
+    let names: Vec<&str> = ir.iter().map(|function| function.name.as_str()).collect();
+    assert_eq!(names, ["square", "main"]);
+    assert!(ir[1]
+        .body
+        .iter()
+        .any(|statement| matches!(statement, IrStatement::Call { .. })));
+
+    let asm = session.codegen(&ir).unwrap();
+    assert!(asm.contains("_square:") && asm.contains("bl\t_square"));
+    assert!(session.diagnostics().is_empty());
+}
+
+#[test]
+fn stages_match_compile() {
+    let options = CompilerOptions::new(Optimizations::level(1));
+    let (_, compiled) = Compiler::compile(SOURCE, &options, &X86_64LinuxGnu);
+
+    let mut session = Session::new(options, &X86_64LinuxGnu);
+    let mut translation_unit = session.parse(SOURCE).unwrap();
+    session.analyze(&mut translation_unit).unwrap();
+    let ir = session.lower(&translation_unit).unwrap();
+    assert_eq!(session.codegen(&ir).unwrap(), compiled.unwrap());
+}
+
+#[test]
+fn optimizations_report_statistics() {
+    let mut session = Session::new(CompilerOptions::new(Optimizations::level(1)), &Aarch64AppleDarwin);
+    let mut translation_unit = session.parse(SOURCE).unwrap();
+    session.analyze(&mut translation_unit).unwrap();
+    session.lower(&translation_unit).unwrap();
+
+    let statistics = session.statistics();
+    assert_eq!(statistics.inlined, 1);
+    let main = statistics
+        .functions
+        .iter()
+        .find(|function| function.name == "main")
+        .unwrap();
+    assert!(main.frame_after < main.frame_before, "{statistics:?}");
+
+    // nothing ran at -O0, so there is nothing to report
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let mut translation_unit = session.parse(SOURCE).unwrap();
+    session.analyze(&mut translation_unit).unwrap();
+    session.lower(&translation_unit).unwrap();
+    assert!(session.statistics().functions.is_empty());
+}
+
+#[test]
+fn errors_stop_at_their_stage() {
+    let mut session = Session::new(CompilerOptions::default(), &Aarch64AppleDarwin);
+    let errors = session.parse("int main() { return 1 }").unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [CompilerError {
+            kind: CompilerErrorKind::SyntaxError,
+            ..
+        }]
+    ));
+
+    let mut translation_unit = session.parse("int main() { return x; }").unwrap();
+    let errors = session.analyze(&mut translation_unit).unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [CompilerError {
+            kind: CompilerErrorKind::SemanticError,
+            ..
+        }]
+    ));
+}
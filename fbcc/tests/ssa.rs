@@ -0,0 +1,107 @@
+//! Checks SSA construction on the programs in `tests/backend`, and that taking the IR
+//! through SSA form and back leaves their behaviour unchanged.
+
+mod common;
+
+use common::{backend_sources, lower, only};
+use fbcc::compiler::Optimizations;
+use fbcc::synthesis::cfg::Cfg;
+use fbcc::synthesis::interpreter::{Interpreter, Trap};
+use fbcc::synthesis::ir::{IrStatement, Operand};
+use fbcc::synthesis::ssa::SsaFunction;
+
+fn run(source: &str) -> Result<i32, Trap> {
+    Interpreter::new(&lower(source, only(|o| o.ssa = true)), Vec::new()).run_main()
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = Interpreter::new(&lower(&source, Optimizations::default()), Vec::new()).run_main();
+        assert_eq!(run(&source), expected, "{name}");
+    }
+}
+
+#[test]
+fn versions_are_assigned_once() {
+    for (name, source) in backend_sources() {
+        for function in lower(&source, Optimizations::default()) {
+            let original = function.slots.len();
+            let ssa = SsaFunction::construct(Cfg::from_function(function));
+
+            let mut definitions = vec![0; ssa.cfg.slots.len()];
+            for phi in ssa.phis.iter().flatten() {
+                definitions[phi.dst.index()] += 1;
+            }
+            for statement in ssa.cfg.blocks.iter().flat_map(|block| &block.statements) {
+                match statement {
+                    IrStatement::BinaryOp { dst, .. }
+                    | IrStatement::UnaryOp { dst, .. }
+                    | IrStatement::Copy { dst, .. }
+                    | IrStatement::Call { dst: Some(dst), .. } => definitions[dst.index()] += 1,
+                    _ => {}
+                }
+            }
+
+            // every variable is promoted, so the original slots are never written again
+            assert!(definitions[..original].iter().all(|count| *count == 0), "{name}");
+            assert!(definitions[original..].iter().all(|count| *count == 1), "{name}");
+
+            let preds = ssa.cfg.predecessors();
+            for (block, phis) in ssa.phis.iter().enumerate() {
+                for phi in phis {
+                    assert_eq!(phi.args.len(), preds[block].len(), "{name}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn swapping_needs_a_temporary() {
+    let source = "int main() {\n\
+                      int a = 1;\n\
+                      int b = 2;\n\
+                      for (int i = 0; i < 3; i += 1) { int t = a; a = b; b = t; }\n\
+                      return a * 10 + b;\n\
+                  }";
+
+    // the copies fold away, leaving two phis at the loop header that read each other
+    let function = lower(source, Optimizations::default()).remove(0);
+    let ssa = SsaFunction::construct(Cfg::from_function(function));
+    let header = ssa
+        .phis
+        .iter()
+        .find(|phis| phis.len() == 3)
+        .expect("a, b and i get phis");
+    let reads = |phi: usize, other: usize| {
+        header[phi]
+            .args
+            .iter()
+            .any(|(_, arg)| *arg == Some(Operand::Var(header[other].dst.clone())))
+    };
+    assert!((0..3).any(|a| (0..3).any(|b| a != b && reads(a, b) && reads(b, a))));
+
+    assert_eq!(run(source), Ok(21));
+}
+
+#[test]
+fn variables_undefined_on_some_paths() {
+    // `x` is only defined on the path that reads it, so no copy may read it on the other
+    assert_eq!(
+        run("int main() { int x; int c = 0; if (c) x = 1; if (c) return x; return 5; }"),
+        Ok(5)
+    );
+    // reading it where it is never defined still traps
+    let trap = run("int main() { int x; int y = 1; return x + y; }");
+    assert!(matches!(trap, Err(Trap::UninitialisedRead { .. })), "{trap:?}");
+}
+
+#[test]
+fn loop_at_function_entry() {
+    // the loop header is the first block, so an entry block has to be added for the phis
+    assert_eq!(
+        run("int f(int n) { while (n > 10) n -= 3; return n; }\nint main() { return f(20); }"),
+        Ok(8)
+    );
+}
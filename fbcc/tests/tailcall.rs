@@ -0,0 +1,100 @@
+//! Checks tail calls: self-recursion turns into loops, other tail calls into jumps where the
+//! stack arguments allow it, and the programs in `tests/backend` still compute the same
+//! results.
+
+mod common;
+
+use common::{backend_sources, lower, only, run};
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::target::Aarch64AppleDarwin;
+use fbcc::synthesis::interpreter::{Interpreter, Trap};
+use fbcc::synthesis::ir::IrStatement;
+
+fn compile(source: &str, optimizations: Optimizations) -> String {
+    let (_, result) = Compiler::compile(source, &CompilerOptions::new(optimizations), &Aarch64AppleDarwin);
+    result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message))
+}
+
+/// The assembly of one function, up to the next one.
+fn function_asm<'a>(asm: &'a str, name: &str) -> &'a str {
+    let start = asm.find(&format!("\n_{name}:")).unwrap();
+    let end = asm[start + 1..].find(".globl").map_or(asm.len(), |end| start + 1 + end);
+    &asm[start..end]
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = run(&lower(&source, Optimizations::default()));
+        for optimizations in [only(|o| o.sibling_calls = true), Optimizations::level(1)] {
+            assert_eq!(run(&lower(&source, optimizations)), expected, "{name}");
+        }
+    }
+}
+
+#[test]
+fn self_recursion_runs_in_constant_stack() {
+    let source = "int sum(int n, int acc) { if (n == 0) return acc; return sum(n - 1, acc + n); }\n\
+                  int main() { return sum(200000, 0) & 255; }";
+
+    let recursive = lower(source, Optimizations::default());
+    assert_eq!(
+        Interpreter::new(&recursive, Vec::new()).run_main(),
+        Err(Trap::StackOverflow)
+    );
+
+    let looping = lower(source, only(|o| o.sibling_calls = true));
+    assert!(
+        !looping[0]
+            .body
+            .iter()
+            .any(|statement| matches!(statement, IrStatement::Call { .. })),
+        "{}",
+        looping[0]
+    );
+    let expected = (200000i64 * 200001 / 2) as i32 & 255;
+    assert_eq!(Interpreter::new(&looping, Vec::new()).run_main(), Ok(expected));
+}
+
+#[test]
+fn arguments_are_assigned_all_at_once() {
+    // each argument reads a parameter that an earlier one overwrites
+    let source = "int rotate(int a, int b, int c, int n) { if (n == 0) return a * 100 + b * 10 + c; \
+                  return rotate(b, c, a, n - 1); }\n\
+                  int main() { return rotate(1, 2, 3, 4); }";
+    let ir = lower(source, only(|o| o.sibling_calls = true));
+    assert_eq!(Interpreter::new(&ir, Vec::new()).run_main(), Ok(231));
+}
+
+#[test]
+fn other_tail_calls_jump() {
+    let source = "int add(int a, int b) { return a + b; }\n\
+                  int wrap(int a) { return add(a, 2); }\n\
+                  int main() { int x = add(1, 2); return wrap(x); }";
+
+    let asm = compile(source, only(|o| o.sibling_calls = true));
+    let wrap = function_asm(&asm, "wrap");
+    assert!(wrap.contains("\tadd\tsp, sp, #32\n\tb\t_add\n"), "{wrap}");
+    assert!(!wrap.contains("\tbl\t"), "{wrap}");
+    // not a tail call: the result is stored first
+    assert!(function_asm(&asm, "main").contains("\tbl\t_add\n"));
+
+    let asm = compile(source, Optimizations::default());
+    assert!(function_asm(&asm, "wrap").contains("\tbl\t_add\n"));
+}
+
+#[test]
+fn stack_arguments_must_fit_the_incoming_area() {
+    let source = "int ten(int a, int b, int c, int d, int e, int f, int g, int h, int i, int j) \
+                  { return a + b + c + d + e + f + g + h + i * j; }\n\
+                  int nine(int a, int b, int c, int d, int e, int f, int g, int h, int i) \
+                  { return ten(a, b, c, d, e, f, g, h, i, 2); }\n\
+                  int one(int a) { return ten(a, a, a, a, a, a, a, a, a, a); }\n\
+                  int main() { return nine(1, 1, 1, 1, 1, 1, 1, 1, 5) + one(1); }";
+    let asm = compile(source, only(|o| o.sibling_calls = true));
+
+    // `nine` was passed one int on the stack, which takes a 16-byte area like `ten`'s two
+    assert!(function_asm(&asm, "nine").contains("\tb\t_ten\n"));
+    // `one` was passed nothing on the stack
+    assert!(function_asm(&asm, "one").contains("\tbl\t_ten\n"));
+}
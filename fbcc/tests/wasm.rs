@@ -0,0 +1,580 @@
+//! Runs the programs in `tests/backend` through the WebAssembly backend.
+//!
+//! The generated text is checked and executed by the small interpreter below, which covers
+//! exactly the subset of WAT the backend emits, so no external Wasm runtime is needed.
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{backend_sources, expected_status};
+
+use fbcc::compiler::{Compiler, CompilerOptions};
+use fbcc::core::target::Wasm32UnknownUnknown;
+use fbcc::synthesis::ir::{InlineHint, IrFunction, IrStatement, Operand};
+use fbcc::synthesis::wasm::WatEmitter;
+
+#[derive(Debug)]
+enum Sexpr {
+    Atom(String),
+    List(Vec<Sexpr>),
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            '(' | ')' => {
+                tokens.push(ch.to_string());
+                chars.next();
+            }
+            ';' => while chars.next().is_some_and(|ch| ch != '\n') {},
+            '"' => {
+                let mut token = String::from(chars.next().unwrap());
+                for ch in chars.by_ref() {
+                    token.push(ch);
+                    if ch == '"' {
+                        break;
+                    }
+                }
+                tokens.push(token);
+            }
+            _ if ch.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' || ch == ')' {
+                        break;
+                    }
+                    token.push(ch);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_sexpr(tokens: &mut std::slice::Iter<String>) -> Sexpr {
+    let token = tokens.next().expect("unexpected end of module");
+    if token != "(" {
+        assert_ne!(token, ")", "unbalanced parentheses");
+        return Sexpr::Atom(token.clone());
+    }
+    let mut items = Vec::new();
+    loop {
+        match tokens.as_slice().first().map(String::as_str) {
+            Some(")") => {
+                tokens.next();
+                return Sexpr::List(items);
+            }
+            Some(_) => items.push(parse_sexpr(tokens)),
+            None => panic!("unbalanced parentheses"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Instr {
+    Block,
+    Loop,
+    If,
+    Else,
+    End,
+    Br(usize),
+    BrIf(usize),
+    Return,
+    Unreachable,
+    Call(String),
+    Drop,
+    LocalGet(usize),
+    LocalSet(usize),
+    Const(i32),
+    Eqz,
+    Binary(String),
+}
+
+struct Function {
+    params: usize,
+    locals: usize,
+    body: Vec<Instr>,
+    /// For every `block`/`loop`/`if`, the index of its `end` (and of its `else`, if any)
+    ends: HashMap<usize, (usize, Option<usize>)>,
+}
+
+struct Module {
+    functions: HashMap<String, Function>,
+    imports: HashMap<String, usize>,
+    exports: Vec<String>,
+}
+
+fn atom(sexpr: &Sexpr) -> &str {
+    match sexpr {
+        Sexpr::Atom(atom) => atom,
+        Sexpr::List(_) => panic!("expected an atom, found {sexpr:?}"),
+    }
+}
+
+fn list(sexpr: &Sexpr) -> &[Sexpr] {
+    match sexpr {
+        Sexpr::List(items) => items,
+        Sexpr::Atom(atom) => panic!("expected a list, found `{atom}`"),
+    }
+}
+
+fn parse_module(text: &str) -> Module {
+    let tokens = tokenize(text);
+    let mut iter = tokens.iter();
+    let module = parse_sexpr(&mut iter);
+    assert!(iter.next().is_none(), "trailing tokens after the module");
+
+    let items = list(&module);
+    assert_eq!(atom(&items[0]), "module");
+
+    let mut parsed = Module {
+        functions: HashMap::new(),
+        imports: HashMap::new(),
+        exports: Vec::new(),
+    };
+    for item in &items[1..] {
+        let fields = list(item);
+        match atom(&fields[0]) {
+            "import" => {
+                let func = list(&fields[3]);
+                assert_eq!(atom(&func[0]), "func");
+                assert!(
+                    parsed.functions.is_empty(),
+                    "imports must come before function definitions"
+                );
+                let params = func[2..]
+                    .iter()
+                    .filter(|field| atom(&list(field)[0]) == "param")
+                    .count();
+                parsed.imports.insert(atom(&func[1]).to_string(), params);
+            }
+            "func" => {
+                let name = atom(&fields[1]).to_string();
+                let (function, export) = parse_function(&fields[2..]);
+                if let Some(export) = export {
+                    parsed.exports.push(export);
+                }
+                assert!(
+                    parsed.functions.insert(name.clone(), function).is_none(),
+                    "`{name}` defined twice"
+                );
+            }
+            other => panic!("unexpected module field `{other}`"),
+        }
+    }
+    parsed
+}
+
+fn parse_function(fields: &[Sexpr]) -> (Function, Option<String>) {
+    let mut export = None;
+    let mut names: HashMap<String, usize> = HashMap::new();
+    let mut params = 0;
+    let mut has_result = false;
+    let mut index = 0;
+
+    while let Some(Sexpr::List(field)) = fields.get(index) {
+        match atom(&field[0]) {
+            "export" => export = Some(atom(&field[1]).trim_matches('"').to_string()),
+            "param" => {
+                assert!(names.insert(atom(&field[1]).to_string(), names.len()).is_none());
+                params += 1;
+            }
+            "result" => has_result = true,
+            "local" => assert!(names.insert(atom(&field[1]).to_string(), names.len()).is_none()),
+            other => panic!("unexpected function field `{other}`"),
+        }
+        index += 1;
+    }
+    assert!(has_result, "every function returns an i32");
+
+    let local = |name: &Sexpr| {
+        *names
+            .get(atom(name))
+            .unwrap_or_else(|| panic!("unknown local {name:?}"))
+    };
+    let mut body = Vec::new();
+    while index < fields.len() {
+        let instr = match atom(&fields[index]) {
+            "block" => Instr::Block,
+            "loop" => Instr::Loop,
+            "if" => Instr::If,
+            "else" => Instr::Else,
+            "end" => Instr::End,
+            "return" => Instr::Return,
+            "unreachable" => Instr::Unreachable,
+            "drop" => Instr::Drop,
+            "i32.eqz" => Instr::Eqz,
+            "br" | "br_if" | "call" | "local.get" | "local.set" | "i32.const" => {
+                let opcode = atom(&fields[index]);
+                index += 1;
+                let operand = &fields[index];
+                match opcode {
+                    "br" => Instr::Br(atom(operand).parse().unwrap()),
+                    "br_if" => Instr::BrIf(atom(operand).parse().unwrap()),
+                    "call" => Instr::Call(atom(operand).to_string()),
+                    "local.get" => Instr::LocalGet(local(operand)),
+                    "local.set" => Instr::LocalSet(local(operand)),
+                    _ => Instr::Const(atom(operand).parse().unwrap()),
+                }
+            }
+            binary if binary.starts_with("i32.") => Instr::Binary(binary.to_string()),
+            other => panic!("unexpected instruction `{other}`"),
+        };
+        body.push(instr);
+        index += 1;
+    }
+
+    // match up the structured instructions
+    let mut ends = HashMap::new();
+    let mut open: Vec<(usize, Option<usize>)> = Vec::new();
+    for (pc, instr) in body.iter().enumerate() {
+        match instr {
+            Instr::Block | Instr::Loop | Instr::If => open.push((pc, None)),
+            Instr::Else => {
+                let (start, els) = open.last_mut().expect("`else` outside of an `if`");
+                assert!(matches!(body[*start], Instr::If) && els.is_none(), "misplaced `else`");
+                *els = Some(pc);
+            }
+            Instr::End => {
+                let (start, els) = open.pop().expect("unbalanced `end`");
+                ends.insert(start, (pc, els));
+            }
+            _ => {}
+        }
+    }
+    assert!(open.is_empty(), "unterminated block");
+
+    let function = Function {
+        params,
+        locals: names.len(),
+        body,
+        ends,
+    };
+    (function, export)
+}
+
+/// Checks the operand stack discipline of every function, with every value an `i32`.
+fn validate(module: &Module) {
+    for (name, function) in &module.functions {
+        // (stack height at entry, values a branch to it carries, unreachable so far)
+        let mut frames: Vec<(usize, usize, bool)> = vec![(0, 1, false)];
+        let mut height = 0;
+
+        let pop = |height: &mut usize, frames: &[(usize, usize, bool)], count: usize| {
+            let (base, _, unreachable) = *frames.last().unwrap();
+            assert!(unreachable || *height >= base + count, "stack underflow in `{name}`");
+            *height = base.max(height.saturating_sub(count));
+        };
+        let diverge = |height: &mut usize, frames: &mut Vec<(usize, usize, bool)>| {
+            let frame = frames.last_mut().unwrap();
+            *height = frame.0;
+            frame.2 = true;
+        };
+
+        for instr in &function.body {
+            match instr {
+                Instr::Block | Instr::Loop => frames.push((height, 0, false)),
+                Instr::If => {
+                    pop(&mut height, &frames, 1);
+                    frames.push((height, 0, false));
+                }
+                Instr::Else | Instr::End => {
+                    let (base, _, unreachable) = *frames.last().unwrap();
+                    assert!(unreachable || height == base, "block leaves values behind in `{name}`");
+                    height = base;
+                    if matches!(instr, Instr::Else) {
+                        frames.last_mut().unwrap().2 = false;
+                    } else {
+                        frames.pop();
+                        assert!(!frames.is_empty(), "unbalanced `end` in `{name}`");
+                    }
+                }
+                Instr::Br(depth) | Instr::BrIf(depth) => {
+                    assert!(*depth < frames.len() - 1, "branch depth out of range in `{name}`");
+                    if matches!(instr, Instr::BrIf(_)) {
+                        pop(&mut height, &frames, 1);
+                    } else {
+                        diverge(&mut height, &mut frames);
+                    }
+                }
+                Instr::Return => {
+                    pop(&mut height, &frames, 1);
+                    diverge(&mut height, &mut frames);
+                }
+                Instr::Unreachable => diverge(&mut height, &mut frames),
+                Instr::Call(callee) => {
+                    let arity = match (module.functions.get(callee), module.imports.get(callee)) {
+                        (Some(function), _) => function.params,
+                        (None, Some(params)) => *params,
+                        (None, None) => panic!("call to undefined `{callee}` in `{name}`"),
+                    };
+                    pop(&mut height, &frames, arity);
+                    height += 1;
+                }
+                Instr::Drop | Instr::LocalSet(_) => pop(&mut height, &frames, 1),
+                Instr::LocalGet(_) | Instr::Const(_) => height += 1,
+                Instr::Eqz => {
+                    pop(&mut height, &frames, 1);
+                    height += 1;
+                }
+                Instr::Binary(_) => {
+                    pop(&mut height, &frames, 2);
+                    height += 1;
+                }
+            }
+        }
+
+        let (base, arity, unreachable) = frames.pop().unwrap();
+        assert!(frames.is_empty(), "unterminated block in `{name}`");
+        assert!(
+            unreachable || height == base + arity,
+            "`{name}` must end with its result"
+        );
+    }
+}
+
+struct Machine<'a> {
+    module: &'a Module,
+    output: String,
+    fuel: usize,
+}
+
+impl Machine<'_> {
+    fn call(&mut self, name: &str, args: Vec<i32>) -> Result<i32, String> {
+        let Some(function) = self.module.functions.get(name) else {
+            return match (name, args.as_slice()) {
+                ("$putchar", [ch]) => {
+                    self.output.push(*ch as u8 as char);
+                    Ok(*ch)
+                }
+                _ => Err(format!("unknown import `{name}`")),
+            };
+        };
+
+        let mut locals = args;
+        locals.resize(function.locals, 0);
+        let mut stack: Vec<i32> = Vec::new();
+        // (pc to continue at, stack height, is a loop)
+        let mut labels: Vec<(usize, usize, bool)> = Vec::new();
+        let mut pc = 0;
+
+        loop {
+            self.fuel = self.fuel.checked_sub(1).ok_or("out of fuel")?;
+            match &function.body[pc] {
+                Instr::Block => labels.push((function.ends[&pc].0, stack.len(), false)),
+                Instr::Loop => labels.push((pc, stack.len(), true)),
+                Instr::If => {
+                    let (end, els) = function.ends[&pc];
+                    if stack.pop().unwrap() != 0 {
+                        labels.push((end, stack.len(), false));
+                    } else if let Some(els) = els {
+                        labels.push((end, stack.len(), false));
+                        pc = els;
+                    } else {
+                        pc = end;
+                    }
+                }
+                // reaching `else` means the `then` arm is done
+                Instr::Else => {
+                    pc = labels.pop().unwrap().0;
+                }
+                Instr::End => {
+                    if labels.pop().is_none() {
+                        return Err("fell off the end of the function".to_string());
+                    }
+                }
+                Instr::Br(depth) | Instr::BrIf(depth) => {
+                    if !matches!(function.body[pc], Instr::BrIf(_)) || stack.pop().unwrap() != 0 {
+                        let (target, height, is_loop) = labels[labels.len() - 1 - depth];
+                        stack.truncate(height);
+                        labels.truncate(labels.len() - 1 - depth);
+                        if is_loop {
+                            labels.push((target, height, true));
+                        }
+                        pc = target;
+                    }
+                }
+                Instr::Return => return Ok(stack.pop().unwrap()),
+                Instr::Unreachable => return Err("unreachable executed".to_string()),
+                Instr::Call(callee) => {
+                    let arity = match self.module.functions.get(callee) {
+                        Some(function) => function.params,
+                        None => self.module.imports[callee],
+                    };
+                    let args = stack.split_off(stack.len() - arity);
+                    let result = self.call(callee, args)?;
+                    stack.push(result);
+                }
+                Instr::Drop => {
+                    stack.pop();
+                }
+                Instr::LocalGet(local) => stack.push(locals[*local]),
+                Instr::LocalSet(local) => locals[*local] = stack.pop().unwrap(),
+                Instr::Const(value) => stack.push(*value),
+                Instr::Eqz => {
+                    let value = stack.pop().unwrap();
+                    stack.push((value == 0) as i32);
+                }
+                Instr::Binary(op) => {
+                    let r = stack.pop().unwrap();
+                    let l = stack.pop().unwrap();
+                    let result = match op.as_str() {
+                        "i32.add" => l.wrapping_add(r),
+                        "i32.sub" => l.wrapping_sub(r),
+                        "i32.mul" => l.wrapping_mul(r),
+                        "i32.div_s" => l.checked_div(r).ok_or("integer divide by zero or overflow")?,
+                        "i32.rem_s" => {
+                            if r == 0 {
+                                return Err("integer divide by zero".to_string());
+                            }
+                            l.wrapping_rem(r)
+                        }
+                        "i32.lt_s" => (l < r) as i32,
+                        "i32.le_s" => (l <= r) as i32,
+                        "i32.gt_s" => (l > r) as i32,
+                        "i32.ge_s" => (l >= r) as i32,
+                        "i32.eq" => (l == r) as i32,
+                        "i32.ne" => (l != r) as i32,
+                        "i32.and" => l & r,
+                        "i32.or" => l | r,
+                        "i32.xor" => l ^ r,
+                        "i32.shl" => l.wrapping_shl(r as u32),
+                        "i32.shr_s" => l.wrapping_shr(r as u32),
+                        other => return Err(format!("unknown instruction `{other}`")),
+                    };
+                    stack.push(result);
+                }
+            }
+            pc += 1;
+        }
+    }
+}
+
+fn compile(source: &str) -> String {
+    let (_, result) = Compiler::compile(source, &CompilerOptions::default(), &Wasm32UnknownUnknown);
+    result.unwrap_or_else(|error| panic!("compilation failed: {}", error[0].message))
+}
+
+fn run(source: &str) -> (Result<i32, String>, String) {
+    execute(&compile(source))
+}
+
+fn execute(wat: &str) -> (Result<i32, String>, String) {
+    let module = parse_module(wat);
+    validate(&module);
+    assert!(
+        module.exports.iter().any(|export| export == "main"),
+        "`main` is not exported"
+    );
+
+    let mut machine = Machine {
+        module: &module,
+        output: String::new(),
+        fuel: 10_000_000,
+    };
+    let result = machine.call("$main", Vec::new());
+    (result, machine.output)
+}
+
+#[test]
+fn backend_programs() {
+    for (name, source) in backend_sources() {
+        let expected = expected_status(&name, &source);
+        let (result, _) = run(&source);
+        let status = result.unwrap_or_else(|trap| panic!("{name} trapped: {trap}"));
+        // compare like an exit status does
+        assert_eq!(status & 0xff, i32::from(expected), "{name}");
+    }
+}
+
+#[test]
+fn undefined_functions_are_imported() {
+    // there is no way to declare `putchar` in C yet, so build the IR by hand
+    let main = IrFunction {
+        name: "main".to_string(),
+        framesize: 16,
+        params: Vec::new(),
+        slots: Vec::new(),
+        inline: InlineHint::None,
+        source: Default::default(),
+        body: vec![
+            IrStatement::Call {
+                dst: None,
+                name: "putchar".to_string(),
+                args: vec![Operand::Const(104)],
+                tail: false,
+            },
+            IrStatement::Call {
+                dst: None,
+                name: "putchar".to_string(),
+                args: vec![Operand::Const(105)],
+                tail: false,
+            },
+            IrStatement::Ret(Operand::Const(0)),
+        ],
+    };
+    let wat = WatEmitter.emit(&[main]).unwrap();
+    assert!(wat.contains("(import \"env\" \"putchar\" (func $putchar (param i32) (result i32)))"));
+
+    let (result, output) = execute(&wat);
+    assert_eq!(result, Ok(0));
+    assert_eq!(output, "hi");
+}
+
+#[test]
+fn nested_loops_with_early_exits() {
+    let source = "int f(int n) {\n\
+             int total = 0;\n\
+             int j;\n\
+             for (int i = 0; i < n; i += 1) {\n\
+                 j = 0;\n\
+                 while (1) {\n\
+                     if (j == i) break;\n\
+                     if ((i + j) % 3 == 0) { j += 1; continue; }\n\
+                     if (total > 1000) return total;\n\
+                     total += i * j;\n\
+                     j += 1;\n\
+                 }\n\
+             }\n\
+             return total;\n\
+         }\n\
+         int main() { return f(6) + f(40) % 7; }";
+    let (result, _) = run(source);
+    assert_eq!(result, Ok(expected_nested_loops(6) + expected_nested_loops(40) % 7));
+}
+
+fn expected_nested_loops(n: i32) -> i32 {
+    let mut total = 0;
+    for i in 0..n {
+        let mut j = 0;
+        loop {
+            if j == i {
+                break;
+            }
+            if (i + j) % 3 == 0 {
+                j += 1;
+                continue;
+            }
+            if total > 1000 {
+                return total;
+            }
+            total += i * j;
+            j += 1;
+        }
+    }
+    total
+}
+
+#[test]
+fn division_by_zero_traps() {
+    let (result, _) = run("int f(int a, int b) { return a / b; }\nint main() { return f(1, 0); }");
+    assert!(result.is_err());
+}
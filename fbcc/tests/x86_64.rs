@@ -0,0 +1,49 @@
+//! Checks the x86-64 backend: the System V calling convention and the epilogues in the text it
+//! generates. `tests/backends.rs` runs the programs in `tests/backend` on an x86-64 Linux host.
+
+mod common;
+
+use common::{assembly, backend_sources, function};
+use fbcc::compiler::Optimizations;
+use fbcc::core::target::X86_64LinuxGnu;
+
+#[test]
+fn every_function_ends_in_an_epilogue() {
+    for (name, source) in backend_sources() {
+        let asm = assembly(&source, Optimizations::default(), &X86_64LinuxGnu);
+        for label in asm.lines().filter_map(|line| line.strip_prefix("\t.globl\t")) {
+            let lines: Vec<&str> = function(&asm, label).lines().collect();
+            assert_eq!(
+                lines[lines.len() - 3..],
+                ["\tmovq\t%rbp, %rsp", "\tpopq\t%rbp", "\tret"],
+                "{name}: {label}"
+            );
+        }
+    }
+}
+
+#[test]
+fn arguments_after_the_sixth_go_on_the_stack() {
+    let source = "int weigh(int a, int b, int c, int d, int e, int f, int g, int h) {\n\
+                      return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h;\n\
+                  }\n\
+                  int main() { return weigh(1, 2, 3, 4, 5, 6, 7, 8); }";
+    let asm = assembly(source, Optimizations::default(), &X86_64LinuxGnu);
+
+    // the callee finds them above the return address and the saved frame pointer
+    let weigh = function(&asm, "weigh");
+    for register in ["%edi", "%esi", "%edx", "%ecx", "%r8d", "%r9d", "16(%rbp)", "24(%rbp)"] {
+        assert!(weigh.contains(&format!("\tmovl\t{register}, ")), "{register}: {weigh}");
+    }
+
+    // the caller stores them in 16 bytes at the bottom of its frame, and takes them off again
+    let main = function(&asm, "main");
+    let call = main.find("\tcall\tweigh\n").unwrap();
+    let before = &main[..call];
+    assert!(before.contains("\tsubq\t$16, %rsp\n"), "{main}");
+    assert!(
+        before.contains(", 0(%rsp)\n") && before.contains(", 8(%rsp)\n"),
+        "{main}"
+    );
+    assert!(main[call..].contains("\taddq\t$16, %rsp\n"), "{main}");
+}
@@ -0,0 +1,22 @@
+//! Compiles whatever libFuzzer comes up with for every target, with and without
+//! optimisations. Any panic is a crash: bad input has to end in diagnostics.
+
+#![no_main]
+
+use fbcc::compiler::{Compiler, CompilerOptions, Optimizations};
+use fbcc::core::target::{target_from_triple, SUPPORTED_TRIPLES};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    for triple in SUPPORTED_TRIPLES {
+        let target = target_from_triple(triple).unwrap();
+        for level in [0, 1] {
+            let options = CompilerOptions::new(Optimizations::level(level));
+            let _ = Compiler::compile(source, &options, target.as_ref());
+        }
+    }
+});
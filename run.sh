@@ -1,7 +1,10 @@
 #!/bin/sh
-# run.sh <file.c> — compile to ARM64 asm, assemble+link with clang, run, show exit code.
+# run.sh <file.c> — compile to asm for the host, then compile and link the binary, run it, show exit code.
 #
 # Usage: ./run.sh tests/test_ir_simple.c
+#
+# The target triple is picked from the host (macOS on Apple silicon, x86-64, RISC-V or ARM64 Linux).
+# The compiler links with cc; set CC to use another compiler.
 
 src="$1"
 if [ -z "$src" ]; then
@@ -12,13 +15,20 @@ fi
 asm="${src%.c}.s"
 bin="/tmp/fbcc-bin"
 
-cargo run -q -p cli -- --emit-asm -o "$asm" "$src" || exit 1
+case "$(uname -s)-$(uname -m)" in
+Darwin-*) target="aarch64-apple-darwin" ;;
+*-x86_64 | *-amd64) target="x86_64-linux-gnu" ;;
+*-riscv64) target="riscv64-linux-gnu" ;;
+*) target="aarch64-linux-gnu" ;;
+esac
+
+cargo run -q -p cli -- --target "$target" -S -o "$asm" "$src" || exit 1
 
 echo "----- $asm -----"
 cat "$asm"
 echo "----------------"
 
-clang "$asm" -o "$bin" || exit 1
+cargo run -q -p cli -- --target "$target" -o "$bin" "$src" || exit 1
 
 "$bin"
 echo "exit = $?"
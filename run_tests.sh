@@ -2,12 +2,36 @@
 # run_tests.sh — run every tests/backend/*.c through the full pipeline and
 # compare the binary's exit code against the `// expect: N` header comment.
 #
-# Usage: ./run_tests.sh
+# Usage: ./run_tests.sh [--c] [compiler flags...]
+#
+# The target triple is picked from the host (macOS on Apple silicon, x86-64, RISC-V or
+# ARM64 Linux). The compiler links the binaries itself with the host compiler, which only
+# assembles where fbcc has no integrated assembler, as on RISC-V. Set CC to use another
+# compiler.
+#
+# With --c the programs go through the C backend instead and the host compiler builds
+# the generated C, which tells front-end and IR bugs apart from codegen bugs. Any other
+# arguments are passed on to the compiler, e.g. `./run_tests.sh --ssa`.
 
 # Build the compiler once up front; abort if it doesn't build.
 cargo build -q -p cli || exit 1
 fbcc="./target/debug/cli"
 
+case "$(uname -s)-$(uname -m)" in
+Darwin-*) target="aarch64-apple-darwin" ;;
+*-x86_64 | *-amd64) target="x86_64-linux-gnu" ;;
+*-riscv64) target="riscv64-linux-gnu" ;;
+*) target="aarch64-linux-gnu" ;;
+esac
+cc="${CC:-cc}"
+
+csrc=""
+bin="/tmp/fbcc-test-bin"
+if [ "$1" = "--c" ]; then
+	csrc="/tmp/fbcc-test.c"
+	shift
+fi
+
 passed=0
 failed=0
 
@@ -22,17 +46,19 @@ for src in tests/backend/*.c; do
 		continue
 	fi
 
-	asm="/tmp/fbcc-test.s"
-	bin="/tmp/fbcc-test-bin"
-
-	if ! "$fbcc" --emit-asm -o "$asm" "$src" >/dev/null 2>&1; then
-		echo "FAIL  $name (compile error)"
-		failed=$((failed + 1))
-		continue
-	fi
-
-	if ! clang "$asm" -o "$bin" 2>/dev/null; then
-		echo "FAIL  $name (assemble/link error)"
+	if [ -n "$csrc" ]; then
+		if ! "$fbcc" --target "$target" --emit-c "$@" -o "$csrc" "$src" >/dev/null 2>&1; then
+			echo "FAIL  $name (compile error)"
+			failed=$((failed + 1))
+			continue
+		fi
+		if ! "$cc" "$csrc" -o "$bin" 2>/dev/null; then
+			echo "FAIL  $name (C compile error)"
+			failed=$((failed + 1))
+			continue
+		fi
+	elif ! "$fbcc" --target "$target" "$@" -o "$bin" "$src" >/dev/null 2>&1; then
+		echo "FAIL  $name (compile/link error)"
 		failed=$((failed + 1))
 		continue
 	fi
@@ -49,7 +75,7 @@ for src in tests/backend/*.c; do
 	fi
 done
 
-rm -f /tmp/fbcc-test.s /tmp/fbcc-test-bin
+rm -f "$csrc" "$bin"
 
 echo "----------------"
 echo "$passed passed, $failed failed"
@@ -103,6 +103,8 @@ _main:
 	str	w9, [sp, #68]
 	ldr	w9, [sp, #68]
 	cbz	w9, .L8
+	mov	w9, #0
+	str	w9, [sp, #72]
 .L10:
 	ldr	w9, [sp, #72]
 	mov	w10, #10
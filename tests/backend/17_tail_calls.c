@@ -0,0 +1,25 @@
+// expect: 198
+// Case 17: calls whose result is returned straight away. `sum` and `gcd` call themselves
+// and become loops under -foptimize-sibling-calls, `offset_sum` jumps to `sum` instead of
+// calling it. sum(1000) % 256 = 20, gcd(1071, 462) = 21, offset_sum(3) = 4 + 6 = 10, and
+// 20 + 21 * 8 + 10 = 198.
+
+int sum(int n, int acc) {
+	if (n == 0)
+		return acc;
+	return sum(n - 1, acc + n);
+}
+
+int gcd(int a, int b) {
+	if (b == 0)
+		return a;
+	return gcd(b, a % b);
+}
+
+int offset_sum(int n) {
+	return sum(n, n + 1);
+}
+
+int main() {
+	return sum(1000, 0) % 256 + gcd(1071, 462) * 8 + offset_sum(3);
+}
@@ -0,0 +1,132 @@
+	.globl	_sum
+_sum:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	ldr	w9, [sp, #0]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	cbz	w9, .L0
+	ldr	w0, [sp, #4]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+.L0:
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	sub	w9, w9, w10
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #4]
+	ldr	w10, [sp, #0]
+	add	w9, w9, w10
+	str	w9, [sp, #20]
+	ldr	w0, [sp, #16]
+	ldr	w1, [sp, #20]
+	bl	_sum
+	str	w0, [sp, #24]
+	ldr	w0, [sp, #24]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+	.globl	_gcd
+_gcd:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	ldr	w9, [sp, #4]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	cbz	w9, .L1
+	ldr	w0, [sp, #0]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+.L1:
+	ldr	w9, [sp, #0]
+	ldr	w10, [sp, #4]
+	sdiv	w11, w9, w10
+	msub	w9, w11, w10, w9
+	str	w9, [sp, #16]
+	ldr	w0, [sp, #4]
+	ldr	w1, [sp, #16]
+	bl	_gcd
+	str	w0, [sp, #20]
+	ldr	w0, [sp, #20]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+	.globl	_offset_sum
+_offset_sum:
+	sub	sp, sp, #32
+	stp	x29, x30, [sp, #16]
+	add	x29, sp, #16
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #4]
+	ldr	w0, [sp, #0]
+	ldr	w1, [sp, #4]
+	bl	_sum
+	str	w0, [sp, #8]
+	ldr	w0, [sp, #8]
+	ldp	x29, x30, [sp, #16]
+	add	sp, sp, #32
+	ret
+	.globl	_main
+_main:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	mov	w0, #1000
+	mov	w1, #0
+	bl	_sum
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #256
+	sdiv	w11, w9, w10
+	msub	w9, w11, w10, w9
+	str	w9, [sp, #4]
+	mov	w0, #1071
+	mov	w1, #462
+	bl	_gcd
+	str	w0, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #8
+	mul	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #4]
+	ldr	w10, [sp, #12]
+	add	w9, w9, w10
+	str	w9, [sp, #16]
+	mov	w0, #3
+	bl	_offset_sum
+	str	w0, [sp, #20]
+	ldr	w9, [sp, #16]
+	ldr	w10, [sp, #20]
+	add	w9, w9, w10
+	str	w9, [sp, #24]
+	ldr	w0, [sp, #24]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
@@ -0,0 +1,61 @@
+// expect: 0
+// Case 18: loops for the loop optimisations. `scaled` multiplies its induction variable,
+// `guarded` divides by an invariant that is zero when the division can't run, `table`
+// runs a fixed number of times and `nested` computes an invariant of the outer loop in
+// the inner one. The checks add up to 0.
+
+int scaled(int n, int k) {
+	int s = 0;
+	int i = 0;
+	while (i < n) {
+		s += i * 4 + (k * 7 + n);
+		i += 1;
+	}
+	return s;
+}
+
+int guarded(int n, int d) {
+	int s = 0;
+	for (int i = 0; i < n; i += 1) {
+		if (d != 0)
+			s += 100 / d;
+		s += i << 2;
+	}
+	return s;
+}
+
+int table() {
+	int s = 0;
+	for (int i = 0; i < 5; i += 1)
+		s = s * 3 + i;
+	return s;
+}
+
+int nested(int n) {
+	int s = 0;
+	for (int i = 0; i < n; i += 1) {
+		for (int j = 0; j < n; j += 1) {
+			if (j == 3)
+				break;
+			s += i * n + j * 2;
+		}
+	}
+	return s;
+}
+
+int main() {
+	int failed = 0;
+	if (scaled(5, 2) != 135)
+		failed += 1;
+	if (scaled(0, 2) != 0)
+		failed += 2;
+	if (guarded(4, 0) != 24)
+		failed += 4;
+	if (guarded(3, 7) != 54)
+		failed += 8;
+	if (table() != 58)
+		failed += 16;
+	if (nested(4) != 96)
+		failed += 32;
+	return failed;
+}
@@ -0,0 +1,381 @@
+	.globl	_scaled
+_scaled:
+	sub	sp, sp, #64
+	stp	x29, x30, [sp, #48]
+	add	x29, sp, #48
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	mov	w9, #0
+	str	w9, [sp, #8]
+	mov	w9, #0
+	str	w9, [sp, #12]
+.L0:
+	ldr	w9, [sp, #12]
+	ldr	w10, [sp, #0]
+	subs	w9, w9, w10
+	cset	w9, lt
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #16]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #20]
+	ldr	w9, [sp, #20]
+	cbz	w9, .L1
+	ldr	w9, [sp, #12]
+	mov	w10, #4
+	mul	w9, w9, w10
+	str	w9, [sp, #24]
+	ldr	w9, [sp, #4]
+	mov	w10, #7
+	mul	w9, w9, w10
+	str	w9, [sp, #28]
+	ldr	w9, [sp, #28]
+	ldr	w10, [sp, #0]
+	add	w9, w9, w10
+	str	w9, [sp, #32]
+	ldr	w9, [sp, #24]
+	ldr	w10, [sp, #32]
+	add	w9, w9, w10
+	str	w9, [sp, #36]
+	ldr	w9, [sp, #8]
+	ldr	w10, [sp, #36]
+	add	w9, w9, w10
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #12]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #12]
+	b	.L0
+.L1:
+	ldr	w0, [sp, #8]
+	ldp	x29, x30, [sp, #48]
+	add	sp, sp, #64
+	ret
+	.globl	_guarded
+_guarded:
+	sub	sp, sp, #64
+	stp	x29, x30, [sp, #48]
+	add	x29, sp, #48
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	mov	w9, #0
+	str	w9, [sp, #8]
+	mov	w9, #0
+	str	w9, [sp, #12]
+.L2:
+	ldr	w9, [sp, #12]
+	ldr	w10, [sp, #0]
+	subs	w9, w9, w10
+	cset	w9, lt
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #16]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #20]
+	ldr	w9, [sp, #20]
+	cbz	w9, .L3
+	ldr	w9, [sp, #4]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #24]
+	ldr	w9, [sp, #24]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #28]
+	ldr	w9, [sp, #28]
+	cbz	w9, .L5
+	mov	w9, #100
+	ldr	w10, [sp, #4]
+	sdiv	w9, w9, w10
+	str	w9, [sp, #32]
+	ldr	w9, [sp, #8]
+	ldr	w10, [sp, #32]
+	add	w9, w9, w10
+	str	w9, [sp, #8]
+.L5:
+	ldr	w9, [sp, #12]
+	mov	w10, #2
+	lsl	w9, w9, w10
+	str	w9, [sp, #36]
+	ldr	w9, [sp, #8]
+	ldr	w10, [sp, #36]
+	add	w9, w9, w10
+	str	w9, [sp, #8]
+.L4:
+	ldr	w9, [sp, #12]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #12]
+	b	.L2
+.L3:
+	ldr	w0, [sp, #8]
+	ldp	x29, x30, [sp, #48]
+	add	sp, sp, #64
+	ret
+	.globl	_table
+_table:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	mov	w9, #0
+	str	w9, [sp, #0]
+	mov	w9, #0
+	str	w9, [sp, #4]
+.L6:
+	ldr	w9, [sp, #4]
+	mov	w10, #5
+	subs	w9, w9, w10
+	cset	w9, lt
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	cbz	w9, .L7
+	ldr	w9, [sp, #0]
+	mov	w10, #3
+	mul	w9, w9, w10
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #16]
+	ldr	w10, [sp, #4]
+	add	w9, w9, w10
+	str	w9, [sp, #20]
+	ldr	w9, [sp, #20]
+	str	w9, [sp, #0]
+.L8:
+	ldr	w9, [sp, #4]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #4]
+	b	.L6
+.L7:
+	ldr	w0, [sp, #0]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+	.globl	_nested
+_nested:
+	sub	sp, sp, #80
+	stp	x29, x30, [sp, #64]
+	add	x29, sp, #64
+	str	w0, [sp, #0]
+	mov	w9, #0
+	str	w9, [sp, #4]
+	mov	w9, #0
+	str	w9, [sp, #8]
+.L9:
+	ldr	w9, [sp, #8]
+	ldr	w10, [sp, #0]
+	subs	w9, w9, w10
+	cset	w9, lt
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #16]
+	cbz	w9, .L10
+	mov	w9, #0
+	str	w9, [sp, #20]
+.L12:
+	ldr	w9, [sp, #20]
+	ldr	w10, [sp, #0]
+	subs	w9, w9, w10
+	cset	w9, lt
+	str	w9, [sp, #24]
+	ldr	w9, [sp, #24]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #28]
+	ldr	w9, [sp, #28]
+	cbz	w9, .L13
+	ldr	w9, [sp, #20]
+	mov	w10, #3
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #32]
+	ldr	w9, [sp, #32]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #36]
+	ldr	w9, [sp, #36]
+	cbz	w9, .L15
+	b	.L13
+.L15:
+	ldr	w9, [sp, #8]
+	ldr	w10, [sp, #0]
+	mul	w9, w9, w10
+	str	w9, [sp, #40]
+	ldr	w9, [sp, #20]
+	mov	w10, #2
+	mul	w9, w9, w10
+	str	w9, [sp, #44]
+	ldr	w9, [sp, #40]
+	ldr	w10, [sp, #44]
+	add	w9, w9, w10
+	str	w9, [sp, #48]
+	ldr	w9, [sp, #4]
+	ldr	w10, [sp, #48]
+	add	w9, w9, w10
+	str	w9, [sp, #4]
+.L14:
+	ldr	w9, [sp, #20]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #20]
+	b	.L12
+.L13:
+.L11:
+	ldr	w9, [sp, #8]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #8]
+	b	.L9
+.L10:
+	ldr	w0, [sp, #4]
+	ldp	x29, x30, [sp, #64]
+	add	sp, sp, #80
+	ret
+	.globl	_main
+_main:
+	sub	sp, sp, #96
+	stp	x29, x30, [sp, #80]
+	add	x29, sp, #80
+	mov	w9, #0
+	str	w9, [sp, #0]
+	mov	w0, #5
+	mov	w1, #2
+	bl	_scaled
+	str	w0, [sp, #4]
+	ldr	w9, [sp, #4]
+	mov	w10, #135
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	cbz	w9, .L16
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L16:
+	mov	w0, #0
+	mov	w1, #2
+	bl	_scaled
+	str	w0, [sp, #16]
+	ldr	w9, [sp, #16]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #20]
+	ldr	w9, [sp, #20]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #24]
+	ldr	w9, [sp, #24]
+	cbz	w9, .L17
+	ldr	w9, [sp, #0]
+	mov	w10, #2
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L17:
+	mov	w0, #4
+	mov	w1, #0
+	bl	_guarded
+	str	w0, [sp, #28]
+	ldr	w9, [sp, #28]
+	mov	w10, #24
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #32]
+	ldr	w9, [sp, #32]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #36]
+	ldr	w9, [sp, #36]
+	cbz	w9, .L18
+	ldr	w9, [sp, #0]
+	mov	w10, #4
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L18:
+	mov	w0, #3
+	mov	w1, #7
+	bl	_guarded
+	str	w0, [sp, #40]
+	ldr	w9, [sp, #40]
+	mov	w10, #54
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #44]
+	ldr	w9, [sp, #44]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #48]
+	ldr	w9, [sp, #48]
+	cbz	w9, .L19
+	ldr	w9, [sp, #0]
+	mov	w10, #8
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L19:
+	bl	_table
+	str	w0, [sp, #52]
+	ldr	w9, [sp, #52]
+	mov	w10, #58
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #56]
+	ldr	w9, [sp, #56]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #60]
+	ldr	w9, [sp, #60]
+	cbz	w9, .L20
+	ldr	w9, [sp, #0]
+	mov	w10, #16
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L20:
+	mov	w0, #4
+	bl	_nested
+	str	w0, [sp, #64]
+	ldr	w9, [sp, #64]
+	mov	w10, #96
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #68]
+	ldr	w9, [sp, #68]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #72]
+	ldr	w9, [sp, #72]
+	cbz	w9, .L21
+	ldr	w9, [sp, #0]
+	mov	w10, #32
+	add	w9, w9, w10
+	str	w9, [sp, #0]
+.L21:
+	ldr	w0, [sp, #0]
+	ldp	x29, x30, [sp, #80]
+	add	sp, sp, #96
+	ret
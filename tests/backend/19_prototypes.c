@@ -0,0 +1,25 @@
+// expect: 21
+// Case 19: function prototypes. `is_even` and `is_odd` call each other, so
+// one of them has to be declared before it is defined; `main` calls both
+// before either definition. Declaring `is_odd` twice is fine, as the types
+// agree.
+
+int is_even(int n);
+int is_odd(int n);
+int is_odd(int n);
+
+int main() {
+	return is_even(10) * 20 + is_odd(7);
+}
+
+int is_even(int n) {
+	if (n == 0)
+		return 1;
+	return is_odd(n - 1);
+}
+
+int is_odd(int n) {
+	if (n == 0)
+		return 0;
+	return is_even(n - 1);
+}
@@ -0,0 +1,91 @@
+	.globl	_main
+_main:
+	sub	sp, sp, #32
+	stp	x29, x30, [sp, #16]
+	add	x29, sp, #16
+	mov	w0, #10
+	bl	_is_even
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #20
+	mul	w9, w9, w10
+	str	w9, [sp, #4]
+	mov	w0, #7
+	bl	_is_odd
+	str	w0, [sp, #8]
+	ldr	w9, [sp, #4]
+	ldr	w10, [sp, #8]
+	add	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w0, [sp, #12]
+	ldp	x29, x30, [sp, #16]
+	add	sp, sp, #32
+	ret
+	.globl	_is_even
+_is_even:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #4]
+	ldr	w9, [sp, #4]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	cbz	w9, .L0
+	mov	w0, #1
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+.L0:
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	sub	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w0, [sp, #12]
+	bl	_is_odd
+	str	w0, [sp, #16]
+	ldr	w0, [sp, #16]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+	.globl	_is_odd
+_is_odd:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #4]
+	ldr	w9, [sp, #4]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	cbz	w9, .L1
+	mov	w0, #0
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+.L1:
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	sub	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w0, [sp, #12]
+	bl	_is_even
+	str	w0, [sp, #16]
+	ldr	w0, [sp, #16]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
@@ -0,0 +1,18 @@
+// expect: 0
+// Case 20: functions that return early on one path and run off the end on another.
+// Running off the end of main returns 0; the epilogue still has to be there after an
+// earlier `return`.
+
+int bump(int x) {
+	if (x > 100)
+		return x;
+	x = x + 1;
+}
+
+int main() {
+	int n = 7;
+	bump(n);
+	if (n == 0)
+		return 1;
+	n = n + 35;
+}
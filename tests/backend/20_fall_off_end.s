@@ -0,0 +1,70 @@
+	.globl	_bump
+_bump:
+	sub	sp, sp, #32
+	stp	x29, x30, [sp, #16]
+	add	x29, sp, #16
+	str	w0, [sp, #0]
+	ldr	w9, [sp, #0]
+	mov	w10, #100
+	subs	w9, w9, w10
+	cset	w9, gt
+	str	w9, [sp, #4]
+	ldr	w9, [sp, #4]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	cbz	w9, .L0
+	ldr	w0, [sp, #0]
+	ldp	x29, x30, [sp, #16]
+	add	sp, sp, #32
+	ret
+.L0:
+	ldr	w9, [sp, #0]
+	mov	w10, #1
+	add	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	str	w9, [sp, #0]
+	mov	w0, #0
+	ldp	x29, x30, [sp, #16]
+	add	sp, sp, #32
+	ret
+	.globl	_main
+_main:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	mov	w9, #7
+	str	w9, [sp, #0]
+	ldr	w0, [sp, #0]
+	bl	_bump
+	str	w0, [sp, #4]
+	ldr	w9, [sp, #0]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, eq
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #8]
+	mov	w10, #0
+	subs	w9, w9, w10
+	cset	w9, ne
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	cbz	w9, .L1
+	mov	w0, #1
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
+.L1:
+	ldr	w9, [sp, #0]
+	mov	w10, #35
+	add	w9, w9, w10
+	str	w9, [sp, #16]
+	ldr	w9, [sp, #16]
+	str	w9, [sp, #0]
+	mov	w0, #0
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret
@@ -0,0 +1,18 @@
+// expect: 205
+// Case 21: calls with more arguments than fit in registers. x86-64 passes six
+// integer arguments in registers, RISC-V and ARM64 eight; the rest go on the
+// stack. The weights make every argument count, so a slot mix-up changes the
+// result.
+
+int weigh(int a, int b, int c, int d, int e, int f, int g, int h, int i, int j) {
+	return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h + 9 * i + 10 * j;
+}
+
+int forward(int a, int b, int c, int d, int e, int f, int g, int h, int i, int j) {
+	int local = 1;
+	return weigh(j, i, h, g, f, e, d, c, b, a) - weigh(a, b, c, d, e, f, g, h, i, j) + local;
+}
+
+int main() {
+	return forward(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) + 6 * weigh(1, 1, 1, 1, 1, 1, 1, 1, 1, 1) + 39;
+}
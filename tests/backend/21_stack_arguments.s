@@ -0,0 +1,209 @@
+	.globl	_weigh
+_weigh:
+	sub	sp, sp, #128
+	stp	x29, x30, [sp, #112]
+	add	x29, sp, #112
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	str	w2, [sp, #8]
+	str	w3, [sp, #12]
+	str	w4, [sp, #16]
+	str	w5, [sp, #20]
+	str	w6, [sp, #24]
+	str	w7, [sp, #28]
+	ldr	w9, [x29, #16]
+	str	w9, [sp, #32]
+	ldr	w9, [x29, #20]
+	str	w9, [sp, #36]
+	mov	w9, #2
+	ldr	w10, [sp, #4]
+	mul	w9, w9, w10
+	str	w9, [sp, #40]
+	ldr	w9, [sp, #0]
+	ldr	w10, [sp, #40]
+	add	w9, w9, w10
+	str	w9, [sp, #44]
+	mov	w9, #3
+	ldr	w10, [sp, #8]
+	mul	w9, w9, w10
+	str	w9, [sp, #48]
+	ldr	w9, [sp, #44]
+	ldr	w10, [sp, #48]
+	add	w9, w9, w10
+	str	w9, [sp, #52]
+	mov	w9, #4
+	ldr	w10, [sp, #12]
+	mul	w9, w9, w10
+	str	w9, [sp, #56]
+	ldr	w9, [sp, #52]
+	ldr	w10, [sp, #56]
+	add	w9, w9, w10
+	str	w9, [sp, #60]
+	mov	w9, #5
+	ldr	w10, [sp, #16]
+	mul	w9, w9, w10
+	str	w9, [sp, #64]
+	ldr	w9, [sp, #60]
+	ldr	w10, [sp, #64]
+	add	w9, w9, w10
+	str	w9, [sp, #68]
+	mov	w9, #6
+	ldr	w10, [sp, #20]
+	mul	w9, w9, w10
+	str	w9, [sp, #72]
+	ldr	w9, [sp, #68]
+	ldr	w10, [sp, #72]
+	add	w9, w9, w10
+	str	w9, [sp, #76]
+	mov	w9, #7
+	ldr	w10, [sp, #24]
+	mul	w9, w9, w10
+	str	w9, [sp, #80]
+	ldr	w9, [sp, #76]
+	ldr	w10, [sp, #80]
+	add	w9, w9, w10
+	str	w9, [sp, #84]
+	mov	w9, #8
+	ldr	w10, [sp, #28]
+	mul	w9, w9, w10
+	str	w9, [sp, #88]
+	ldr	w9, [sp, #84]
+	ldr	w10, [sp, #88]
+	add	w9, w9, w10
+	str	w9, [sp, #92]
+	mov	w9, #9
+	ldr	w10, [sp, #32]
+	mul	w9, w9, w10
+	str	w9, [sp, #96]
+	ldr	w9, [sp, #92]
+	ldr	w10, [sp, #96]
+	add	w9, w9, w10
+	str	w9, [sp, #100]
+	mov	w9, #10
+	ldr	w10, [sp, #36]
+	mul	w9, w9, w10
+	str	w9, [sp, #104]
+	ldr	w9, [sp, #100]
+	ldr	w10, [sp, #104]
+	add	w9, w9, w10
+	str	w9, [sp, #108]
+	ldr	w0, [sp, #108]
+	ldp	x29, x30, [sp, #112]
+	add	sp, sp, #128
+	ret
+	.globl	_forward
+_forward:
+	sub	sp, sp, #80
+	stp	x29, x30, [sp, #64]
+	add	x29, sp, #64
+	str	w0, [sp, #0]
+	str	w1, [sp, #4]
+	str	w2, [sp, #8]
+	str	w3, [sp, #12]
+	str	w4, [sp, #16]
+	str	w5, [sp, #20]
+	str	w6, [sp, #24]
+	str	w7, [sp, #28]
+	ldr	w9, [x29, #16]
+	str	w9, [sp, #32]
+	ldr	w9, [x29, #20]
+	str	w9, [sp, #36]
+	mov	w9, #1
+	str	w9, [sp, #40]
+	sub	sp, sp, #16
+	ldr	w9, [sp, #20]
+	str	w9, [sp, #0]
+	ldr	w9, [sp, #16]
+	str	w9, [sp, #4]
+	ldr	w0, [sp, #52]
+	ldr	w1, [sp, #48]
+	ldr	w2, [sp, #44]
+	ldr	w3, [sp, #40]
+	ldr	w4, [sp, #36]
+	ldr	w5, [sp, #32]
+	ldr	w6, [sp, #28]
+	ldr	w7, [sp, #24]
+	bl	_weigh
+	add	sp, sp, #16
+	str	w0, [sp, #44]
+	sub	sp, sp, #16
+	ldr	w9, [sp, #48]
+	str	w9, [sp, #0]
+	ldr	w9, [sp, #52]
+	str	w9, [sp, #4]
+	ldr	w0, [sp, #16]
+	ldr	w1, [sp, #20]
+	ldr	w2, [sp, #24]
+	ldr	w3, [sp, #28]
+	ldr	w4, [sp, #32]
+	ldr	w5, [sp, #36]
+	ldr	w6, [sp, #40]
+	ldr	w7, [sp, #44]
+	bl	_weigh
+	add	sp, sp, #16
+	str	w0, [sp, #48]
+	ldr	w9, [sp, #44]
+	ldr	w10, [sp, #48]
+	sub	w9, w9, w10
+	str	w9, [sp, #52]
+	ldr	w9, [sp, #52]
+	ldr	w10, [sp, #40]
+	add	w9, w9, w10
+	str	w9, [sp, #56]
+	ldr	w0, [sp, #56]
+	ldp	x29, x30, [sp, #64]
+	add	sp, sp, #80
+	ret
+	.globl	_main
+_main:
+	sub	sp, sp, #48
+	stp	x29, x30, [sp, #32]
+	add	x29, sp, #32
+	sub	sp, sp, #16
+	mov	w9, #9
+	str	w9, [sp, #0]
+	mov	w9, #10
+	str	w9, [sp, #4]
+	mov	w0, #1
+	mov	w1, #2
+	mov	w2, #3
+	mov	w3, #4
+	mov	w4, #5
+	mov	w5, #6
+	mov	w6, #7
+	mov	w7, #8
+	bl	_forward
+	add	sp, sp, #16
+	str	w0, [sp, #0]
+	sub	sp, sp, #16
+	mov	w9, #1
+	str	w9, [sp, #0]
+	mov	w9, #1
+	str	w9, [sp, #4]
+	mov	w0, #1
+	mov	w1, #1
+	mov	w2, #1
+	mov	w3, #1
+	mov	w4, #1
+	mov	w5, #1
+	mov	w6, #1
+	mov	w7, #1
+	bl	_weigh
+	add	sp, sp, #16
+	str	w0, [sp, #4]
+	mov	w9, #6
+	ldr	w10, [sp, #4]
+	mul	w9, w9, w10
+	str	w9, [sp, #8]
+	ldr	w9, [sp, #0]
+	ldr	w10, [sp, #8]
+	add	w9, w9, w10
+	str	w9, [sp, #12]
+	ldr	w9, [sp, #12]
+	mov	w10, #39
+	add	w9, w9, w10
+	str	w9, [sp, #16]
+	ldr	w0, [sp, #16]
+	ldp	x29, x30, [sp, #32]
+	add	sp, sp, #48
+	ret